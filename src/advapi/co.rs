@@ -2,6 +2,40 @@
 
 use crate::co::*;
 
+const_ordinary! { CM_RESOURCE_TYPE: u8;
+	/// [`CM_PARTIAL_RESOURCE_DESCRIPTOR`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_cm_partial_resource_descriptor)
+	/// and
+	/// [`IO_RESOURCE_DESCRIPTOR`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_io_resource_descriptor)
+	/// `Type` (`u8`).
+	=>
+	Null 0
+	Port 1
+	Interrupt 2
+	Memory 3
+	Dma 4
+	DeviceSpecific 5
+	BusNumber 6
+	MemoryLarge 7
+	/// Also defined as `CmResourceTypeConfigData`.
+	NonArbitrated 128
+	DevicePrivate 129
+	PcCardConfig 130
+	MfCardConfig 131
+	Connection 132
+}
+
+const_ordinary! { CM_SHARE_DISPOSITION: u8;
+	/// [`CM_PARTIAL_RESOURCE_DESCRIPTOR`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_cm_partial_resource_descriptor)
+	/// and
+	/// [`IO_RESOURCE_DESCRIPTOR`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_io_resource_descriptor)
+	/// `ShareDisposition` (`u8`).
+	=>
+	Undetermined 0
+	DeviceExclusive 1
+	DriverExclusive 2
+	Shared 3
+}
+
 const_ordinary! { EVENTLOG: u16;
 	/// [`HEVENTLOG::ReportEvent`](crate::HEVENTLOG::ReportEvent) `event_type`
 	/// [`u16`]. undefined
@@ -14,6 +48,40 @@ const_ordinary! { EVENTLOG: u16;
 	WARNING_TYPE 0x0002
 }
 
+const_ordinary! { INTERFACE_TYPE: i32;
+	/// [`INTERFACE_TYPE`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ne-wdm-_interface_type)
+	/// enumeration (`i32`).
+	=>
+	InterfaceTypeUndefined -1
+	Internal 0
+	Isa 1
+	Eisa 2
+	MicroChannel 3
+	TurboChannel 4
+	PCIBus 5
+	VMEBus 6
+	NuBus 7
+	PCMCIABus 8
+	CBus 9
+	MPIBus 10
+	MPSABus 11
+	ProcessorInternal 12
+	InternalPowerBus 13
+	PNPISABus 14
+	PNPBus 15
+	Vmcs 16
+	ACPIBus 17
+}
+
+const_bitflag! { IO_RESOURCE: u8;
+	/// [`IO_RESOURCE_DESCRIPTOR`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_io_resource_descriptor)
+	/// `Option` (`u8`).
+	=>
+	PREFERRED 0x01
+	DEFAULT 0x02
+	ALTERNATIVE 0x08
+}
+
 const_bitflag! { KEY: u32;
 	/// [Registry access rights](https://learn.microsoft.com/en-us/windows/win32/sysinfo/registry-key-security-and-access-rights)
 	/// (`u32`).
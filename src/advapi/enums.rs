@@ -22,6 +22,9 @@ pub enum RegistryValue {
 	Binary(Vec<u8>),
 	/// An `u32` integer value, defined as [`REG::DWORD`](crate::co::REG::DWORD).
 	Dword(u32),
+	/// An `u32` integer value stored in big-endian format, defined as
+	/// [`REG::DWORD_BIG_ENDIAN`](crate::co::REG::DWORD_BIG_ENDIAN).
+	DwordBigEndian(u32),
 	/// An `u64` integer value, defined as [`REG::QWORD`](crate::co::REG::QWORD).
 	Qword(u64),
 	/// String value, defined as [`REG::SZ`](crate::co::REG::SZ).
@@ -31,29 +34,46 @@ pub enum RegistryValue {
 	/// references, use
 	/// [`ExpandEnvironmentStrings`](crate::ExpandEnvironmentStrings).
 	ExpandSz(String),
+	/// Path of the target of a symbolic link, defined as
+	/// [`REG::LINK`](crate::co::REG::LINK). Unlike other strings, it's stored
+	/// without a terminating null.
+	Link(String),
 	/// Multiple strings, defined as [`REG::MULTI_SZ`](crate::co::REG::MULTI_SZ).
 	MultiSz(Vec<String>),
-	/// No value, defined as [`REG::NONE`](crate::co::REG::NONE). Also used for
-	/// non-implemented value types.
+	/// Device driver resources, defined as
+	/// [`REG::RESOURCE_LIST`](crate::co::REG::RESOURCE_LIST).
+	ResourceList(ResourceList),
+	/// Hardware resources of a device, defined as
+	/// [`REG::FULL_RESOURCE_DESCRIPTOR`](crate::co::REG::FULL_RESOURCE_DESCRIPTOR).
+	FullResourceDescriptor(FullResourceDescriptor),
+	/// Possible resource sets for a device, defined as
+	/// [`REG::RESOURCE_REQUIREMENTS_LIST`](crate::co::REG::RESOURCE_REQUIREMENTS_LIST).
+	ResourceRequirementsList(ResourceRequirementsList),
+	/// No value, defined as [`REG::NONE`](crate::co::REG::NONE).
 	None,
+	/// Raw bytes of any other type, like the device property types under
+	/// `HKEY_LOCAL_MACHINE\SYSTEM`, or of [`REG::NONE`](crate::co::REG::NONE)
+	/// with data. They are written back unchanged.
+	Unknown(co::REG, Vec<u8>),
 }
 
 impl std::fmt::Display for RegistryValue {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		use RegistryValue::*;
+		let hex = |b: &[u8]| {
+			b.iter()
+				.map(|n| format!("{:02x}", *n))
+				.collect::<Vec<_>>()
+				.join(" ")
+		};
 		match self {
-			Binary(b) => write!(
-				f,
-				"[REG_BINARY] {}",
-				b.iter()
-					.map(|n| format!("{:02x}", *n))
-					.collect::<Vec<_>>()
-					.join(" "),
-			),
+			Binary(b) => write!(f, "[REG_BINARY] {}", hex(b)),
 			Dword(n) => write!(f, "[REG_DWORD] {}", *n),
+			DwordBigEndian(n) => write!(f, "[REG_DWORD_BIG_ENDIAN] {}", *n),
 			Qword(n) => write!(f, "[REG_QWORD] {}", *n),
 			Sz(s) => write!(f, "[REG_SZ] \"{}\"", s),
 			ExpandSz(s) => write!(f, "[REG_EXPAND_SZ] \"{}\"", s),
+			Link(s) => write!(f, "[REG_LINK] \"{}\"", s),
 			MultiSz(v) => write!(
 				f,
 				"[REG_MULTI_SZ] {}",
@@ -62,7 +82,15 @@ impl std::fmt::Display for RegistryValue {
 					.collect::<Vec<_>>()
 					.join(", "),
 			),
+			ResourceList(r) => write!(f, "[REG_RESOURCE_LIST] {}", hex(&r.serialize())),
+			FullResourceDescriptor(r) => {
+				write!(f, "[REG_FULL_RESOURCE_DESCRIPTOR] {}", hex(&r.serialize()))
+			},
+			ResourceRequirementsList(r) => {
+				write!(f, "[REG_RESOURCE_REQUIREMENTS_LIST] {}", hex(&r.serialize()))
+			},
 			None => write!(f, "[REG_NONE]"),
+			Unknown(reg_type, b) => write!(f, "[{}] {}", reg_type, hex(b)),
 		}
 	}
 }
@@ -77,6 +105,9 @@ impl RegistryValue {
 	///
 	/// Whilst a few validations are made, assumes the binary data block has the
 	/// correct content, according to the informed [`co::REG`](crate::co::REG).
+	///
+	/// Unknown types, and [`REG::NONE`](crate::co::REG::NONE) with data, are
+	/// kept as [`RegistryValue::Unknown`](crate::RegistryValue::Unknown).
 	#[must_use]
 	pub unsafe fn from_raw(buf: Vec<u8>, reg_type: co::REG) -> SysResult<RegistryValue> {
 		match reg_type {
@@ -99,6 +130,15 @@ impl RegistryValue {
 					})))
 				}
 			},
+			co::REG::DWORD_BIG_ENDIAN => {
+				if buf.len() != std::mem::size_of::<u32>() {
+					Err(co::ERROR::INVALID_DATA) // validate buf size
+				} else {
+					Ok(RegistryValue::DwordBigEndian(u32::from_be_bytes(
+						buf.as_slice().try_into().unwrap(),
+					)))
+				}
+			},
 			co::REG::SZ => {
 				let (_, vec16, _) = unsafe { buf.align_to::<u16>() };
 				Ok(RegistryValue::Sz(WString::from_wchars_slice(&vec16).to_string()))
@@ -107,24 +147,39 @@ impl RegistryValue {
 				let (_, vec16, _) = unsafe { buf.align_to::<u16>() };
 				Ok(RegistryValue::ExpandSz(WString::from_wchars_slice(&vec16).to_string()))
 			},
+			co::REG::LINK => {
+				let (_, vec16, _) = unsafe { buf.align_to::<u16>() };
+				Ok(RegistryValue::Link(WString::from_wchars_slice(vec16).to_string()))
+			},
 			co::REG::MULTI_SZ => {
 				let (_, vec16, _) = unsafe { buf.align_to::<u16>() };
 				Ok(RegistryValue::MultiSz(unsafe {
 					parse_multi_z_str(vec16.as_ptr(), Some(vec16.len()))
 				}))
 			},
-			co::REG::NONE => Ok(RegistryValue::None),
-			_ => Err(co::ERROR::CALL_NOT_IMPLEMENTED), // other types not implemented yet
+			co::REG::RESOURCE_LIST => Ok(RegistryValue::ResourceList(ResourceList::parse(&buf)?)),
+			co::REG::FULL_RESOURCE_DESCRIPTOR => {
+				Ok(RegistryValue::FullResourceDescriptor(FullResourceDescriptor::parse(&buf)?))
+			},
+			co::REG::RESOURCE_REQUIREMENTS_LIST => {
+				Ok(RegistryValue::ResourceRequirementsList(ResourceRequirementsList::parse(&buf)?))
+			},
+			co::REG::NONE if buf.is_empty() => Ok(RegistryValue::None),
+			_ => Ok(RegistryValue::Unknown(reg_type, buf)), // keep the bytes of an unknown value type
 		}
 	}
 
 	/// Returns a pointer to the raw data, along with the raw data length in bytes.
+	///
+	/// Values which must be serialized, like strings, are written into
+	/// `str_buf`, which must outlive the returned pointer.
 	#[must_use]
 	pub fn as_ptr_with_len(&self, str_buf: &mut WString) -> (*const std::ffi::c_void, u32) {
 		use RegistryValue::*;
 		match self {
 			Binary(b) => (b.as_ptr() as _, b.len() as _),
 			Dword(n) => (n as *const _ as _, std::mem::size_of::<u32>() as _),
+			DwordBigEndian(n) => bytes_into_buf(&n.to_be_bytes(), str_buf),
 			Qword(n) => (n as *const _ as _, std::mem::size_of::<u64>() as _),
 			Sz(s) => {
 				*str_buf = WString::from_str(s); // serialize the string into the external buffer
@@ -136,15 +191,24 @@ impl RegistryValue {
 				let buf_sz = (str_buf.str_len() + 1) * std::mem::size_of::<u16>(); // count terminating null
 				(str_buf.as_ptr() as _, buf_sz as _)
 			},
+			Link(s) => {
+				*str_buf = WString::from_str(s); // serialize the string into the external buffer
+				let buf_sz = str_buf.str_len() * std::mem::size_of::<u16>(); // no terminating null
+				(str_buf.as_ptr() as _, buf_sz as _)
+			},
 			MultiSz(v) => {
 				*str_buf = WString::from_str_vec(v); // serialize the string into the external buffer
-				let tot_chars = v.iter() // number of chars of all strings, including terminating nulls
-					.fold(0, |tot, s| tot + s.chars().count() + 1) // include terminating null
+				let tot_units = v.iter() // number of UTF-16 units of all strings, including terminating nulls
+					.fold(0, |tot, s| tot + s.encode_utf16().count() + 1) // include terminating null
 					+ 1; // double terminating null
-				let buf_sz = tot_chars * std::mem::size_of::<u16>();
+				let buf_sz = tot_units * std::mem::size_of::<u16>();
 				(str_buf.as_ptr() as _, buf_sz as _)
 			},
+			ResourceList(r) => bytes_into_buf(&r.serialize(), str_buf),
+			FullResourceDescriptor(r) => bytes_into_buf(&r.serialize(), str_buf),
+			ResourceRequirementsList(r) => bytes_into_buf(&r.serialize(), str_buf),
 			None => (std::ptr::null(), 0),
+			Unknown(_, b) => (b.as_ptr() as _, b.len() as _),
 		}
 	}
 
@@ -155,15 +219,33 @@ impl RegistryValue {
 		match self {
 			Binary(_) => co::REG::BINARY,
 			Dword(_) => co::REG::DWORD,
+			DwordBigEndian(_) => co::REG::DWORD_BIG_ENDIAN,
 			Qword(_) => co::REG::QWORD,
 			Sz(_) => co::REG::SZ,
 			ExpandSz(_) => co::REG::EXPAND_SZ,
+			Link(_) => co::REG::LINK,
 			MultiSz(_) => co::REG::MULTI_SZ,
+			ResourceList(_) => co::REG::RESOURCE_LIST,
+			FullResourceDescriptor(_) => co::REG::FULL_RESOURCE_DESCRIPTOR,
+			ResourceRequirementsList(_) => co::REG::RESOURCE_REQUIREMENTS_LIST,
 			None => co::REG::NONE,
+			Unknown(reg_type, _) => *reg_type,
 		}
 	}
 }
 
+/// Copies raw bytes into the external buffer, returning the pointer and the
+/// length in bytes.
+#[must_use]
+fn bytes_into_buf(bytes: &[u8], str_buf: &mut WString) -> (*const std::ffi::c_void, u32) {
+	*str_buf = WString::new_alloc_buf(bytes.len().div_ceil(2)); // UTF-16 units
+	bytes
+		.chunks(2)
+		.zip(str_buf.as_mut_slice())
+		.for_each(|(src, dest)| *dest = u16::from_ne_bytes([src[0], *src.get(1).unwrap_or(&0)]));
+	(str_buf.as_ptr() as _, bytes.len() as _)
+}
+
 /// Notification content for
 /// [`HSERVICESTATUS::RegisterServiceCtrlHandlerEx`](crate::HSERVICESTATUS::RegisterServiceCtrlHandlerEx)
 /// callback, describing [`co::SERVICE_CONTROL`](crate::co::SERVICE_CONTROL).
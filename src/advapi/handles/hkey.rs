@@ -42,6 +42,165 @@ impl HKEY {
 			&& (self.0 as usize) <= (Self::PERFORMANCE_NLSTEXT.0 as usize)
 	}
 
+	/// Walks the key tree at `key_path`, with
+	/// [`HKEY::RegEnumKeyEx`](crate::HKEY::RegEnumKeyEx) and
+	/// [`HKEY::RegEnumValue`](crate::HKEY::RegEnumValue), returning its
+	/// contents as a [`RegFile`](crate::RegFile).
+	///
+	/// `key_path` is a full path starting with the root key name, like
+	/// `HKEY_CURRENT_USER\Software\Foo`; abbreviations like `HKCU` are also
+	/// accepted, and written with the full root key name.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let reg_file = w::HKEY::export_reg_file("HKEY_CURRENT_USER\\Control Panel\\Mouse")?;
+	/// println!("{reg_file}");
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn export_reg_file(key_path: &str) -> SysResult<RegFile> {
+		let (root, root_name, sub_key) = Self::split_root_key(key_path)?;
+		let hkey = root.RegOpenKeyEx(sub_key, co::REG_OPTION::default(), co::KEY::READ)?;
+
+		let path = match sub_key {
+			Some(sub_key) => format!("{}\\{}", root_name, sub_key),
+			None => root_name.to_owned(),
+		};
+		let mut reg_file = RegFile::default();
+		hkey.export_key(&path, &mut reg_file)?;
+		Ok(reg_file)
+	}
+
+	fn export_key(&self, path: &str, reg_file: &mut RegFile) -> SysResult<()> {
+		let values = self
+			.RegEnumValue()?
+			.map(|name_and_type| {
+				let (name, _) = name_and_type?;
+				let name = if name.is_empty() { None } else { Some(name) }; // default value
+				let (reg_type, bytes) = self.query_value_raw(name.as_deref())?;
+				Ok(RegFileValue {
+					data: RegFileData::from_raw(bytes, reg_type),
+					name,
+				})
+			})
+			.collect::<SysResult<Vec<_>>>()?;
+
+		reg_file.keys.push(RegFileKey {
+			path: path.to_owned(),
+			delete: false,
+			values,
+		});
+
+		let sub_key_names = self.RegEnumKeyEx()?.collect::<SysResult<Vec<_>>>()?;
+		for sub_key_name in sub_key_names.iter() {
+			let hsub = self.RegOpenKeyEx(
+				Some(sub_key_name),
+				co::REG_OPTION::default(),
+				co::KEY::READ,
+			)?;
+			hsub.export_key(&format!("{}\\{}", path, sub_key_name), reg_file)?;
+		}
+		Ok(())
+	}
+
+	/// Applies the contents of a [`RegFile`](crate::RegFile) to the registry,
+	/// like Regedit does: keys are created as needed, and the deletion markers
+	/// delete keys and values. Keys and values which are already absent are
+	/// not an error.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let reg_file = w::RegFile::parse(
+	///     "Windows Registry Editor Version 5.00\r\n\
+	///     \r\n\
+	///     [HKEY_CURRENT_USER\\Software\\Foo]\r\n\
+	///     \"Name\"=\"bar\"\r\n\
+	///     \"Old\"=-\r\n",
+	/// )?;
+	///
+	/// w::HKEY::import_reg_file(&reg_file)?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	pub fn import_reg_file(reg_file: &RegFile) -> SysResult<()> {
+		let ignore_not_found = |res: SysResult<()>| match res {
+			Err(co::ERROR::FILE_NOT_FOUND) => Ok(()),
+			res => res,
+		};
+
+		for key in reg_file.keys.iter() {
+			let (root, _, sub_key) = Self::split_root_key(&key.path)?;
+
+			if key.delete {
+				let sub_key = sub_key.ok_or(co::ERROR::INVALID_PARAMETER)?; // won't delete a root key
+				ignore_not_found(root.RegDeleteTree(Some(sub_key)))?;
+				continue;
+			}
+
+			let hkey = match sub_key {
+				Some(sub_key) => {
+					root.RegCreateKeyEx(
+						sub_key,
+						None,
+						co::REG_OPTION::default(),
+						co::KEY::WRITE,
+						None,
+					)?
+					.0
+				},
+				None => root.RegOpenKeyEx(None, co::REG_OPTION::default(), co::KEY::WRITE)?,
+			};
+
+			for val in key.values.iter() {
+				match &val.data {
+					RegFileData::Delete => {
+						ignore_not_found(hkey.RegDeleteValue(val.name.as_deref()))?
+					},
+					RegFileData::Value(data) => {
+						hkey.RegSetValueEx(val.name.as_deref(), data.clone())?
+					},
+					RegFileData::Raw(reg_type, bytes) => ErrorRet(unsafe {
+						ffi::RegSetValueExW(
+							hkey.ptr(),
+							WString::from_opt_str(val.name.as_deref()).as_ptr(),
+							0,
+							reg_type.raw(),
+							vec_ptr(bytes) as _,
+							bytes.len() as _,
+						)
+					})
+					.to_sysresult()?,
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Splits a full key path into the predefined root key, its full name, and
+	/// the sub key.
+	fn split_root_key(key_path: &str) -> SysResult<(HKEY, &'static str, Option<&str>)> {
+		let key_path = key_path.trim_end_matches('\\');
+		let (root_name, sub_key) = match key_path.split_once('\\') {
+			Some((root_name, sub_key)) => (root_name, Some(sub_key)),
+			None => (key_path, None),
+		};
+
+		let (root, root_name) = match root_name.to_uppercase().as_str() {
+			"HKEY_CLASSES_ROOT" | "HKCR" => (Self::CLASSES_ROOT, "HKEY_CLASSES_ROOT"),
+			"HKEY_CURRENT_USER" | "HKCU" => (Self::CURRENT_USER, "HKEY_CURRENT_USER"),
+			"HKEY_LOCAL_MACHINE" | "HKLM" => (Self::LOCAL_MACHINE, "HKEY_LOCAL_MACHINE"),
+			"HKEY_USERS" | "HKU" => (Self::USERS, "HKEY_USERS"),
+			"HKEY_CURRENT_CONFIG" | "HKCC" => (Self::CURRENT_CONFIG, "HKEY_CURRENT_CONFIG"),
+			_ => return Err(co::ERROR::INVALID_PARAMETER), // unknown root key
+		};
+		Ok((root, root_name, sub_key))
+	}
+
 	/// [`RegConnectRegistry`](https://learn.microsoft.com/en-us/windows/win32/api/winreg/nf-winreg-regconnectregistryw)
 	/// function.
 	///
@@ -636,6 +795,12 @@ impl HKEY {
 	/// ```
 	#[must_use]
 	pub fn RegQueryValueEx(&self, value_name: Option<&str>) -> SysResult<RegistryValue> {
+		let (reg_type, buf) = self.query_value_raw(value_name)?;
+		unsafe { RegistryValue::from_raw(buf, reg_type) }
+	}
+
+	/// Retrieves the type and the raw bytes of a value.
+	fn query_value_raw(&self, value_name: Option<&str>) -> SysResult<(co::REG, Vec<u8>)> {
 		let value_name_w = WString::from_opt_str(value_name);
 		let mut buf = Vec::<u8>::default();
 
@@ -669,7 +834,7 @@ impl HKEY {
 			} {
 				co::ERROR::SUCCESS => {
 					buf.resize(data_len as _, 0x00); // data length may have shrunk
-					return unsafe { RegistryValue::from_raw(buf, co::REG::from_raw(data_type)) };
+					return Ok((unsafe { co::REG::from_raw(data_type) }, buf));
 				},
 				co::ERROR::MORE_DATA => continue, // value changed in a concurrent operation; retry
 				e => return Err(e),
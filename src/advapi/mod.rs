@@ -8,6 +8,7 @@ mod handles;
 mod iterators;
 mod privs;
 mod structs;
+mod utilities;
 
 pub mod co;
 pub mod guards;
@@ -17,4 +18,5 @@ pub mod decl {
 	pub use super::funcs::*;
 	pub use super::handles::decl::*;
 	pub use super::structs::*;
+	pub use super::utilities::*;
 }
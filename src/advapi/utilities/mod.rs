@@ -0,0 +1,8 @@
+mod reg_file;
+mod resources;
+
+pub use reg_file::{RegFile, RegFileData, RegFileKey, RegFileValue};
+pub use resources::{
+	FullResourceDescriptor, IoResource, IoResourceDescriptor, IoResourceList, PartialResource,
+	PartialResourceDescriptor, ResourceList, ResourceRequirementsList,
+};
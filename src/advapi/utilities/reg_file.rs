@@ -0,0 +1,460 @@
+use crate::co;
+use crate::decl::*;
+
+const HEADER_5: &str = "Windows Registry Editor Version 5.00";
+const HEADER_4: &str = "REGEDIT4";
+
+/// Maximum length of a line written by Regedit, when wrapping hex values.
+const MAX_LINE_LEN: usize = 80;
+
+/// Contents of a `.reg` file, in the Regedit 5.00 text format.
+///
+/// This is a pure parser and writer: no registry calls are made. To read and
+/// write the registry, see
+/// [`HKEY::export_reg_file`](crate::HKEY::export_reg_file) and
+/// [`HKEY::import_reg_file`](crate::HKEY::import_reg_file).
+///
+/// The text is written by the [`Display`](std::fmt::Display) implementation,
+/// with `\r\n` line breaks. Regedit itself saves the files as UTF-16 with a
+/// BOM.
+///
+/// # Examples
+///
+/// Exporting a key tree to a `.reg` file:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let reg_file = w::HKEY::export_reg_file("HKEY_CURRENT_USER\\Control Panel\\Mouse")?;
+///
+/// let raw_bytes = w::WString::from_str(reg_file.to_string())
+///     .encode(w::Encoding::Utf16le, true, w::EncodeFallback::Error)?;
+///
+/// let fout = w::File::open("C:\\Temp\\mouse.reg", w::FileAccess::OpenOrCreateRW)?;
+/// fout.erase_and_write(&raw_bytes)?;
+/// # w::SysResult::Ok(())
+/// ```
+///
+/// Loading a `.reg` file and applying it:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let fin = w::FileMapped::open("C:\\Temp\\mouse.reg", w::FileAccess::ExistingReadOnly)?;
+/// let text = w::WString::parse(fin.as_slice())?.to_string();
+/// let reg_file = w::RegFile::parse(&text)?;
+///
+/// w::HKEY::import_reg_file(&reg_file)?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RegFile {
+	/// The keys, in file order. The same key may appear more than once.
+	pub keys: Vec<RegFileKey>,
+}
+
+impl std::fmt::Display for RegFile {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}\r\n", HEADER_5)?;
+		for key in self.keys.iter() {
+			if key.delete {
+				write!(f, "\r\n[-{}]\r\n", key.path)?; // values are meaningless here
+			} else {
+				write!(f, "\r\n[{}]\r\n", key.path)?;
+				for val in key.values.iter() {
+					write!(f, "{}\r\n", val)?;
+				}
+			}
+		}
+		write!(f, "\r\n")
+	}
+}
+
+impl RegFile {
+	/// Parses the text of a `.reg` file, either in the Regedit 5.00 format or
+	/// in the older `REGEDIT4` one, whose strings are Windows-1252.
+	///
+	/// Comments, blank lines and line continuations are accepted. Malformed
+	/// text yields [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(text: &str) -> SysResult<Self> {
+		let text = text.strip_prefix('\u{feff}').unwrap_or(text); // BOM
+		let mut lines = text.lines();
+
+		let ansi = match lines.by_ref().map(str::trim).find(|line| !line.is_empty()) {
+			Some(HEADER_5) => false,
+			Some(HEADER_4) => true,
+			_ => return Err(co::ERROR::INVALID_DATA), // no header
+		};
+
+		let mut reg_file = Self::default();
+		let mut logical_line = String::new();
+
+		for line in lines {
+			let line = line.trim();
+			if logical_line.is_empty() && (line.is_empty() || line.starts_with(';')) {
+				continue; // blank line or comment
+			}
+			if let Some(head) = line.strip_suffix('\\') {
+				logical_line.push_str(head); // continues in the next line
+				continue;
+			}
+			logical_line.push_str(line);
+			reg_file.push_line(&logical_line, ansi)?;
+			logical_line.clear();
+		}
+
+		if !logical_line.is_empty() {
+			reg_file.push_line(&logical_line, ansi)?; // continuation at end of file
+		}
+		Ok(reg_file)
+	}
+
+	fn push_line(&mut self, line: &str, ansi: bool) -> SysResult<()> {
+		if let Some(path) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+			let (path, delete) = match path.strip_prefix('-') {
+				Some(path) => (path, true),
+				None => (path, false),
+			};
+			self.keys.push(RegFileKey {
+				path: path.to_owned(),
+				delete,
+				values: Vec::new(),
+			});
+		} else {
+			let key = self.keys.last_mut().ok_or(co::ERROR::INVALID_DATA)?; // value outside a key
+			if !key.delete {
+				key.values.push(RegFileValue::parse(line, ansi)?);
+			} // Regedit ignores the values of a deleted key
+		}
+		Ok(())
+	}
+}
+
+/// A key of a [`RegFile`](crate::RegFile).
+#[derive(Clone, Debug, Default)]
+pub struct RegFileKey {
+	/// Full path of the key, starting with the root key name, like
+	/// `HKEY_CURRENT_USER\Software\Foo`.
+	pub path: String,
+	/// If `true`, the whole key tree is deleted, written as `[-path]`. In this
+	/// case, `values` are ignored.
+	pub delete: bool,
+	/// The values to be set or deleted.
+	pub values: Vec<RegFileValue>,
+}
+
+/// A value of a [`RegFileKey`](crate::RegFileKey).
+#[derive(Clone, Debug)]
+pub struct RegFileValue {
+	/// Name of the value; `None` is the default value, written as `@`.
+	pub name: Option<String>,
+	/// Data of the value.
+	pub data: RegFileData,
+}
+
+/// Data of a [`RegFileValue`](crate::RegFileValue).
+#[derive(Clone, Debug)]
+pub enum RegFileData {
+	/// Deletes the value, written as `"name"=-`.
+	Delete,
+	/// A typed value.
+	Value(RegistryValue),
+	/// Raw bytes of a value which a typed
+	/// [`RegistryValue`](crate::RegistryValue) wouldn't write back byte for
+	/// byte, like a malformed string, or of an unknown type. Written as
+	/// `hex(type):`.
+	Raw(co::REG, Vec<u8>),
+}
+
+impl RegFileData {
+	/// Creates the data from the raw bytes of a registry value, falling back
+	/// to [`RegFileData::Raw`](crate::RegFileData::Raw) when serializing the
+	/// parsed value wouldn't give back the same bytes.
+	#[must_use]
+	pub(in crate::advapi) fn from_raw(bytes: Vec<u8>, reg_type: co::REG) -> Self {
+		if reg_type == co::REG::NONE {
+			return Self::Raw(reg_type, bytes);
+		}
+		match unsafe { RegistryValue::from_raw(bytes.clone(), reg_type) } {
+			Ok(RegistryValue::Unknown(..)) => Self::Raw(reg_type, bytes),
+			Ok(val) if raw_bytes(&val) == bytes => Self::Value(val),
+			_ => Self::Raw(reg_type, bytes), // unknown type, malformed or non-canonical data
+		}
+	}
+}
+
+impl std::fmt::Display for RegFileValue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let name = match &self.name {
+			Some(name) => format!("\"{}\"", escape(name)),
+			None => "@".to_owned(),
+		};
+
+		match &self.data {
+			RegFileData::Delete => write!(f, "{}=-", name),
+			RegFileData::Value(RegistryValue::Sz(s)) if !s.contains(['\r', '\n']) => {
+				write!(f, "{}=\"{}\"", name, escape(s))
+			},
+			RegFileData::Value(RegistryValue::Dword(n)) => write!(f, "{}=dword:{:08x}", name, *n),
+			RegFileData::Value(RegistryValue::Binary(b)) => {
+				write_hex(f, &format!("{}=hex:", name), b)
+			},
+			RegFileData::Value(val) => {
+				write_hex(f, &format!("{}=hex({:x}):", name, val.reg_type().raw()), &raw_bytes(val))
+			},
+			RegFileData::Raw(reg_type, bytes) => {
+				write_hex(f, &format!("{}=hex({:x}):", name, reg_type.raw()), bytes)
+			},
+		}
+	}
+}
+
+impl RegFileValue {
+	fn parse(line: &str, ansi: bool) -> SysResult<Self> {
+		let (name, rest) = match line.strip_prefix('@') {
+			Some(rest) => (None, rest),
+			None => {
+				let (name, rest) = unquote(line)?;
+				(Some(name), rest)
+			},
+		};
+
+		let data = rest
+			.trim_start()
+			.strip_prefix('=')
+			.ok_or(co::ERROR::INVALID_DATA)?
+			.trim();
+
+		let data = if data == "-" {
+			RegFileData::Delete
+		} else if data.starts_with('"') {
+			let (s, rest) = unquote(data)?;
+			if !rest.trim().is_empty() {
+				return Err(co::ERROR::INVALID_DATA); // garbage after the string
+			}
+			RegFileData::Value(RegistryValue::Sz(s))
+		} else if let Some(num) = data.strip_prefix("dword:") {
+			RegFileData::Value(RegistryValue::Dword(
+				u32::from_str_radix(num.trim(), 16).map_err(|_| co::ERROR::INVALID_DATA)?,
+			))
+		} else if let Some(hex) = data.strip_prefix("hex:") {
+			RegFileData::Value(RegistryValue::Binary(parse_hex(hex)?))
+		} else if let Some(rest) = data.strip_prefix("hex(") {
+			let (reg_type, hex) = rest.split_once("):").ok_or(co::ERROR::INVALID_DATA)?;
+			let reg_type = unsafe {
+				co::REG::from_raw(
+					u32::from_str_radix(reg_type, 16).map_err(|_| co::ERROR::INVALID_DATA)?,
+				)
+			};
+			let mut bytes = parse_hex(hex)?;
+			if ansi && matches!(reg_type, co::REG::SZ | co::REG::EXPAND_SZ | co::REG::MULTI_SZ) {
+				bytes = co::CP::WINDOWS_1252
+					.decode(&bytes)?
+					.iter()
+					.flat_map(|ch| ch.to_le_bytes())
+					.collect(); // REGEDIT4 strings are 8-bit
+			}
+			RegFileData::from_raw(bytes, reg_type)
+		} else {
+			return Err(co::ERROR::INVALID_DATA); // unknown data format
+		};
+
+		Ok(Self { name, data })
+	}
+}
+
+/// Escapes backslashes and quotes.
+#[must_use]
+fn escape(s: &str) -> String {
+	s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Parses a quoted string, unescaping it, and returns the rest of the text.
+fn unquote(text: &str) -> SysResult<(String, &str)> {
+	let mut chars = text
+		.strip_prefix('"')
+		.ok_or(co::ERROR::INVALID_DATA)?
+		.char_indices();
+	let mut s = String::new();
+
+	while let Some((_, ch)) = chars.next() {
+		match ch {
+			'"' => return Ok((s, chars.as_str())),
+			'\\' => match chars.next() {
+				Some((_, esc @ ('\\' | '"'))) => s.push(esc),
+				Some((_, other)) => {
+					s.push('\\'); // not an escape sequence, keep it as it is
+					s.push(other);
+				},
+				None => break,
+			},
+			_ => s.push(ch),
+		}
+	}
+	Err(co::ERROR::INVALID_DATA) // unterminated string
+}
+
+/// Parses comma-separated hex bytes.
+fn parse_hex(hex: &str) -> SysResult<Vec<u8>> {
+	hex.split(',')
+		.map(str::trim)
+		.filter(|b| !b.is_empty())
+		.map(|b| u8::from_str_radix(b, 16).map_err(|_| co::ERROR::INVALID_DATA))
+		.collect()
+}
+
+/// Writes comma-separated hex bytes after the prefix, wrapping the lines like
+/// Regedit does.
+fn write_hex(f: &mut std::fmt::Formatter<'_>, prefix: &str, bytes: &[u8]) -> std::fmt::Result {
+	write!(f, "{}", prefix)?;
+	let mut line_len = prefix.len();
+
+	for (idx, b) in bytes.iter().enumerate() {
+		let piece =
+			if idx == bytes.len() - 1 { format!("{:02x}", *b) } else { format!("{:02x},", *b) };
+		if line_len + piece.len() >= MAX_LINE_LEN {
+			write!(f, "\\\r\n  ")?; // room for the backslash
+			line_len = 2;
+		}
+		write!(f, "{}", piece)?;
+		line_len += piece.len();
+	}
+	Ok(())
+}
+
+/// Serializes the value into its raw bytes.
+#[must_use]
+fn raw_bytes(val: &RegistryValue) -> Vec<u8> {
+	let mut str_buf = WString::new();
+	let (ptr, len) = val.as_ptr_with_len(&mut str_buf);
+	if ptr.is_null() {
+		vec![0x00; len as _] // empty strings are not allocated
+	} else {
+		unsafe { std::slice::from_raw_parts(ptr as *const u8, len as _) }.to_vec()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trip() {
+		let text = "Windows Registry Editor Version 5.00\r\n\
+			\r\n\
+			[HKEY_CURRENT_USER\\Software\\Foo]\r\n\
+			@=\"default \\\"quoted\\\"\"\r\n\
+			\"Num\"=dword:0000002a\r\n\
+			\"Bin\"=hex:01,02,ff\r\n\
+			\"Marker\"=hex(0):de,ad,be,ef\r\n\
+			\"Old\"=-\r\n\
+			\r\n\
+			[-HKEY_CURRENT_USER\\Software\\Bar]\r\n\
+			\r\n";
+
+		let reg_file = RegFile::parse(text).unwrap();
+		assert_eq!(reg_file.keys.len(), 2);
+		assert!(matches!(
+			&reg_file.keys[0].values[3].data,
+			RegFileData::Raw(co::REG::NONE, bytes) if bytes == &[0xde, 0xad, 0xbe, 0xef]
+		));
+		assert_eq!(reg_file.to_string(), text);
+	}
+
+	#[test]
+	fn delete_key_has_no_values() {
+		let reg_file = RegFile {
+			keys: vec![RegFileKey {
+				path: "HKEY_CURRENT_USER\\Software\\Foo".to_owned(),
+				delete: true,
+				values: vec![RegFileValue {
+					name: Some("Num".to_owned()),
+					data: RegFileData::Value(RegistryValue::Dword(1)),
+				}],
+			}],
+		};
+		assert_eq!(
+			reg_file.to_string(),
+			"Windows Registry Editor Version 5.00\r\n\
+			\r\n\
+			[-HKEY_CURRENT_USER\\Software\\Foo]\r\n\
+			\r\n",
+		);
+	}
+
+	#[test]
+	fn values_of_deleted_key_are_skipped() {
+		let text = "Windows Registry Editor Version 5.00\r\n\
+			\r\n\
+			[-HKEY_CURRENT_USER\\Software\\Foo]\r\n\
+			\"Num\"=dword:00000001\r\n\
+			\r\n\
+			[HKEY_CURRENT_USER\\Software\\Bar]\r\n\
+			\"Num\"=dword:00000002\r\n";
+
+		let reg_file = RegFile::parse(text).unwrap();
+		assert_eq!(reg_file.keys.len(), 2);
+		assert!(reg_file.keys[0].delete);
+		assert!(reg_file.keys[0].values.is_empty());
+		assert!(matches!(
+			reg_file.keys[1].values[0].data,
+			RegFileData::Value(RegistryValue::Dword(2))
+		));
+
+		assert_eq!(
+			RegFile::parse("Windows Registry Editor Version 5.00\r\n\"Num\"=dword:00000001\r\n")
+				.err(),
+			Some(co::ERROR::INVALID_DATA), // value outside a key
+		);
+	}
+
+	#[test]
+	fn raw_data_is_kept_byte_for_byte() {
+		let utf16z = |s: &str| {
+			s.encode_utf16()
+				.flat_map(|ch| ch.to_le_bytes())
+				.collect::<Vec<_>>()
+		};
+
+		for (reg_type, bytes, canonical) in [
+			(co::REG::SZ, utf16z("abc\0"), true),
+			(co::REG::SZ, utf16z("\0"), true),
+			(co::REG::SZ, utf16z("abc"), false),       // no terminator
+			(co::REG::SZ, utf16z("abc\0\0"), false),   // extra null
+			(co::REG::SZ, vec![b'a', 0, b'b'], false), // odd length
+			(co::REG::EXPAND_SZ, utf16z("%TEMP%\0"), true),
+			(co::REG::MULTI_SZ, utf16z("a\0b\0\0"), true),
+			(co::REG::MULTI_SZ, utf16z("a\0b\0\0zz"), false), // trailing data
+			(co::REG::DWORD, vec![1, 2, 3, 4], true),
+			(co::REG::QWORD, vec![1, 2, 3, 4], false), // too short
+			(co::REG::NONE, vec![1, 2], false),
+			(unsafe { co::REG::from_raw(0xffff_0012) }, vec![1, 2, 3], false), // device property
+		] {
+			match RegFileData::from_raw(bytes.clone(), reg_type) {
+				RegFileData::Value(val) => {
+					assert!(canonical, "{} {:?}", reg_type, bytes);
+					assert_eq!(raw_bytes(&val), bytes);
+				},
+				RegFileData::Raw(raw_type, raw) => {
+					assert!(!canonical, "{} {:?}", reg_type, bytes);
+					assert_eq!(raw_type, reg_type);
+					assert_eq!(raw, bytes);
+				},
+				RegFileData::Delete => panic!(),
+			}
+		}
+	}
+
+	#[test]
+	fn unknown_type_is_kept() {
+		let reg_type = unsafe { co::REG::from_raw(0xffff_0012) };
+		let val = unsafe { RegistryValue::from_raw(vec![1, 2, 3], reg_type) }.unwrap();
+		assert!(matches!(&val, RegistryValue::Unknown(t, b) if *t == reg_type && b == &[1, 2, 3]));
+		assert_eq!(val.reg_type(), reg_type);
+		assert_eq!(raw_bytes(&val), [1, 2, 3]);
+
+		let val = unsafe { RegistryValue::from_raw(Vec::new(), co::REG::NONE) }.unwrap();
+		assert!(matches!(val, RegistryValue::None));
+	}
+}
@@ -0,0 +1,813 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::bytes::*;
+
+/// Size of the `u` union of `CM_PARTIAL_RESOURCE_DESCRIPTOR` on 32-bit Windows.
+const PARTIAL_UNION_32: usize = 12;
+/// Size of the `u` union of `CM_PARTIAL_RESOURCE_DESCRIPTOR` on 64-bit Windows,
+/// where `KAFFINITY` is 8 bytes long.
+const PARTIAL_UNION_64: usize = 16;
+/// Size of the `u` union of `IO_RESOURCE_DESCRIPTOR`, on both architectures.
+const IO_UNION: usize = 24;
+
+/// Owned representation of a
+/// [`CM_RESOURCE_LIST`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_cm_resource_list)
+/// struct, the data of a
+/// [`REG::RESOURCE_LIST`](crate::co::REG::RESOURCE_LIST) value.
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let val = w::HKEY::LOCAL_MACHINE.RegGetValue(
+///     Some("HARDWARE\\RESOURCEMAP\\System Resources\\Physical Memory"),
+///     Some(".Translated"),
+///     co::RRF::RT_ANY,
+/// )?;
+///
+/// if let w::RegistryValue::ResourceList(list) = val {
+///     for full in list.descriptors.iter() {
+///         for partial in full.partial_descriptors.iter() {
+///             println!("{:?}", partial.resource);
+///         }
+///     }
+/// }
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResourceList {
+	/// The full resource descriptors, one for each bus.
+	pub descriptors: Vec<FullResourceDescriptor>,
+}
+
+impl ResourceList {
+	/// Parses the raw data of a `CM_RESOURCE_LIST`, written either by 32-bit or
+	/// 64-bit Windows.
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(data: &[u8]) -> SysResult<Self> {
+		parse_any_layout(data, |data, off, layout_64| {
+			let count = read_dword(data, *off)?;
+			*off += 4;
+			Ok(Self {
+				descriptors: (0..count)
+					.map(|_| FullResourceDescriptor::read(data, off, layout_64))
+					.collect::<SysResult<_>>()?,
+			})
+		})
+	}
+
+	/// Serializes into the raw data of a `CM_RESOURCE_LIST`.
+	#[must_use]
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut buf = Vec::new();
+		buf.extend_from_slice(&(self.descriptors.len() as u32).to_le_bytes());
+		self.descriptors.iter().for_each(|d| d.write(&mut buf));
+		buf
+	}
+}
+
+/// Owned representation of a
+/// [`CM_FULL_RESOURCE_DESCRIPTOR`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_cm_full_resource_descriptor)
+/// struct, the data of a
+/// [`REG::FULL_RESOURCE_DESCRIPTOR`](crate::co::REG::FULL_RESOURCE_DESCRIPTOR)
+/// value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FullResourceDescriptor {
+	/// Bus type.
+	pub interface_type: co::INTERFACE_TYPE,
+	/// Zero-based number of the bus, for the given bus type.
+	pub bus_number: u32,
+	/// Version of the partial resource list.
+	pub version: u16,
+	/// Revision of the partial resource list.
+	pub revision: u16,
+	/// The resources assigned to the device.
+	pub partial_descriptors: Vec<PartialResourceDescriptor>,
+	layout_64: bool,
+}
+
+impl Default for FullResourceDescriptor {
+	/// Creates an empty descriptor with the layout of the current process.
+	fn default() -> Self {
+		Self::new(cfg!(target_pointer_width = "64"))
+	}
+}
+
+impl FullResourceDescriptor {
+	/// Creates an empty descriptor whose partial descriptors are written with
+	/// the 64-bit layout, 20 bytes long, or the 32-bit one, 16 bytes long.
+	#[must_use]
+	pub const fn new(layout_64: bool) -> Self {
+		Self {
+			interface_type: co::INTERFACE_TYPE::Internal,
+			bus_number: 0,
+			version: 0,
+			revision: 0,
+			partial_descriptors: Vec::new(),
+			layout_64,
+		}
+	}
+
+	/// Returns whether the partial descriptors are written with the 64-bit
+	/// layout, either parsed from the data or given to
+	/// [`new`](crate::FullResourceDescriptor::new).
+	#[must_use]
+	pub const fn is_layout_64(&self) -> bool {
+		self.layout_64
+	}
+
+	/// Parses the raw data of a `CM_FULL_RESOURCE_DESCRIPTOR`, written either by
+	/// 32-bit or 64-bit Windows.
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(data: &[u8]) -> SysResult<Self> {
+		parse_any_layout(data, Self::read)
+	}
+
+	/// Serializes into the raw data of a `CM_FULL_RESOURCE_DESCRIPTOR`.
+	#[must_use]
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut buf = Vec::new();
+		self.write(&mut buf);
+		buf
+	}
+
+	fn read(data: &[u8], off: &mut usize, layout_64: bool) -> SysResult<Self> {
+		let at = *off;
+		let interface_type = unsafe { co::INTERFACE_TYPE::from_raw(read_dword(data, at)? as _) };
+		let bus_number = read_dword(data, at + 4)?;
+		let version = read_word(data, at + 8)?;
+		let revision = read_word(data, at + 10)?;
+		let count = read_dword(data, at + 12)?;
+		*off = at + 16;
+		Ok(Self {
+			interface_type,
+			bus_number,
+			version,
+			revision,
+			partial_descriptors: (0..count)
+				.map(|_| PartialResourceDescriptor::read(data, off, layout_64))
+				.collect::<SysResult<_>>()?,
+			layout_64,
+		})
+	}
+
+	fn write(&self, buf: &mut Vec<u8>) {
+		buf.extend_from_slice(&self.interface_type.raw().to_le_bytes());
+		buf.extend_from_slice(&self.bus_number.to_le_bytes());
+		buf.extend_from_slice(&self.version.to_le_bytes());
+		buf.extend_from_slice(&self.revision.to_le_bytes());
+		buf.extend_from_slice(&(self.partial_descriptors.len() as u32).to_le_bytes());
+		self.partial_descriptors
+			.iter()
+			.for_each(|d| d.write(buf, self.layout_64));
+	}
+}
+
+/// Owned representation of a
+/// [`CM_PARTIAL_RESOURCE_DESCRIPTOR`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_cm_partial_resource_descriptor)
+/// struct.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialResourceDescriptor {
+	/// How the resource can be shared.
+	pub share_disposition: co::CM_SHARE_DISPOSITION,
+	/// Flags whose meaning depends on the resource type, like
+	/// `CM_RESOURCE_PORT_IO` or `CM_RESOURCE_INTERRUPT_LATCHED`.
+	pub flags: u16,
+	/// The resource itself, which also determines the `Type` member.
+	pub resource: PartialResource,
+}
+
+impl PartialResourceDescriptor {
+	fn read(data: &[u8], off: &mut usize, layout_64: bool) -> SysResult<Self> {
+		let at = *off;
+		let union_len = if layout_64 { PARTIAL_UNION_64 } else { PARTIAL_UNION_32 };
+		let head = data
+			.get(at..at + 4 + union_len)
+			.ok_or(co::ERROR::INVALID_DATA)?;
+		let resource_type = unsafe { co::CM_RESOURCE_TYPE::from_raw(head[0]) };
+		let share_disposition = unsafe { co::CM_SHARE_DISPOSITION::from_raw(head[1]) };
+		let flags = read_word(head, 2)?;
+		let u = &head[4..];
+		*off = at + head.len();
+
+		let resource = match resource_type {
+			co::CM_RESOURCE_TYPE::Port => PartialResource::Port {
+				start: read_qword(u, 0)? as _,
+				length: read_dword(u, 8)?,
+			},
+			co::CM_RESOURCE_TYPE::Interrupt => PartialResource::Interrupt {
+				level: read_word(u, 0)?,
+				group: read_word(u, 2)?,
+				vector: read_dword(u, 4)?,
+				affinity: if layout_64 { read_qword(u, 8)? } else { read_dword(u, 8)? as _ },
+			},
+			co::CM_RESOURCE_TYPE::Memory => PartialResource::Memory {
+				start: read_qword(u, 0)? as _,
+				length: read_dword(u, 8)?,
+			},
+			co::CM_RESOURCE_TYPE::Dma => PartialResource::Dma {
+				channel: read_dword(u, 0)?,
+				port: read_dword(u, 4)?,
+			},
+			co::CM_RESOURCE_TYPE::DeviceSpecific => {
+				let data_size = read_dword(u, 0)? as usize; // the data follows the descriptor
+				let dev_data = data
+					.get(*off..)
+					.and_then(|rest| rest.get(..data_size))
+					.ok_or(co::ERROR::INVALID_DATA)?;
+				*off += data_size;
+				PartialResource::DeviceSpecific(dev_data.to_vec())
+			},
+			co::CM_RESOURCE_TYPE::BusNumber => PartialResource::BusNumber {
+				start: read_dword(u, 0)?,
+				length: read_dword(u, 4)?,
+			},
+			co::CM_RESOURCE_TYPE::MemoryLarge => PartialResource::MemoryLarge {
+				start: read_qword(u, 0)? as _,
+				length: read_dword(u, 8)?,
+			},
+			_ => PartialResource::Other { resource_type, data: u.to_vec() },
+		};
+
+		Ok(Self { share_disposition, flags, resource })
+	}
+
+	fn write(&self, buf: &mut Vec<u8>, layout_64: bool) {
+		buf.push(self.resource.resource_type().raw());
+		buf.push(self.share_disposition.raw());
+		buf.extend_from_slice(&self.flags.to_le_bytes());
+
+		let mut u = Vec::with_capacity(PARTIAL_UNION_64);
+		match &self.resource {
+			PartialResource::Port { start, length }
+			| PartialResource::Memory { start, length }
+			| PartialResource::MemoryLarge { start, length } => {
+				u.extend_from_slice(&start.to_le_bytes());
+				u.extend_from_slice(&length.to_le_bytes());
+			},
+			PartialResource::Interrupt { level, group, vector, affinity } => {
+				u.extend_from_slice(&level.to_le_bytes());
+				u.extend_from_slice(&group.to_le_bytes());
+				u.extend_from_slice(&vector.to_le_bytes());
+				if layout_64 {
+					u.extend_from_slice(&affinity.to_le_bytes());
+				} else {
+					u.extend_from_slice(&(*affinity as u32).to_le_bytes());
+				}
+			},
+			PartialResource::Dma { channel, port } => {
+				u.extend_from_slice(&channel.to_le_bytes());
+				u.extend_from_slice(&port.to_le_bytes());
+			},
+			PartialResource::DeviceSpecific(data) => {
+				u.extend_from_slice(&(data.len() as u32).to_le_bytes());
+			},
+			PartialResource::BusNumber { start, length } => {
+				u.extend_from_slice(&start.to_le_bytes());
+				u.extend_from_slice(&length.to_le_bytes());
+			},
+			PartialResource::Other { data, .. } => u.extend_from_slice(data),
+		}
+		u.resize(if layout_64 { PARTIAL_UNION_64 } else { PARTIAL_UNION_32 }, 0x00);
+		buf.extend_from_slice(&u);
+
+		if let PartialResource::DeviceSpecific(data) = &self.resource {
+			buf.extend_from_slice(data);
+		}
+	}
+}
+
+/// The resource of a
+/// [`PartialResourceDescriptor`](crate::PartialResourceDescriptor), which is
+/// the `u` union of
+/// [`CM_PARTIAL_RESOURCE_DESCRIPTOR`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_cm_partial_resource_descriptor).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PartialResource {
+	/// [`CM_RESOURCE_TYPE::Port`](crate::co::CM_RESOURCE_TYPE::Port) range.
+	Port { start: i64, length: u32 },
+	/// [`CM_RESOURCE_TYPE::Interrupt`](crate::co::CM_RESOURCE_TYPE::Interrupt)
+	/// vector. Message-signaled interrupts have the same layout.
+	Interrupt { level: u16, group: u16, vector: u32, affinity: u64 },
+	/// [`CM_RESOURCE_TYPE::Memory`](crate::co::CM_RESOURCE_TYPE::Memory)
+	/// range.
+	Memory { start: i64, length: u32 },
+	/// [`CM_RESOURCE_TYPE::Dma`](crate::co::CM_RESOURCE_TYPE::Dma) channel.
+	Dma { channel: u32, port: u32 },
+	/// [`CM_RESOURCE_TYPE::DeviceSpecific`](crate::co::CM_RESOURCE_TYPE::DeviceSpecific)
+	/// data, which follows the descriptor.
+	DeviceSpecific(Vec<u8>),
+	/// [`CM_RESOURCE_TYPE::BusNumber`](crate::co::CM_RESOURCE_TYPE::BusNumber)
+	/// range.
+	BusNumber { start: u32, length: u32 },
+	/// [`CM_RESOURCE_TYPE::MemoryLarge`](crate::co::CM_RESOURCE_TYPE::MemoryLarge)
+	/// range; `length` must be shifted according to the
+	/// `CM_RESOURCE_MEMORY_LARGE_*` flags.
+	MemoryLarge { start: i64, length: u32 },
+	/// Any other resource type, with the raw bytes of the union.
+	Other { resource_type: co::CM_RESOURCE_TYPE, data: Vec<u8> },
+}
+
+impl PartialResource {
+	/// Returns the correspondent
+	/// [`co::CM_RESOURCE_TYPE`](crate::co::CM_RESOURCE_TYPE) constant.
+	#[must_use]
+	pub const fn resource_type(&self) -> co::CM_RESOURCE_TYPE {
+		match self {
+			Self::Port { .. } => co::CM_RESOURCE_TYPE::Port,
+			Self::Interrupt { .. } => co::CM_RESOURCE_TYPE::Interrupt,
+			Self::Memory { .. } => co::CM_RESOURCE_TYPE::Memory,
+			Self::Dma { .. } => co::CM_RESOURCE_TYPE::Dma,
+			Self::DeviceSpecific(_) => co::CM_RESOURCE_TYPE::DeviceSpecific,
+			Self::BusNumber { .. } => co::CM_RESOURCE_TYPE::BusNumber,
+			Self::MemoryLarge { .. } => co::CM_RESOURCE_TYPE::MemoryLarge,
+			Self::Other { resource_type, .. } => *resource_type,
+		}
+	}
+}
+
+/// Owned representation of an
+/// [`IO_RESOURCE_REQUIREMENTS_LIST`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_io_resource_requirements_list)
+/// struct, the data of a
+/// [`REG::RESOURCE_REQUIREMENTS_LIST`](crate::co::REG::RESOURCE_REQUIREMENTS_LIST)
+/// value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResourceRequirementsList {
+	/// Bus type.
+	pub interface_type: co::INTERFACE_TYPE,
+	/// Zero-based number of the bus, for the given bus type.
+	pub bus_number: u32,
+	/// Slot number of the device on the bus.
+	pub slot_number: u32,
+	/// Alternative sets of resources, any of which can be assigned to the
+	/// device.
+	pub alternative_lists: Vec<IoResourceList>,
+	layout_64: bool,
+}
+
+impl Default for ResourceRequirementsList {
+	/// Creates an empty list with the layout of the current process.
+	fn default() -> Self {
+		Self::new(cfg!(target_pointer_width = "64"))
+	}
+}
+
+impl ResourceRequirementsList {
+	/// Creates an empty list whose interrupt descriptors are written with the
+	/// 64-bit layout, where `TargetedProcessors` is 8 bytes long, or the 32-bit
+	/// one, where it's 4 bytes long.
+	#[must_use]
+	pub const fn new(layout_64: bool) -> Self {
+		Self {
+			interface_type: co::INTERFACE_TYPE::Internal,
+			bus_number: 0,
+			slot_number: 0,
+			alternative_lists: Vec::new(),
+			layout_64,
+		}
+	}
+
+	/// Returns whether the interrupt descriptors are written with the 64-bit
+	/// layout, either given when parsing or to
+	/// [`new`](crate::ResourceRequirementsList::new).
+	#[must_use]
+	pub const fn is_layout_64(&self) -> bool {
+		self.layout_64
+	}
+
+	/// Parses the raw data of an `IO_RESOURCE_REQUIREMENTS_LIST`, written with
+	/// the layout of the current process.
+	///
+	/// Both layouts have the same size, so the layout can't be detected; use
+	/// [`parse_with_layout`](crate::ResourceRequirementsList::parse_with_layout)
+	/// for data written by the other architecture.
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(data: &[u8]) -> SysResult<Self> {
+		Self::parse_with_layout(data, cfg!(target_pointer_width = "64"))
+	}
+
+	/// Parses the raw data of an `IO_RESOURCE_REQUIREMENTS_LIST`, written with
+	/// the 64-bit or the 32-bit layout.
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse_with_layout(data: &[u8], layout_64: bool) -> SysResult<Self> {
+		// ListSize, at offset 0, is recomputed when serializing.
+		let interface_type = unsafe { co::INTERFACE_TYPE::from_raw(read_dword(data, 4)? as _) };
+		let bus_number = read_dword(data, 8)?;
+		let slot_number = read_dword(data, 12)?;
+		let count = read_dword(data, 28)?; // after 3 reserved DWORDs
+		let mut off = 32;
+		Ok(Self {
+			interface_type,
+			bus_number,
+			slot_number,
+			alternative_lists: (0..count)
+				.map(|_| IoResourceList::read(data, &mut off, layout_64))
+				.collect::<SysResult<_>>()?,
+			layout_64,
+		})
+	}
+
+	/// Serializes into the raw data of an `IO_RESOURCE_REQUIREMENTS_LIST`.
+	#[must_use]
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut buf = Vec::new();
+		buf.extend_from_slice(&0u32.to_le_bytes()); // ListSize, written at the end
+		buf.extend_from_slice(&self.interface_type.raw().to_le_bytes());
+		buf.extend_from_slice(&self.bus_number.to_le_bytes());
+		buf.extend_from_slice(&self.slot_number.to_le_bytes());
+		buf.extend_from_slice(&[0x00; 3 * std::mem::size_of::<u32>()]);
+		buf.extend_from_slice(&(self.alternative_lists.len() as u32).to_le_bytes());
+		self.alternative_lists
+			.iter()
+			.for_each(|l| l.write(&mut buf, self.layout_64));
+
+		let list_size = (buf.len() as u32).to_le_bytes();
+		buf[..list_size.len()].copy_from_slice(&list_size);
+		buf
+	}
+}
+
+/// Owned representation of an
+/// [`IO_RESOURCE_LIST`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_io_resource_list)
+/// struct.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IoResourceList {
+	/// Version of the list.
+	pub version: u16,
+	/// Revision of the list.
+	pub revision: u16,
+	/// The resources which make up this alternative.
+	pub descriptors: Vec<IoResourceDescriptor>,
+}
+
+impl IoResourceList {
+	fn read(data: &[u8], off: &mut usize, layout_64: bool) -> SysResult<Self> {
+		let at = *off;
+		let version = read_word(data, at)?;
+		let revision = read_word(data, at + 2)?;
+		let count = read_dword(data, at + 4)?;
+		*off = at + 8;
+		Ok(Self {
+			version,
+			revision,
+			descriptors: (0..count)
+				.map(|_| IoResourceDescriptor::read(data, off, layout_64))
+				.collect::<SysResult<_>>()?,
+		})
+	}
+
+	fn write(&self, buf: &mut Vec<u8>, layout_64: bool) {
+		buf.extend_from_slice(&self.version.to_le_bytes());
+		buf.extend_from_slice(&self.revision.to_le_bytes());
+		buf.extend_from_slice(&(self.descriptors.len() as u32).to_le_bytes());
+		self.descriptors
+			.iter()
+			.for_each(|d| d.write(buf, layout_64));
+	}
+}
+
+/// Owned representation of an
+/// [`IO_RESOURCE_DESCRIPTOR`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_io_resource_descriptor)
+/// struct.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IoResourceDescriptor {
+	/// Whether the resource is preferred or an alternative.
+	pub option: co::IO_RESOURCE,
+	/// How the resource can be shared.
+	pub share_disposition: co::CM_SHARE_DISPOSITION,
+	/// Flags whose meaning depends on the resource type, the same of
+	/// [`PartialResourceDescriptor`](crate::PartialResourceDescriptor).
+	pub flags: u16,
+	/// The resource range itself, which also determines the `Type` member.
+	pub resource: IoResource,
+}
+
+impl IoResourceDescriptor {
+	fn read(data: &[u8], off: &mut usize, layout_64: bool) -> SysResult<Self> {
+		let at = *off;
+		let head = data
+			.get(at..at + 8 + IO_UNION)
+			.ok_or(co::ERROR::INVALID_DATA)?;
+		let option = unsafe { co::IO_RESOURCE::from_raw(head[0]) };
+		let resource_type = unsafe { co::CM_RESOURCE_TYPE::from_raw(head[1]) };
+		let share_disposition = unsafe { co::CM_SHARE_DISPOSITION::from_raw(head[2]) };
+		let flags = read_word(head, 4)?; // after Spare1; followed by Spare2
+		let u = &head[8..];
+		*off = at + head.len();
+
+		let resource = match resource_type {
+			co::CM_RESOURCE_TYPE::Port => IoResource::Port {
+				length: read_dword(u, 0)?,
+				alignment: read_dword(u, 4)?,
+				minimum_address: read_qword(u, 8)? as _,
+				maximum_address: read_qword(u, 16)? as _,
+			},
+			co::CM_RESOURCE_TYPE::Interrupt => IoResource::Interrupt {
+				minimum_vector: read_dword(u, 0)?,
+				maximum_vector: read_dword(u, 4)?,
+				affinity_policy: read_word(u, 8)?,
+				group: read_word(u, 10)?,
+				priority_policy: read_dword(u, 12)?,
+				targeted_processors: if layout_64 {
+					read_qword(u, 16)?
+				} else {
+					read_dword(u, 16)? as _
+				},
+			},
+			co::CM_RESOURCE_TYPE::Memory => IoResource::Memory {
+				length: read_dword(u, 0)?,
+				alignment: read_dword(u, 4)?,
+				minimum_address: read_qword(u, 8)? as _,
+				maximum_address: read_qword(u, 16)? as _,
+			},
+			co::CM_RESOURCE_TYPE::Dma => IoResource::Dma {
+				minimum_channel: read_dword(u, 0)?,
+				maximum_channel: read_dword(u, 4)?,
+			},
+			co::CM_RESOURCE_TYPE::BusNumber => IoResource::BusNumber {
+				length: read_dword(u, 0)?,
+				min_bus_number: read_dword(u, 4)?,
+				max_bus_number: read_dword(u, 8)?,
+			},
+			co::CM_RESOURCE_TYPE::MemoryLarge => IoResource::MemoryLarge {
+				length: read_dword(u, 0)?,
+				alignment: read_dword(u, 4)?,
+				minimum_address: read_qword(u, 8)? as _,
+				maximum_address: read_qword(u, 16)? as _,
+			},
+			_ => IoResource::Other {
+				resource_type,
+				data: u.try_into().unwrap(),
+			},
+		};
+
+		Ok(Self {
+			option,
+			share_disposition,
+			flags,
+			resource,
+		})
+	}
+
+	fn write(&self, buf: &mut Vec<u8>, layout_64: bool) {
+		buf.push(self.option.raw());
+		buf.push(self.resource.resource_type().raw());
+		buf.push(self.share_disposition.raw());
+		buf.push(0x00); // Spare1
+		buf.extend_from_slice(&self.flags.to_le_bytes());
+		buf.extend_from_slice(&0u16.to_le_bytes()); // Spare2
+
+		let mut u = Vec::with_capacity(IO_UNION);
+		match &self.resource {
+			IoResource::Port {
+				length,
+				alignment,
+				minimum_address,
+				maximum_address,
+			}
+			| IoResource::Memory {
+				length,
+				alignment,
+				minimum_address,
+				maximum_address,
+			}
+			| IoResource::MemoryLarge {
+				length,
+				alignment,
+				minimum_address,
+				maximum_address,
+			} => {
+				u.extend_from_slice(&length.to_le_bytes());
+				u.extend_from_slice(&alignment.to_le_bytes());
+				u.extend_from_slice(&minimum_address.to_le_bytes());
+				u.extend_from_slice(&maximum_address.to_le_bytes());
+			},
+			IoResource::Interrupt {
+				minimum_vector,
+				maximum_vector,
+				affinity_policy,
+				group,
+				priority_policy,
+				targeted_processors,
+			} => {
+				u.extend_from_slice(&minimum_vector.to_le_bytes());
+				u.extend_from_slice(&maximum_vector.to_le_bytes());
+				u.extend_from_slice(&affinity_policy.to_le_bytes());
+				u.extend_from_slice(&group.to_le_bytes());
+				u.extend_from_slice(&priority_policy.to_le_bytes());
+				if layout_64 {
+					u.extend_from_slice(&targeted_processors.to_le_bytes());
+				} else {
+					u.extend_from_slice(&(*targeted_processors as u32).to_le_bytes());
+				}
+			},
+			IoResource::Dma { minimum_channel, maximum_channel } => {
+				u.extend_from_slice(&minimum_channel.to_le_bytes());
+				u.extend_from_slice(&maximum_channel.to_le_bytes());
+			},
+			IoResource::BusNumber { length, min_bus_number, max_bus_number } => {
+				u.extend_from_slice(&length.to_le_bytes());
+				u.extend_from_slice(&min_bus_number.to_le_bytes());
+				u.extend_from_slice(&max_bus_number.to_le_bytes());
+			},
+			IoResource::Other { data, .. } => u.extend_from_slice(data),
+		}
+		u.resize(IO_UNION, 0x00);
+		buf.extend_from_slice(&u);
+	}
+}
+
+/// The resource range of an
+/// [`IoResourceDescriptor`](crate::IoResourceDescriptor), which is the `u`
+/// union of
+/// [`IO_RESOURCE_DESCRIPTOR`](https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/wdm/ns-wdm-_io_resource_descriptor).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IoResource {
+	/// [`CM_RESOURCE_TYPE::Port`](crate::co::CM_RESOURCE_TYPE::Port) range.
+	Port { length: u32, alignment: u32, minimum_address: i64, maximum_address: i64 },
+	/// [`CM_RESOURCE_TYPE::Interrupt`](crate::co::CM_RESOURCE_TYPE::Interrupt)
+	/// range.
+	Interrupt {
+		minimum_vector: u32,
+		maximum_vector: u32,
+		affinity_policy: u16,
+		group: u16,
+		priority_policy: u32,
+		targeted_processors: u64,
+	},
+	/// [`CM_RESOURCE_TYPE::Memory`](crate::co::CM_RESOURCE_TYPE::Memory)
+	/// range.
+	Memory { length: u32, alignment: u32, minimum_address: i64, maximum_address: i64 },
+	/// [`CM_RESOURCE_TYPE::Dma`](crate::co::CM_RESOURCE_TYPE::Dma) channel
+	/// range.
+	Dma { minimum_channel: u32, maximum_channel: u32 },
+	/// [`CM_RESOURCE_TYPE::BusNumber`](crate::co::CM_RESOURCE_TYPE::BusNumber)
+	/// range.
+	BusNumber { length: u32, min_bus_number: u32, max_bus_number: u32 },
+	/// [`CM_RESOURCE_TYPE::MemoryLarge`](crate::co::CM_RESOURCE_TYPE::MemoryLarge)
+	/// range; `length` must be shifted according to the
+	/// `CM_RESOURCE_MEMORY_LARGE_*` flags.
+	MemoryLarge { length: u32, alignment: u32, minimum_address: i64, maximum_address: i64 },
+	/// Any other resource type, with the raw bytes of the union.
+	Other { resource_type: co::CM_RESOURCE_TYPE, data: [u8; IO_UNION] },
+}
+
+impl IoResource {
+	/// Returns the correspondent
+	/// [`co::CM_RESOURCE_TYPE`](crate::co::CM_RESOURCE_TYPE) constant.
+	#[must_use]
+	pub const fn resource_type(&self) -> co::CM_RESOURCE_TYPE {
+		match self {
+			Self::Port { .. } => co::CM_RESOURCE_TYPE::Port,
+			Self::Interrupt { .. } => co::CM_RESOURCE_TYPE::Interrupt,
+			Self::Memory { .. } => co::CM_RESOURCE_TYPE::Memory,
+			Self::Dma { .. } => co::CM_RESOURCE_TYPE::Dma,
+			Self::BusNumber { .. } => co::CM_RESOURCE_TYPE::BusNumber,
+			Self::MemoryLarge { .. } => co::CM_RESOURCE_TYPE::MemoryLarge,
+			Self::Other { resource_type, .. } => *resource_type,
+		}
+	}
+}
+
+/// Parses the data with the 64-bit and the 32-bit layouts of
+/// `CM_PARTIAL_RESOURCE_DESCRIPTOR`, native one first, keeping the one which
+/// consumes the whole data.
+fn parse_any_layout<T>(
+	data: &[u8],
+	read: impl Fn(&[u8], &mut usize, bool) -> SysResult<T>,
+) -> SysResult<T> {
+	let native_64 = cfg!(target_pointer_width = "64");
+	[native_64, !native_64]
+		.iter()
+		.find_map(|layout_64| {
+			let mut off = 0;
+			read(data, &mut off, *layout_64)
+				.ok()
+				.filter(|_| off == data.len())
+		})
+		.ok_or(co::ERROR::INVALID_DATA)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn full_descriptor_layouts() {
+		for layout_64 in [false, true] {
+			let mut full = FullResourceDescriptor::new(layout_64);
+			full.partial_descriptors.push(PartialResourceDescriptor {
+				share_disposition: co::CM_SHARE_DISPOSITION::DeviceExclusive,
+				flags: 0,
+				resource: PartialResource::Interrupt {
+					level: 5,
+					group: 0,
+					vector: 0x35,
+					affinity: 0xffff_ffff,
+				},
+			});
+			let data = full.serialize();
+			assert_eq!(data.len(), 16 + if layout_64 { 20 } else { 16 });
+
+			let parsed = FullResourceDescriptor::parse(&data).unwrap();
+			assert_eq!(parsed.is_layout_64(), layout_64);
+			assert_eq!(parsed, full);
+		}
+	}
+
+	#[test]
+	fn io_interrupt_layouts() {
+		#[rustfmt::skip]
+		const UNION_32: [u8; IO_UNION] = [
+			0x10, 0, 0, 0, // MinimumVector
+			0x20, 0, 0, 0, // MaximumVector
+			0x01, 0, // AffinityPolicy
+			0x00, 0, // Group
+			0x02, 0, 0, 0, // PriorityPolicy
+			0x0f, 0, 0, 0, // TargetedProcessors
+			0xcc, 0xcc, 0xcc, 0xcc, // padding, must not be read
+		];
+
+		let mut data = Vec::new();
+		data.extend_from_slice(&0u32.to_le_bytes()); // ListSize
+		data.extend_from_slice(&co::INTERFACE_TYPE::Internal.raw().to_le_bytes());
+		data.extend_from_slice(&[0x00; 5 * 4]); // BusNumber, SlotNumber, Reserved
+		data.extend_from_slice(&1u32.to_le_bytes()); // AlternativeLists
+		data.extend_from_slice(&[1, 0, 1, 0, 1, 0, 0, 0]); // Version, Revision, Count
+		data.extend_from_slice(&[
+			co::IO_RESOURCE::PREFERRED.raw(),
+			co::CM_RESOURCE_TYPE::Interrupt.raw(),
+			co::CM_SHARE_DISPOSITION::Shared.raw(),
+			0x00, // Spare1
+		]);
+		data.extend_from_slice(&[0x00; 4]); // Flags, Spare2
+		data.extend_from_slice(&UNION_32);
+
+		let list = ResourceRequirementsList::parse_with_layout(&data, false).unwrap();
+		assert!(!list.is_layout_64());
+		let interrupt = &list.alternative_lists[0].descriptors[0].resource;
+		assert_eq!(
+			*interrupt,
+			IoResource::Interrupt {
+				minimum_vector: 0x10,
+				maximum_vector: 0x20,
+				affinity_policy: 1,
+				group: 0,
+				priority_policy: 2,
+				targeted_processors: 0x0f,
+			},
+		);
+
+		let reparsed =
+			ResourceRequirementsList::parse_with_layout(&list.serialize(), false).unwrap();
+		assert_eq!(reparsed, list);
+
+		let mut list_64 = list.clone();
+		list_64.layout_64 = true;
+		let reparsed_64 =
+			ResourceRequirementsList::parse_with_layout(&list_64.serialize(), true).unwrap();
+		assert_eq!(reparsed_64, list_64);
+	}
+
+	#[test]
+	fn resource_list_device_specific() {
+		for layout_64 in [false, true] {
+			let mut full = FullResourceDescriptor::new(layout_64);
+			full.partial_descriptors = vec![
+				PartialResourceDescriptor {
+					share_disposition: co::CM_SHARE_DISPOSITION::DeviceExclusive,
+					flags: 1,
+					resource: PartialResource::Port { start: 0x3f8, length: 8 },
+				},
+				PartialResourceDescriptor {
+					share_disposition: co::CM_SHARE_DISPOSITION::Undetermined,
+					flags: 0,
+					resource: PartialResource::DeviceSpecific(vec![0xaa, 0xbb, 0xcc]),
+				},
+			];
+			let list = ResourceList { descriptors: vec![full] };
+			let data = list.serialize();
+
+			let parsed = ResourceList::parse(&data).unwrap();
+			assert_eq!(parsed, list);
+			assert_eq!(parsed.serialize(), data);
+
+			assert_eq!(
+				ResourceList::parse(&data[..data.len() - 1]),
+				Err(co::ERROR::INVALID_DATA), // truncated device-specific data
+			);
+		}
+	}
+}
@@ -0,0 +1,40 @@
+#![allow(non_camel_case_types, non_snake_case)]
+
+use crate::co;
+use crate::decl::*;
+use crate::kernel::{ffi_types::*, privs::*};
+use crate::ole::privs::*;
+
+impl HINSTANCE {
+	/// Calls the
+	/// [`DllGetVersion`](https://learn.microsoft.com/en-us/windows/win32/api/shlwapi/nc-shlwapi-dllgetversionproc)
+	/// function exported by the module.
+	///
+	/// Fails with
+	/// [`co::HRESULT::E_NOTIMPL`](crate::co::HRESULT::E_NOTIMPL) if the
+	/// module doesn't export it.
+	///
+	/// # Examples
+	///
+	/// Checking whether ComCtl32 version 6 – which requires an application
+	/// manifest – is loaded:
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let hcomctl = w::HINSTANCE::GetModuleHandle(Some("comctl32.dll"))?;
+	/// let ver = hcomctl.DllGetVersion()?;
+	/// println!("ComCtl32 v6: {}", ver.dwMajorVersion >= 6);
+	/// # w::AnyResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn DllGetVersion(&self) -> HrResult<DLLVERSIONINFO> {
+		let proc = self
+			.GetProcAddress("DllGetVersion")
+			.map_err(|_| co::HRESULT::E_NOTIMPL)?;
+		let func: extern "system" fn(PVOID) -> HRES = unsafe { std::mem::transmute(proc) };
+
+		let mut dvi = DLLVERSIONINFO::default();
+		HrRet(func(pvoid(&mut dvi))).to_hrresult().map(|_| dvi)
+	}
+}
@@ -1,4 +1,5 @@
 mod himagelist;
+mod hinstance;
 mod hpropsheetpage;
 mod hwnd;
 
@@ -54,6 +54,19 @@ pub struct DATETIMEPICKERINFO {
 
 impl_default!(DATETIMEPICKERINFO, cbSize);
 
+/// [`DLLVERSIONINFO`](https://learn.microsoft.com/en-us/windows/win32/api/shlwapi/ns-shlwapi-dllversioninfo)
+/// struct.
+#[repr(C)]
+pub struct DLLVERSIONINFO {
+	cbSize: u32,
+	pub dwMajorVersion: u32,
+	pub dwMinorVersion: u32,
+	pub dwBuildNumber: u32,
+	pub dwPlatformID: u32,
+}
+
+impl_default!(DLLVERSIONINFO, cbSize);
+
 /// [`EDITBALLOONTIP`](https://learn.microsoft.com/en-us/windows/win32/api/commctrl/ns-commctrl-editballoontip)
 /// struct.
 #[repr(C)]
@@ -16,6 +16,7 @@ const_ordinary! { BI: u32;
 	BITFIELDS 3
 	JPEG 4
 	PNG 5
+	ALPHABITFIELDS 6
 }
 
 const_ordinary! { BKMODE: i32;
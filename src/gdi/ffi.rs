@@ -17,6 +17,7 @@ extern_sys! { "gdi32";
 	CreateBrushIndirect(PCVOID) -> HANDLE
 	CreateCompatibleBitmap(HANDLE, i32, i32) -> HANDLE
 	CreateCompatibleDC(HANDLE) -> HANDLE
+	CreateDIBSection(HANDLE, PCVOID, u32, PVOID, HANDLE, u32) -> HANDLE
 	CreateFontIndirectW(PCVOID) -> HANDLE
 	CreateFontW(i32, i32, i32, i32, i32, u32, u32, u32, u32, u32, u32, u32, u32, PCSTR) -> HANDLE
 	CreateHalftonePalette(HANDLE) -> HANDLE
@@ -127,5 +128,6 @@ extern_sys! { "msimg32";
 }
 
 extern_sys! { "user32";
+	CreateIconFromResourceEx(PCVOID, u32, BOOL, u32, i32, i32, u32) -> HANDLE
 	LoadImageW(HANDLE, PCSTR, u32, i32, i32, u32) -> HANDLE // returns GdiObjectGuard, so needs gdi feature
 }
@@ -200,6 +200,36 @@ impl HDC {
 		}
 	}
 
+	/// [`CreateDIBSection`](https://learn.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-createdibsection)
+	/// function.
+	///
+	/// Returns the bitmap along with a pointer to its bits, which are valid
+	/// while the bitmap exists. The size of the bits buffer is determined by
+	/// the dimensions and color depth in `bmi`.
+	///
+	/// For an example, see
+	/// [`DibImage::to_hbitmap`](crate::DibImage::to_hbitmap).
+	#[must_use]
+	pub fn CreateDIBSection(
+		&self,
+		bmi: &BITMAPINFO,
+		usage: co::DIB,
+	) -> SysResult<(DeleteObjectGuard<HBITMAP>, *mut u8)> {
+		let mut bits = std::ptr::null_mut::<std::ffi::c_void>();
+		unsafe {
+			PtrRet(ffi::CreateDIBSection(
+				self.ptr(),
+				pcvoid(bmi),
+				usage.raw(),
+				pvoid(&mut bits),
+				std::ptr::null_mut(),
+				0,
+			))
+			.to_invalidparm_handle()
+			.map(|h| (DeleteObjectGuard::new(h), bits as _))
+		}
+	}
+
 	/// [`CreateHalftonePalette`](https://learn.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-createhalftonepalette)
 	/// function.
 	#[must_use]
@@ -0,0 +1,39 @@
+#![allow(non_camel_case_types, non_snake_case)]
+
+use crate::co;
+use crate::decl::*;
+use crate::gdi::ffi;
+use crate::guard::*;
+use crate::kernel::privs::*;
+
+impl HICON {
+	/// [`CreateIconFromResourceEx`](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createiconfromresourceex)
+	/// function.
+	///
+	/// `res_bits` is the data of a single icon image, either a DIB or a PNG,
+	/// as stored in an [`RT::ICON`](crate::co::RT::ICON) resource or in an
+	/// [`IconImage`](crate::IconImage). If `is_icon` is `false`, a cursor is
+	/// created, and `res_bits` must start with the two `u16` hotspot
+	/// coordinates.
+	#[must_use]
+	pub fn CreateIconFromResourceEx(
+		res_bits: &[u8],
+		is_icon: bool,
+		sz: SIZE,
+		flags: co::LR,
+	) -> SysResult<DestroyIconGuard> {
+		unsafe {
+			PtrRet(ffi::CreateIconFromResourceEx(
+				vec_ptr(res_bits) as _,
+				res_bits.len() as _,
+				is_icon as _,
+				0x0003_0000,
+				sz.cx,
+				sz.cy,
+				flags.raw(),
+			))
+			.to_sysresult_handle()
+			.map(|h| DestroyIconGuard::new(h))
+		}
+	}
+}
@@ -2,6 +2,7 @@ mod hbitmap;
 mod hbrush;
 mod hdc;
 mod hfont;
+mod hicon;
 mod hinstance;
 mod hpalette;
 mod hpen;
@@ -4,6 +4,7 @@ mod enums;
 mod funcs;
 mod handles;
 mod structs;
+mod utilities;
 
 pub mod co;
 pub(in crate::gdi) mod ffi;
@@ -17,4 +18,5 @@ pub mod decl {
 	pub use super::funcs::*;
 	pub use super::handles::decl::*;
 	pub use super::structs::*;
+	pub use super::utilities::*;
 }
@@ -0,0 +1,681 @@
+use crate::co;
+use crate::decl::*;
+use crate::guard::*;
+use crate::kernel::bytes::*;
+
+/// Size of `BITMAPFILEHEADER`.
+const FILE_HEADER_SZ: usize = 14;
+/// Size of `BITMAPCOREHEADER`, the OS/2 header.
+const CORE_HEADER_SZ: usize = 12;
+/// Size of `BITMAPINFOHEADER`.
+const INFO_HEADER_SZ: usize = 40;
+/// Limit of pixels accepted by the decoder, which is 1 GB of BGRA data.
+const MAX_PIXELS: usize = 1 << 28;
+
+/// Decoded device-independent bitmap, which can be read from and written to
+/// `.bmp` files and packed DIBs, and converted to and from
+/// [`HBITMAP`](crate::HBITMAP).
+///
+/// The decoder supports the `BITMAPCOREHEADER`, `BITMAPINFOHEADER`,
+/// `BITMAPV4HEADER` and `BITMAPV5HEADER` headers; 1, 4, 8, 16, 24 and 32 bits
+/// per pixel; top-down and bottom-up row order; and the
+/// [`BI::RGB`](crate::co::BI::RGB), [`BI::RLE8`](crate::co::BI::RLE8),
+/// [`BI::RLE4`](crate::co::BI::RLE4),
+/// [`BI::BITFIELDS`](crate::co::BI::BITFIELDS) and
+/// [`BI::ALPHABITFIELDS`](crate::co::BI::ALPHABITFIELDS) compressions. The
+/// encoder writes 24-bit images, or 32-bit if any pixel is not opaque.
+///
+/// Parsing and serializing make no system calls.
+///
+/// # Examples
+///
+/// Taking a screenshot and saving to file:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let cx_screen = w::GetSystemMetrics(co::SM::CXSCREEN);
+/// let cy_screen = w::GetSystemMetrics(co::SM::CYSCREEN);
+///
+/// let hdc_screen = w::HWND::DESKTOP.GetDC()?;
+/// let hbmp = hdc_screen.CreateCompatibleBitmap(cx_screen, cy_screen)?;
+/// let hdc_mem = hdc_screen.CreateCompatibleDC()?;
+/// let _hbmp_guard = hdc_mem.SelectObject(&*hbmp)?;
+///
+/// hdc_mem.BitBlt(
+///     w::POINT::new(),
+///     w::SIZE::with(cx_screen, cy_screen),
+///     &hdc_screen,
+///     w::POINT::new(),
+///     co::ROP::SRCCOPY,
+/// )?;
+///
+/// let img = w::DibImage::from_hbitmap(&hbmp)?;
+///
+/// let fout = w::File::open("C:\\Temp\\screen.bmp", w::FileAccess::OpenOrCreateRW)?;
+/// fout.erase_and_write(&img.serialize_bmp())?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct DibImage {
+	/// Width of the image, in pixels.
+	pub width: u32,
+	/// Height of the image, in pixels.
+	pub height: u32,
+	/// Horizontal resolution, in pixels per meter. Zero if unspecified.
+	pub x_pels_per_meter: i32,
+	/// Vertical resolution, in pixels per meter. Zero if unspecified.
+	pub y_pels_per_meter: i32,
+	/// Pixels in top-down order, 4 bytes each, in the
+	/// [`RGBQUAD`](crate::RGBQUAD) layout: blue, green, red and alpha. The
+	/// alpha is not premultiplied.
+	pub bgra: Vec<u8>,
+}
+
+impl DibImage {
+	/// Parses the raw contents of a `.bmp` file, which starts with a
+	/// [`BITMAPFILEHEADER`](crate::BITMAPFILEHEADER).
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA), and JPEG
+	/// or PNG compression yields
+	/// [`co::ERROR::NOT_SUPPORTED`](crate::co::ERROR::NOT_SUPPORTED).
+	#[must_use]
+	pub fn parse_bmp(blob: &[u8]) -> SysResult<Self> {
+		if !blob.starts_with(b"BM") {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let bits_off = read_dword(blob, 10)? as usize;
+		let bits_off = bits_off
+			.checked_sub(FILE_HEADER_SZ)
+			.ok_or(co::ERROR::INVALID_DATA)?;
+		decode(&blob[FILE_HEADER_SZ..], Some(bits_off))
+	}
+
+	/// Parses a packed DIB: a bitmap header immediately followed by the color
+	/// table and the bits, as found in [`RT::BITMAP`](crate::co::RT::BITMAP)
+	/// resources and in the `CF_DIB` clipboard format.
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA), and JPEG
+	/// or PNG compression yields
+	/// [`co::ERROR::NOT_SUPPORTED`](crate::co::ERROR::NOT_SUPPORTED).
+	#[must_use]
+	pub fn parse_dib(blob: &[u8]) -> SysResult<Self> {
+		decode(blob, None)
+	}
+
+	/// Serializes the image into the raw contents of a `.bmp` file.
+	#[must_use]
+	pub fn serialize_bmp(&self) -> Vec<u8> {
+		let dib = self.serialize_dib();
+		let mut bfh = BITMAPFILEHEADER::default();
+		bfh.bfOffBits = (FILE_HEADER_SZ + INFO_HEADER_SZ) as _;
+		bfh.bfSize = (FILE_HEADER_SZ + dib.len()) as _;
+
+		let mut buf = Vec::<u8>::with_capacity(FILE_HEADER_SZ + dib.len());
+		buf.extend_from_slice(bfh.serialize());
+		buf.extend_from_slice(&dib);
+		buf
+	}
+
+	/// Serializes the image into a packed DIB, with a
+	/// [`BITMAPINFOHEADER`](crate::BITMAPINFOHEADER) and bottom-up rows.
+	#[must_use]
+	pub fn serialize_dib(&self) -> Vec<u8> {
+		let bit_count: usize = if self.is_opaque() { 24 } else { 32 };
+		let stride = row_stride(self.width as _, bit_count);
+
+		let mut bih = BITMAPINFOHEADER::default();
+		bih.biWidth = self.width as _;
+		bih.biHeight = self.height as _;
+		bih.biPlanes = 1;
+		bih.biBitCount = bit_count as _;
+		bih.biCompression = co::BI::RGB;
+		bih.biSizeImage = (stride * self.height as usize) as _;
+		bih.biXPelsPerMeter = self.x_pels_per_meter;
+		bih.biYPelsPerMeter = self.y_pels_per_meter;
+
+		let mut buf = Vec::<u8>::with_capacity(INFO_HEADER_SZ + bih.biSizeImage as usize);
+		buf.extend_from_slice(bih.serialize());
+		if self.width > 0 {
+			for row in self.bgra.chunks_exact(self.width as usize * 4).rev() {
+				let row_start = buf.len();
+				if bit_count == 32 {
+					buf.extend_from_slice(row);
+				} else {
+					row.chunks_exact(4)
+						.for_each(|px| buf.extend_from_slice(&px[..3]));
+				}
+				buf.resize(row_start + stride, 0);
+			}
+		}
+		buf
+	}
+
+	/// Creates an image from a bitmap, retrieving its pixels with
+	/// [`HDC::GetDIBits`](crate::HDC::GetDIBits).
+	///
+	/// Since most bitmaps don't have an alpha channel, if all alpha values
+	/// are zero, the image is made opaque.
+	#[must_use]
+	pub fn from_hbitmap(hbm: &HBITMAP) -> SysResult<Self> {
+		let bmp = hbm.GetObject()?;
+		let mut bi = BITMAPINFO::default();
+		bi.bmiHeader.biWidth = bmp.bmWidth;
+		bi.bmiHeader.biHeight = -bmp.bmHeight; // top-down
+		bi.bmiHeader.biPlanes = 1;
+		bi.bmiHeader.biBitCount = 32;
+		bi.bmiHeader.biCompression = co::BI::RGB;
+
+		let mut bgra = vec![0u8; bmp.bmWidth as usize * bmp.bmHeight as usize * 4];
+		let hdc = HWND::DESKTOP.GetDC()?;
+		unsafe {
+			hdc.GetDIBits(
+				hbm,
+				0,
+				bmp.bmHeight as _,
+				Some(&mut bgra),
+				&mut bi,
+				co::DIB::RGB_COLORS,
+			)?;
+		}
+
+		if bgra.chunks_exact(4).all(|px| px[3] == 0) {
+			bgra.chunks_exact_mut(4).for_each(|px| px[3] = 0xff);
+		}
+		Ok(Self {
+			width: bmp.bmWidth as _,
+			height: bmp.bmHeight as _,
+			x_pels_per_meter: 0,
+			y_pels_per_meter: 0,
+			bgra,
+		})
+	}
+
+	/// Creates a 32-bit bitmap with
+	/// [`HDC::CreateDIBSection`](crate::HDC::CreateDIBSection), and copies the
+	/// pixels into it.
+	///
+	/// # Examples
+	///
+	/// Loading a `.bmp` file:
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let fin = w::FileMapped::open("C:\\Temp\\image.bmp", w::FileAccess::ExistingReadOnly)?;
+	/// let img = w::DibImage::parse_bmp(fin.as_slice())?;
+	/// let hbmp = img.to_hbitmap()?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn to_hbitmap(&self) -> SysResult<DeleteObjectGuard<HBITMAP>> {
+		if self.bgra.len() != self.width as usize * self.height as usize * 4 {
+			return Err(co::ERROR::INVALID_PARAMETER);
+		}
+
+		let mut bi = BITMAPINFO::default();
+		bi.bmiHeader.biWidth = self.width as _;
+		bi.bmiHeader.biHeight = -(self.height as i32); // top-down
+		bi.bmiHeader.biPlanes = 1;
+		bi.bmiHeader.biBitCount = 32;
+		bi.bmiHeader.biCompression = co::BI::RGB;
+
+		let hdc = HWND::DESKTOP.GetDC()?;
+		let (hbm, bits) = hdc.CreateDIBSection(&bi, co::DIB::RGB_COLORS)?;
+		unsafe {
+			std::ptr::copy_nonoverlapping(self.bgra.as_ptr(), bits, self.bgra.len()); // 32-bit rows need no padding
+		}
+		Ok(hbm)
+	}
+
+	/// Tells whether all pixels are fully opaque.
+	#[must_use]
+	pub fn is_opaque(&self) -> bool {
+		self.bgra.chunks_exact(4).all(|px| px[3] == 0xff)
+	}
+}
+
+/// Masks of a bit-field pixel: red, green, blue and alpha.
+type Masks = [u32; 4];
+
+/// Decodes a DIB which starts with its header. If `bits_off` is not given,
+/// the bits are assumed to immediately follow the color table.
+fn decode(dib: &[u8], bits_off: Option<usize>) -> SysResult<DibImage> {
+	let header_sz = read_dword(dib, 0)? as usize;
+	let (width, height, bit_count, compression, x_ppm, y_ppm, clr_used) =
+		if header_sz == CORE_HEADER_SZ {
+			(
+				read_word(dib, 4)? as i32,
+				read_word(dib, 6)? as i32,
+				read_word(dib, 10)?,
+				co::BI::RGB,
+				0,
+				0,
+				0,
+			)
+		} else if header_sz >= INFO_HEADER_SZ {
+			(
+				read_dword(dib, 4)? as i32,
+				read_dword(dib, 8)? as i32,
+				read_word(dib, 14)?,
+				unsafe { co::BI::from_raw(read_dword(dib, 16)?) },
+				read_dword(dib, 24)? as i32,
+				read_dword(dib, 28)? as i32,
+				read_dword(dib, 32)? as usize,
+			)
+		} else {
+			return Err(co::ERROR::INVALID_DATA);
+		};
+
+	let top_down = height < 0;
+	let (width, height) = (width as usize, height.unsigned_abs() as usize);
+	if width == 0
+		|| height == 0
+		|| width > MAX_PIXELS
+		|| height > MAX_PIXELS
+		|| width * height > MAX_PIXELS
+	{
+		return Err(co::ERROR::INVALID_DATA);
+	}
+
+	let valid_depth = match compression {
+		co::BI::RGB => [1, 4, 8, 16, 24, 32].contains(&bit_count),
+		co::BI::RLE8 => bit_count == 8,
+		co::BI::RLE4 => bit_count == 4,
+		co::BI::BITFIELDS | co::BI::ALPHABITFIELDS => [16, 32].contains(&bit_count),
+		co::BI::JPEG | co::BI::PNG => return Err(co::ERROR::NOT_SUPPORTED),
+		_ => false,
+	};
+	if !valid_depth {
+		return Err(co::ERROR::INVALID_DATA);
+	}
+
+	// Bit-field masks follow a BITMAPINFOHEADER, or are part of larger headers.
+	let mut off = header_sz;
+	let mut masks: Option<Masks> = None;
+	if compression == co::BI::BITFIELDS || compression == co::BI::ALPHABITFIELDS {
+		let (masks_off, num_masks) = if header_sz > INFO_HEADER_SZ {
+			(INFO_HEADER_SZ, if header_sz >= INFO_HEADER_SZ + 16 { 4 } else { 3 })
+		} else {
+			let num_masks = if compression == co::BI::ALPHABITFIELDS { 4 } else { 3 };
+			off += num_masks * 4;
+			(INFO_HEADER_SZ, num_masks)
+		};
+		let mut m = [0u32; 4];
+		for (idx, mask) in m.iter_mut().enumerate().take(num_masks) {
+			*mask = read_dword(dib, masks_off + idx * 4)?;
+		}
+		masks = Some(m);
+	}
+
+	let palette_entry_sz = if header_sz == CORE_HEADER_SZ { 3 } else { 4 };
+	let palette_len = match bit_count {
+		1 | 4 | 8 if clr_used == 0 => 1 << bit_count,
+		1 | 4 | 8 => clr_used.min(1 << bit_count),
+		_ => clr_used.min(256), // optional table, only skipped
+	};
+	let palette = dib
+		.get(off..off + palette_len * palette_entry_sz)
+		.ok_or(co::ERROR::INVALID_DATA)?;
+	let palette = palette
+		.chunks_exact(palette_entry_sz)
+		.map(|c| [c[0], c[1], c[2], 0xff])
+		.collect::<Vec<_>>();
+	off += palette_len * palette_entry_sz;
+
+	let bits = dib
+		.get(bits_off.unwrap_or(off)..)
+		.ok_or(co::ERROR::INVALID_DATA)?;
+
+	let mut bgra = vec![0u8; width * height * 4];
+	if compression == co::BI::RLE8 || compression == co::BI::RLE4 {
+		let indexes = decode_rle(bits, width, height, compression == co::BI::RLE4)?;
+		for (y, row) in indexes.chunks_exact(width).enumerate() {
+			let dest_y = if top_down { y } else { height - 1 - y };
+			for (x, idx) in row.iter().enumerate() {
+				let color = palette.get(*idx as usize).unwrap_or(&[0, 0, 0, 0xff]);
+				bgra[(dest_y * width + x) * 4..][..4].copy_from_slice(color);
+			}
+		}
+	} else {
+		let stride = row_stride(width, bit_count as _);
+		if bits.len() < stride * height {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let masks = masks.unwrap_or(match bit_count {
+			16 => [0x7c00, 0x03e0, 0x001f, 0], // 5-5-5
+			_ => [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0],
+		});
+		let use_alpha_byte = bit_count == 32
+			&& compression == co::BI::RGB
+			&& bits[..stride * height].chunks_exact(4).any(|px| px[3] != 0); // alpha is "reserved", but often used
+
+		for y in 0..height {
+			let src_row = &bits[(if top_down { y } else { height - 1 - y }) * stride..][..stride];
+			for x in 0..width {
+				let px = &mut bgra[(y * width + x) * 4..][..4];
+				match bit_count {
+					1 | 4 | 8 => {
+						let bits_px = bit_count as usize;
+						let idx = (src_row[x * bits_px / 8] >> (8 - bits_px - (x * bits_px % 8)))
+							& ((1u16 << bits_px) - 1) as u8;
+						px.copy_from_slice(palette.get(idx as usize).unwrap_or(&[0, 0, 0, 0xff]));
+					},
+					24 => {
+						px[..3].copy_from_slice(&src_row[x * 3..x * 3 + 3]);
+						px[3] = 0xff;
+					},
+					16 => {
+						let raw = u16::from_le_bytes([src_row[x * 2], src_row[x * 2 + 1]]);
+						px.copy_from_slice(&unpack_bitfields(raw as _, &masks));
+					},
+					_ => {
+						let raw = &src_row[x * 4..x * 4 + 4];
+						if use_alpha_byte {
+							px.copy_from_slice(raw);
+						} else {
+							let raw = u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]);
+							px.copy_from_slice(&unpack_bitfields(raw, &masks));
+						}
+					},
+				}
+			}
+		}
+	}
+
+	Ok(DibImage {
+		width: width as _,
+		height: height as _,
+		x_pels_per_meter: x_ppm,
+		y_pels_per_meter: y_ppm,
+		bgra,
+	})
+}
+
+/// Decodes RLE8 or RLE4 bits into palette indexes, in bottom-up row order.
+/// Pixels skipped by deltas, or not reached, are left as index zero.
+fn decode_rle(bits: &[u8], width: usize, height: usize, is_rle4: bool) -> SysResult<Vec<u8>> {
+	let mut indexes = vec![0u8; width * height];
+	let (mut x, mut y) = (0usize, 0usize);
+	let mut off = 0;
+	let mut put = |x: usize, y: usize, idx: u8| {
+		if x < width && y < height {
+			indexes[y * width + x] = idx;
+		}
+	};
+
+	while off + 1 < bits.len() && y < height {
+		let (count, value) = (bits[off] as usize, bits[off + 1]);
+		off += 2;
+		if count > 0 {
+			for i in 0..count {
+				let idx = if is_rle4 {
+					if i % 2 == 0 { value >> 4 } else { value & 0x0f }
+				} else {
+					value
+				};
+				put(x, y, idx);
+				x += 1;
+			}
+			continue;
+		}
+
+		match value {
+			0 => {
+				x = 0; // end of line
+				y += 1;
+			},
+			1 => break, // end of bitmap
+			2 => {
+				let delta = bits.get(off..off + 2).ok_or(co::ERROR::INVALID_DATA)?;
+				x += delta[0] as usize;
+				y += delta[1] as usize;
+				off += 2;
+			},
+			n => {
+				let n = n as usize; // absolute mode
+				let n_bytes = if is_rle4 { n.div_ceil(2) } else { n };
+				let run = bits
+					.get(off..off + n_bytes)
+					.ok_or(co::ERROR::INVALID_DATA)?;
+				for i in 0..n {
+					let idx = if is_rle4 {
+						if i % 2 == 0 { run[i / 2] >> 4 } else { run[i / 2] & 0x0f }
+					} else {
+						run[i]
+					};
+					put(x, y, idx);
+					x += 1;
+				}
+				off += n_bytes.next_multiple_of(2); // runs are WORD-aligned
+			},
+		}
+	}
+	Ok(indexes)
+}
+
+/// Extracts the BGRA channels of a bit-field pixel, scaling each one to 8
+/// bits. Without an alpha mask, the pixel is opaque.
+#[must_use]
+fn unpack_bitfields(raw: u32, masks: &Masks) -> [u8; 4] {
+	let channel = |mask: u32, absent: u8| -> u8 {
+		if mask == 0 {
+			return absent;
+		}
+		let max = (mask >> mask.trailing_zeros()) as u64;
+		let val = ((raw & mask) >> mask.trailing_zeros()) as u64;
+		((val * 255 + max / 2) / max) as u8
+	};
+	[channel(masks[2], 0), channel(masks[1], 0), channel(masks[0], 0), channel(masks[3], 0xff)]
+}
+
+/// Returns the size of each row of bits, which is DWORD-aligned.
+#[must_use]
+const fn row_stride(width: usize, bit_count: usize) -> usize {
+	(width * bit_count).div_ceil(32) * 4
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Writes a bitmap header of the given size; the fields beyond
+	/// `BITMAPINFOHEADER` are zeroed.
+	fn header(sz: usize, w: i32, h: i32, bits: u16, compression: co::BI, clr_used: u32) -> Vec<u8> {
+		let mut buf = vec![0u8; sz];
+		buf[0..4].copy_from_slice(&(sz as u32).to_le_bytes());
+		buf[4..8].copy_from_slice(&w.to_le_bytes());
+		buf[8..12].copy_from_slice(&h.to_le_bytes());
+		buf[12..14].copy_from_slice(&1u16.to_le_bytes());
+		buf[14..16].copy_from_slice(&bits.to_le_bytes());
+		buf[16..20].copy_from_slice(&compression.raw().to_le_bytes());
+		buf[32..36].copy_from_slice(&clr_used.to_le_bytes());
+		buf
+	}
+
+	fn dwords(vals: &[u32]) -> Vec<u8> {
+		vals.iter().flat_map(|v| v.to_le_bytes()).collect()
+	}
+
+	const BLACK: [u8; 4] = [0x00, 0x00, 0x00, 0xff];
+	const WHITE: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+	const RED: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+	const GREEN: [u8; 4] = [0x00, 0xff, 0x00, 0xff];
+	const BLUE: [u8; 4] = [0xff, 0x00, 0x00, 0xff];
+
+	/// Palette of BLACK, WHITE, RED, GREEN, BLUE and 3 grays.
+	#[rustfmt::skip]
+	const PALETTE: [u8; 32] = [
+		0x00, 0x00, 0x00, 0x00,  0xff, 0xff, 0xff, 0x00,
+		0x00, 0x00, 0xff, 0x00,  0x00, 0xff, 0x00, 0x00,
+		0xff, 0x00, 0x00, 0x00,  0x40, 0x40, 0x40, 0x00,
+		0x80, 0x80, 0x80, 0x00,  0xc0, 0xc0, 0xc0, 0x00,
+	];
+	const GRAYS: [[u8; 4]; 3] =
+		[[0x40, 0x40, 0x40, 0xff], [0x80, 0x80, 0x80, 0xff], [0xc0, 0xc0, 0xc0, 0xff]];
+
+	fn pixels(img: &DibImage) -> Vec<[u8; 4]> {
+		img.bgra
+			.chunks_exact(4)
+			.map(|px| px.try_into().unwrap())
+			.collect()
+	}
+
+	#[test]
+	fn one_bit() {
+		let mut dib = header(INFO_HEADER_SZ, 3, 2, 1, co::BI::RGB, 0);
+		dib.extend_from_slice(&PALETTE[..8]);
+		#[rustfmt::skip]
+		dib.extend_from_slice(&[
+			0b0110_0000, 0, 0, 0, // bottom row
+			0b1010_0000, 0, 0, 0,
+		]);
+		let img = DibImage::parse_dib(&dib).unwrap();
+		assert_eq!((img.width, img.height), (3, 2));
+		assert_eq!(pixels(&img), [WHITE, BLACK, WHITE, BLACK, WHITE, WHITE]);
+	}
+
+	#[test]
+	fn four_bit() {
+		let mut dib = header(INFO_HEADER_SZ, 3, 1, 4, co::BI::RGB, 8);
+		dib.extend_from_slice(&PALETTE);
+		dib.extend_from_slice(&[0x24, 0x70, 0, 0]);
+		let img = DibImage::parse_dib(&dib).unwrap();
+		assert_eq!(pixels(&img), [RED, BLUE, GRAYS[2]]);
+	}
+
+	#[test]
+	fn row_order() {
+		let bits = [0x01, 0x02, 0x03, 0x00, 0x04, 0x05, 0x06, 0x00];
+		let (a, b) = ([0x01, 0x02, 0x03, 0xff], [0x04, 0x05, 0x06, 0xff]);
+
+		let mut dib = header(INFO_HEADER_SZ, 1, 2, 24, co::BI::RGB, 0);
+		dib.extend_from_slice(&bits);
+		assert_eq!(pixels(&DibImage::parse_dib(&dib).unwrap()), [b, a]); // bottom-up
+
+		let mut dib = header(INFO_HEADER_SZ, 1, -2, 24, co::BI::RGB, 0);
+		dib.extend_from_slice(&bits);
+		let img = DibImage::parse_dib(&dib).unwrap();
+		assert_eq!(img.height, 2);
+		assert_eq!(pixels(&img), [a, b]); // top-down
+	}
+
+	#[test]
+	fn rle8() {
+		let mut dib = header(INFO_HEADER_SZ, 4, 3, 8, co::BI::RLE8, 8);
+		dib.extend_from_slice(&PALETTE);
+		#[rustfmt::skip]
+		dib.extend_from_slice(&[
+			3, 1, // encoded run, bottom row
+			0, 0, // end of line
+			0, 2, 1, 1, // delta, skips the middle row
+			0, 3, 2, 3, 4, 0, // absolute run, padded
+			0, 1, // end of bitmap
+		]);
+		let img = DibImage::parse_dib(&dib).unwrap();
+		#[rustfmt::skip]
+		assert_eq!(pixels(&img), [
+			BLACK, RED, GREEN, BLUE,
+			BLACK, BLACK, BLACK, BLACK,
+			WHITE, WHITE, WHITE, BLACK,
+		]);
+
+		dib.truncate(dib.len() - 8); // absolute run cut short
+		dib.extend_from_slice(&[0, 3, 2]);
+		assert!(DibImage::parse_dib(&dib).is_err());
+	}
+
+	#[test]
+	fn rle4() {
+		let mut dib = header(INFO_HEADER_SZ, 8, 2, 4, co::BI::RLE4, 8);
+		dib.extend_from_slice(&PALETTE);
+		#[rustfmt::skip]
+		dib.extend_from_slice(&[
+			3, 0x12, // encoded run, alternating
+			0, 5, 0x34, 0x56, 0x70, 0x00, // absolute run of 3 bytes, padded
+			0, 0, // end of line
+			0, 2, 6, 0, // delta
+			2, 0x77, // encoded run
+			0, 1, // end of bitmap
+		]);
+		let img = DibImage::parse_dib(&dib).unwrap();
+		let g = GRAYS;
+		#[rustfmt::skip]
+		assert_eq!(pixels(&img), [
+			BLACK, BLACK, BLACK, BLACK, BLACK, BLACK, g[2], g[2],
+			WHITE, RED, WHITE, GREEN, BLUE, g[0], g[1], g[2],
+		]);
+	}
+
+	#[test]
+	fn bitfields() {
+		let mut dib = header(INFO_HEADER_SZ, 2, 1, 16, co::BI::BITFIELDS, 0);
+		dib.extend_from_slice(&dwords(&[0xf800, 0x07e0, 0x001f])); // 5-6-5
+		dib.extend_from_slice(&[0x00, 0xf8, 0x00, 0x04]);
+		let img = DibImage::parse_dib(&dib).unwrap();
+		assert_eq!(pixels(&img), [RED, [0x00, 0x82, 0x00, 0xff]]);
+
+		let mut dib = header(INFO_HEADER_SZ, 1, 1, 32, co::BI::ALPHABITFIELDS, 0);
+		dib.extend_from_slice(&dwords(&[0xff00_0000, 0x00ff_0000, 0x0000_ff00, 0x0000_00ff]));
+		dib.extend_from_slice(&dwords(&[0x1122_3380])); // RGBA
+		let img = DibImage::parse_dib(&dib).unwrap();
+		assert_eq!(pixels(&img), [[0x33, 0x22, 0x11, 0x80]]);
+	}
+
+	#[test]
+	fn v4_v5_headers() {
+		let mut dib = header(108, 1, 1, 32, co::BI::BITFIELDS, 0); // BITMAPV4HEADER
+		dib[40..56].copy_from_slice(&dwords(&[0xff, 0xff00, 0xff_0000, 0]));
+		dib.extend_from_slice(&dwords(&[0x0033_2211])); // XBGR
+		let img = DibImage::parse_dib(&dib).unwrap();
+		assert_eq!(pixels(&img), [[0x33, 0x22, 0x11, 0xff]]);
+
+		let mut dib = header(124, 2, 1, 16, co::BI::BITFIELDS, 0); // BITMAPV5HEADER
+		dib[40..56].copy_from_slice(&dwords(&[0x0f00, 0x00f0, 0x000f, 0xf000])); // 4-4-4-4
+		dib.extend_from_slice(&[0x0f, 0x8f, 0xf0, 0x00, 0, 0]);
+		let img = DibImage::parse_dib(&dib).unwrap();
+		assert_eq!(pixels(&img), [[0xff, 0x00, 0xff, 0x88], [0x00, 0xff, 0x00, 0x00]]);
+	}
+
+	#[test]
+	fn malformed() {
+		let mut dib = header(INFO_HEADER_SZ, 2, 2, 24, co::BI::RGB, 0);
+		dib.extend_from_slice(&[0; 15]); // 1 byte short
+		assert!(DibImage::parse_dib(&dib).is_err());
+		assert!(DibImage::parse_dib(&header(INFO_HEADER_SZ, 1, 1, 24, co::BI::RLE8, 0)).is_err());
+		assert!(DibImage::parse_dib(&header(INFO_HEADER_SZ, 0, 1, 24, co::BI::RGB, 0)).is_err());
+		let mut dib = header(INFO_HEADER_SZ, 1, 1, 24, co::BI::RGB, 0);
+		dib[0] = 32; // unknown header size
+		assert!(DibImage::parse_dib(&dib).is_err());
+		assert!(
+			DibImage::parse_dib(&header(INFO_HEADER_SZ, 1, 1, 24, co::BI::PNG, 0))
+				== Err(co::ERROR::NOT_SUPPORTED)
+		);
+		assert!(DibImage::parse_bmp(&[b'B', b'M', 0, 0]).is_err());
+	}
+
+	#[test]
+	fn round_trip() {
+		let mut img = DibImage {
+			width: 3,
+			height: 2,
+			x_pels_per_meter: 2835,
+			y_pels_per_meter: 2835,
+			bgra: [RED, GREEN, BLUE, WHITE, BLACK, GRAYS[0]].concat(),
+		};
+		let bmp = img.serialize_bmp();
+		assert_eq!(bmp.len(), FILE_HEADER_SZ + INFO_HEADER_SZ + 12 * 2); // padded rows
+		assert_eq!(read_word(&bmp, FILE_HEADER_SZ + 14).unwrap(), 24);
+		assert_eq!(read_dword(&bmp, 10).unwrap(), (FILE_HEADER_SZ + INFO_HEADER_SZ) as u32);
+		assert!(DibImage::parse_bmp(&bmp).unwrap() == img);
+		assert!(DibImage::parse_dib(&img.serialize_dib()).unwrap() == img);
+
+		img.bgra[3] = 0x80;
+		img.bgra[7] = 0x00;
+		let bmp = img.serialize_bmp();
+		assert_eq!(read_word(&bmp, FILE_HEADER_SZ + 14).unwrap(), 32);
+		assert!(DibImage::parse_bmp(&bmp).unwrap() == img);
+	}
+}
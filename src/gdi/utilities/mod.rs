@@ -0,0 +1,3 @@
+mod dib_image;
+
+pub use dib_image::DibImage;
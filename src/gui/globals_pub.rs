@@ -69,6 +69,32 @@ pub fn dpi(x_val: i32, y_val: i32) -> (i32, i32) {
 	(dpi_x(x_val), dpi_y(y_val))
 }
 
+/// Returns whether version 6 of ComCtl32 is loaded in the current process, by
+/// calling [`HINSTANCE::DllGetVersion`](crate::HINSTANCE::DllGetVersion).
+///
+/// ComCtl32 v6 – which provides visual styles to the native controls – is
+/// only loaded if the application has a manifest, which can be created with
+/// [`AppManifest::new_gui`](crate::AppManifest::new_gui). Without it, the
+/// controls will still work, but with the old appearance.
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, gui};
+///
+/// if !gui::is_comctl_v6() {
+///     eprintln!("Application manifest is missing.");
+/// }
+/// ```
+#[must_use]
+pub fn is_comctl_v6() -> bool {
+	InitCommonControls(); // make sure ComCtl32 is loaded
+	HINSTANCE::GetModuleHandle(Some("comctl32.dll"))
+		.ok()
+		.and_then(|hcomctl| hcomctl.DllGetVersion().ok())
+		.is_some_and(|dvi| dvi.dwMajorVersion >= 6)
+}
+
 /// The class background brush to be loaded for
 /// [`WindowMainOpts`](crate::gui::WindowMainOpts),
 /// [`WindowModalOpts`](crate::gui::WindowModalOpts) or
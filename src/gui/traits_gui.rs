@@ -1,7 +1,7 @@
 use std::any::Any;
 
 use crate::decl::*;
-use crate::gui::privs::*;
+use crate::gui::{privs::*, LayoutNode};
 use crate::msg::*;
 
 /// This trait is enabled with the `gui` feature, and is implemented by all
@@ -112,6 +112,18 @@ pub trait GuiParent: GuiWindow + Clone + AsRef<BaseWnd> {
 		self.as_ref().spawn_thread(func)
 	}
 
+	/// Sets the layout tree which arranges the child controls whenever the
+	/// window is resized, replacing any previous one.
+	///
+	/// The tree may be set before the window and its controls are created.
+	/// Controls placed in the tree should be created with
+	/// `resize_behavior` set to `(gui::Horz::None, gui::Vert::None)`.
+	///
+	/// See [`LayoutNode`](crate::gui::LayoutNode) for an example.
+	fn set_layout(&self, root: LayoutNode) {
+		self.as_ref().set_layout(root)
+	}
+
 	/// Runs a closure synchronously in the window's original UI thread,
 	/// allowing UI updates without the risk of a deadlock.
 	///
@@ -87,6 +87,10 @@ impl BaseWnd {
 		self.layout.add_child(&self.hwnd(), hchild, resize_behavior);
 	}
 
+	pub(in crate::gui) fn set_layout(&self, root: LayoutNode) {
+		self.layout.set_tree(self.hwnd(), root);
+	}
+
 	pub(in crate::gui) fn spawn_thread<F>(&self, func: F)
 	where
 		F: FnOnce() -> AnyResult<()> + Send + 'static,
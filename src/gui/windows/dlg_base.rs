@@ -4,12 +4,47 @@ use crate::gui::privs::*;
 use crate::msg::*;
 use crate::prelude::*;
 
+/// Where the dialog template comes from.
+pub(in crate::gui) enum DlgSrc {
+	/// A dialog resource ID.
+	Id(u16),
+	/// A serialized in-memory template, stored as `u32` to keep the DWORD
+	/// alignment required by the system.
+	Template(Vec<u32>),
+}
+
+impl DlgSrc {
+	#[must_use]
+	pub(in crate::gui) fn from_template(tmpl: &DlgTemplateEx) -> Self {
+		let bytes = tmpl
+			.serialize()
+			.unwrap_or_else(|err| panic!("Invalid dialog template: {err}"));
+		Self::Template(
+			bytes
+				.chunks(4)
+				.map(|b| {
+					let mut dw = [0u8; 4];
+					dw[..b.len()].copy_from_slice(b);
+					u32::from_le_bytes(dw)
+				})
+				.collect(),
+		)
+	}
+
+	/// Returns the header of the in-memory template, which is followed by its
+	/// variable-length data.
+	#[must_use]
+	fn header(buf: &[u32]) -> &DLGTEMPLATE {
+		unsafe { &*(buf.as_ptr() as *const DLGTEMPLATE) }
+	}
+}
+
 /// Base to all dialog windows.
 ///
 /// Owns the window procedure for all dialog windows.
 pub(in crate::gui) struct DlgBase {
 	base: BaseWnd,
-	dlg_id: u16,
+	src: DlgSrc,
 }
 
 impl Drop for DlgBase {
@@ -24,8 +59,8 @@ impl Drop for DlgBase {
 
 impl DlgBase {
 	#[must_use]
-	pub(in crate::gui) fn new(dlg_id: u16) -> Self {
-		Self { base: BaseWnd::new(WndTy::Dlg), dlg_id }
+	pub(in crate::gui) fn new(src: DlgSrc) -> Self {
+		Self { base: BaseWnd::new(WndTy::Dlg), src }
 	}
 
 	#[must_use]
@@ -40,12 +75,20 @@ impl DlgBase {
 
 		unsafe {
 			// The hwnd member is saved in WM_INITDIALOG processing in dlg_proc.
-			hinst.CreateDialogParam(
-				IdStr::Id(self.dlg_id),
-				hparent,
-				Self::dlg_proc,
-				Some(self as *const _ as _), // pointer to object itself
-			)
+			match &self.src {
+				DlgSrc::Id(dlg_id) => hinst.CreateDialogParam(
+					IdStr::Id(*dlg_id),
+					hparent,
+					Self::dlg_proc,
+					Some(self as *const _ as _), // pointer to object itself
+				),
+				DlgSrc::Template(buf) => hinst.CreateDialogIndirectParam(
+					DlgSrc::header(buf),
+					hparent,
+					Self::dlg_proc,
+					Some(self as *const _ as _),
+				),
+			}
 		}
 		.expect(DONTFAIL);
 	}
@@ -56,12 +99,20 @@ impl DlgBase {
 
 		unsafe {
 			// The hwnd member is saved in WM_INITDIALOG processing in dlg_proc.
-			hinst.DialogBoxParam(
-				IdStr::Id(self.dlg_id),
-				hparent,
-				Self::dlg_proc,
-				Some(self as *const _ as _), // pointer to object itself
-			)
+			match &self.src {
+				DlgSrc::Id(dlg_id) => hinst.DialogBoxParam(
+					IdStr::Id(*dlg_id),
+					hparent,
+					Self::dlg_proc,
+					Some(self as *const _ as _), // pointer to object itself
+				),
+				DlgSrc::Template(buf) => hinst.DialogBoxIndirectParam(
+					DlgSrc::header(buf),
+					hparent,
+					Self::dlg_proc,
+					Some(self as *const _ as _),
+				),
+			}
 		}
 		.expect(DONTFAIL);
 	}
@@ -30,7 +30,7 @@ impl DlgControl {
 	) -> Self {
 		let ctrl_id2 = ctrl_id.unwrap_or_else(|| auto_id::next());
 		let new_self = Self(Arc::pin(DlgControlObj {
-			dlg_base: DlgBase::new(dlg_id),
+			dlg_base: DlgBase::new(DlgSrc::Id(dlg_id)),
 			ctrl_id: ctrl_id2,
 			_pin: PhantomPinned,
 		}));
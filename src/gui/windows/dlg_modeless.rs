@@ -26,7 +26,7 @@ impl DlgModeless {
 		position: (i32, i32),
 	) -> Self {
 		let new_self = Self(Arc::pin(DlgModelessObj {
-			dlg_base: DlgBase::new(dlg_id),
+			dlg_base: DlgBase::new(DlgSrc::Id(dlg_id)),
 			_pin: PhantomPinned,
 		}));
 
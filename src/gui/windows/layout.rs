@@ -5,7 +5,7 @@ use std::sync::Arc;
 
 use crate::co;
 use crate::decl::*;
-use crate::gui::privs::*;
+use crate::gui::{privs::*, *};
 use crate::msg::*;
 use crate::prelude::*;
 
@@ -51,6 +51,7 @@ struct ChildInfo {
 struct LayoutObj {
 	ctrls: UnsafeCell<Vec<ChildInfo>>,
 	sz_parent_orig: UnsafeCell<Option<SIZE>>, // original parent client area, filled at WM_CREATE/INITDIALOG
+	tree: UnsafeCell<Option<LayoutNode>>,
 	_pin: PhantomPinned,
 }
 
@@ -64,6 +65,7 @@ impl Layout {
 		Self(Arc::pin(LayoutObj {
 			ctrls: UnsafeCell::new(Vec::new()),
 			sz_parent_orig: UnsafeCell::new(None),
+			tree: UnsafeCell::new(None),
 			_pin: PhantomPinned,
 		}))
 	}
@@ -101,15 +103,29 @@ impl Layout {
 		}
 	}
 
+	/// Sets the layout tree, which will arrange its controls whenever the
+	/// parent window is resized. If the parent was already created, the tree
+	/// is immediately applied.
+	pub(in crate::gui) fn set_tree(&self, hparent: &HWND, root: LayoutNode) {
+		*unsafe { &mut *self.0.tree.get() } = Some(root);
+		if *hparent != HWND::NULL {
+			let rc_parent = hparent.GetClientRect().expect(DONTFAIL);
+			self.rearrange_tree(SIZE::with(rc_parent.right, rc_parent.bottom));
+		}
+	}
+
 	/// Rearranges all child controls to fit the new width/height of parent
 	/// window.
 	pub(in crate::gui) fn rearrange(&self, p: wm::Size) {
-		let ctrls = unsafe { &mut *self.0.ctrls.get() };
-		if ctrls.is_empty() // no controls
-			|| p.request == co::SIZE_R::MINIMIZED
-		{
+		if p.request == co::SIZE_R::MINIMIZED {
 			return; // we're minimized
 		}
+		self.rearrange_tree(p.client_area);
+
+		let ctrls = unsafe { &mut *self.0.ctrls.get() };
+		if ctrls.is_empty() {
+			return; // no controls
+		}
 
 		let sz_parent_orig = match unsafe { &mut *self.0.sz_parent_orig.get() } {
 			Some(sz) => *sz,
@@ -172,4 +188,48 @@ impl Layout {
 			.expect(DONTFAIL);
 		}
 	}
+
+	/// Applies the layout tree, if any, to the given parent client area.
+	fn rearrange_tree(&self, client_area: SIZE) {
+		let tree = match unsafe { &mut *self.0.tree.get() } {
+			Some(tree) => tree,
+			None => return,
+		};
+
+		let mut hctrls = Vec::with_capacity(tree.leaf_count());
+		tree.for_each_item(&mut |item| {
+			let hctrl = item
+				.hwnd()
+				.filter(|hctrl| **hctrl != HWND::NULL)
+				.map(|hctrl| unsafe { hctrl.raw_copy() });
+			if let Some(hctrl) = &hctrl {
+				if item.sizing.size == SIZE::new() {
+					let rc = hctrl.GetWindowRect().expect(DONTFAIL);
+					item.sizing.size = SIZE::with(rc.right - rc.left, rc.bottom - rc.top); // keep original control size
+				}
+			}
+			hctrls.push(hctrl);
+		});
+
+		let rcs = tree.arrange(RECT {
+			left: 0,
+			top: 0,
+			right: client_area.cx,
+			bottom: client_area.cy,
+		});
+
+		let mut hdwp = HDWP::BeginDeferWindowPos(hctrls.len() as _).expect(DONTFAIL);
+		for (hctrl, rc) in hctrls.iter().zip(rcs.iter()) {
+			if let Some(hctrl) = hctrl {
+				hdwp.DeferWindowPos(
+					hctrl,
+					HwndPlace::None,
+					POINT::with(rc.left, rc.top),
+					SIZE::with(rc.right - rc.left, rc.bottom - rc.top),
+					co::SWP::NOZORDER,
+				)
+				.expect(DONTFAIL);
+			}
+		}
+	}
 }
@@ -0,0 +1,905 @@
+use crate::decl::*;
+use crate::gui::*;
+use crate::prelude::*;
+
+/// Direction in which a [`LayoutStack`](crate::gui::LayoutStack) lays out its
+/// children.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+	/// Children are placed side by side, from left to right.
+	Horz,
+	/// Children are placed one below the other, from top to bottom.
+	Vert,
+}
+
+/// Edge of the remaining area to which a child of a
+/// [`LayoutDock`](crate::gui::LayoutDock) is attached.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dock {
+	/// Attached to the left edge, taking the whole remaining height.
+	Left,
+	/// Attached to the top edge, taking the whole remaining width.
+	Top,
+	/// Attached to the right edge, taking the whole remaining height.
+	Right,
+	/// Attached to the bottom edge, taking the whole remaining width.
+	Bottom,
+	/// Takes all the area left by the previous children. Should be the last
+	/// child.
+	Fill,
+}
+
+/// Spacing around the four edges of an area, used as margins of layout nodes
+/// and as padding of layout containers.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Margins {
+	/// Space at the left edge.
+	pub left: i32,
+	/// Space at the top edge.
+	pub top: i32,
+	/// Space at the right edge.
+	pub right: i32,
+	/// Space at the bottom edge.
+	pub bottom: i32,
+}
+
+impl Margins {
+	/// Constructs a new `Margins` with the same value on all four edges.
+	#[must_use]
+	pub const fn all(val: i32) -> Self {
+		Self {
+			left: val,
+			top: val,
+			right: val,
+			bottom: val,
+		}
+	}
+
+	/// Constructs a new `Margins` with the given values adjusted to the
+	/// current system DPI with [`gui::dpi`](crate::gui::dpi).
+	#[must_use]
+	pub fn dpi(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+		let (left, top) = dpi(left, top);
+		let (right, bottom) = dpi(right, bottom);
+		Self { left, top, right, bottom }
+	}
+
+	#[must_use]
+	const fn horz(&self) -> i32 {
+		self.left.saturating_add(self.right)
+	}
+
+	#[must_use]
+	const fn vert(&self) -> i32 {
+		self.top.saturating_add(self.bottom)
+	}
+
+	#[must_use]
+	const fn shrink(&self, rc: RECT) -> RECT {
+		RECT {
+			left: rc.left + self.left,
+			top: rc.top + self.top,
+			right: rc.right - self.right,
+			bottom: rc.bottom - self.bottom,
+		}
+	}
+}
+
+/// Size constraints of a [`LayoutNode`](crate::gui::LayoutNode).
+///
+/// All values are in pixels; use [`gui::dpi`](crate::gui::dpi) to make them
+/// DPI-aware.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LayoutSizing {
+	/// Preferred size, used when there's no extra space to stretch into.
+	///
+	/// For a control, if left zero, the size the control had when the layout
+	/// was first applied is used. For a container, the preferred size of its
+	/// children is used if larger.
+	///
+	/// Defaults to zero.
+	pub size: SIZE,
+	/// Minimum size. The node won't shrink below it, even if it overflows the
+	/// parent area.
+	///
+	/// Defaults to zero.
+	pub min: SIZE,
+	/// Maximum size. The node won't stretch beyond it.
+	///
+	/// Defaults to `i32::MAX`.
+	pub max: SIZE,
+	/// Horizontal and vertical stretch weights. The extra space of a container
+	/// is split among its children proportionally to their weights. A zero
+	/// weight keeps the node at its preferred size.
+	///
+	/// Defaults to `(0, 0)`.
+	pub stretch: (u32, u32),
+	/// Space around the node, which is not given to it.
+	///
+	/// Defaults to zero.
+	pub margin: Margins,
+}
+
+impl Default for LayoutSizing {
+	fn default() -> Self {
+		Self {
+			size: SIZE::new(),
+			min: SIZE::new(),
+			max: SIZE::with(i32::MAX, i32::MAX),
+			stretch: (0, 0),
+			margin: Margins::default(),
+		}
+	}
+}
+
+/// Position of a child within a [`LayoutGrid`](crate::gui::LayoutGrid).
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GridCell {
+	/// Zero-based row index.
+	pub row: usize,
+	/// Zero-based column index.
+	pub col: usize,
+	/// Number of rows taken by the child. Defaults to 1.
+	pub row_span: usize,
+	/// Number of columns taken by the child. Defaults to 1.
+	pub col_span: usize,
+}
+
+impl Default for GridCell {
+	fn default() -> Self {
+		Self { row: 0, col: 0, row_span: 1, col_span: 1 }
+	}
+}
+
+impl GridCell {
+	/// Constructs a new `GridCell` taking a single cell.
+	#[must_use]
+	pub const fn at(row: usize, col: usize) -> Self {
+		Self { row, col, row_span: 1, col_span: 1 }
+	}
+}
+
+/// A leaf of the layout tree: either a control or an empty spacer.
+pub struct LayoutItem {
+	ctrl: Option<Box<dyn GuiWindow>>,
+	/// Size constraints of the item.
+	pub sizing: LayoutSizing,
+}
+
+impl LayoutItem {
+	/// Returns the handle of the control, if this item is not a spacer.
+	#[must_use]
+	pub fn hwnd(&self) -> Option<&HWND> {
+		self.ctrl.as_ref().map(|ctrl| ctrl.hwnd())
+	}
+}
+
+/// Layout container which places its children in a single row or column.
+///
+/// Along the main axis, the extra space is split among the children according
+/// to their stretch weights; when space is lacking, the children shrink
+/// towards their minimum sizes. Along the cross axis, children with a non-zero
+/// stretch weight fill the whole container.
+pub struct LayoutStack {
+	/// Size constraints of the container itself.
+	pub sizing: LayoutSizing,
+	/// Direction of the children.
+	///
+	/// Defaults to `Orientation::Horz`.
+	pub orientation: Orientation,
+	/// Space between the container edges and its children.
+	///
+	/// Defaults to zero.
+	pub padding: Margins,
+	/// Space between two consecutive children.
+	///
+	/// Defaults to zero.
+	pub spacing: i32,
+	/// The children.
+	///
+	/// Defaults to none.
+	pub children: Vec<LayoutNode>,
+}
+
+impl Default for LayoutStack {
+	fn default() -> Self {
+		Self {
+			sizing: LayoutSizing::default(),
+			orientation: Orientation::Horz,
+			padding: Margins::default(),
+			spacing: 0,
+			children: Vec::new(),
+		}
+	}
+}
+
+/// Layout container which places its children in cells of rows and columns.
+///
+/// Columns and rows are as large as their largest single-span child; the extra
+/// space is split according to the column and row stretch weights. Children
+/// placed outside the grid are not shown.
+#[derive(Default)]
+pub struct LayoutGrid {
+	/// Size constraints of the container itself.
+	pub sizing: LayoutSizing,
+	/// Stretch weight of each column; the number of elements defines the
+	/// number of columns.
+	///
+	/// Defaults to none.
+	pub cols: Vec<u32>,
+	/// Stretch weight of each row; the number of elements defines the number
+	/// of rows.
+	///
+	/// Defaults to none.
+	pub rows: Vec<u32>,
+	/// Space between the container edges and its children.
+	///
+	/// Defaults to zero.
+	pub padding: Margins,
+	/// Horizontal and vertical space between cells.
+	///
+	/// Defaults to `(0, 0)`.
+	pub spacing: (i32, i32),
+	/// The children, with their cells.
+	///
+	/// Defaults to none.
+	pub children: Vec<(GridCell, LayoutNode)>,
+}
+
+/// Layout container which attaches each child to an edge of the area left by
+/// the previous children.
+///
+/// Docked children take their preferred size along the docking axis, and fill
+/// the remaining area along the other one.
+#[derive(Default)]
+pub struct LayoutDock {
+	/// Size constraints of the container itself.
+	pub sizing: LayoutSizing,
+	/// Space between the container edges and its children.
+	///
+	/// Defaults to zero.
+	pub padding: Margins,
+	/// Space between a docked child and the remaining area.
+	///
+	/// Defaults to zero.
+	pub spacing: i32,
+	/// The children, with the edges they're attached to.
+	///
+	/// Defaults to none.
+	pub children: Vec<(Dock, LayoutNode)>,
+}
+
+/// A node of the layout tree, which arranges the child controls of a window
+/// when it's resized.
+///
+/// The tree is set with [`GuiParent::set_layout`](crate::prelude::GuiParent::set_layout).
+/// Its geometry computation is independent of any window, so it can be
+/// performed with plain [`RECT`](crate::RECT) and [`SIZE`](crate::SIZE)
+/// values through [`arrange`](crate::gui::LayoutNode::arrange).
+///
+/// # Examples
+///
+/// A list view filling the window, with two buttons at the bottom right:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, gui};
+///
+/// let wnd: gui::WindowMain; // initialized somewhere
+/// let lst: gui::ListView<()>;
+/// let btn_ok: gui::Button;
+/// let btn_cancel: gui::Button;
+/// # let wnd = gui::WindowMain::new(gui::WindowMainOpts::default());
+/// # let lst = gui::ListView::<()>::new(&wnd, gui::ListViewOpts::default());
+/// # let btn_ok = gui::Button::new(&wnd, gui::ButtonOpts::default());
+/// # let btn_cancel = gui::Button::new(&wnd, gui::ButtonOpts::default());
+///
+/// wnd.set_layout(gui::LayoutNode::Stack(gui::LayoutStack {
+///     orientation: gui::Orientation::Vert,
+///     padding: gui::Margins::dpi(10, 10, 10, 10),
+///     spacing: gui::dpi_y(8),
+///     children: vec![
+///         gui::LayoutNode::ctrl(&lst, gui::LayoutSizing {
+///             stretch: (1, 1),
+///             ..Default::default()
+///         }),
+///         gui::LayoutNode::Stack(gui::LayoutStack {
+///             spacing: gui::dpi_x(8),
+///             sizing: gui::LayoutSizing { stretch: (1, 0), ..Default::default() },
+///             children: vec![
+///                 gui::LayoutNode::spacer((1, 0)),
+///                 gui::LayoutNode::ctrl(&btn_ok, gui::LayoutSizing::default()),
+///                 gui::LayoutNode::ctrl(&btn_cancel, gui::LayoutSizing::default()),
+///             ],
+///             ..Default::default()
+///         }),
+///     ],
+///     ..Default::default()
+/// }));
+/// ```
+pub enum LayoutNode {
+	/// A control or a spacer.
+	Item(LayoutItem),
+	/// A row or column of nodes.
+	Stack(LayoutStack),
+	/// A grid of nodes.
+	Grid(LayoutGrid),
+	/// Nodes docked to the edges.
+	Dock(LayoutDock),
+}
+
+impl LayoutNode {
+	/// Constructs a leaf node holding the given control.
+	///
+	/// The control may not have been created yet; its handle is retrieved only
+	/// when the layout is applied.
+	#[must_use]
+	pub fn ctrl(ctrl: &(impl GuiControl + Clone + 'static), sizing: LayoutSizing) -> Self {
+		Self::Item(LayoutItem {
+			ctrl: Some(Box::new(ctrl.clone())),
+			sizing,
+		})
+	}
+
+	/// Constructs an empty leaf node with the given stretch weights, which
+	/// just takes up space.
+	#[must_use]
+	pub fn spacer(stretch: (u32, u32)) -> Self {
+		Self::Item(LayoutItem {
+			ctrl: None,
+			sizing: LayoutSizing { stretch, ..Default::default() },
+		})
+	}
+
+	/// Returns the size constraints of the node.
+	#[must_use]
+	pub const fn sizing(&self) -> &LayoutSizing {
+		match self {
+			Self::Item(item) => &item.sizing,
+			Self::Stack(stack) => &stack.sizing,
+			Self::Grid(grid) => &grid.sizing,
+			Self::Dock(dock) => &dock.sizing,
+		}
+	}
+
+	/// Returns a mutable reference to the size constraints of the node.
+	#[must_use]
+	pub fn sizing_mut(&mut self) -> &mut LayoutSizing {
+		match self {
+			Self::Item(item) => &mut item.sizing,
+			Self::Stack(stack) => &mut stack.sizing,
+			Self::Grid(grid) => &mut grid.sizing,
+			Self::Dock(dock) => &mut dock.sizing,
+		}
+	}
+
+	/// Returns the preferred size of the node, excluding its margins.
+	#[must_use]
+	pub fn preferred_size(&self) -> SIZE {
+		let s = self.sizing();
+		let natural = match self {
+			Self::Item(_) => SIZE::new(),
+			Self::Stack(stack) => stack.natural(Self::outer_preferred),
+			Self::Grid(grid) => grid.natural(Self::outer_preferred),
+			Self::Dock(dock) => dock.natural(Self::outer_preferred),
+		};
+		let min = self.min_size();
+		let max = self.max_size();
+		SIZE::with(
+			s.size.cx.max(natural.cx).min(max.cx).max(min.cx),
+			s.size.cy.max(natural.cy).min(max.cy).max(min.cy),
+		)
+	}
+
+	/// Returns the minimum size of the node, excluding its margins.
+	#[must_use]
+	pub fn min_size(&self) -> SIZE {
+		let s = self.sizing();
+		let natural = match self {
+			Self::Item(_) => SIZE::new(),
+			Self::Stack(stack) => stack.natural(Self::outer_min),
+			Self::Grid(grid) => grid.natural(Self::outer_min),
+			Self::Dock(dock) => dock.natural(Self::outer_min),
+		};
+		SIZE::with(s.min.cx.max(natural.cx), s.min.cy.max(natural.cy))
+	}
+
+	#[must_use]
+	fn max_size(&self) -> SIZE {
+		let s = self.sizing();
+		let min = self.min_size();
+		SIZE::with(s.max.cx.max(min.cx), s.max.cy.max(min.cy))
+	}
+
+	#[must_use]
+	fn outer_preferred(&self) -> SIZE {
+		self.outer(self.preferred_size())
+	}
+
+	#[must_use]
+	fn outer_min(&self) -> SIZE {
+		self.outer(self.min_size())
+	}
+
+	#[must_use]
+	fn outer_max(&self) -> SIZE {
+		self.outer(self.max_size())
+	}
+
+	#[must_use]
+	fn outer(&self, sz: SIZE) -> SIZE {
+		let m = &self.sizing().margin;
+		SIZE::with(sz.cx.saturating_add(m.horz()), sz.cy.saturating_add(m.vert()))
+	}
+
+	/// Computes the position of all the leaf nodes – controls and spacers –
+	/// within the given area. The node itself, minus its margins, takes the
+	/// whole area, regardless of its stretch weights.
+	///
+	/// The rectangles are returned in depth-first order of the leaves, that is,
+	/// the same order they appear when the tree is written down. Leaves placed
+	/// outside of a grid receive an empty rectangle.
+	///
+	/// This method performs no system calls.
+	#[must_use]
+	pub fn arrange(&self, rc: RECT) -> Vec<RECT> {
+		let mut rcs = Vec::with_capacity(self.leaf_count());
+		self.arrange_within(self.sizing().margin.shrink(rc), &mut rcs);
+		rcs
+	}
+
+	/// Returns the number of leaf nodes – controls and spacers – in the tree.
+	#[must_use]
+	pub fn leaf_count(&self) -> usize {
+		match self {
+			Self::Item(_) => 1,
+			Self::Stack(stack) => stack.children.iter().map(|ch| ch.leaf_count()).sum(),
+			Self::Grid(grid) => grid.children.iter().map(|(_, ch)| ch.leaf_count()).sum(),
+			Self::Dock(dock) => dock.children.iter().map(|(_, ch)| ch.leaf_count()).sum(),
+		}
+	}
+
+	/// Calls the closure for each leaf node, in the same order of
+	/// [`arrange`](crate::gui::LayoutNode::arrange).
+	pub(in crate::gui) fn for_each_item(&mut self, func: &mut impl FnMut(&mut LayoutItem)) {
+		match self {
+			Self::Item(item) => func(item),
+			Self::Stack(stack) => stack
+				.children
+				.iter_mut()
+				.for_each(|ch| ch.for_each_item(func)),
+			Self::Grid(grid) => grid
+				.children
+				.iter_mut()
+				.for_each(|(_, ch)| ch.for_each_item(func)),
+			Self::Dock(dock) => dock
+				.children
+				.iter_mut()
+				.for_each(|(_, ch)| ch.for_each_item(func)),
+		}
+	}
+
+	/// Places the node within the given cell, honoring its margins, stretch
+	/// weights and size limits. The node is aligned at the top left of the
+	/// cell.
+	fn place(&self, cell: RECT, rcs: &mut Vec<RECT>) {
+		self.place_stretched(cell, (false, false), rcs);
+	}
+
+	/// Like `place`, but forcing the stretch on the given axes.
+	fn place_stretched(&self, cell: RECT, stretched: (bool, bool), rcs: &mut Vec<RECT>) {
+		let s = self.sizing();
+		let avail = s.margin.shrink(cell);
+		let (pref, min, max) = (self.preferred_size(), self.min_size(), self.max_size());
+		let (avail_cx, avail_cy) = (avail.right - avail.left, avail.bottom - avail.top);
+		let cx = if stretched.0 || s.stretch.0 > 0 { avail_cx } else { pref.cx.min(avail_cx) };
+		let cy = if stretched.1 || s.stretch.1 > 0 { avail_cy } else { pref.cy.min(avail_cy) };
+		self.arrange_within(
+			RECT {
+				left: avail.left,
+				top: avail.top,
+				right: avail.left + cx.min(max.cx).max(min.cx),
+				bottom: avail.top + cy.min(max.cy).max(min.cy),
+			},
+			rcs,
+		);
+	}
+
+	/// Arranges the node so it takes exactly the given area.
+	fn arrange_within(&self, rc: RECT, rcs: &mut Vec<RECT>) {
+		match self {
+			Self::Item(_) => rcs.push(rc),
+			Self::Stack(stack) => stack.arrange(rc, rcs),
+			Self::Grid(grid) => grid.arrange(rc, rcs),
+			Self::Dock(dock) => dock.arrange(rc, rcs),
+		}
+	}
+
+	/// Pushes empty rectangles for all leaves of a node which is not shown.
+	fn hide(&self, rcs: &mut Vec<RECT>) {
+		rcs.extend(std::iter::repeat_n(RECT::new(), self.leaf_count()));
+	}
+}
+
+impl LayoutStack {
+	#[must_use]
+	fn natural(&self, measure: fn(&LayoutNode) -> SIZE) -> SIZE {
+		let (mut main, mut cross) = (0i32, 0i32);
+		for child in self.children.iter() {
+			let (m, c) = self.axes(measure(child));
+			main = main.saturating_add(m);
+			cross = cross.max(c);
+		}
+		main = main.saturating_add(gaps(self.spacing, self.children.len()));
+		let (cx, cy) = self.axes(SIZE::with(main, cross)); // swapping twice goes back
+		SIZE::with(cx.saturating_add(self.padding.horz()), cy.saturating_add(self.padding.vert()))
+	}
+
+	/// Returns the (main, cross) components of a size.
+	#[must_use]
+	const fn axes(&self, sz: SIZE) -> (i32, i32) {
+		match self.orientation {
+			Orientation::Horz => (sz.cx, sz.cy),
+			Orientation::Vert => (sz.cy, sz.cx),
+		}
+	}
+
+	fn arrange(&self, rc: RECT, rcs: &mut Vec<RECT>) {
+		let inner = self.padding.shrink(rc);
+		let (main_len, _) = self.axes(rect_size(inner));
+		let avail = main_len - gaps(self.spacing, self.children.len());
+
+		let tracks = self
+			.children
+			.iter()
+			.map(|child| {
+				let weight = match self.orientation {
+					Orientation::Horz => child.sizing().stretch.0,
+					Orientation::Vert => child.sizing().stretch.1,
+				};
+				Track {
+					pref: self.axes(child.outer_preferred()).0,
+					min: self.axes(child.outer_min()).0,
+					max: self.axes(child.outer_max()).0,
+					weight,
+				}
+			})
+			.collect::<Vec<_>>();
+
+		let mut pos = match self.orientation {
+			Orientation::Horz => inner.left,
+			Orientation::Vert => inner.top,
+		};
+		for (child, len) in self.children.iter().zip(distribute(&tracks, avail)) {
+			let cell = match self.orientation {
+				Orientation::Horz => RECT { left: pos, right: pos + len, ..inner },
+				Orientation::Vert => RECT { top: pos, bottom: pos + len, ..inner },
+			};
+			child.place(cell, rcs);
+			pos += len + self.spacing;
+		}
+	}
+}
+
+impl LayoutGrid {
+	#[must_use]
+	fn natural(&self, measure: fn(&LayoutNode) -> SIZE) -> SIZE {
+		let (cols, rows) = self.track_sizes(measure);
+		SIZE::with(
+			cols.iter()
+				.fold(gaps(self.spacing.0, cols.len()), |acc, v| acc.saturating_add(*v))
+				.saturating_add(self.padding.horz()),
+			rows.iter()
+				.fold(gaps(self.spacing.1, rows.len()), |acc, v| acc.saturating_add(*v))
+				.saturating_add(self.padding.vert()),
+		)
+	}
+
+	/// Returns the sizes of columns and rows, according to the single-span
+	/// children they contain.
+	#[must_use]
+	fn track_sizes(&self, measure: fn(&LayoutNode) -> SIZE) -> (Vec<i32>, Vec<i32>) {
+		let mut cols = vec![0; self.cols.len()];
+		let mut rows = vec![0; self.rows.len()];
+		for (cell, child) in self.children.iter() {
+			let sz = measure(child);
+			if cell.col_span == 1 && cell.col < cols.len() {
+				cols[cell.col] = cols[cell.col].max(sz.cx);
+			}
+			if cell.row_span == 1 && cell.row < rows.len() {
+				rows[cell.row] = rows[cell.row].max(sz.cy);
+			}
+		}
+		(cols, rows)
+	}
+
+	fn arrange(&self, rc: RECT, rcs: &mut Vec<RECT>) {
+		let inner = self.padding.shrink(rc);
+		let (pref_cols, pref_rows) = self.track_sizes(LayoutNode::outer_preferred);
+		let (min_cols, min_rows) = self.track_sizes(LayoutNode::outer_min);
+
+		let to_tracks = |prefs: &[i32], mins: &[i32], weights: &[u32]| {
+			prefs
+				.iter()
+				.zip(mins)
+				.zip(weights)
+				.map(|((pref, min), weight)| Track {
+					pref: *pref,
+					min: *min,
+					max: i32::MAX,
+					weight: *weight,
+				})
+				.collect::<Vec<_>>()
+		};
+		let col_lens = distribute(
+			&to_tracks(&pref_cols, &min_cols, &self.cols),
+			inner.right - inner.left - gaps(self.spacing.0, self.cols.len()),
+		);
+		let row_lens = distribute(
+			&to_tracks(&pref_rows, &min_rows, &self.rows),
+			inner.bottom - inner.top - gaps(self.spacing.1, self.rows.len()),
+		);
+		let col_pos = positions(inner.left, &col_lens, self.spacing.0);
+		let row_pos = positions(inner.top, &row_lens, self.spacing.1);
+
+		for (cell, child) in self.children.iter() {
+			let last_col = cell.col + cell.col_span.max(1) - 1;
+			let last_row = cell.row + cell.row_span.max(1) - 1;
+			if last_col >= col_lens.len() || last_row >= row_lens.len() {
+				child.hide(rcs); // outside the grid
+				continue;
+			}
+			child.place(
+				RECT {
+					left: col_pos[cell.col],
+					top: row_pos[cell.row],
+					right: col_pos[last_col] + col_lens[last_col],
+					bottom: row_pos[last_row] + row_lens[last_row],
+				},
+				rcs,
+			);
+		}
+	}
+}
+
+impl LayoutDock {
+	#[must_use]
+	fn natural(&self, measure: fn(&LayoutNode) -> SIZE) -> SIZE {
+		// Walk backwards, wrapping the area needed by the following children.
+		let mut sz = SIZE::new();
+		let mut is_first = true;
+		for (dock, child) in self.children.iter().rev() {
+			let ch = measure(child);
+			let gap = if is_first { 0 } else { self.spacing };
+			sz = match dock {
+				Dock::Left | Dock::Right => {
+					SIZE::with(sz.cx.saturating_add(ch.cx).saturating_add(gap), sz.cy.max(ch.cy))
+				},
+				Dock::Top | Dock::Bottom => {
+					SIZE::with(sz.cx.max(ch.cx), sz.cy.saturating_add(ch.cy).saturating_add(gap))
+				},
+				Dock::Fill => SIZE::with(sz.cx.max(ch.cx), sz.cy.max(ch.cy)),
+			};
+			is_first = false;
+		}
+		SIZE::with(
+			sz.cx.saturating_add(self.padding.horz()),
+			sz.cy.saturating_add(self.padding.vert()),
+		)
+	}
+
+	fn arrange(&self, rc: RECT, rcs: &mut Vec<RECT>) {
+		let mut rem = self.padding.shrink(rc);
+		for (dock, child) in self.children.iter() {
+			let pref = child.outer_preferred();
+			let min = child.outer_min();
+			let cx = pref.cx.min(rem.right - rem.left).max(min.cx);
+			let cy = pref.cy.min(rem.bottom - rem.top).max(min.cy);
+			let cell = match dock {
+				Dock::Left => {
+					let cell = RECT { right: rem.left + cx, ..rem };
+					rem.left = (cell.right + self.spacing).min(rem.right);
+					cell
+				},
+				Dock::Top => {
+					let cell = RECT { bottom: rem.top + cy, ..rem };
+					rem.top = (cell.bottom + self.spacing).min(rem.bottom);
+					cell
+				},
+				Dock::Right => {
+					let cell = RECT { left: rem.right - cx, ..rem };
+					rem.right = (cell.left - self.spacing).max(rem.left);
+					cell
+				},
+				Dock::Bottom => {
+					let cell = RECT { top: rem.bottom - cy, ..rem };
+					rem.bottom = (cell.top - self.spacing).max(rem.top);
+					cell
+				},
+				Dock::Fill => rem,
+			};
+
+			// Docked children fill the cross axis, so they stretch regardless
+			// of their weights.
+			let stretched = match dock {
+				Dock::Left | Dock::Right => (false, true),
+				Dock::Top | Dock::Bottom => (true, false),
+				Dock::Fill => (true, true),
+			};
+			child.place_stretched(cell, stretched, rcs);
+		}
+	}
+}
+
+/// Constraints of a column, row or stack slot, including margins.
+struct Track {
+	pref: i32,
+	min: i32,
+	max: i32,
+	weight: u32,
+}
+
+/// Splits the available length among the tracks: extra space goes to the
+/// tracks with non-zero weights, proportionally, up to their maximum; lacking
+/// space is taken from all tracks, proportionally to how much each one can
+/// shrink down to its minimum.
+#[must_use]
+fn distribute(tracks: &[Track], avail: i32) -> Vec<i32> {
+	let mut lens = tracks
+		.iter()
+		.map(|t| t.pref.min(t.max.max(t.min)).max(t.min))
+		.collect::<Vec<_>>();
+	let total = lens.iter().map(|len| *len as i64).sum::<i64>();
+	let avail = avail.max(0) as i64;
+
+	if avail > total {
+		let mut extra = avail - total;
+		while extra > 0 {
+			let active = (0..tracks.len())
+				.filter(|i| tracks[*i].weight > 0 && lens[*i] < tracks[*i].max)
+				.collect::<Vec<_>>();
+			let weight_sum = active.iter().map(|i| tracks[*i].weight as i64).sum::<i64>();
+			if weight_sum == 0 {
+				break; // nobody can grow anymore
+			}
+
+			let mut given = 0;
+			let mut acc_weight = 0;
+			for i in active {
+				let prev = extra * acc_weight / weight_sum;
+				acc_weight += tracks[i].weight as i64;
+				let share = extra * acc_weight / weight_sum - prev; // rounding errors don't accumulate
+				let add = share.min((tracks[i].max - lens[i]) as i64);
+				lens[i] += add as i32;
+				given += add;
+			}
+			if given == 0 {
+				break;
+			}
+			extra -= given; // what's left was capped by max sizes, so loop again
+		}
+	} else if avail < total {
+		let slacks = tracks
+			.iter()
+			.zip(lens.iter())
+			.map(|(t, len)| (*len - t.min) as i64)
+			.collect::<Vec<_>>();
+		let slack_sum = slacks.iter().sum::<i64>();
+		if slack_sum > 0 {
+			let take = (total - avail).min(slack_sum);
+			let mut acc_slack = 0;
+			for (len, slack) in lens.iter_mut().zip(slacks) {
+				let prev = take * acc_slack / slack_sum;
+				acc_slack += slack;
+				*len -= (take * acc_slack / slack_sum - prev) as i32;
+			}
+		}
+	}
+
+	lens
+}
+
+/// Returns the starting position of each track.
+#[must_use]
+fn positions(start: i32, lens: &[i32], spacing: i32) -> Vec<i32> {
+	let mut pos = start;
+	lens.iter()
+		.map(|len| {
+			let cur = pos;
+			pos += len + spacing;
+			cur
+		})
+		.collect()
+}
+
+/// Returns the total spacing between the given number of items.
+#[must_use]
+const fn gaps(spacing: i32, count: usize) -> i32 {
+	if count == 0 { 0 } else { spacing.saturating_mul(count as i32 - 1) }
+}
+
+#[must_use]
+const fn rect_size(rc: RECT) -> SIZE {
+	SIZE::with(rc.right - rc.left, rc.bottom - rc.top)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn spacer(cx: i32, cy: i32, stretch: (u32, u32)) -> LayoutNode {
+		let mut node = LayoutNode::spacer(stretch);
+		node.sizing_mut().size = SIZE::with(cx, cy);
+		node
+	}
+
+	const fn rc(left: i32, top: i32, right: i32, bottom: i32) -> RECT {
+		RECT { left, top, right, bottom }
+	}
+
+	#[test]
+	fn stack_stretch() {
+		let root = LayoutNode::Stack(LayoutStack {
+			spacing: 4,
+			children: vec![spacer(10, 10, (0, 0)), spacer(0, 0, (1, 0)), spacer(0, 0, (2, 1))],
+			..Default::default()
+		});
+		assert!(root.preferred_size() == SIZE::with(18, 10));
+		assert!(
+			root.arrange(rc(0, 0, 100, 20))
+				== [rc(0, 0, 10, 10), rc(14, 0, 41, 0), rc(45, 0, 100, 20)]
+		);
+	}
+
+	#[test]
+	fn stack_shrink() {
+		let mut first = spacer(20, 20, (1, 1));
+		first.sizing_mut().min = SIZE::with(0, 10);
+		let root = LayoutNode::Stack(LayoutStack {
+			orientation: Orientation::Vert,
+			children: vec![first, spacer(20, 20, (1, 1))],
+			..Default::default()
+		});
+		assert!(root.min_size() == SIZE::with(0, 10));
+		assert!(root.arrange(rc(0, 0, 20, 30)) == [rc(0, 0, 20, 17), rc(0, 17, 20, 30)]);
+	}
+
+	#[test]
+	fn grid_cells() {
+		let root = LayoutNode::Grid(LayoutGrid {
+			cols: vec![0, 1],
+			rows: vec![0, 0],
+			spacing: (10, 5),
+			children: vec![
+				(GridCell::at(0, 0), spacer(20, 10, (0, 0))),
+				(GridCell { col_span: 2, ..GridCell::at(1, 0) }, spacer(0, 15, (1, 1))),
+				(GridCell::at(5, 5), spacer(10, 10, (0, 0))),
+			],
+			..Default::default()
+		});
+		assert_eq!(root.leaf_count(), 3);
+		assert!(
+			root.arrange(rc(0, 0, 100, 50)) == [rc(0, 0, 20, 10), rc(0, 15, 100, 30), RECT::new()]
+		);
+	}
+
+	#[test]
+	fn dock_edges() {
+		let mut root = LayoutNode::Dock(LayoutDock {
+			padding: Margins::all(5),
+			spacing: 2,
+			children: vec![
+				(Dock::Top, spacer(0, 10, (0, 0))),
+				(Dock::Left, spacer(20, 0, (0, 0))),
+				(Dock::Bottom, spacer(0, 8, (0, 0))),
+				(Dock::Fill, spacer(0, 0, (0, 0))),
+			],
+			..Default::default()
+		});
+		root.sizing_mut().margin = Margins::all(1);
+		assert!(root.preferred_size() == SIZE::with(32, 32));
+		assert!(
+			root.arrange(rc(0, 0, 102, 82))
+				== [rc(6, 6, 96, 16), rc(6, 18, 26, 76), rc(28, 68, 96, 76), rc(28, 18, 96, 66)]
+		);
+	}
+}
@@ -5,6 +5,7 @@ mod dlg_main;
 mod dlg_modal;
 mod dlg_modeless;
 mod layout;
+mod layout_tree;
 mod raw_base;
 mod raw_control;
 mod raw_main;
@@ -18,7 +19,7 @@ mod window_modeless;
 
 pub(in crate::gui) mod privs {
 	pub(in crate::gui) use super::base_wnd::BaseWnd;
-	pub(in crate::gui) use super::dlg_base::DlgBase;
+	pub(in crate::gui) use super::dlg_base::{DlgBase, DlgSrc};
 	pub(in crate::gui) use super::dlg_control::DlgControl;
 	pub(in crate::gui) use super::dlg_main::DlgMain;
 	pub(in crate::gui) use super::dlg_modal::DlgModal;
@@ -33,6 +34,10 @@ pub(in crate::gui) mod privs {
 
 pub mod decl {
 	pub use super::layout::{Horz, Vert};
+	pub use super::layout_tree::{
+		Dock, GridCell, LayoutDock, LayoutGrid, LayoutItem, LayoutNode, LayoutSizing, LayoutStack,
+		Margins, Orientation,
+	};
 	pub use super::raw_control::WindowControlOpts;
 	pub use super::raw_main::WindowMainOpts;
 	pub use super::raw_modal::WindowModalOpts;
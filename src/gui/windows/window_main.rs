@@ -101,7 +101,28 @@ impl WindowMain {
 	/// [`HINSTANCE::CreateDialogParam`](crate::HINSTANCE::CreateDialogParam).
 	#[must_use]
 	pub fn new_dlg(dlg_id: u16, icon_id: Option<u16>, accel_tbl_id: Option<u16>) -> Self {
-		Self(RawDlg::Dlg(DlgMain::new(dlg_id, icon_id, accel_tbl_id)))
+		Self(RawDlg::Dlg(DlgMain::new(DlgSrc::Id(dlg_id), icon_id, accel_tbl_id)))
+	}
+
+	/// Instantiates a new `WindowMain` object, to be created from an in-memory
+	/// dialog template with
+	/// [`HINSTANCE::CreateDialogIndirectParam`](crate::HINSTANCE::CreateDialogIndirectParam).
+	///
+	/// # Panics
+	///
+	/// Panics if the template can't be serialized; see
+	/// [`DlgTemplateEx::serialize`](crate::DlgTemplateEx::serialize).
+	#[must_use]
+	pub fn new_dlg_template(
+		template: &DlgTemplateEx,
+		icon_id: Option<u16>,
+		accel_tbl_id: Option<u16>,
+	) -> Self {
+		Self(RawDlg::Dlg(DlgMain::new(
+			DlgSrc::from_template(template),
+			icon_id,
+			accel_tbl_id,
+		)))
 	}
 
 	/// Physically creates the window, then runs the main application loop. This
@@ -55,7 +55,20 @@ impl WindowModal {
 	/// [`HINSTANCE::DialogBoxParam`](crate::HINSTANCE::DialogBoxParam).
 	#[must_use]
 	pub fn new_dlg(dlg_id: u16) -> Self {
-		Self(RawDlg::Dlg(DlgModal::new(dlg_id)))
+		Self(RawDlg::Dlg(DlgModal::new(DlgSrc::Id(dlg_id))))
+	}
+
+	/// Instantiates a new `WindowModal` object, to be created from an in-memory
+	/// dialog template with
+	/// [`HINSTANCE::DialogBoxIndirectParam`](crate::HINSTANCE::DialogBoxIndirectParam).
+	///
+	/// # Panics
+	///
+	/// Panics if the template can't be serialized; see
+	/// [`DlgTemplateEx::serialize`](crate::DlgTemplateEx::serialize).
+	#[must_use]
+	pub fn new_dlg_template(template: &DlgTemplateEx) -> Self {
+		Self(RawDlg::Dlg(DlgModal::new(DlgSrc::from_template(template))))
 	}
 
 	/// Physically creates the window, then runs the modal loop. This method
@@ -0,0 +1,65 @@
+//! Little-endian helpers shared by the parsers and writers of binary file
+//! formats, like resources, images and compound files.
+//!
+//! Reading out of bounds yields
+//! [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+
+use crate::co;
+use crate::decl::*;
+
+/// Rounds the offset up to the next multiple of 4.
+#[must_use]
+pub(crate) const fn align4(off: usize) -> usize {
+	(off + 3) & !3
+}
+
+pub(crate) fn read_word(data: &[u8], at: usize) -> SysResult<u16> {
+	data.get(at..at + 2)
+		.map(|b| u16::from_le_bytes([b[0], b[1]]))
+		.ok_or(co::ERROR::INVALID_DATA)
+}
+
+pub(crate) fn read_dword(data: &[u8], at: usize) -> SysResult<u32> {
+	data.get(at..at + 4)
+		.map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+		.ok_or(co::ERROR::INVALID_DATA)
+}
+
+pub(crate) fn read_qword(data: &[u8], at: usize) -> SysResult<u64> {
+	data.get(at..at + 8)
+		.map(|b| u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+		.ok_or(co::ERROR::INVALID_DATA)
+}
+
+#[cfg(feature = "shell")]
+pub(crate) fn read_guid(data: &[u8], at: usize) -> SysResult<GUID> {
+	data.get(at..at + 16)
+		.map(|b| GUID::from_le_bytes(b.try_into().unwrap()))
+		.ok_or(co::ERROR::INVALID_DATA)
+}
+
+/// Reads a null-terminated UTF-16 string.
+#[cfg(any(feature = "shell", feature = "version"))]
+pub(crate) fn read_utf16z(data: &[u8], at: usize) -> SysResult<String> {
+	let raw = data.get(at..).ok_or(co::ERROR::INVALID_DATA)?;
+	let wchars = raw
+		.chunks_exact(2)
+		.map(|ch| u16::from_le_bytes([ch[0], ch[1]]))
+		.take_while(|ch| *ch != 0)
+		.collect::<Vec<_>>();
+	if wchars.len() == raw.len() / 2 {
+		return Err(co::ERROR::INVALID_DATA); // no terminating null
+	}
+	Ok(String::from_utf16_lossy(&wchars))
+}
+
+/// Reads a null-terminated 8-bit string, decoded as Latin-1.
+#[cfg(feature = "shell")]
+pub(crate) fn read_ansiz(data: &[u8], at: usize) -> SysResult<String> {
+	let raw = data.get(at..).ok_or(co::ERROR::INVALID_DATA)?;
+	let len = raw
+		.iter()
+		.position(|ch| *ch == 0)
+		.ok_or(co::ERROR::INVALID_DATA)?;
+	Ok(raw[..len].iter().map(|ch| *ch as char).collect())
+}
@@ -0,0 +1,250 @@
+use crate::co::*;
+use crate::decl::*;
+
+impl CP {
+	/// Returns `true` if the code page is a single-byte code page with a
+	/// built-in conversion table, used by [`CP::decode`](crate::co::CP::decode)
+	/// and [`CP::encode`](crate::co::CP::encode). These are:
+	///
+	/// * [`CP::WINDOWS_1250`](crate::co::CP::WINDOWS_1250)
+	/// * [`CP::WINDOWS_1251`](crate::co::CP::WINDOWS_1251)
+	/// * [`CP::WINDOWS_1252`](crate::co::CP::WINDOWS_1252)
+	/// * [`CP::WINDOWS_1253`](crate::co::CP::WINDOWS_1253)
+	/// * [`CP::IBM437`](crate::co::CP::IBM437)
+	/// * [`CP::IBM850`](crate::co::CP::IBM850)
+	#[must_use]
+	pub const fn has_builtin_table(self) -> bool {
+		self.table().is_some()
+	}
+
+	/// Converts bytes of a single-byte code page into an UTF-16 string, using
+	/// the built-in conversion table, in pure Rust. Unlike
+	/// [`MultiByteToWideChar`](crate::MultiByteToWideChar), the result doesn't
+	/// depend on the host.
+	///
+	/// Bytes undefined in the code page are mapped the same way Windows does:
+	/// to the C1 control chars of same value, or to private use chars in
+	/// Windows-1253.
+	///
+	/// Fails with
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER) if
+	/// the code page has no [built-in table](crate::co::CP::has_builtin_table).
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let wide = co::CP::WINDOWS_1252.decode(&[0x80, 0x20, 0x31, 0x30])?;
+	/// assert_eq!(w::WString::from_wchars_slice(&wide).to_string(), "€ 10");
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn decode(self, data: &[u8]) -> SysResult<Vec<u16>> {
+		let table = self.table().ok_or(ERROR::INVALID_PARAMETER)?;
+		Ok(data
+			.iter()
+			.map(|ch| if *ch < 0x80 { *ch as u16 } else { table[(*ch - 0x80) as usize] })
+			.collect())
+	}
+
+	/// Converts an UTF-16 string into bytes of a single-byte code page, using
+	/// the built-in conversion table, in pure Rust. Unlike
+	/// [`WideCharToMultiByte`](crate::WideCharToMultiByte), the result doesn't
+	/// depend on the host.
+	///
+	/// Characters not present in the code page, including unpaired surrogates,
+	/// are handled according to `fallback`; the best fit is an ASCII char.
+	///
+	/// Fails with
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER) if
+	/// the code page has no [built-in table](crate::co::CP::has_builtin_table).
+	#[must_use]
+	pub fn encode(self, text: &[u16], fallback: EncodeFallback) -> SysResult<Vec<u8>> {
+		let table = self.table().ok_or(ERROR::INVALID_PARAMETER)?;
+		let mut buf = Vec::with_capacity(text.len());
+
+		for ch in char::decode_utf16(text.iter().copied()) {
+			let ch = ch.unwrap_or(char::REPLACEMENT_CHARACTER); // never present in tables
+			let code_point = ch as u32;
+
+			let byte = if code_point < 0x80 {
+				Some(code_point as u8)
+			} else if code_point <= 0xffff {
+				table
+					.iter()
+					.position(|t| *t as u32 == code_point)
+					.map(|idx| idx as u8 + 0x80)
+			} else {
+				None
+			};
+
+			buf.push(match byte {
+				Some(byte) => byte,
+				None => match fallback {
+					EncodeFallback::Error => return Err(ERROR::NO_UNICODE_TRANSLATION),
+					EncodeFallback::Replace => b'?',
+					EncodeFallback::BestFit => Encoding::ascii_best_fit(ch).unwrap_or(b'?'),
+				},
+			});
+		}
+		Ok(buf)
+	}
+
+	/// Returns the upper half of the conversion table, if any; the lower half
+	/// is ASCII for all built-in code pages.
+	#[must_use]
+	const fn table(self) -> Option<&'static [u16; 128]> {
+		match self {
+			Self::WINDOWS_1250 => Some(&CP1250),
+			Self::WINDOWS_1251 => Some(&CP1251),
+			Self::WINDOWS_1252 => Some(&CP1252),
+			Self::WINDOWS_1253 => Some(&CP1253),
+			Self::IBM437 => Some(&CP437),
+			Self::IBM850 => Some(&CP850),
+			_ => None,
+		}
+	}
+}
+
+/// Windows-1250, Central European.
+static CP1250: [u16; 128] = [
+	0x20ac, 0x0081, 0x201a, 0x0083, 0x201e, 0x2026, 0x2020, 0x2021, 0x0088, 0x2030, 0x0160, 0x2039,
+	0x015a, 0x0164, 0x017d, 0x0179, 0x0090, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+	0x0098, 0x2122, 0x0161, 0x203a, 0x015b, 0x0165, 0x017e, 0x017a, 0x00a0, 0x02c7, 0x02d8, 0x0141,
+	0x00a4, 0x0104, 0x00a6, 0x00a7, 0x00a8, 0x00a9, 0x015e, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x017b,
+	0x00b0, 0x00b1, 0x02db, 0x0142, 0x00b4, 0x00b5, 0x00b6, 0x00b7, 0x00b8, 0x0105, 0x015f, 0x00bb,
+	0x013d, 0x02dd, 0x013e, 0x017c, 0x0154, 0x00c1, 0x00c2, 0x0102, 0x00c4, 0x0139, 0x0106, 0x00c7,
+	0x010c, 0x00c9, 0x0118, 0x00cb, 0x011a, 0x00cd, 0x00ce, 0x010e, 0x0110, 0x0143, 0x0147, 0x00d3,
+	0x00d4, 0x0150, 0x00d6, 0x00d7, 0x0158, 0x016e, 0x00da, 0x0170, 0x00dc, 0x00dd, 0x0162, 0x00df,
+	0x0155, 0x00e1, 0x00e2, 0x0103, 0x00e4, 0x013a, 0x0107, 0x00e7, 0x010d, 0x00e9, 0x0119, 0x00eb,
+	0x011b, 0x00ed, 0x00ee, 0x010f, 0x0111, 0x0144, 0x0148, 0x00f3, 0x00f4, 0x0151, 0x00f6, 0x00f7,
+	0x0159, 0x016f, 0x00fa, 0x0171, 0x00fc, 0x00fd, 0x0163, 0x02d9,
+];
+
+/// Windows-1251, Cyrillic.
+static CP1251: [u16; 128] = [
+	0x0402, 0x0403, 0x201a, 0x0453, 0x201e, 0x2026, 0x2020, 0x2021, 0x20ac, 0x2030, 0x0409, 0x2039,
+	0x040a, 0x040c, 0x040b, 0x040f, 0x0452, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+	0x0098, 0x2122, 0x0459, 0x203a, 0x045a, 0x045c, 0x045b, 0x045f, 0x00a0, 0x040e, 0x045e, 0x0408,
+	0x00a4, 0x0490, 0x00a6, 0x00a7, 0x0401, 0x00a9, 0x0404, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x0407,
+	0x00b0, 0x00b1, 0x0406, 0x0456, 0x0491, 0x00b5, 0x00b6, 0x00b7, 0x0451, 0x2116, 0x0454, 0x00bb,
+	0x0458, 0x0405, 0x0455, 0x0457, 0x0410, 0x0411, 0x0412, 0x0413, 0x0414, 0x0415, 0x0416, 0x0417,
+	0x0418, 0x0419, 0x041a, 0x041b, 0x041c, 0x041d, 0x041e, 0x041f, 0x0420, 0x0421, 0x0422, 0x0423,
+	0x0424, 0x0425, 0x0426, 0x0427, 0x0428, 0x0429, 0x042a, 0x042b, 0x042c, 0x042d, 0x042e, 0x042f,
+	0x0430, 0x0431, 0x0432, 0x0433, 0x0434, 0x0435, 0x0436, 0x0437, 0x0438, 0x0439, 0x043a, 0x043b,
+	0x043c, 0x043d, 0x043e, 0x043f, 0x0440, 0x0441, 0x0442, 0x0443, 0x0444, 0x0445, 0x0446, 0x0447,
+	0x0448, 0x0449, 0x044a, 0x044b, 0x044c, 0x044d, 0x044e, 0x044f,
+];
+
+/// Windows-1252, Western European.
+static CP1252: [u16; 128] = [
+	0x20ac, 0x0081, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021, 0x02c6, 0x2030, 0x0160, 0x2039,
+	0x0152, 0x008d, 0x017d, 0x008f, 0x0090, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+	0x02dc, 0x2122, 0x0161, 0x203a, 0x0153, 0x009d, 0x017e, 0x0178, 0x00a0, 0x00a1, 0x00a2, 0x00a3,
+	0x00a4, 0x00a5, 0x00a6, 0x00a7, 0x00a8, 0x00a9, 0x00aa, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x00af,
+	0x00b0, 0x00b1, 0x00b2, 0x00b3, 0x00b4, 0x00b5, 0x00b6, 0x00b7, 0x00b8, 0x00b9, 0x00ba, 0x00bb,
+	0x00bc, 0x00bd, 0x00be, 0x00bf, 0x00c0, 0x00c1, 0x00c2, 0x00c3, 0x00c4, 0x00c5, 0x00c6, 0x00c7,
+	0x00c8, 0x00c9, 0x00ca, 0x00cb, 0x00cc, 0x00cd, 0x00ce, 0x00cf, 0x00d0, 0x00d1, 0x00d2, 0x00d3,
+	0x00d4, 0x00d5, 0x00d6, 0x00d7, 0x00d8, 0x00d9, 0x00da, 0x00db, 0x00dc, 0x00dd, 0x00de, 0x00df,
+	0x00e0, 0x00e1, 0x00e2, 0x00e3, 0x00e4, 0x00e5, 0x00e6, 0x00e7, 0x00e8, 0x00e9, 0x00ea, 0x00eb,
+	0x00ec, 0x00ed, 0x00ee, 0x00ef, 0x00f0, 0x00f1, 0x00f2, 0x00f3, 0x00f4, 0x00f5, 0x00f6, 0x00f7,
+	0x00f8, 0x00f9, 0x00fa, 0x00fb, 0x00fc, 0x00fd, 0x00fe, 0x00ff,
+];
+
+/// Windows-1253, Greek.
+static CP1253: [u16; 128] = [
+	0x20ac, 0x0081, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021, 0x0088, 0x2030, 0x008a, 0x2039,
+	0x008c, 0x008d, 0x008e, 0x008f, 0x0090, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+	0x0098, 0x2122, 0x009a, 0x203a, 0x009c, 0x009d, 0x009e, 0x009f, 0x00a0, 0x0385, 0x0386, 0x00a3,
+	0x00a4, 0x00a5, 0x00a6, 0x00a7, 0x00a8, 0x00a9, 0xf8f9, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x2015,
+	0x00b0, 0x00b1, 0x00b2, 0x00b3, 0x0384, 0x00b5, 0x00b6, 0x00b7, 0x0388, 0x0389, 0x038a, 0x00bb,
+	0x038c, 0x00bd, 0x038e, 0x038f, 0x0390, 0x0391, 0x0392, 0x0393, 0x0394, 0x0395, 0x0396, 0x0397,
+	0x0398, 0x0399, 0x039a, 0x039b, 0x039c, 0x039d, 0x039e, 0x039f, 0x03a0, 0x03a1, 0xf8fa, 0x03a3,
+	0x03a4, 0x03a5, 0x03a6, 0x03a7, 0x03a8, 0x03a9, 0x03aa, 0x03ab, 0x03ac, 0x03ad, 0x03ae, 0x03af,
+	0x03b0, 0x03b1, 0x03b2, 0x03b3, 0x03b4, 0x03b5, 0x03b6, 0x03b7, 0x03b8, 0x03b9, 0x03ba, 0x03bb,
+	0x03bc, 0x03bd, 0x03be, 0x03bf, 0x03c0, 0x03c1, 0x03c2, 0x03c3, 0x03c4, 0x03c5, 0x03c6, 0x03c7,
+	0x03c8, 0x03c9, 0x03ca, 0x03cb, 0x03cc, 0x03cd, 0x03ce, 0xf8fb,
+];
+
+/// IBM437, OEM United States.
+static CP437: [u16; 128] = [
+	0x00c7, 0x00fc, 0x00e9, 0x00e2, 0x00e4, 0x00e0, 0x00e5, 0x00e7, 0x00ea, 0x00eb, 0x00e8, 0x00ef,
+	0x00ee, 0x00ec, 0x00c4, 0x00c5, 0x00c9, 0x00e6, 0x00c6, 0x00f4, 0x00f6, 0x00f2, 0x00fb, 0x00f9,
+	0x00ff, 0x00d6, 0x00dc, 0x00a2, 0x00a3, 0x00a5, 0x20a7, 0x0192, 0x00e1, 0x00ed, 0x00f3, 0x00fa,
+	0x00f1, 0x00d1, 0x00aa, 0x00ba, 0x00bf, 0x2310, 0x00ac, 0x00bd, 0x00bc, 0x00a1, 0x00ab, 0x00bb,
+	0x2591, 0x2592, 0x2593, 0x2502, 0x2524, 0x2561, 0x2562, 0x2556, 0x2555, 0x2563, 0x2551, 0x2557,
+	0x255d, 0x255c, 0x255b, 0x2510, 0x2514, 0x2534, 0x252c, 0x251c, 0x2500, 0x253c, 0x255e, 0x255f,
+	0x255a, 0x2554, 0x2569, 0x2566, 0x2560, 0x2550, 0x256c, 0x2567, 0x2568, 0x2564, 0x2565, 0x2559,
+	0x2558, 0x2552, 0x2553, 0x256b, 0x256a, 0x2518, 0x250c, 0x2588, 0x2584, 0x258c, 0x2590, 0x2580,
+	0x03b1, 0x00df, 0x0393, 0x03c0, 0x03a3, 0x03c3, 0x00b5, 0x03c4, 0x03a6, 0x0398, 0x03a9, 0x03b4,
+	0x221e, 0x03c6, 0x03b5, 0x2229, 0x2261, 0x00b1, 0x2265, 0x2264, 0x2320, 0x2321, 0x00f7, 0x2248,
+	0x00b0, 0x2219, 0x00b7, 0x221a, 0x207f, 0x00b2, 0x25a0, 0x00a0,
+];
+
+/// IBM850, OEM Multilingual Latin 1.
+static CP850: [u16; 128] = [
+	0x00c7, 0x00fc, 0x00e9, 0x00e2, 0x00e4, 0x00e0, 0x00e5, 0x00e7, 0x00ea, 0x00eb, 0x00e8, 0x00ef,
+	0x00ee, 0x00ec, 0x00c4, 0x00c5, 0x00c9, 0x00e6, 0x00c6, 0x00f4, 0x00f6, 0x00f2, 0x00fb, 0x00f9,
+	0x00ff, 0x00d6, 0x00dc, 0x00f8, 0x00a3, 0x00d8, 0x00d7, 0x0192, 0x00e1, 0x00ed, 0x00f3, 0x00fa,
+	0x00f1, 0x00d1, 0x00aa, 0x00ba, 0x00bf, 0x00ae, 0x00ac, 0x00bd, 0x00bc, 0x00a1, 0x00ab, 0x00bb,
+	0x2591, 0x2592, 0x2593, 0x2502, 0x2524, 0x00c1, 0x00c2, 0x00c0, 0x00a9, 0x2563, 0x2551, 0x2557,
+	0x255d, 0x00a2, 0x00a5, 0x2510, 0x2514, 0x2534, 0x252c, 0x251c, 0x2500, 0x253c, 0x00e3, 0x00c3,
+	0x255a, 0x2554, 0x2569, 0x2566, 0x2560, 0x2550, 0x256c, 0x00a4, 0x00f0, 0x00d0, 0x00ca, 0x00cb,
+	0x00c8, 0x0131, 0x00cd, 0x00ce, 0x00cf, 0x2518, 0x250c, 0x2588, 0x2584, 0x00a6, 0x00cc, 0x2580,
+	0x00d3, 0x00df, 0x00d4, 0x00d2, 0x00f5, 0x00d5, 0x00b5, 0x00fe, 0x00de, 0x00da, 0x00db, 0x00d9,
+	0x00fd, 0x00dd, 0x00af, 0x00b4, 0x00ad, 0x00b1, 0x2017, 0x00be, 0x00b6, 0x00a7, 0x00f7, 0x00b8,
+	0x00b0, 0x00a8, 0x00b7, 0x00b9, 0x00b3, 0x00b2, 0x25a0, 0x00a0,
+];
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const BUILTIN: [CP; 6] = [
+		CP::WINDOWS_1250,
+		CP::WINDOWS_1251,
+		CP::WINDOWS_1252,
+		CP::WINDOWS_1253,
+		CP::IBM437,
+		CP::IBM850,
+	];
+
+	#[test]
+	fn known_chars() {
+		for (cp, bytes, text) in [
+			(CP::WINDOWS_1250, &[0x8a, 0xb9, 0xe8][..], "\u{160}\u{105}\u{10d}"),
+			(CP::WINDOWS_1251, &[0x88, 0xa8, 0xc0, 0xff], "\u{20ac}\u{401}\u{410}\u{44f}"),
+			(CP::WINDOWS_1252, &[0x80, 0x9f, 0xe9, 0x81], "\u{20ac}\u{178}\u{e9}\u{81}"),
+			(CP::WINDOWS_1253, &[0xa2, 0xc1, 0xf9], "\u{386}\u{391}\u{3c9}"),
+			(CP::IBM437, &[0x80, 0xb0, 0xe1, 0xff], "\u{c7}\u{2591}\u{df}\u{a0}"),
+			(CP::IBM850, &[0x80, 0x9b, 0xd5, 0xff], "\u{c7}\u{f8}\u{131}\u{a0}"),
+		] {
+			let wide = text.encode_utf16().collect::<Vec<_>>();
+			assert_eq!(cp.decode(bytes).unwrap(), wide, "{}", cp);
+			assert_eq!(cp.encode(&wide, EncodeFallback::Error).unwrap(), bytes, "{}", cp);
+		}
+	}
+
+	#[test]
+	fn round_trip_all_bytes() {
+		let all = (0..=255u8).collect::<Vec<_>>();
+		for cp in BUILTIN {
+			assert!(cp.has_builtin_table());
+			let wide = cp.decode(&all).unwrap();
+			assert_eq!(cp.encode(&wide, EncodeFallback::Error).unwrap(), all, "{}", cp);
+		}
+	}
+
+	#[test]
+	fn fallback() {
+		let text = "\u{3b1}\u{e9}".encode_utf16().collect::<Vec<_>>(); // Greek alpha isn't in 1252
+		let cp = CP::WINDOWS_1252;
+		assert_eq!(cp.encode(&text, EncodeFallback::Error), Err(ERROR::NO_UNICODE_TRANSLATION));
+		assert_eq!(cp.encode(&text, EncodeFallback::Replace).unwrap(), b"?\xe9");
+		assert_eq!(cp.encode(&[0xd800], EncodeFallback::Replace).unwrap(), b"?");
+
+		assert!(!CP::UTF8.has_builtin_table());
+		assert_eq!(CP::UTF8.decode(b"a"), Err(ERROR::INVALID_PARAMETER));
+	}
+}
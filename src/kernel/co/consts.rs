@@ -97,6 +97,10 @@ const_ordinary! { CP: u16;
 	/// Symbol code page (42).
 	SYMBOL 42
 
+	/// OEM United States.
+	IBM437 437
+	/// OEM Multilingual Latin 1; Western European (DOS).
+	IBM850 850
 	/// Unicode UTF-16 little endian byte order (BMP of ISO 10646); available
 	/// only to managed applications.
 	UTF16 1200
@@ -600,6 +604,92 @@ const_ordinary! { HF32: u32;
 	DEFAULT 1
 }
 
+const_bitflag! { IMAGE_DLLCHARACTERISTICS: u16;
+	/// [`PeFile`](crate::PeFile) `dll_characteristics` (`u16`).
+	=>
+	HIGH_ENTROPY_VA 0x0020
+	DYNAMIC_BASE 0x0040
+	FORCE_INTEGRITY 0x0080
+	NX_COMPAT 0x0100
+	NO_ISOLATION 0x0200
+	NO_SEH 0x0400
+	NO_BIND 0x0800
+	APPCONTAINER 0x1000
+	WDM_DRIVER 0x2000
+	GUARD_CF 0x4000
+	TERMINAL_SERVER_AWARE 0x8000
+}
+
+const_bitflag! { IMAGE_FILE: u16;
+	/// [`PeFile`](crate::PeFile) `characteristics` (`u16`).
+	=>
+	RELOCS_STRIPPED 0x0001
+	EXECUTABLE_IMAGE 0x0002
+	LINE_NUMS_STRIPPED 0x0004
+	LOCAL_SYMS_STRIPPED 0x0008
+	AGGRESIVE_WS_TRIM 0x0010
+	LARGE_ADDRESS_AWARE 0x0020
+	BYTES_REVERSED_LO 0x0080
+	MACHINE_32BIT 0x0100
+	DEBUG_STRIPPED 0x0200
+	REMOVABLE_RUN_FROM_SWAP 0x0400
+	NET_RUN_FROM_SWAP 0x0800
+	SYSTEM 0x1000
+	DLL 0x2000
+	UP_SYSTEM_ONLY 0x4000
+	BYTES_REVERSED_HI 0x8000
+}
+
+const_ordinary! { IMAGE_FILE_MACHINE: u16;
+	/// [`PeFile`](crate::PeFile) `machine` (`u16`).
+	=>
+	UNKNOWN 0
+	I386 0x014c
+	ARMNT 0x01c4
+	IA64 0x0200
+	AMD64 0x8664
+	ARM64 0xaa64
+}
+
+const_bitflag! { IMAGE_SCN: u32;
+	/// [`PeSection`](crate::PeSection) `characteristics` (`u32`).
+	=>
+	CNT_CODE 0x0000_0020
+	CNT_INITIALIZED_DATA 0x0000_0040
+	CNT_UNINITIALIZED_DATA 0x0000_0080
+	LNK_INFO 0x0000_0200
+	LNK_REMOVE 0x0000_0800
+	LNK_COMDAT 0x0000_1000
+	GPREL 0x0000_8000
+	LNK_NRELOC_OVFL 0x0100_0000
+	MEM_DISCARDABLE 0x0200_0000
+	MEM_NOT_CACHED 0x0400_0000
+	MEM_NOT_PAGED 0x0800_0000
+	MEM_SHARED 0x1000_0000
+	MEM_EXECUTE 0x2000_0000
+	MEM_READ 0x4000_0000
+	MEM_WRITE 0x8000_0000
+}
+
+const_ordinary! { IMAGE_SUBSYSTEM: u16;
+	/// [`PeFile`](crate::PeFile) `subsystem` (`u16`).
+	=>
+	UNKNOWN 0
+	NATIVE 1
+	WINDOWS_GUI 2
+	WINDOWS_CUI 3
+	OS2_CUI 5
+	POSIX_CUI 7
+	NATIVE_WINDOWS 8
+	WINDOWS_CE_GUI 9
+	EFI_APPLICATION 10
+	EFI_BOOT_SERVICE_DRIVER 11
+	EFI_RUNTIME_DRIVER 12
+	EFI_ROM 13
+	XBOX 14
+	WINDOWS_BOOT_APPLICATION 16
+}
+
 const_ordinary! { LANG: u16;
 	/// Language
 	/// [identifier](https://learn.microsoft.com/en-us/windows/win32/intl/language-identifier-constants-and-strings)
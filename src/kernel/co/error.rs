@@ -28,11 +28,15 @@ const_basic_decl! { ERROR: u32;
 	/// Will print:
 	///
 	/// ```text
-	/// [0x001c 28] The printer is out of paper.
+	/// ERROR::OUT_OF_PAPER [28] The printer is out of paper.
 	/// ```
 	///
-	/// The numbers inside the brackets are the system error code, in
-	/// hexadecimal and decimal formats for convenience.
+	/// The number inside the brackets is the system error code. The
+	/// [`Debug`](std::fmt::Debug) output shows it in hexadecimal and decimal
+	/// formats for convenience.
+	///
+	/// The symbolic name can be parsed back with
+	/// [`FromStr`](std::str::FromStr).
 }
 
 impl std::error::Error for ERROR {
@@ -43,19 +47,31 @@ impl std::error::Error for ERROR {
 
 impl std::fmt::Display for ERROR {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if let Some(name) = crate::kernel::privs::const_name(Self::ALL_NAMES, self.0 as _) {
+			write!(f, "ERROR::{} ", name)?;
+		}
 		write!(f, "[{}] {}", self.0, self.FormatMessage().trim_end())
 	}
 }
 impl std::fmt::Debug for ERROR {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match crate::kernel::privs::const_name(Self::ALL_NAMES, self.0 as _) {
+			Some(name) => write!(f, "ERROR::{} ", name)?,
+			None => write!(f, "ERROR")?,
+		}
 		if self.0 > 0xffff {
-			write!(f, "ERROR({:#010x} {}) {}", self.0, self.0, self.FormatMessage().trim_end())
+			write!(f, "({:#010x} {}) {}", self.0, self.0, self.FormatMessage().trim_end())
 		} else {
-			write!(f, "ERROR({:#06x} {}) {}", self.0, self.0, self.FormatMessage().trim_end())
+			write!(f, "({:#06x} {}) {}", self.0, self.0, self.FormatMessage().trim_end())
 		}
 	}
 }
 
+const_impl_names!(ERROR ordinary [
+	NAMES,
+	#[cfg(feature = "wininet")] NAMES_WININET
+]);
+
 impl SystemError for ERROR {}
 
 const_values_pub! { ERROR;
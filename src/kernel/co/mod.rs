@@ -1,3 +1,4 @@
+mod code_page;
 mod consts;
 mod error;
 mod guids;
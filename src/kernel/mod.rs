@@ -10,11 +10,13 @@ mod iterators;
 mod structs;
 mod utilities;
 
+pub(crate) mod bytes;
 pub mod co;
 pub(crate) mod ffi_types;
 pub mod guards;
 pub(crate) mod privs;
 pub(crate) mod traits;
+pub(crate) mod xml;
 
 pub mod decl {
 	pub use super::aliases::*;
@@ -269,3 +269,237 @@ pub(crate) fn create_wstr_ptr_vecs(strings: &[impl AsRef<str>]) -> (Vec<WString>
 		(wstrs, pwstrs)
 	}
 }
+
+/// Name tables of a numeric newtype constant, written by `const_values_pub!`.
+pub(crate) type ConstNames = [&'static [(&'static str, u64)]];
+
+/// Mask with the bits of a numeric newtype constant with the given size.
+#[must_use]
+const fn const_mask(size: u32) -> u64 {
+	if size >= 8 { u64::MAX } else { (1 << (size * 8)) - 1 }
+}
+
+/// Returns the first name of the given value, if any.
+#[must_use]
+pub(crate) fn const_name(tables: &ConstNames, val: u64) -> Option<&'static str> {
+	tables
+		.iter()
+		.flat_map(|table| table.iter())
+		.find(|(_, v)| *v == val)
+		.map(|(name, _)| *name)
+}
+
+/// Decomposes a bitflag value into names, greedily taking the flags with more
+/// bits first. Returns the names and the bits which have no name.
+#[must_use]
+pub(crate) fn const_flag_names(tables: &ConstNames, val: u64) -> (Vec<&'static str>, u64) {
+	let mut flags = tables
+		.iter()
+		.flat_map(|table| table.iter())
+		.filter(|(_, v)| *v != 0 && val & *v == *v)
+		.collect::<Vec<_>>();
+	flags.sort_by_key(|(_, v)| std::cmp::Reverse(v.count_ones())); // stable
+
+	let mut names = Vec::<&'static str>::new();
+	let mut remaining = val;
+	for (name, v) in flags {
+		if remaining & *v == *v {
+			names.push(name);
+			remaining &= !*v;
+		}
+	}
+	(names, remaining)
+}
+
+/// Writes the symbolic representation of a numeric newtype constant, like
+/// `WM::CLOSE`, or `WS::CAPTION | WS::SYSMENU | 0x4` for bitflags. Values
+/// without names are written as `WM(0x1234)`.
+pub(crate) fn fmt_const(
+	f: &mut std::fmt::Formatter<'_>,
+	type_name: &str,
+	tables: &ConstNames,
+	val: u64,
+	size: u32,
+	bitflag: bool,
+) -> std::fmt::Result {
+	if let Some(name) = const_name(tables, val) {
+		return write!(f, "{}::{}", type_name, name);
+	}
+
+	if bitflag {
+		let (names, remaining) = const_flag_names(tables, val);
+		let remaining = remaining & const_mask(size); // sign extension bits
+		if !names.is_empty() {
+			for (idx, name) in names.iter().enumerate() {
+				if idx > 0 {
+					write!(f, " | ")?;
+				}
+				write!(f, "{}::{}", type_name, name)?;
+			}
+			if remaining != 0 {
+				write!(f, " | {:#x}", remaining)?;
+			}
+			return Ok(());
+		}
+	}
+
+	let val = val & const_mask(size);
+	if val > 0xffff {
+		write!(f, "{}({:#010x})", type_name, val)
+	} else {
+		write!(f, "{}({:#06x})", type_name, val)
+	}
+}
+
+/// Parses the symbolic representation written by
+/// [`fmt_const`](crate::kernel::privs::fmt_const). Names may be prefixed with
+/// the type name or not, and numbers can be hex or decimal.
+#[must_use]
+pub(crate) fn parse_const(
+	type_name: &str,
+	tables: &ConstNames,
+	s: &str,
+	size: u32,
+	bitflag: bool,
+) -> Option<u64> {
+	let parse_one = |part: &str| -> Option<u64> {
+		let mut part = part.trim();
+		if let Some(rest) = part.strip_prefix(type_name) {
+			if let Some(name) = rest.strip_prefix("::") {
+				part = name;
+			} else if let Some(num) = rest.strip_prefix('(').and_then(|r| r.strip_suffix(')')) {
+				part = num.trim();
+			}
+		}
+
+		if let Some((_, v)) = tables
+			.iter()
+			.flat_map(|table| table.iter())
+			.find(|(name, _)| *name == part)
+		{
+			return Some(*v);
+		}
+
+		let (negative, num) = match part.strip_prefix('-') {
+			Some(num) => (true, num),
+			None => (false, part),
+		};
+		let v = match num.strip_prefix("0x").or_else(|| num.strip_prefix("0X")) {
+			Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+			None => num.parse::<u64>().ok()?,
+		};
+		Some(if negative { (v as i64).checked_neg()? as u64 } else { v })
+	};
+
+	let val = if bitflag {
+		s.split('|')
+			.map(parse_one)
+			.try_fold(0, |acc, v| v.map(|v| acc | v))?
+	} else {
+		parse_one(s)?
+	};
+
+	let mask = const_mask(size);
+	if val & !mask == 0 || val | mask == u64::MAX {
+		Some(val) // fits, or is sign-extended
+	} else {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::co;
+
+	const TABLE: &[(&str, u64)] =
+		&[("NONE", 0), ("ONE", 0x1), ("TWO", 0x2), ("BOTH", 0x3), ("HIGH", 0x80)];
+	const TABLES: &ConstNames = &[TABLE];
+
+	struct Fmt(u64, u32, bool);
+
+	impl std::fmt::Display for Fmt {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			fmt_const(f, "T", TABLES, self.0, self.1, self.2)
+		}
+	}
+
+	#[test]
+	fn names() {
+		assert_eq!(const_name(TABLES, 0x2), Some("TWO"));
+		assert_eq!(const_name(TABLES, 0x4), None);
+		assert_eq!(const_flag_names(TABLES, 0x83), (vec!["BOTH", "HIGH"], 0));
+		assert_eq!(const_flag_names(TABLES, 0x86), (vec!["TWO", "HIGH"], 0x4));
+		assert_eq!(const_flag_names(TABLES, 0x4), (vec![], 0x4));
+	}
+
+	#[test]
+	fn format() {
+		assert_eq!(Fmt(0x2, 1, false).to_string(), "T::TWO");
+		assert_eq!(Fmt(0x0, 1, true).to_string(), "T::NONE");
+		assert_eq!(Fmt(0x40, 1, false).to_string(), "T(0x0040)");
+		assert_eq!(Fmt(0x83, 1, true).to_string(), "T::BOTH | T::HIGH");
+		assert_eq!(Fmt(0x86, 1, true).to_string(), "T::TWO | T::HIGH | 0x4");
+		assert_eq!(Fmt(0x4, 1, true).to_string(), "T(0x0004)");
+		assert_eq!(Fmt(0x12_3456, 4, false).to_string(), "T(0x00123456)");
+		assert_eq!(Fmt(-2i64 as u64, 2, false).to_string(), "T(0xfffe)");
+	}
+
+	#[test]
+	fn parse() {
+		let parse = |s: &str, bitflag: bool| parse_const("T", TABLES, s, 1, bitflag);
+		assert_eq!(parse("T::TWO", false), Some(0x2));
+		assert_eq!(parse("TWO", false), Some(0x2));
+		assert_eq!(parse(" T(0x40) ", false), Some(0x40));
+		assert_eq!(parse("0X40", false), Some(0x40));
+		assert_eq!(parse("64", false), Some(64));
+		assert_eq!(parse("-1", false), Some(-1i64 as u64));
+		assert_eq!(parse("T::ONE | T::TWO", false), None);
+		assert_eq!(parse("T::ONE | TWO | 0x4", true), Some(0x7));
+		assert_eq!(parse("0x100", false), None);
+		assert_eq!(parse("T::THREE", false), None);
+		assert_eq!(parse("T::ONE |", true), None);
+		assert_eq!(parse("", false), None);
+	}
+
+	#[test]
+	fn round_trip() {
+		for val in 0..=0xff {
+			for bitflag in [false, true] {
+				let s = Fmt(val, 1, bitflag).to_string();
+				assert_eq!(parse_const("T", TABLES, &s, 1, bitflag), Some(val), "{}", s);
+			}
+		}
+	}
+
+	#[test]
+	fn from_str() {
+		assert_eq!(co::SW::SHOWNA.to_string(), "SW::SHOWNA");
+		assert_eq!("SW::SHOWNA".parse::<co::SW>(), Ok(co::SW::SHOWNA));
+		assert_eq!("RESTORE".parse::<co::SW>(), Ok(co::SW::RESTORE));
+		assert!("SW::NOPE".parse::<co::SW>().is_err());
+
+		let attrs = co::FILE_ATTRIBUTE::HIDDEN
+			| co::FILE_ATTRIBUTE::DIRECTORY
+			| unsafe { co::FILE_ATTRIBUTE::from_raw(0x8) };
+		assert_eq!(attrs.to_string(), "FILE_ATTRIBUTE::HIDDEN | FILE_ATTRIBUTE::DIRECTORY | 0x8");
+		assert_eq!(
+			"HIDDEN | FILE_ATTRIBUTE::DIRECTORY | 8".parse::<co::FILE_ATTRIBUTE>(),
+			Ok(attrs),
+		);
+
+		let (neg, unnamed) = unsafe { (co::SW::from_raw(-7), co::SW::from_raw(0x1234)) };
+		for val in [co::SW::HIDE, co::SW::SHOWNA, neg, unnamed] {
+			assert_eq!(val.to_string().parse::<co::SW>(), Ok(val));
+		}
+		for val in [
+			co::FILE_ATTRIBUTE::NORMAL,
+			attrs,
+			unsafe { co::FILE_ATTRIBUTE::from_raw(0) },
+			unsafe { co::FILE_ATTRIBUTE::from_raw(0xff80_0000) },
+			unsafe { co::FILE_ATTRIBUTE::from_raw(u32::MAX) },
+		] {
+			assert_eq!(val.to_string().parse::<co::FILE_ATTRIBUTE>(), Ok(val));
+		}
+	}
+}
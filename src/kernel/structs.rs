@@ -387,6 +387,46 @@ impl GUID {
 		}
 	}
 
+	/// Creates a `GUID` from its 16 bytes, as laid out in memory and in binary
+	/// file formats.
+	#[cfg(feature = "ole")]
+	#[must_use]
+	pub(crate) const fn from_le_bytes(b: [u8; 16]) -> Self {
+		Self {
+			data1: u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+			data2: u16::from_le_bytes([b[4], b[5]]),
+			data3: u16::from_le_bytes([b[6], b[7]]),
+			data4: u64::from_le_bytes([b[8], b[9], b[10], b[11], b[12], b[13], b[14], b[15]]),
+		}
+	}
+
+	/// Returns the 16 bytes of the `GUID`, as laid out in memory and in binary
+	/// file formats.
+	#[cfg(feature = "ole")]
+	#[must_use]
+	pub(crate) const fn to_le_bytes(self) -> [u8; 16] {
+		let mut b = [0u8; 16];
+		let (d1, d2, d3, d4) = (
+			self.data1.to_le_bytes(),
+			self.data2.to_le_bytes(),
+			self.data3.to_le_bytes(),
+			self.data4.to_le_bytes(),
+		);
+		let mut idx = 0;
+		while idx < 8 {
+			if idx < 4 {
+				b[idx] = d1[idx];
+			}
+			if idx < 2 {
+				b[4 + idx] = d2[idx];
+				b[6 + idx] = d3[idx];
+			}
+			b[8 + idx] = d4[idx];
+			idx += 1;
+		}
+		b
+	}
+
 	#[must_use]
 	const fn parse_block<const N: usize>(chars: [u8; N]) -> u64 {
 		let mut res: u64 = 0;
@@ -0,0 +1,487 @@
+use crate::co;
+use crate::decl::*;
+use crate::guard::*;
+use crate::kernel::xml::XmlElem;
+
+const NS_ASM_V1: &str = "urn:schemas-microsoft-com:asm.v1";
+const NS_ASM_V3: &str = "urn:schemas-microsoft-com:asm.v3";
+const NS_COMPATIBILITY: &str = "urn:schemas-microsoft-com:compatibility.v1";
+const NS_SETTINGS_2005: &str = "http://schemas.microsoft.com/SMI/2005/WindowsSettings";
+const NS_SETTINGS_2016: &str = "http://schemas.microsoft.com/SMI/2016/WindowsSettings";
+const NS_SETTINGS_2019: &str = "http://schemas.microsoft.com/SMI/2019/WindowsSettings";
+
+/// Typed model of a
+/// [side-by-side application manifest](https://learn.microsoft.com/en-us/windows/win32/sbscs/application-manifests),
+/// which can be rendered to XML and parsed from existing manifests.
+///
+/// Only the commonly used settings are modeled; other elements are ignored
+/// when parsing.
+///
+/// # Examples
+///
+/// Embedding a manifest into an executable:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let manifest = w::AppManifest::new_gui();
+/// let entry = manifest.to_res_entry();
+///
+/// let hupd = w::HUPDATERSRC::BeginUpdateResource("C:\\Temp\\app.exe", false)?;
+/// hupd.UpdateResource(entry.res_type, entry.name, entry.lang, &entry.data)?;
+/// # w::SysResult::Ok(())
+/// ```
+///
+/// Reading the manifest of an executable:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let fin = w::FileMapped::open("C:\\Temp\\app.exe", w::FileAccess::ExistingReadOnly)?;
+/// let pe = w::PeFile::parse(fin.as_slice())?;
+/// if let Some(data) = pe.manifest()? {
+///     let manifest = w::AppManifest::parse(data)?;
+///     println!("Common controls v6: {}", manifest.uses_common_controls_v6());
+/// }
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct AppManifest {
+	/// Identity of the application itself.
+	pub identity: Option<ManifestAssembly>,
+	/// Description of the application.
+	pub description: Option<String>,
+	/// Side-by-side assemblies the application depends on, like
+	/// [`ManifestAssembly::common_controls_v6`](crate::ManifestAssembly::common_controls_v6).
+	pub dependencies: Vec<ManifestAssembly>,
+	/// The `requestedExecutionLevel`, which controls UAC elevation.
+	pub execution_level: Option<ManifestExecutionLevel>,
+	/// Whether the application bypasses UI restrictions, used by
+	/// accessibility tools.
+	pub ui_access: bool,
+	/// GUIDs of the supported operating systems, like
+	/// [`AppManifest::OS_WIN10`](crate::AppManifest::OS_WIN10), which
+	/// disable compatibility behaviors.
+	pub supported_os: Vec<String>,
+	/// The DPI awareness mode.
+	pub dpi_awareness: Option<ManifestDpiAwareness>,
+	/// Whether the application accepts paths longer than `MAX_PATH`.
+	pub long_path_aware: bool,
+	/// The process code page, usually `UTF-8`, used by the ANSI functions.
+	pub active_code_page: Option<String>,
+}
+
+/// Identity of a side-by-side assembly, in an
+/// [`AppManifest`](crate::AppManifest).
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ManifestAssembly {
+	/// The assembly type, usually `win32`.
+	pub assembly_type: Option<String>,
+	/// The assembly name, like `Company.Product.App`.
+	pub name: String,
+	/// The version, in the `major.minor.build.revision` format.
+	pub version: String,
+	/// The processor architecture, like `amd64`, or `*` for any.
+	pub processor_architecture: Option<String>,
+	/// The public key token of a signed assembly.
+	pub public_key_token: Option<String>,
+	/// The language, or `*` for any.
+	pub language: Option<String>,
+}
+
+/// The `requestedExecutionLevel` of an [`AppManifest`](crate::AppManifest).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManifestExecutionLevel {
+	/// Runs with the same token as the parent process.
+	AsInvoker,
+	/// Runs with the highest privileges available to the user.
+	HighestAvailable,
+	/// Runs only as administrator, prompting for elevation.
+	RequireAdministrator,
+}
+
+/// The DPI awareness mode of an [`AppManifest`](crate::AppManifest).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManifestDpiAwareness {
+	/// The application is scaled by the system.
+	Unaware,
+	/// The application scales itself to the primary monitor DPI.
+	System,
+	/// The application scales itself to each monitor DPI.
+	PerMonitor,
+	/// Like `PerMonitor`, with non-client area and dialog scaling, falling
+	/// back to `PerMonitor` on older systems.
+	PerMonitorV2,
+}
+
+impl AppManifest {
+	/// `supportedOS` GUID of Windows Vista.
+	pub const OS_VISTA: &'static str = "{e2011457-1546-43c5-a5fe-008deee3d3f0}";
+	/// `supportedOS` GUID of Windows 7.
+	pub const OS_WIN7: &'static str = "{35138b9a-5d96-4fbd-8e2d-a2440225f93a}";
+	/// `supportedOS` GUID of Windows 8.
+	pub const OS_WIN8: &'static str = "{4a2f28e3-53b9-4441-ba9c-d69d4a4a6e38}";
+	/// `supportedOS` GUID of Windows 8.1.
+	pub const OS_WIN81: &'static str = "{1f676c76-80e1-4239-95bb-83d0f6d0da78}";
+	/// `supportedOS` GUID of Windows 10 and 11.
+	pub const OS_WIN10: &'static str = "{8e0f7a12-bfb3-4fe8-b9a5-48fd50a15a9a}";
+
+	/// Returns the manifest required by the [`gui`](crate::gui) module:
+	/// ComCtl32 v6, `asInvoker`, `PerMonitorV2` DPI awareness, long path
+	/// awareness, UTF-8 code page and all supported operating systems.
+	#[must_use]
+	pub fn new_gui() -> Self {
+		Self {
+			dependencies: vec![ManifestAssembly::common_controls_v6()],
+			execution_level: Some(ManifestExecutionLevel::AsInvoker),
+			supported_os: [
+				Self::OS_VISTA,
+				Self::OS_WIN7,
+				Self::OS_WIN8,
+				Self::OS_WIN81,
+				Self::OS_WIN10,
+			]
+			.iter()
+			.map(|id| id.to_string())
+			.collect(),
+			dpi_awareness: Some(ManifestDpiAwareness::PerMonitorV2),
+			long_path_aware: true,
+			active_code_page: Some("UTF-8".to_owned()),
+			..Default::default()
+		}
+	}
+
+	/// Parses the raw contents of a manifest, either UTF-8 or UTF-16 with
+	/// BOM.
+	///
+	/// Malformed XML, or a root element other than `assembly`, yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(data: &[u8]) -> SysResult<Self> {
+		let root = XmlElem::parse_bytes(data)?;
+		if root.name != "assembly" {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+
+		let trust = root.find("trustInfo").and_then(|t| {
+			t.find_path(&["security", "requestedPrivileges", "requestedExecutionLevel"])
+		});
+		let settings = root
+			.find_all("application")
+			.flat_map(|app| app.find_all("windowsSettings"))
+			.collect::<Vec<_>>(); // settings are often split in many blocks
+		let setting = |name: &str| settings.iter().find_map(|s| s.child_text(name));
+
+		Ok(Self {
+			identity: root
+				.find("assemblyIdentity")
+				.map(ManifestAssembly::from_xml),
+			description: root.child_text("description").map(|s| s.to_owned()),
+			dependencies: root
+				.find_all("dependency")
+				.filter_map(|dep| dep.find_path(&["dependentAssembly", "assemblyIdentity"]))
+				.map(ManifestAssembly::from_xml)
+				.collect(),
+			execution_level: trust.and_then(|t| t.get_attr("level")).and_then(
+				|level| match level {
+					"asInvoker" => Some(ManifestExecutionLevel::AsInvoker),
+					"highestAvailable" => Some(ManifestExecutionLevel::HighestAvailable),
+					"requireAdministrator" => Some(ManifestExecutionLevel::RequireAdministrator),
+					_ => None,
+				},
+			),
+			ui_access: trust
+				.and_then(|t| t.get_attr("uiAccess"))
+				.is_some_and(|v| v.eq_ignore_ascii_case("true")),
+			supported_os: root
+				.find_path(&["compatibility", "application"])
+				.map(|app| {
+					app.find_all("supportedOS")
+						.filter_map(|os| os.get_attr("Id"))
+						.map(|id| id.to_owned())
+						.collect()
+				})
+				.unwrap_or_default(),
+			dpi_awareness: setting("dpiAwareness")
+				.and_then(ManifestDpiAwareness::from_awareness)
+				.or_else(|| setting("dpiAware").and_then(ManifestDpiAwareness::from_aware)),
+			long_path_aware: setting("longPathAware")
+				.is_some_and(|v| v.eq_ignore_ascii_case("true")),
+			active_code_page: setting("activeCodePage").map(|s| s.to_owned()),
+		})
+	}
+
+	/// Renders the manifest as UTF-8 XML.
+	#[must_use]
+	pub fn to_xml(&self) -> String {
+		let mut root = XmlElem::new("assembly")
+			.attr("xmlns", NS_ASM_V1)
+			.attr("manifestVersion", "1.0");
+
+		if let Some(identity) = &self.identity {
+			root = root.child(identity.to_xml());
+		}
+		if let Some(description) = &self.description {
+			root = root.child(XmlElem::with_text("description", description));
+		}
+		for dep in self.dependencies.iter() {
+			root = root.child(
+				XmlElem::new("dependency")
+					.child(XmlElem::new("dependentAssembly").child(dep.to_xml())),
+			);
+		}
+
+		if let Some(level) = self.execution_level {
+			root = root.child(
+				XmlElem::new("trustInfo").attr("xmlns", NS_ASM_V3).child(
+					XmlElem::new("security").child(
+						XmlElem::new("requestedPrivileges").child(
+							XmlElem::new("requestedExecutionLevel")
+								.attr(
+									"level",
+									match level {
+										ManifestExecutionLevel::AsInvoker => "asInvoker",
+										ManifestExecutionLevel::HighestAvailable => {
+											"highestAvailable"
+										},
+										ManifestExecutionLevel::RequireAdministrator => {
+											"requireAdministrator"
+										},
+									},
+								)
+								.attr("uiAccess", if self.ui_access { "true" } else { "false" }),
+						),
+					),
+				),
+			);
+		}
+
+		if !self.supported_os.is_empty() {
+			let mut app = XmlElem::new("application");
+			for id in self.supported_os.iter() {
+				app = app.child(XmlElem::new("supportedOS").attr("Id", id));
+			}
+			root = root.child(
+				XmlElem::new("compatibility")
+					.attr("xmlns", NS_COMPATIBILITY)
+					.child(app),
+			);
+		}
+
+		let mut settings = XmlElem::new("windowsSettings");
+		if let Some(dpi) = self.dpi_awareness {
+			let (aware, awareness) = match dpi {
+				ManifestDpiAwareness::Unaware => ("false", "unaware"),
+				ManifestDpiAwareness::System => ("true", "system"),
+				ManifestDpiAwareness::PerMonitor => ("true/pm", "PerMonitor"),
+				ManifestDpiAwareness::PerMonitorV2 => ("true/pm", "PerMonitorV2, PerMonitor"),
+			};
+			settings = settings
+				.child(XmlElem::with_text("dpiAware", aware).attr("xmlns", NS_SETTINGS_2005))
+				.child(
+					XmlElem::with_text("dpiAwareness", awareness).attr("xmlns", NS_SETTINGS_2016),
+				);
+		}
+		if self.long_path_aware {
+			settings = settings
+				.child(XmlElem::with_text("longPathAware", "true").attr("xmlns", NS_SETTINGS_2016));
+		}
+		if let Some(cp) = &self.active_code_page {
+			settings = settings
+				.child(XmlElem::with_text("activeCodePage", cp).attr("xmlns", NS_SETTINGS_2019));
+		}
+		if !settings.children.is_empty() {
+			root = root.child(
+				XmlElem::new("application")
+					.attr("xmlns", NS_ASM_V3)
+					.child(settings),
+			);
+		}
+
+		root.render("UTF-8", true)
+	}
+
+	/// Serializes the manifest into the payload of an
+	/// [`RT::MANIFEST`](crate::co::RT::MANIFEST) resource.
+	#[must_use]
+	pub fn serialize(&self) -> Vec<u8> {
+		self.to_xml().into_bytes()
+	}
+
+	/// Returns the manifest as a language-neutral
+	/// [`RT::MANIFEST`](crate::co::RT::MANIFEST) resource with ID 1, which is
+	/// the one read when an executable is started. DLLs use ID 2 instead.
+	#[must_use]
+	pub fn to_res_entry(&self) -> ResEntry {
+		ResEntry::new(RtStr::Rt(co::RT::MANIFEST), IdStr::Id(1), LANGID::NEUTRAL, self.serialize())
+	}
+
+	/// Tells whether ComCtl32 v6 is among the dependencies.
+	#[must_use]
+	pub fn uses_common_controls_v6(&self) -> bool {
+		self.dependencies.iter().any(|dep| {
+			dep.name
+				.eq_ignore_ascii_case(ManifestAssembly::COMMON_CONTROLS)
+				&& dep.version.starts_with("6.")
+		})
+	}
+
+	/// Creates an activation context from the manifest with
+	/// [`HACTCTX::CreateActCtx`](crate::HACTCTX::CreateActCtx), which can be
+	/// activated with
+	/// [`HACTCTX::ActivateActCtx`](crate::HACTCTX::ActivateActCtx).
+	///
+	/// Since the function reads the manifest from a file, a temporary file is
+	/// written and deleted right after.
+	#[must_use]
+	pub fn create_act_ctx(&self) -> SysResult<ReleaseActCtxGuard> {
+		let path = GetTempFileName(&GetTempPath()?, "man", 0)?;
+		let hact = File::open(&path, FileAccess::OpenOrCreateRW)
+			.and_then(|fout| fout.erase_and_write(&self.serialize())) // file closed here
+			.and_then(|_| {
+				let mut wpath = WString::from_str(&path);
+				let mut actctx = ACTCTX::default();
+				actctx.set_lpSource(Some(&mut wpath));
+				HACTCTX::CreateActCtx(&mut actctx)
+			});
+		let _ = DeleteFile(&path);
+		hact
+	}
+}
+
+impl ManifestAssembly {
+	/// Name of the ComCtl32 assembly.
+	pub const COMMON_CONTROLS: &'static str = "Microsoft.Windows.Common-Controls";
+
+	/// Returns the identity of ComCtl32 v6, which enables visual styles.
+	#[must_use]
+	pub fn common_controls_v6() -> Self {
+		Self {
+			assembly_type: Some("win32".to_owned()),
+			name: Self::COMMON_CONTROLS.to_owned(),
+			version: "6.0.0.0".to_owned(),
+			processor_architecture: Some("*".to_owned()),
+			public_key_token: Some("6595b64144ccf1df".to_owned()),
+			language: Some("*".to_owned()),
+		}
+	}
+
+	fn from_xml(elem: &XmlElem) -> Self {
+		let attr = |name: &str| elem.get_attr(name).map(|v| v.to_owned());
+		Self {
+			assembly_type: attr("type"),
+			name: attr("name").unwrap_or_default(),
+			version: attr("version").unwrap_or_default(),
+			processor_architecture: attr("processorArchitecture"),
+			public_key_token: attr("publicKeyToken"),
+			language: attr("language"),
+		}
+	}
+
+	fn to_xml(&self) -> XmlElem {
+		let mut elem = XmlElem::new("assemblyIdentity");
+		if let Some(ty) = &self.assembly_type {
+			elem = elem.attr("type", ty);
+		}
+		elem = elem.attr("name", &self.name).attr("version", &self.version);
+		for (name, val) in [
+			("processorArchitecture", &self.processor_architecture),
+			("publicKeyToken", &self.public_key_token),
+			("language", &self.language),
+		] {
+			if let Some(val) = val {
+				elem = elem.attr(name, val);
+			}
+		}
+		elem
+	}
+}
+
+impl ManifestDpiAwareness {
+	/// Parses a `dpiAwareness` value, a list whose first recognized item is
+	/// used.
+	fn from_awareness(val: &str) -> Option<Self> {
+		val.split(',')
+			.map(|item| item.trim().to_ascii_lowercase())
+			.find_map(|item| match item.as_str() {
+				"unaware" => Some(Self::Unaware),
+				"system" => Some(Self::System),
+				"permonitor" => Some(Self::PerMonitor),
+				"permonitorv2" => Some(Self::PerMonitorV2),
+				_ => None,
+			})
+	}
+
+	/// Parses a legacy `dpiAware` value.
+	fn from_aware(val: &str) -> Option<Self> {
+		match val.trim().to_ascii_lowercase().as_str() {
+			"false" => Some(Self::Unaware),
+			"true" => Some(Self::System),
+			"true/pm" | "per monitor" => Some(Self::PerMonitor),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn gui_round_trip() {
+		let manifest = AppManifest::new_gui();
+		assert!(manifest.uses_common_controls_v6());
+		assert!(AppManifest::parse(&manifest.serialize()).unwrap() == manifest);
+
+		let manifest = AppManifest {
+			identity: Some(ManifestAssembly {
+				assembly_type: Some("win32".to_owned()),
+				name: "Company.Product.App".to_owned(),
+				version: "1.2.3.4".to_owned(),
+				..Default::default()
+			}),
+			description: Some("A & B".to_owned()),
+			execution_level: Some(ManifestExecutionLevel::RequireAdministrator),
+			ui_access: true,
+			dpi_awareness: Some(ManifestDpiAwareness::System),
+			..Default::default()
+		};
+		assert!(AppManifest::parse(&manifest.serialize()).unwrap() == manifest);
+		assert!(
+			AppManifest::parse(&AppManifest::default().serialize()).unwrap()
+				== AppManifest::default()
+		);
+	}
+
+	#[test]
+	fn split_settings() {
+		let manifest = AppManifest::parse(
+			br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+			<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0"
+				xmlns:asmv3="urn:schemas-microsoft-com:asm.v3">
+				<asmv3:application>
+					<asmv3:windowsSettings>
+						<dpiAware xmlns="http://schemas.microsoft.com/SMI/2005/WindowsSettings">True/PM</dpiAware>
+					</asmv3:windowsSettings>
+				</asmv3:application>
+				<asmv3:application>
+					<asmv3:windowsSettings xmlns:ws2="http://schemas.microsoft.com/SMI/2016/WindowsSettings">
+						<ws2:longPathAware>true</ws2:longPathAware>
+					</asmv3:windowsSettings>
+				</asmv3:application>
+			</assembly>"#,
+		)
+		.unwrap();
+		assert_eq!(manifest.dpi_awareness, Some(ManifestDpiAwareness::PerMonitor));
+		assert!(manifest.long_path_aware);
+		assert_eq!(manifest.active_code_page, None);
+		assert!(!manifest.uses_common_controls_v6());
+	}
+
+	#[test]
+	fn malformed() {
+		assert!(AppManifest::parse(b"<application/>").is_err());
+		assert!(AppManifest::parse(b"<assembly>").is_err());
+		assert!(AppManifest::parse(b"\xff\xfe<\0").is_err());
+	}
+}
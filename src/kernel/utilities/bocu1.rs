@@ -0,0 +1,242 @@
+//! [Binary Ordered Compression for Unicode](https://www.unicode.org/notes/tn6/)
+//! codec, implemented in pure Rust, following the reference implementation.
+
+use crate::co;
+use crate::decl::*;
+
+const ASCII_PREV: i32 = 0x40;
+const MIN: i32 = 0x21;
+const MIDDLE: i32 = 0x90;
+const RESET: u8 = 0xff;
+
+const TRAIL_CONTROLS_COUNT: i32 = 20;
+const TRAIL_BYTE_OFFSET: i32 = MIN - TRAIL_CONTROLS_COUNT;
+const TRAIL_COUNT: i32 = (0xff - MIN + 1) + TRAIL_CONTROLS_COUNT;
+
+const SINGLE: i32 = 64;
+const LEAD_2: i32 = 43;
+const LEAD_3: i32 = 3;
+
+const REACH_POS_1: i32 = SINGLE - 1;
+const REACH_NEG_1: i32 = -SINGLE;
+const REACH_POS_2: i32 = REACH_POS_1 + LEAD_2 * TRAIL_COUNT;
+const REACH_NEG_2: i32 = REACH_NEG_1 - LEAD_2 * TRAIL_COUNT;
+const REACH_POS_3: i32 = REACH_POS_2 + LEAD_3 * TRAIL_COUNT * TRAIL_COUNT;
+const REACH_NEG_3: i32 = REACH_NEG_2 - LEAD_3 * TRAIL_COUNT * TRAIL_COUNT;
+
+const START_POS_2: i32 = MIDDLE + REACH_POS_1 + 1;
+const START_POS_3: i32 = START_POS_2 + LEAD_2;
+const START_POS_4: i32 = START_POS_3 + LEAD_3;
+const START_NEG_2: i32 = MIDDLE + REACH_NEG_1;
+const START_NEG_3: i32 = START_NEG_2 - LEAD_2;
+
+/// C0 control bytes which can be used as trail bytes, in trail value order.
+const TRAIL_TO_BYTE: [u8; TRAIL_CONTROLS_COUNT as usize] = [
+	0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19,
+	0x1c, 0x1d, 0x1e, 0x1f,
+];
+
+/// Computes the next `prev` state from the last code point.
+#[must_use]
+const fn next_prev(c: i32) -> i32 {
+	match c {
+		0x3040..=0x309f => 0x3070,                // Hiragana is not 128-aligned
+		0x4e00..=0x9fa5 => 0x4e00 - REACH_NEG_2,  // CJK Unihan
+		0xac00..=0xd7a3 => (0xd7a3 + 0xac00) / 2, // Korean Hangul
+		_ => (c & !0x7f) + ASCII_PREV,            // mostly small scripts
+	}
+}
+
+/// Converts a trail byte into its trail value.
+#[must_use]
+fn byte_to_trail(b: u8) -> Option<i32> {
+	if b <= 0x20 {
+		TRAIL_TO_BYTE.iter().position(|t| *t == b).map(|t| t as _)
+	} else {
+		Some(b as i32 - TRAIL_BYTE_OFFSET)
+	}
+}
+
+/// Decodes BOCU-1 data into UTF-16. A leading U+FEFF signature is skipped, and
+/// decoding stops at the first null char, if any.
+///
+/// Since the signature changes the decoder state, `data` must include it, if
+/// present.
+///
+/// Malformed input yields
+/// [`co::ERROR::NO_UNICODE_TRANSLATION`](crate::co::ERROR::NO_UNICODE_TRANSLATION).
+#[must_use]
+pub(crate) fn decode(data: &[u8]) -> SysResult<Vec<u16>> {
+	let mut out = Vec::with_capacity(data.len());
+	let mut prev = ASCII_PREV;
+	let mut i = 0;
+
+	while i < data.len() {
+		let lead = data[i] as i32;
+		i += 1;
+
+		let c = if lead <= 0x20 {
+			if lead != 0x20 {
+				prev = ASCII_PREV; // C0 controls reset the state, space doesn't
+			}
+			lead
+		} else if lead == RESET as i32 {
+			prev = ASCII_PREV; // only resets the state, no code point
+			continue;
+		} else if (START_NEG_2..START_POS_2).contains(&lead) {
+			prev + (lead - MIDDLE) // single-byte difference
+		} else {
+			let (mut diff, count) = if lead >= START_NEG_2 {
+				if lead < START_POS_3 {
+					((lead - START_POS_2) * TRAIL_COUNT + REACH_POS_1 + 1, 1)
+				} else if lead < START_POS_4 {
+					((lead - START_POS_3) * TRAIL_COUNT * TRAIL_COUNT + REACH_POS_2 + 1, 2)
+				} else {
+					(REACH_POS_3 + 1, 3)
+				}
+			} else if lead >= START_NEG_3 {
+				((lead - START_NEG_2) * TRAIL_COUNT + REACH_NEG_1, 1)
+			} else if lead > MIN {
+				((lead - START_NEG_3) * TRAIL_COUNT * TRAIL_COUNT + REACH_NEG_2, 2)
+			} else {
+				(-TRAIL_COUNT * TRAIL_COUNT * TRAIL_COUNT + REACH_NEG_3, 3)
+			};
+
+			for exp in (0..count).rev() {
+				let b = *data.get(i).ok_or(co::ERROR::NO_UNICODE_TRANSLATION)?; // truncated sequence
+				i += 1;
+				let t = byte_to_trail(b).ok_or(co::ERROR::NO_UNICODE_TRANSLATION)?;
+				diff += t * TRAIL_COUNT.pow(exp);
+			}
+			prev + diff
+		};
+
+		if c == 0x0000 {
+			break; // terminating null
+		}
+		if lead > 0x20 {
+			prev = next_prev(c);
+		}
+		if i == 3 && c == 0xfeff {
+			continue; // skip signature
+		}
+
+		let ch = u32::try_from(c)
+			.ok()
+			.and_then(char::from_u32)
+			.ok_or(co::ERROR::NO_UNICODE_TRANSLATION)?;
+		let mut buf = [0u16; 2];
+		out.extend_from_slice(ch.encode_utf16(&mut buf));
+	}
+
+	Ok(out)
+}
+
+/// Encodes the chars into BOCU-1, optionally prepending the U+FEFF signature.
+#[must_use]
+pub(crate) fn encode(chars: &[char], with_bom: bool) -> Vec<u8> {
+	let mut out = Vec::with_capacity(chars.len() * 2 + 3);
+	let mut prev = ASCII_PREV;
+
+	let bom = if with_bom { Some('\u{feff}') } else { None };
+	for ch in bom.iter().chain(chars.iter()) {
+		let c = *ch as i32;
+		if c <= 0x20 {
+			if c != 0x20 {
+				prev = ASCII_PREV; // C0 controls reset the state, space doesn't
+			}
+			out.push(c as _);
+			continue;
+		}
+
+		pack_diff(c - prev, &mut out);
+		prev = next_prev(c);
+	}
+
+	out
+}
+
+/// Writes the lead byte and the trail bytes of the difference.
+fn pack_diff(diff: i32, out: &mut Vec<u8>) {
+	if (REACH_NEG_1..=REACH_POS_1).contains(&diff) {
+		out.push((MIDDLE + diff) as _); // single byte
+		return;
+	}
+
+	let (lead, mut m, count) = if diff > 0 {
+		if diff <= REACH_POS_2 {
+			let d = diff - REACH_POS_1 - 1;
+			(START_POS_2 + d / TRAIL_COUNT, d % TRAIL_COUNT, 1)
+		} else if diff <= REACH_POS_3 {
+			let d = diff - REACH_POS_2 - 1;
+			(START_POS_3 + d / (TRAIL_COUNT * TRAIL_COUNT), d % (TRAIL_COUNT * TRAIL_COUNT), 2)
+		} else {
+			(START_POS_4, diff - REACH_POS_3 - 1, 3)
+		}
+	} else if diff >= REACH_NEG_2 {
+		let d = diff - REACH_NEG_1;
+		let (q, r) = (d.div_euclid(TRAIL_COUNT), d.rem_euclid(TRAIL_COUNT));
+		(START_NEG_2 + q, r, 1)
+	} else if diff >= REACH_NEG_3 {
+		let d = diff - REACH_NEG_2;
+		let tc2 = TRAIL_COUNT * TRAIL_COUNT;
+		(START_NEG_3 + d.div_euclid(tc2), d.rem_euclid(tc2), 2)
+	} else {
+		(MIN, diff - REACH_NEG_3 + TRAIL_COUNT * TRAIL_COUNT * TRAIL_COUNT, 3)
+	};
+
+	out.push(lead as _);
+	for exp in (0..count).rev() {
+		let t = m / TRAIL_COUNT.pow(exp);
+		m %= TRAIL_COUNT.pow(exp);
+		out.push(if t < TRAIL_CONTROLS_COUNT {
+			TRAIL_TO_BYTE[t as usize]
+		} else {
+			(t + TRAIL_BYTE_OFFSET) as _
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn utf16(s: &str) -> Vec<u16> {
+		s.encode_utf16().collect()
+	}
+
+	#[test]
+	fn signature() {
+		assert_eq!(encode(&[], true), [0xfb, 0xee, 0x28]);
+		assert_eq!(decode(&[0xfb, 0xee, 0x28, RESET, 0xb1]).unwrap(), utf16("a"));
+	}
+
+	#[test]
+	fn ascii_is_single_byte() {
+		let enc = encode(&"Hello".chars().collect::<Vec<_>>(), false);
+		assert_eq!(enc, [0x98, 0xb5, 0xbc, 0xbc, 0xbf]);
+		assert_eq!(decode(b"\x98\xb5\xbc\xbc\xbf\x00ignored").unwrap(), utf16("Hello"));
+	}
+
+	#[test]
+	fn round_trip() {
+		for text in [
+			"",
+			"plain ASCII\r\n\ttext",
+			"caf\u{e9} \u{3b1}\u{3b2}\u{3b3} \u{5d0}\u{5d1}",
+			"\u{65e5}\u{672c}\u{8a9e}\u{306e}\u{30c6}\u{30ad}\u{30b9}\u{30c8}",
+			"\u{d55c}\u{ad6d}\u{c5b4} and \u{1f600}\u{1f680} \u{10348}",
+			"\u{10ffff}\u{1}\u{e000} \u{7f}",
+		] {
+			for with_bom in [false, true] {
+				let enc = encode(&text.chars().collect::<Vec<_>>(), with_bom);
+				assert_eq!(decode(&enc).unwrap(), utf16(text), "{:?}", text);
+			}
+		}
+	}
+
+	#[test]
+	fn malformed() {
+		assert!(decode(&[0xfb, 0xee]).is_err()); // truncated sequence
+	}
+}
@@ -1,5 +1,25 @@
 use std::cmp::Ordering;
 
+use crate::co;
+use crate::decl::*;
+use crate::kernel::utilities::{bocu1, scsu};
+
+/// What to do with characters which cannot be represented in the target
+/// encoding, when calling [`Encoding::encode`](crate::Encoding::encode).
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EncodeFallback {
+	/// Fails with
+	/// [`co::ERROR::NO_UNICODE_TRANSLATION`](crate::co::ERROR::NO_UNICODE_TRANSLATION).
+	Error,
+	/// Replaces the character with `?` in single-byte encodings, or with the
+	/// U+FFFD replacement character in Unicode encodings.
+	Replace,
+	/// Replaces the character with a similar one, like `é` with `e`, if
+	/// available; otherwise behaves like
+	/// [`EncodeFallback::Replace`](crate::EncodeFallback::Replace).
+	BestFit,
+}
+
 /// String encodings.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Encoding {
@@ -46,143 +66,315 @@ impl std::fmt::Display for Encoding {
 }
 
 impl Encoding {
-	/// Guesses the encoding of the given raw data, also returning the size of
-	/// its [BOM](https://en.wikipedia.org/wiki/Byte_order_mark), if any.
+	/// Returns the [BOM](https://en.wikipedia.org/wiki/Byte_order_mark) bytes
+	/// of the encoding, or an empty slice if the encoding has no BOM.
 	#[must_use]
-	pub fn guess(data: &[u8]) -> (Self, usize) {
-		if let Some((enc, bom_sz)) = Self::guess_bom(data) {
-			return (enc, bom_sz); // BOM found, we already guessed the encoding
+	pub const fn bom(&self) -> &'static [u8] {
+		match self {
+			Self::Unknown | Self::Ansi | Self::Win1252 => &[],
+			Self::Utf8 => &[0xef, 0xbb, 0xbf],
+			Self::Utf16be => &[0xfe, 0xff],
+			Self::Utf16le => &[0xff, 0xfe],
+			Self::Utf32be => &[0x00, 0x00, 0xfe, 0xff],
+			Self::Utf32le => &[0xff, 0xfe, 0x00, 0x00],
+			Self::Scsu => &[0x0e, 0xfe, 0xff],
+			Self::Bocu1 => &[0xfb, 0xee, 0x28],
 		}
+	}
 
-		if Self::guess_utf8(data) {
-			return (Self::Utf8, 0);
+	/// Serializes an UTF-16 string into raw bytes of this encoding, optionally
+	/// prepending the [BOM](https://en.wikipedia.org/wiki/Byte_order_mark). No
+	/// terminating null is appended. Encodings without a BOM, like
+	/// [`Encoding::Ansi`](crate::Encoding::Ansi) and
+	/// [`Encoding::Win1252`](crate::Encoding::Win1252), ignore `with_bom`.
+	///
+	/// This is the inverse of [`WString::parse`](crate::WString::parse). All
+	/// encodings are serialized in pure Rust, Windows-1252 with the built-in
+	/// table of [`co::CP::encode`](crate::co::CP::encode), so the result
+	/// doesn't depend on the host.
+	///
+	/// Unpaired surrogates are unrepresentable in all encodings but UTF-16;
+	/// they, and any other unrepresentable character, are handled according to
+	/// `fallback`. Serializing to
+	/// [`Encoding::Unknown`](crate::Encoding::Unknown) fails with
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER).
+	#[must_use]
+	pub fn encode(
+		&self,
+		text: &[u16],
+		with_bom: bool,
+		fallback: EncodeFallback,
+	) -> SysResult<Vec<u8>> {
+		let mut buf = Vec::<u8>::with_capacity(text.len() * 2 + 4);
+		if with_bom && *self != Self::Bocu1 {
+			buf.extend_from_slice(self.bom()); // BOCU-1 BOM changes the encoder state
 		}
 
-		let has_non_ansi_char = data.iter().find(|ch| **ch > 0x7f).is_some();
-		if has_non_ansi_char {
-			(Self::Win1252, 0) // by exclusion, not assertive
-		} else {
-			(Self::Ansi, 0)
+		match self {
+			Self::Unknown => return Err(co::ERROR::INVALID_PARAMETER),
+			Self::Ansi => {
+				for ch in Self::chars(text, fallback)? {
+					buf.push(Self::encode_ansi(ch, fallback)?);
+				}
+			},
+			Self::Win1252 => buf.extend_from_slice(&co::CP::WINDOWS_1252.encode(text, fallback)?),
+			Self::Utf8 => {
+				let mut ch8 = [0u8; 4];
+				for ch in Self::chars(text, fallback)? {
+					buf.extend_from_slice(ch.encode_utf8(&mut ch8).as_bytes());
+				}
+			},
+			Self::Utf16be => text
+				.iter()
+				.for_each(|ch| buf.extend_from_slice(&ch.to_be_bytes())),
+			Self::Utf16le => text
+				.iter()
+				.for_each(|ch| buf.extend_from_slice(&ch.to_le_bytes())),
+			Self::Utf32be => Self::chars(text, fallback)?
+				.iter()
+				.for_each(|ch| buf.extend_from_slice(&(*ch as u32).to_be_bytes())),
+			Self::Utf32le => Self::chars(text, fallback)?
+				.iter()
+				.for_each(|ch| buf.extend_from_slice(&(*ch as u32).to_le_bytes())),
+			Self::Scsu => buf.extend_from_slice(&scsu::encode(&Self::chars(text, fallback)?)),
+			Self::Bocu1 => {
+				buf.extend_from_slice(&bocu1::encode(&Self::chars(text, fallback)?, with_bom))
+			},
 		}
+		Ok(buf)
 	}
 
+	/// Serializes a [`&str`](str) into raw bytes of this encoding, optionally
+	/// prepending the [BOM](https://en.wikipedia.org/wiki/Byte_order_mark).
+	///
+	/// This is a shorthand to [`Encoding::encode`](crate::Encoding::encode).
 	#[must_use]
-	fn guess_bom(data: &[u8]) -> Option<(Self, usize)> {
-		let has_bom = |bom_bytes: &[u8]| -> bool {
-			data.len() >= bom_bytes.len()
-				&& data[..bom_bytes.len()].cmp(bom_bytes) == Ordering::Equal
-		};
-
-		const UTF8: [u8; 3] = [0xef, 0xbb, 0xbf]; // UTF-8 BOM
-		if has_bom(&UTF8) {
-			return Some((Self::Utf8, UTF8.len()));
-		}
-
-		const UTF16BE: [u8; 2] = [0xfe, 0xff];
-		if has_bom(&UTF16BE) {
-			return Some((Self::Utf16be, UTF16BE.len()));
-		}
+	pub fn encode_str(
+		&self,
+		text: &str,
+		with_bom: bool,
+		fallback: EncodeFallback,
+	) -> SysResult<Vec<u8>> {
+		self.encode(&text.encode_utf16().collect::<Vec<_>>(), with_bom, fallback)
+	}
 
-		const UTF16LE: [u8; 2] = [0xff, 0xfe];
-		if has_bom(&UTF16LE) {
-			return Some((Self::Utf16le, UTF16LE.len()));
-		}
+	/// Decodes the UTF-16 units, handling unpaired surrogates according to the
+	/// fallback.
+	fn chars(text: &[u16], fallback: EncodeFallback) -> SysResult<Vec<char>> {
+		char::decode_utf16(text.iter().copied())
+			.map(|ch| match ch {
+				Ok(ch) => Ok(ch),
+				Err(_) => match fallback {
+					EncodeFallback::Error => Err(co::ERROR::NO_UNICODE_TRANSLATION),
+					EncodeFallback::Replace | EncodeFallback::BestFit => {
+						Ok(char::REPLACEMENT_CHARACTER)
+					},
+				},
+			})
+			.collect()
+	}
 
-		const UTF32BE: [u8; 4] = [0x00, 0x00, 0xfe, 0xff];
-		if has_bom(&UTF32BE) {
-			return Some((Self::Utf32be, UTF32BE.len()));
+	fn encode_ansi(ch: char, fallback: EncodeFallback) -> SysResult<u8> {
+		if ch.is_ascii() {
+			return Ok(ch as _);
 		}
-
-		const UTF32LE: [u8; 4] = [0xff, 0xfe, 0x00, 0x00];
-		if has_bom(&UTF32LE) {
-			return Some((Self::Utf32le, UTF32LE.len()));
+		match fallback {
+			EncodeFallback::Error => Err(co::ERROR::NO_UNICODE_TRANSLATION),
+			EncodeFallback::Replace => Ok(b'?'),
+			EncodeFallback::BestFit => Ok(Self::ascii_best_fit(ch).unwrap_or(b'?')),
 		}
+	}
 
-		const SCSU: [u8; 3] = [0x0e, 0xfe, 0xff];
-		if has_bom(&SCSU) {
-			return Some((Self::Scsu, SCSU.len()));
-		}
+	/// Returns the ASCII character most similar to the given one, if any.
+	#[must_use]
+	pub(crate) fn ascii_best_fit(ch: char) -> Option<u8> {
+		// Latin-1 Supplement and Latin Extended-A letters, from U+00C0 to
+		// U+017F, without their diacritics; spaces mean no equivalent.
+		const LATIN: &[u8; 192] = b"\
+			AAAAAAACEEEEIIIIDNOOOOO OUUUUY s\
+			aaaaaaaceeeeiiiidnooooo ouuuuy y\
+			AaAaAaCcCcCcCcDdDdEeEeEeEeEeGgGg\
+			GgGgHhHhIiIiIiIiIiIiJjKkkLlLlLlL\
+			lLlNnNnNnnNnOoOoOoOoRrRrRrSsSsSs\
+			SsTtTtTtUuUuUuUuUuUuWwYyYZzZzZzs";
 
-		const BOCU1: [u8; 3] = [0xfb, 0xee, 0x28];
-		if has_bom(&BOCU1) {
-			return Some((Self::Bocu1, BOCU1.len()));
+		let code_point = ch as u32;
+		match code_point {
+			0x00a0 | 0x2000..=0x200a | 0x202f | 0x205f => Some(b' '), // typographic spaces
+			0x00a9 => Some(b'c'),                                     // copyright sign
+			0x00ab | 0x00bb | 0x201c..=0x201f | 0x2033 => Some(b'"'), // double quotes
+			0x00ad | 0x2010..=0x2015 | 0x2212 => Some(b'-'),          // hyphens and dashes
+			0x00ae => Some(b'R'),                                     // registered sign
+			0x00b7 | 0x2022 => Some(b'.'),                            // middle dot and bullet
+			0x00d7 => Some(b'x'),                                     // multiplication sign
+			0x00f7 => Some(b'/'),                                     // division sign
+			0x00c0..=0x017f => match LATIN[(code_point - 0x00c0) as usize] {
+				b' ' => None,
+				fit => Some(fit),
+			},
+			0x2018..=0x201b | 0x2032 => Some(b'\''), // single quotes and prime
+			0x2039 => Some(b'<'),
+			0x203a => Some(b'>'),
+			0xff01..=0xff5e => Some((code_point - 0xfee0) as _), // fullwidth forms
+			_ => None,
 		}
+	}
 
-		None // no BOM found
+	/// Guesses the encoding of the given raw data, also returning the size of
+	/// its [BOM](https://en.wikipedia.org/wiki/Byte_order_mark), if any.
+	///
+	/// This is a shorthand to feed the whole data to an
+	/// [`EncodingDetector`](crate::EncodingDetector), which can also process
+	/// the data in chunks and report confidence scores.
+	#[must_use]
+	pub fn guess(data: &[u8]) -> (Self, usize) {
+		let mut detector = EncodingDetector::new();
+		detector.feed(data);
+		detector.guess()
 	}
 
 	#[must_use]
-	fn guess_utf8(data: &[u8]) -> bool {
-		let mut i = 0; // https://stackoverflow.com/a/1031773/6923555
-		while i < data.len() {
-			let ch0 = unsafe { *data.get_unchecked(i) };
+	pub(crate) fn guess_bom(data: &[u8]) -> Option<(Self, usize)> {
+		[
+			Self::Utf8,
+			Self::Utf32be,
+			Self::Utf32le, // must come before UTF-16 LE, which is its prefix
+			Self::Utf16be,
+			Self::Utf16le,
+			Self::Scsu,
+			Self::Bocu1,
+		]
+		.iter()
+		.find(|enc| {
+			let bom = enc.bom();
+			data.len() >= bom.len() && data[..bom.len()].cmp(bom) == Ordering::Equal
+		})
+		.map(|enc| (*enc, enc.bom().len()))
+	}
+}
 
-			if ch0 == 0x00 {
-				break; // end of string
-			}
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ALL: [Encoding; 9] = [
+		Encoding::Ansi,
+		Encoding::Win1252,
+		Encoding::Utf8,
+		Encoding::Utf16be,
+		Encoding::Utf16le,
+		Encoding::Utf32be,
+		Encoding::Utf32le,
+		Encoding::Scsu,
+		Encoding::Bocu1,
+	];
 
-			if ch0 == 0x09 || // ASCII
-				ch0 == 0x0a ||
-				ch0 == 0x0d ||
-				(0x20 <= ch0 && ch0 <= 0x7e)
-			{
-				i += 1;
-				continue;
+	#[test]
+	fn bom() {
+		for enc in ALL {
+			let plain = enc.encode_str("Abc", false, EncodeFallback::Error).unwrap();
+			let bommed = enc.encode_str("Abc", true, EncodeFallback::Error).unwrap();
+			if enc == Encoding::Bocu1 {
+				assert!(bommed.starts_with(enc.bom())); // BOM also sets the encoder state
+			} else {
+				assert_eq!(bommed, [enc.bom(), &plain].concat(), "{}", enc);
 			}
+			if !enc.bom().is_empty() {
+				assert!(Encoding::guess_bom(&bommed) == Some((enc, enc.bom().len())));
+			}
+		}
+		assert!(
+			Encoding::Unknown.encode_str("A", true, EncodeFallback::Replace)
+				== Err(co::ERROR::INVALID_PARAMETER)
+		);
+	}
 
-			if i < data.len() - 1 {
-				let ch1 = unsafe { *data.get_unchecked(i + 1) };
+	#[test]
+	fn utf16_utf32() {
+		let text = "A\u{20ac}\u{1f600}";
+		let enc = |e: Encoding| e.encode_str(text, false, EncodeFallback::Error).unwrap();
 
-				if (0xc2 <= ch0 && ch0 <= 0xdf) && // non-overlong 2-byte
-					(0x80 <= ch1 && ch1 <= 0xbf)
-				{
-					i += 2;
-					continue;
-				}
+		#[rustfmt::skip]
+		assert_eq!(enc(Encoding::Utf16be), [0x00, 0x41, 0x20, 0xac, 0xd8, 0x3d, 0xde, 0x00]);
+		#[rustfmt::skip]
+		assert_eq!(enc(Encoding::Utf16le), [0x41, 0x00, 0xac, 0x20, 0x3d, 0xd8, 0x00, 0xde]);
+		#[rustfmt::skip]
+		assert_eq!(enc(Encoding::Utf32be), [
+			0x00, 0x00, 0x00, 0x41,
+			0x00, 0x00, 0x20, 0xac,
+			0x00, 0x01, 0xf6, 0x00,
+		]);
+		#[rustfmt::skip]
+		assert_eq!(enc(Encoding::Utf32le), [
+			0x41, 0x00, 0x00, 0x00,
+			0xac, 0x20, 0x00, 0x00,
+			0x00, 0xf6, 0x01, 0x00,
+		]);
+	}
 
-				if i < data.len() - 2 {
-					let ch2 = unsafe { *data.get_unchecked(i + 2) };
-
-					if (ch0 == 0xe0 && // excluding overlongs
-							(0xa0 <= ch1 && ch1 <= 0xbf) &&
-							(0x80 <= ch2 && ch2 <= 0xbf))
-						|| (((0xe1 <= ch0 && ch0 <= 0xec) || // straight 3-byte
-								ch0 == 0xee ||
-								ch0 == 0xef) && (0x80 <= ch1 && ch1 <= 0xbf)
-							&& (0x80 <= ch2 && ch2 <= 0xbf))
-						|| (ch0 == 0xed && // excluding surrogates
-							(0x80 <= ch1 && ch1 <= 0x9f) &&
-							(0x80 <= ch2 && ch2 <= 0xbf))
-					{
-						i += 3;
-						continue;
-					}
-
-					if i < data.len() - 3 {
-						let ch3 = unsafe { *data.get_unchecked(i + 3) };
-
-						if (ch0 == 0xf0 && // planes 1-3
-								(0x90 <= ch1 && ch1 <= 0xbf) &&
-								(0x80 <= ch2 && ch2 <= 0xbf) &&
-								(0x80 <= ch3 && ch3 <= 0xbf))
-							|| ((0xf1 <= ch0 && ch0 <= 0xf3) && // planes 4-15
-								(0x80 <= ch1 && ch1 <= 0xbf) &&
-								(0x80 <= ch2 && ch2 <= 0xbf) &&
-								(0x80 <= ch3 && ch3 <= 0xbf))
-							|| (ch0 == 0xf4 && // plane 16
-								(0x80 <= ch1 && ch1 <= 0x8f) &&
-								(0x80 <= ch2 && ch2 <= 0xbf) &&
-								(0x80 <= ch3 && ch3 <= 0xbf))
-						{
-							i += 4;
-							continue;
-						}
-					}
-				}
-			}
+	#[test]
+	fn unpaired_surrogate() {
+		let text = [0x0041, 0xd800, 0x0042];
+		assert_eq!(
+			Encoding::Utf16le
+				.encode(&text, false, EncodeFallback::Error)
+				.unwrap(),
+			[0x41, 0x00, 0x00, 0xd8, 0x42, 0x00], // representable in UTF-16 only
+		);
+		assert!(
+			Encoding::Utf8.encode(&text, false, EncodeFallback::Error)
+				== Err(co::ERROR::NO_UNICODE_TRANSLATION)
+		);
+		assert_eq!(
+			Encoding::Utf8
+				.encode(&text, false, EncodeFallback::Replace)
+				.unwrap(),
+			[0x41, 0xef, 0xbf, 0xbd, 0x42],
+		);
+		assert_eq!(
+			Encoding::Ansi
+				.encode(&text, false, EncodeFallback::BestFit)
+				.unwrap(),
+			b"A?B"
+		);
+	}
 
-			return false; // none of the conditions were accepted, not UTF-8
+	#[test]
+	fn fallback_ansi() {
+		let text = "caf\u{e9} \u{2014} \u{20ac}\u{ff21}";
+		let enc = |fallback| Encoding::Ansi.encode_str(text, false, fallback);
+		assert!(enc(EncodeFallback::Error) == Err(co::ERROR::NO_UNICODE_TRANSLATION));
+		assert_eq!(enc(EncodeFallback::Replace).unwrap(), b"caf? ? ??");
+		assert_eq!(enc(EncodeFallback::BestFit).unwrap(), b"cafe - ?A");
+	}
+
+	#[test]
+	fn fallback_win1252() {
+		let text = "caf\u{e9} \u{20ac} \u{2713}\u{ff21}\u{141}";
+		let enc = |fallback| Encoding::Win1252.encode_str(text, false, fallback);
+		assert!(enc(EncodeFallback::Error) == Err(co::ERROR::NO_UNICODE_TRANSLATION));
+		assert_eq!(enc(EncodeFallback::Replace).unwrap(), b"caf\xe9 \x80 ???");
+		assert_eq!(enc(EncodeFallback::BestFit).unwrap(), b"caf\xe9 \x80 ?AL");
+	}
+
+	#[test]
+	fn round_trip() {
+		let text = "Hello, w\u{f6}rld \u{20ac} \u{3042}\u{1f600}";
+		for enc in ALL.into_iter().filter(|enc| !enc.bom().is_empty()) {
+			let data = enc.encode_str(text, true, EncodeFallback::Error).unwrap();
+			assert_eq!(WString::parse(&data).unwrap().to_string(), text, "{}", enc);
 		}
-		true // all the conditions accepted through the whole string
+
+		let text = "Plain text, caf\u{e9} for \u{20ac}5.";
+		let data = Encoding::Win1252
+			.encode_str(text, false, EncodeFallback::Error)
+			.unwrap();
+		assert_eq!(WString::parse(&data).unwrap().to_string(), text);
+
+		let text = "Plain ASCII text.";
+		let data = Encoding::Ansi
+			.encode_str(text, false, EncodeFallback::Error)
+			.unwrap();
+		assert_eq!(WString::parse(&data).unwrap().to_string(), text);
 	}
 }
@@ -0,0 +1,376 @@
+use crate::decl::*;
+
+/// Incremental encoding detector, which accepts the raw data in chunks and
+/// reports a confidence score for each candidate [`Encoding`](crate::Encoding).
+///
+/// Besides the [BOM](https://en.wikipedia.org/wiki/Byte_order_mark), it
+/// validates UTF-8 sequences across chunk boundaries, and recognizes BOM-less
+/// UTF-16 and UTF-32 by the position of their null bytes.
+///
+/// [`Encoding::guess`](crate::Encoding::guess) uses this detector to process a
+/// whole buffer at once.
+///
+/// # Examples
+///
+/// Guessing the encoding of a large file, stopping as soon as the detector is
+/// confident enough:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let f = w::FileMapped::open(
+///     "C:\\Temp\\big.log",
+///     w::FileAccess::ExistingReadOnly,
+/// )?;
+///
+/// let mut detector = w::EncodingDetector::new();
+/// for chunk in f.as_slice().chunks(64 * 1024) {
+///     detector.feed(chunk);
+///     if detector.candidates()[0].1 >= 0.9 {
+///         break;
+///     }
+/// }
+///
+/// let (encoding, sz_bom) = detector.guess();
+/// println!("{} {}", encoding, sz_bom);
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Default, Clone)]
+pub struct EncodingDetector {
+	head: Vec<u8>,
+	bom: Option<(Encoding, usize)>,
+	head_done: bool,
+	total: u64,
+	stats: Stats,
+}
+
+/// Statistics of the data after the first 4 bytes are known not to be a BOM.
+#[derive(Default, Clone)]
+struct Stats {
+	count: u64,
+	count_at: [u64; 4],    // bytes at each position modulo 4
+	nulls_at: [u64; 4],    // null bytes at each position modulo 4
+	high: u64,             // bytes above 0x7f
+	undefined_1252: u64,   // bytes undefined in Windows-1252
+	utf32le_invalid: bool, // byte at position 3 modulo 4 is not null
+	utf32be_invalid: bool, // byte at position 0 modulo 4 is not null
+	trailing_nulls: u64,   // null bytes at the end of the data
+	utf8: Utf8State,
+}
+
+/// UTF-8 validation state, kept between chunks.
+#[derive(Clone)]
+struct Utf8State {
+	pending: u8, // continuation bytes still expected
+	lo: u8,      // allowed range for the next continuation byte
+	hi: u8,
+	invalid: bool,
+	multibyte: u64, // complete multibyte sequences found
+}
+
+impl Default for Utf8State {
+	fn default() -> Self {
+		Self {
+			pending: 0,
+			lo: 0x80,
+			hi: 0xbf,
+			invalid: false,
+			multibyte: 0,
+		}
+	}
+}
+
+impl EncodingDetector {
+	/// Creates a new detector, with no data.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns the number of bytes fed so far.
+	#[must_use]
+	pub fn bytes_fed(&self) -> u64 {
+		self.total
+	}
+
+	/// Returns all plausible encodings with their confidence scores, from `0.0`
+	/// to `1.0`, sorted from the most to the least likely. The list is never
+	/// empty.
+	///
+	/// Only an encoding found by its BOM has a `1.0` score. A UTF-8 sequence
+	/// truncated at the end of the data fed so far lowers the UTF-8 score.
+	#[must_use]
+	pub fn candidates(&self) -> Vec<(Encoding, f32)> {
+		if !self.head_done {
+			let mut tmp = self.clone(); // evaluate the partial head without consuming it
+			tmp.finish_head();
+			return tmp.candidates();
+		}
+
+		if let Some((enc, _)) = self.bom {
+			return vec![(enc, 1.0)]; // BOM found, we already guessed the encoding
+		}
+
+		let mut cands = self.stats.scores();
+		cands.retain(|(_, score)| *score > 0.0);
+		cands.sort_by(|a, b| b.1.total_cmp(&a.1)); // stable, ties keep the original order
+		cands
+	}
+
+	/// Feeds the next chunk of raw data.
+	pub fn feed(&mut self, chunk: &[u8]) {
+		self.total += chunk.len() as u64;
+		let mut chunk = chunk;
+		if !self.head_done {
+			let missing = 4 - self.head.len();
+			let (head, rest) = chunk.split_at(missing.min(chunk.len()));
+			self.head.extend_from_slice(head);
+			chunk = rest;
+			if self.head.len() < 4 {
+				return; // still waiting for the first 4 bytes
+			}
+			self.finish_head();
+		}
+
+		if self.bom.is_none() {
+			chunk.iter().for_each(|b| self.stats.push(*b));
+		}
+	}
+
+	/// Returns the most likely encoding, also returning the size of its
+	/// [BOM](https://en.wikipedia.org/wiki/Byte_order_mark), if any.
+	///
+	/// This is the same result of
+	/// [`Encoding::guess`](crate::Encoding::guess), if the same data has been
+	/// fed.
+	#[must_use]
+	pub fn guess(&self) -> (Encoding, usize) {
+		if !self.head_done {
+			let mut tmp = self.clone();
+			tmp.finish_head();
+			return tmp.guess();
+		}
+
+		match self.bom {
+			Some(bom) => bom,
+			None => (self.candidates()[0].0, 0),
+		}
+	}
+
+	/// Checks the first bytes for a BOM; if there's none, they're processed as
+	/// regular data.
+	fn finish_head(&mut self) {
+		self.head_done = true;
+		self.bom = Encoding::guess_bom(&self.head);
+		if self.bom.is_none() {
+			let head = std::mem::take(&mut self.head);
+			head.iter().for_each(|b| self.stats.push(*b));
+		}
+	}
+}
+
+impl Stats {
+	fn push(&mut self, b: u8) {
+		let pos = (self.count % 4) as usize;
+		self.count += 1;
+		self.count_at[pos] += 1;
+
+		if b == 0x00 {
+			self.nulls_at[pos] += 1;
+			self.trailing_nulls += 1;
+		} else {
+			self.trailing_nulls = 0;
+			if pos == 3 {
+				self.utf32le_invalid = true; // code points never exceed 0x10ffff
+			} else if pos == 0 {
+				self.utf32be_invalid = true;
+			}
+		}
+
+		if b > 0x7f {
+			self.high += 1;
+			if matches!(b, 0x81 | 0x8d | 0x8f | 0x90 | 0x9d) {
+				self.undefined_1252 += 1;
+			}
+		}
+
+		self.utf8.push(b);
+	}
+
+	/// Ratio of null bytes at the given positions modulo 4.
+	#[must_use]
+	fn null_ratio(&self, positions: &[usize]) -> f32 {
+		let count: u64 = positions.iter().map(|pos| self.count_at[*pos]).sum();
+		let nulls: u64 = positions.iter().map(|pos| self.nulls_at[*pos]).sum();
+		if count == 0 { 0.0 } else { nulls as f32 / count as f32 }
+	}
+
+	/// Returns the null byte counts without a single trailing null, which is
+	/// taken as a string terminator, not as half of a UTF-16 or UTF-32 char.
+	#[must_use]
+	fn without_terminator(&self) -> Self {
+		let mut stats = self.clone();
+		if self.trailing_nulls == 1 {
+			let pos = ((self.count - 1) % 4) as usize;
+			stats.count -= 1;
+			stats.count_at[pos] -= 1;
+			stats.nulls_at[pos] -= 1;
+			stats.trailing_nulls = 0;
+		}
+		stats
+	}
+
+	/// Computes the scores of all encodings which can be found without a BOM.
+	#[must_use]
+	fn scores(&self) -> Vec<(Encoding, f32)> {
+		const NO_BOM_MAX: f32 = 0.95; // without a BOM we're never sure
+
+		let stats = self.without_terminator();
+		let nulls = stats.null_ratio(&[0, 1, 2, 3]);
+		let not_binary = 1.0 - nulls; // null bytes are unlikely in 8-bit text
+
+		let utf8 = if self.utf8.invalid {
+			0.0
+		} else if self.utf8.multibyte > 0 {
+			0.8 + 0.15 * (self.utf8.multibyte.min(16) as f32 / 16.0)
+		} else {
+			0.5 // plain ASCII
+		};
+		let utf8 = if self.utf8.pending > 0 {
+			utf8 * 0.5 // truncated sequence at the end
+		} else {
+			utf8
+		};
+
+		let ansi = if self.high > 0 { 0.0 } else { 0.5 };
+
+		let win1252 = if self.high == 0 {
+			0.0
+		} else if !self.utf8.invalid && self.utf8.multibyte > 0 {
+			0.2 // high bytes, but they're valid UTF-8 sequences
+		} else if self.undefined_1252 > 0 {
+			0.3
+		} else {
+			0.6 // by exclusion, not assertive
+		};
+
+		let utf16le = stats.null_ratio(&[1, 3]) - stats.null_ratio(&[0, 2]);
+		let utf16be = stats.null_ratio(&[0, 2]) - stats.null_ratio(&[1, 3]);
+
+		let utf32le = if self.utf32le_invalid {
+			0.0
+		} else {
+			stats.null_ratio(&[2, 3]) - stats.null_ratio(&[0])
+		};
+		let utf32be = if self.utf32be_invalid {
+			0.0
+		} else {
+			stats.null_ratio(&[0, 1]) - stats.null_ratio(&[3])
+		};
+
+		vec![
+			(Encoding::Utf8, utf8 * not_binary),
+			(Encoding::Ansi, ansi * not_binary),
+			(Encoding::Utf32le, utf32le.clamp(0.0, 1.0) * NO_BOM_MAX),
+			(Encoding::Utf32be, utf32be.clamp(0.0, 1.0) * NO_BOM_MAX),
+			(Encoding::Utf16le, utf16le.clamp(0.0, 1.0) * NO_BOM_MAX),
+			(Encoding::Utf16be, utf16be.clamp(0.0, 1.0) * NO_BOM_MAX),
+			(Encoding::Win1252, (win1252 * not_binary).max(0.01)), // any bytes can be decoded
+		]
+	}
+}
+
+impl Utf8State {
+	fn push(&mut self, b: u8) {
+		if self.invalid {
+			return;
+		}
+
+		if self.pending > 0 {
+			if (self.lo..=self.hi).contains(&b) {
+				self.pending -= 1;
+				(self.lo, self.hi) = (0x80, 0xbf);
+				if self.pending == 0 {
+					self.multibyte += 1;
+				}
+			} else {
+				self.invalid = true;
+			}
+			return;
+		}
+
+		// https://stackoverflow.com/a/1031773/6923555
+		(self.pending, self.lo, self.hi) = match b {
+			0x00..=0x7f => (0, 0x80, 0xbf),               // ASCII
+			0xc2..=0xdf => (1, 0x80, 0xbf),               // non-overlong 2-byte
+			0xe0 => (2, 0xa0, 0xbf),                      // excluding overlongs
+			0xe1..=0xec | 0xee | 0xef => (2, 0x80, 0xbf), // straight 3-byte
+			0xed => (2, 0x80, 0x9f),                      // excluding surrogates
+			0xf0 => (3, 0x90, 0xbf),                      // planes 1-3
+			0xf1..=0xf3 => (3, 0x80, 0xbf),               // planes 4-15
+			0xf4 => (3, 0x80, 0x8f),                      // plane 16
+			_ => {
+				self.invalid = true;
+				return;
+			},
+		};
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bytes_fed_counts_everything() {
+		let mut detector = EncodingDetector::new();
+		detector.feed(&[0xef, 0xbb]);
+		assert_eq!(detector.bytes_fed(), 2);
+		detector.feed(&[0xbf, b'a', b'b']);
+		assert_eq!(detector.bytes_fed(), 5);
+		detector.feed(b"cdef");
+		assert_eq!(detector.bytes_fed(), 9);
+		assert!(detector.guess() == (Encoding::Utf8, 3));
+	}
+
+	#[test]
+	fn utf8_across_chunks() {
+		let text = "a\u{e1}\u{e9}\u{20ac}z".as_bytes();
+		let mut detector = EncodingDetector::new();
+		text.iter().for_each(|b| detector.feed(&[*b]));
+		assert!(detector.guess() == (Encoding::Utf8, 0));
+		assert_eq!(detector.bytes_fed(), text.len() as u64);
+	}
+
+	#[test]
+	fn truncated_utf8_lowers_score() {
+		let utf8_score = |data: &[u8]| {
+			let mut detector = EncodingDetector::new();
+			detector.feed(data);
+			detector
+				.candidates()
+				.iter()
+				.find(|(enc, _)| *enc == Encoding::Utf8)
+				.map_or(0.0, |(_, score)| *score)
+		};
+
+		let whole = "caf\u{e9} na\u{ef}ve \u{20ac}".as_bytes();
+		let truncated = &whole[..whole.len() - 1];
+		assert!(utf8_score(truncated) < utf8_score(whole));
+		assert!(utf8_score(truncated) > 0.0);
+	}
+
+	#[test]
+	fn trailing_null_is_terminator() {
+		let guess = |data: &[u8]| {
+			let mut detector = EncodingDetector::new();
+			detector.feed(data);
+			detector.guess()
+		};
+
+		assert!(guess(b"ab\0") == (Encoding::Utf8, 0));
+		assert!(guess(b"abc\0") == (Encoding::Utf8, 0));
+		assert!(guess(b"a\0b\0c\0") == (Encoding::Utf16le, 0));
+		assert!(guess(b"\0a\0b\0c\0\0") == (Encoding::Utf16be, 0));
+	}
+}
@@ -1,11 +1,25 @@
+mod app_manifest;
+mod bocu1;
 mod encoding;
+mod encoding_detector;
 mod file;
 mod file_mapped;
+mod pe_file;
+mod res_file;
+mod scsu;
+mod string_table;
 mod w_string;
 
 pub mod path;
 
-pub use encoding::Encoding;
+pub use app_manifest::{
+	AppManifest, ManifestAssembly, ManifestDpiAwareness, ManifestExecutionLevel,
+};
+pub use encoding::{EncodeFallback, Encoding};
+pub use encoding_detector::EncodingDetector;
 pub use file::{File, FileAccess};
 pub use file_mapped::FileMapped;
+pub use pe_file::{PeDataDirectory, PeExport, PeFile, PeImport, PeImportSymbol, PeSection};
+pub use res_file::{ResEntry, ResFile};
+pub use string_table::{StringCatalog, StringTable};
 pub use w_string::WString;
@@ -0,0 +1,900 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::bytes::*;
+
+/// Number of data directories defined by the PE format.
+const NUM_DIRS: usize = 16;
+const DIR_EXPORT: usize = 0;
+const DIR_IMPORT: usize = 1;
+const DIR_RESOURCE: usize = 2;
+const DIR_SECURITY: usize = 4;
+const DIR_DELAY_IMPORT: usize = 13;
+/// Limit of resource directory entries visited, which guards against
+/// directories shared between many parents.
+const MAX_RES_ENTRIES: usize = 1 << 16;
+/// Limit of the length of names in import and export tables.
+const MAX_NAME_LEN: usize = 4096;
+
+/// Type, name, language and data of a resource.
+type ResLeaf<'a> = (RtStr, IdStr, LANGID, &'a [u8]);
+
+/// Read-only view over a PE32 or PE32+ image – an `.exe`, `.dll`, `.sys` or
+/// any other executable – which reads its headers, sections, resources,
+/// imports and exports.
+///
+/// This is a pure parser: no system calls are made and the image is never
+/// loaded, so any binary can be inspected on any platform. The data can come
+/// from a byte slice or a [`FileMapped`](crate::FileMapped).
+///
+/// # Examples
+///
+/// Reading the `FileVersion` of an executable:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let fin = w::FileMapped::open("C:\\Temp\\app.exe", w::FileAccess::ExistingReadOnly)?;
+/// let pe = w::PeFile::parse(fin.as_slice())?;
+///
+/// let res = pe.resources()?;
+/// if let Some(entry) = res.find(&w::RtStr::Rt(co::RT::VERSION), &w::IdStr::Id(1)) {
+///     let vi = w::VersionInfo::parse(&entry.data)?;
+///     for table in vi.string_tables.iter() {
+///         if let Some(ver) = table.str_val("FileVersion") {
+///             println!("{}", ver);
+///         }
+///     }
+/// }
+/// # w::SysResult::Ok(())
+/// ```
+///
+/// Listing the imported functions:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let blob = std::fs::read("C:\\Temp\\app.exe").unwrap();
+/// let pe = w::PeFile::parse(&blob)?;
+///
+/// for import in pe.imports()?.iter() {
+///     for sym in import.symbols.iter() {
+///         match sym {
+///             w::PeImportSymbol::Name { name, .. } => println!("{}!{}", import.dll_name, name),
+///             w::PeImportSymbol::Ordinal(ord) => println!("{}!#{}", import.dll_name, ord),
+///         }
+///     }
+/// }
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone)]
+pub struct PeFile<'a> {
+	data: &'a [u8],
+	/// Target architecture.
+	pub machine: co::IMAGE_FILE_MACHINE,
+	/// Time the image was linked, as seconds since 1970. Reproducible builds
+	/// store a hash instead.
+	pub time_date_stamp: u32,
+	/// Attributes of the image.
+	pub characteristics: co::IMAGE_FILE,
+	/// Whether the image is PE32+, the 64-bit format.
+	pub is_pe32_plus: bool,
+	/// Major and minor versions of the linker.
+	pub linker_version: (u8, u8),
+	/// RVA of the entry point, or zero if none.
+	pub address_of_entry_point: u32,
+	/// Preferred address of the image when loaded.
+	pub image_base: u64,
+	/// Alignment of sections when loaded.
+	pub section_alignment: u32,
+	/// Alignment of sections in the file.
+	pub file_alignment: u32,
+	/// Major and minor versions of the required operating system.
+	pub os_version: (u16, u16),
+	/// Major and minor versions of the image.
+	pub image_version: (u16, u16),
+	/// Major and minor versions of the subsystem.
+	pub subsystem_version: (u16, u16),
+	/// Size of the image when loaded.
+	pub size_of_image: u32,
+	/// Size of all headers, in the file.
+	pub size_of_headers: u32,
+	/// Image checksum, usually zero for user-mode images.
+	pub checksum: u32,
+	/// Subsystem required to run the image.
+	pub subsystem: co::IMAGE_SUBSYSTEM,
+	/// Security and loader attributes of the image.
+	pub dll_characteristics: co::IMAGE_DLLCHARACTERISTICS,
+	/// The data directories, indexed by the `IMAGE_DIRECTORY_ENTRY_*`
+	/// constants. Absent directories are zeroed.
+	pub data_directories: Vec<PeDataDirectory>,
+	/// The section table.
+	pub sections: Vec<PeSection>,
+}
+
+/// A data directory of a [`PeFile`](crate::PeFile).
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct PeDataDirectory {
+	/// RVA of the data. For the certificate table, this is a file offset.
+	pub virtual_address: u32,
+	/// Size of the data, in bytes.
+	pub size: u32,
+}
+
+/// A section of a [`PeFile`](crate::PeFile).
+#[derive(Clone, PartialEq, Eq)]
+pub struct PeSection {
+	/// Name of the section, like `.text`. Names longer than 8 characters,
+	/// found in object files, appear as `/` followed by a string table offset.
+	pub name: String,
+	/// Size of the section when loaded.
+	pub virtual_size: u32,
+	/// RVA of the section.
+	pub virtual_address: u32,
+	/// Size of the section data in the file.
+	pub size_of_raw_data: u32,
+	/// Offset of the section data in the file.
+	pub pointer_to_raw_data: u32,
+	/// Attributes of the section.
+	pub characteristics: co::IMAGE_SCN,
+}
+
+/// A DLL imported by a [`PeFile`](crate::PeFile).
+#[derive(Clone, PartialEq, Eq)]
+pub struct PeImport {
+	/// Name of the DLL, like `KERNEL32.dll`.
+	pub dll_name: String,
+	/// The imported symbols, in table order.
+	pub symbols: Vec<PeImportSymbol>,
+}
+
+/// A symbol of a [`PeImport`](crate::PeImport).
+#[derive(Clone, PartialEq, Eq)]
+pub enum PeImportSymbol {
+	/// Symbol imported by name.
+	Name {
+		/// Index into the export name table of the DLL, used as a lookup
+		/// hint by the loader.
+		hint: u16,
+		/// The symbol name.
+		name: String,
+	},
+	/// Symbol imported by ordinal.
+	Ordinal(u16),
+}
+
+/// A symbol exported by a [`PeFile`](crate::PeFile).
+#[derive(Clone, PartialEq, Eq)]
+pub struct PeExport {
+	/// The ordinal, already added to the ordinal base.
+	pub ordinal: u32,
+	/// The name, if the symbol is exported by name.
+	pub name: Option<String>,
+	/// RVA of the symbol. For forwarders, this points to the forwarder
+	/// string.
+	pub rva: u32,
+	/// If the symbol is forwarded to another DLL, the target, like
+	/// `NTDLL.RtlAllocateHeap`.
+	pub forwarder: Option<String>,
+}
+
+impl<'a> PeFile<'a> {
+	/// Parses the headers and the section table of a PE image. The other
+	/// parts are read on demand.
+	///
+	/// Malformed data, including object files and 16-bit executables, yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(data: &'a [u8]) -> SysResult<Self> {
+		if !data.starts_with(b"MZ") {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let pe_off = read_dword(data, 0x3c)? as usize;
+		if data.get(pe_off..pe_off + 4) != Some(b"PE\0\0") {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+
+		let coff_off = pe_off + 4;
+		let num_sections = read_word(data, coff_off + 2)? as usize;
+		let opt_sz = read_word(data, coff_off + 16)? as usize;
+		let opt_off = coff_off + 20;
+		let opt = data
+			.get(opt_off..opt_off + opt_sz)
+			.ok_or(co::ERROR::INVALID_DATA)?;
+
+		let is_pe32_plus = match read_word(opt, 0)? {
+			0x10b => false,
+			0x20b => true,
+			_ => return Err(co::ERROR::INVALID_DATA),
+		};
+		let (image_base, dirs_off) = if is_pe32_plus {
+			(read_qword(opt, 24)?, 112)
+		} else {
+			(read_dword(opt, 28)? as u64, 96)
+		};
+
+		let num_dirs = (read_dword(opt, dirs_off - 4)? as usize)
+			.min(NUM_DIRS)
+			.min(opt_sz.saturating_sub(dirs_off) / 8);
+		let mut data_directories = vec![PeDataDirectory::default(); NUM_DIRS];
+		for (idx, dir) in data_directories.iter_mut().enumerate().take(num_dirs) {
+			dir.virtual_address = read_dword(opt, dirs_off + idx * 8)?;
+			dir.size = read_dword(opt, dirs_off + idx * 8 + 4)?;
+		}
+
+		let sections_off = opt_off + opt_sz;
+		let sections = (0..num_sections)
+			.map(|idx| {
+				let sec = data
+					.get(sections_off + idx * 40..sections_off + (idx + 1) * 40)
+					.ok_or(co::ERROR::INVALID_DATA)?;
+				let name = &sec[..8];
+				let name_len = name.iter().position(|ch| *ch == 0).unwrap_or(8);
+				Ok(PeSection {
+					name: String::from_utf8_lossy(&name[..name_len]).into_owned(),
+					virtual_size: read_dword(sec, 8)?,
+					virtual_address: read_dword(sec, 12)?,
+					size_of_raw_data: read_dword(sec, 16)?,
+					pointer_to_raw_data: read_dword(sec, 20)?,
+					characteristics: unsafe { co::IMAGE_SCN::from_raw(read_dword(sec, 36)?) },
+				})
+			})
+			.collect::<SysResult<Vec<_>>>()?;
+
+		Ok(Self {
+			data,
+			machine: unsafe { co::IMAGE_FILE_MACHINE::from_raw(read_word(data, coff_off)?) },
+			time_date_stamp: read_dword(data, coff_off + 4)?,
+			characteristics: unsafe { co::IMAGE_FILE::from_raw(read_word(data, coff_off + 18)?) },
+			is_pe32_plus,
+			linker_version: (opt[2], opt[3]),
+			address_of_entry_point: read_dword(opt, 16)?,
+			image_base,
+			section_alignment: read_dword(opt, 32)?,
+			file_alignment: read_dword(opt, 36)?,
+			os_version: (read_word(opt, 40)?, read_word(opt, 42)?),
+			image_version: (read_word(opt, 44)?, read_word(opt, 46)?),
+			subsystem_version: (read_word(opt, 48)?, read_word(opt, 50)?),
+			size_of_image: read_dword(opt, 56)?,
+			size_of_headers: read_dword(opt, 60)?,
+			checksum: read_dword(opt, 64)?,
+			subsystem: unsafe { co::IMAGE_SUBSYSTEM::from_raw(read_word(opt, 68)?) },
+			dll_characteristics: unsafe {
+				co::IMAGE_DLLCHARACTERISTICS::from_raw(read_word(opt, 70)?)
+			},
+			data_directories,
+			sections,
+		})
+	}
+
+	/// Returns the raw bytes of the image.
+	#[must_use]
+	pub const fn as_slice(&self) -> &'a [u8] {
+		self.data
+	}
+
+	/// Converts an RVA into a file offset, or returns `None` if the RVA is not
+	/// backed by file data.
+	#[must_use]
+	pub fn rva_to_offset(&self, rva: u32) -> Option<usize> {
+		if rva < self.size_of_headers && (rva as usize) < self.data.len() {
+			return Some(rva as _);
+		}
+		self.sections.iter().find_map(|sec| {
+			let span = if sec.virtual_size == 0 { sec.size_of_raw_data } else { sec.virtual_size };
+			let delta = rva.checked_sub(sec.virtual_address)?;
+			if delta < span && delta < sec.size_of_raw_data {
+				let off = sec.pointer_to_raw_data as usize + delta as usize;
+				(off < self.data.len()).then_some(off)
+			} else {
+				None
+			}
+		})
+	}
+
+	/// Returns the file data of the section, truncated if it goes beyond the
+	/// end of the file.
+	#[must_use]
+	pub fn section_data(&self, section: &PeSection) -> &'a [u8] {
+		let start = (section.pointer_to_raw_data as usize).min(self.data.len());
+		let end = start
+			.saturating_add(section.size_of_raw_data as _)
+			.min(self.data.len());
+		&self.data[start..end]
+	}
+
+	/// Reads all resources from the resource directory tree, in tree order.
+	/// Returns an empty [`ResFile`](crate::ResFile) if the image has no
+	/// resources.
+	///
+	/// The memory flags of the entries are the resource compiler defaults,
+	/// since the image doesn't store them.
+	#[must_use]
+	pub fn resources(&self) -> SysResult<ResFile> {
+		Ok(ResFile {
+			entries: self
+				.resource_leaves()?
+				.into_iter()
+				.map(|(res_type, name, lang, data)| {
+					ResEntry::new(res_type, name, lang, data.to_vec())
+				})
+				.collect(),
+		})
+	}
+
+	/// Returns the embedded application manifest, which is the first
+	/// [`RT::MANIFEST`](crate::co::RT::MANIFEST) resource, if any.
+	#[must_use]
+	pub fn manifest(&self) -> SysResult<Option<&'a [u8]>> {
+		Ok(self
+			.resource_leaves()?
+			.into_iter()
+			.find(|(res_type, ..)| matches!(res_type, RtStr::Rt(rt) if *rt == co::RT::MANIFEST))
+			.map(|(.., data)| data))
+	}
+
+	/// Reads the import table.
+	#[must_use]
+	pub fn imports(&self) -> SysResult<Vec<PeImport>> {
+		let dir = self.dir(DIR_IMPORT);
+		if dir.virtual_address == 0 {
+			return Ok(Vec::new());
+		}
+
+		let mut imports = Vec::<PeImport>::new();
+		let mut desc_rva = dir.virtual_address;
+		loop {
+			let desc = self.bytes_at(desc_rva, 20)?;
+			let (orig_thunks_rva, name_rva, thunks_rva) =
+				(read_dword(desc, 0)?, read_dword(desc, 12)?, read_dword(desc, 16)?);
+			if name_rva == 0 && thunks_rva == 0 {
+				break; // null descriptor terminates the table
+			}
+			imports.push(PeImport {
+				dll_name: self.str_at(name_rva)?,
+				symbols: self.import_symbols(if orig_thunks_rva != 0 {
+					orig_thunks_rva
+				} else {
+					thunks_rva
+				})?,
+			});
+			desc_rva = desc_rva.checked_add(20).ok_or(co::ERROR::INVALID_DATA)?;
+		}
+		Ok(imports)
+	}
+
+	/// Reads the delay-load import table, which lists the DLLs loaded only
+	/// when one of their functions is first called.
+	#[must_use]
+	pub fn delay_imports(&self) -> SysResult<Vec<PeImport>> {
+		let dir = self.dir(DIR_DELAY_IMPORT);
+		if dir.virtual_address == 0 {
+			return Ok(Vec::new());
+		}
+
+		let mut imports = Vec::<PeImport>::new();
+		let mut desc_rva = dir.virtual_address;
+		loop {
+			let desc = self.bytes_at(desc_rva, 32)?;
+			let (attrs, name_addr, names_addr) =
+				(read_dword(desc, 0)?, read_dword(desc, 4)?, read_dword(desc, 16)?);
+			if name_addr == 0 {
+				break; // null descriptor terminates the table
+			}
+			let to_rva = |addr: u32| -> SysResult<u32> {
+				if attrs & 1 != 0 {
+					Ok(addr)
+				} else {
+					// Old linkers stored virtual addresses instead of RVAs.
+					u32::try_from((addr as u64).wrapping_sub(self.image_base))
+						.map_err(|_| co::ERROR::INVALID_DATA)
+				}
+			};
+			imports.push(PeImport {
+				dll_name: self.str_at(to_rva(name_addr)?)?,
+				symbols: self.import_symbols(to_rva(names_addr)?)?,
+			});
+			desc_rva = desc_rva.checked_add(32).ok_or(co::ERROR::INVALID_DATA)?;
+		}
+		Ok(imports)
+	}
+
+	/// Reads the export table, sorted by ordinal. Unused ordinals are skipped.
+	#[must_use]
+	pub fn exports(&self) -> SysResult<Vec<PeExport>> {
+		let dir = self.dir(DIR_EXPORT);
+		if dir.virtual_address == 0 {
+			return Ok(Vec::new());
+		}
+
+		let exp = self.bytes_at(dir.virtual_address, 40)?;
+		let ordinal_base = read_dword(exp, 16)?;
+		let num_funcs = read_dword(exp, 20)? as usize;
+		let num_names = read_dword(exp, 24)? as usize;
+		let funcs = self.table_at(read_dword(exp, 28)?, num_funcs, 4)?;
+		let names = self.table_at(read_dword(exp, 32)?, num_names, 4)?;
+		let name_ords = self.table_at(read_dword(exp, 36)?, num_names, 2)?;
+
+		let mut func_names = vec![None::<u32>; num_funcs];
+		for idx in 0..num_names {
+			let func_idx = read_word(name_ords, idx * 2)? as usize;
+			if let Some(slot) = func_names.get_mut(func_idx) {
+				slot.get_or_insert(read_dword(names, idx * 4)?);
+			}
+		}
+
+		let dir_end = dir.virtual_address as u64 + dir.size as u64;
+		let mut exports = Vec::<PeExport>::new();
+		for (idx, name_rva) in func_names.into_iter().enumerate() {
+			let rva = read_dword(funcs, idx * 4)?;
+			if rva == 0 {
+				continue;
+			}
+			let is_forwarder = rva >= dir.virtual_address && (rva as u64) < dir_end;
+			exports.push(PeExport {
+				ordinal: ordinal_base.wrapping_add(idx as _),
+				name: name_rva.map(|rva| self.str_at(rva)).transpose()?,
+				rva,
+				forwarder: if is_forwarder { Some(self.str_at(rva)?) } else { None },
+			});
+		}
+		Ok(exports)
+	}
+
+	/// Returns the file offset and the size of the certificate table, which
+	/// holds the Authenticode signatures, if the image is signed.
+	///
+	/// The table is not part of the loaded image, and it's not validated.
+	#[must_use]
+	pub fn certificate_table(&self) -> Option<(u32, u32)> {
+		let dir = self.dir(DIR_SECURITY);
+		(dir.virtual_address != 0 && dir.size != 0).then_some((dir.virtual_address, dir.size))
+	}
+
+	/// Returns the data directory at the index, zeroed if absent.
+	fn dir(&self, idx: usize) -> PeDataDirectory {
+		self.data_directories.get(idx).copied().unwrap_or_default()
+	}
+
+	/// Walks the resource directory tree, returning type, name, language and
+	/// data of each leaf.
+	fn resource_leaves(&self) -> SysResult<Vec<ResLeaf<'a>>> {
+		let root_rva = self.dir(DIR_RESOURCE).virtual_address;
+		if root_rva == 0 {
+			return Ok(Vec::new());
+		}
+
+		let mut leaves = Vec::new();
+		let mut visited = 0;
+		for (res_type, type_off, is_dir) in self.res_dir(root_rva, 0, &mut visited)? {
+			if !is_dir {
+				return Err(co::ERROR::INVALID_DATA);
+			}
+			let res_type = match res_type {
+				IdStr::Id(id) => RtStr::Rt(unsafe { co::RT::from_raw(id) }),
+				IdStr::Str(ws) => RtStr::Str(ws),
+			};
+			for (name, name_off, is_dir) in self.res_dir(root_rva, type_off, &mut visited)? {
+				if !is_dir {
+					return Err(co::ERROR::INVALID_DATA);
+				}
+				for (lang, data_entry_off, is_dir) in
+					self.res_dir(root_rva, name_off, &mut visited)?
+				{
+					let (IdStr::Id(lang), false) = (lang, is_dir) else {
+						return Err(co::ERROR::INVALID_DATA); // languages are IDs pointing to data
+					};
+					let data_entry = self.bytes_at(add_rva(root_rva, data_entry_off)?, 16)?;
+					let data =
+						self.bytes_at(read_dword(data_entry, 0)?, read_dword(data_entry, 4)? as _)?;
+					leaves.push((
+						res_type.clone(),
+						name.clone(),
+						unsafe { LANGID::from_raw(lang) },
+						data,
+					));
+				}
+			}
+		}
+		Ok(leaves)
+	}
+
+	/// Reads the entries of a resource directory, at the given offset from the
+	/// root. Each entry has its identifier, the offset of its target and
+	/// whether the target is a subdirectory, rather than a data entry.
+	fn res_dir(
+		&self,
+		root_rva: u32,
+		dir_off: u32,
+		visited: &mut usize,
+	) -> SysResult<Vec<(IdStr, u32, bool)>> {
+		let dir_rva = add_rva(root_rva, dir_off)?;
+		let header = self.bytes_at(dir_rva, 16)?;
+		let num_entries = read_word(header, 12)? as usize + read_word(header, 14)? as usize;
+		*visited += num_entries;
+		if *visited > MAX_RES_ENTRIES {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+
+		let entries = self.table_at(add_rva(dir_rva, 16)?, num_entries, 8)?;
+		(0..num_entries)
+			.map(|idx| {
+				let name = read_dword(entries, idx * 8)?;
+				let target = read_dword(entries, idx * 8 + 4)?;
+				let id = if name & 0x8000_0000 != 0 {
+					let str_rva = add_rva(root_rva, name & 0x7fff_ffff)?;
+					let len = read_word(self.bytes_at(str_rva, 2)?, 0)? as usize;
+					let wchars = self
+						.table_at(add_rva(str_rva, 2)?, len, 2)?
+						.chunks_exact(2)
+						.map(|ch| u16::from_le_bytes([ch[0], ch[1]]))
+						.collect::<Vec<_>>();
+					IdStr::Str(WString::from_wchars_slice(&wchars))
+				} else {
+					IdStr::Id(name as _)
+				};
+				Ok((id, target & 0x7fff_ffff, target & 0x8000_0000 != 0))
+			})
+			.collect()
+	}
+
+	/// Reads an import name table, which is terminated by a zero entry.
+	fn import_symbols(&self, rva: u32) -> SysResult<Vec<PeImportSymbol>> {
+		let thunk_sz: u32 = if self.is_pe32_plus { 8 } else { 4 };
+		let mut symbols = Vec::<PeImportSymbol>::new();
+		let mut thunk_rva = rva;
+		loop {
+			let thunk = self.bytes_at(thunk_rva, thunk_sz as _)?;
+			let (val, is_ordinal) = if self.is_pe32_plus {
+				let val = read_qword(thunk, 0)?;
+				(val, val & (1 << 63) != 0)
+			} else {
+				let val = read_dword(thunk, 0)? as u64;
+				(val, val & (1 << 31) != 0)
+			};
+			if val == 0 {
+				break;
+			}
+
+			symbols.push(if is_ordinal {
+				PeImportSymbol::Ordinal(val as _)
+			} else {
+				let name_rva = u32::try_from(val).map_err(|_| co::ERROR::INVALID_DATA)?;
+				PeImportSymbol::Name {
+					hint: read_word(self.bytes_at(name_rva, 2)?, 0)?,
+					name: self.str_at(add_rva(name_rva, 2)?)?,
+				}
+			});
+			thunk_rva = add_rva(thunk_rva, thunk_sz)?;
+		}
+		Ok(symbols)
+	}
+
+	/// Returns `len` bytes starting at the RVA.
+	fn bytes_at(&self, rva: u32, len: usize) -> SysResult<&'a [u8]> {
+		if len == 0 {
+			return Ok(&[]);
+		}
+		let off = self.rva_to_offset(rva).ok_or(co::ERROR::INVALID_DATA)?;
+		self.data
+			.get(off..off.checked_add(len).ok_or(co::ERROR::INVALID_DATA)?)
+			.ok_or(co::ERROR::INVALID_DATA)
+	}
+
+	/// Returns a table of `count` items of `item_sz` bytes starting at the RVA.
+	fn table_at(&self, rva: u32, count: usize, item_sz: usize) -> SysResult<&'a [u8]> {
+		self.bytes_at(rva, count.checked_mul(item_sz).ok_or(co::ERROR::INVALID_DATA)?)
+	}
+
+	/// Reads a null-terminated ANSI string starting at the RVA.
+	fn str_at(&self, rva: u32) -> SysResult<String> {
+		let off = self.rva_to_offset(rva).ok_or(co::ERROR::INVALID_DATA)?;
+		let tail = &self.data[off..self.data.len().min(off + MAX_NAME_LEN)];
+		let len = tail
+			.iter()
+			.position(|ch| *ch == 0)
+			.ok_or(co::ERROR::INVALID_DATA)?;
+		Ok(String::from_utf8_lossy(&tail[..len]).into_owned())
+	}
+}
+
+fn add_rva(rva: u32, delta: u32) -> SysResult<u32> {
+	rva.checked_add(delta).ok_or(co::ERROR::INVALID_DATA)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SEC_RVA: u32 = 0x1000;
+	const SEC_OFF: usize = 0x200;
+
+	/// Little-endian writer of the test images.
+	struct Buf(Vec<u8>);
+
+	impl Buf {
+		fn rva(&self) -> u32 {
+			SEC_RVA + self.0.len() as u32
+		}
+		fn bytes(&mut self, b: &[u8]) -> &mut Self {
+			self.0.extend_from_slice(b);
+			self
+		}
+		fn word(&mut self, v: u16) -> &mut Self {
+			self.bytes(&v.to_le_bytes())
+		}
+		fn dword(&mut self, v: u32) -> &mut Self {
+			self.bytes(&v.to_le_bytes())
+		}
+		fn qword(&mut self, v: u64) -> &mut Self {
+			self.bytes(&v.to_le_bytes())
+		}
+		fn res_dir(&mut self, entries: &[(u32, u32)]) -> &mut Self {
+			self.dword(0)
+				.dword(0)
+				.dword(0)
+				.word(0)
+				.word(entries.len() as _);
+			entries.iter().for_each(|(id, target)| {
+				self.dword(*id).dword(*target);
+			});
+			self
+		}
+		fn set_dword(&mut self, off: usize, v: u32) {
+			self.0[off..off + 4].copy_from_slice(&v.to_le_bytes());
+		}
+	}
+
+	/// Resource tree with RT_VERSION and RT_MANIFEST, ID 1, en-US.
+	fn version_and_manifest(sec: &mut Buf) {
+		let data_rva = sec.rva() + 160;
+		sec.res_dir(&[(16, 0x8000_0000 | 32), (24, 0x8000_0000 | 56)]) // root
+			.res_dir(&[(1, 0x8000_0000 | 80)]) // RT_VERSION, at 32
+			.res_dir(&[(1, 0x8000_0000 | 104)]) // RT_MANIFEST, at 56
+			.res_dir(&[(0x0409, 128)]) // at 80
+			.res_dir(&[(0x0409, 144)]); // at 104
+		sec.dword(data_rva).dword(8).dword(0).dword(0); // at 128
+		sec.dword(data_rva + 8).dword(9).dword(0).dword(0); // at 144
+		sec.bytes(b"VERSION\0<assembly");
+	}
+
+	/// Resource tree whose directories are shared by all the entries of
+	/// their parents, so it has 48 * 48 * 48 leaves.
+	fn fan_out(sec: &mut Buf) {
+		const N: u32 = 48;
+		let sz = 16 + N * 8;
+		let data_rva = sec.rva() + sz * 3 + 16;
+		for level in 1..=3 {
+			let target = if level == 3 { sz * 3 } else { 0x8000_0000 | (sz * level) };
+			sec.res_dir(&(0..N).map(|id| (id + 1, target)).collect::<Vec<_>>());
+		}
+		sec.dword(data_rva).dword(1).dword(0).dword(0).bytes(b"x");
+	}
+
+	/// Builds an image with a single section holding an import table, an
+	/// export table with a forwarder, and the given resource tree.
+	fn image(pe32_plus: bool, res_tree: fn(&mut Buf)) -> Vec<u8> {
+		let mut sec = Buf(Vec::new());
+
+		let dll_rva = sec.rva();
+		sec.bytes(b"KERNEL32.dll\0");
+		let hint_rva = sec.rva();
+		sec.word(0x15).bytes(b"ExitProcess\0\0");
+		let thunks_rva = sec.rva();
+		if pe32_plus {
+			sec.qword(hint_rva as _).qword(1 << 63 | 7).qword(0);
+		} else {
+			sec.dword(hint_rva).dword(1 << 31 | 7).dword(0);
+		}
+		let import_rva = sec.rva();
+		sec.dword(thunks_rva)
+			.dword(0)
+			.dword(0)
+			.dword(dll_rva)
+			.dword(thunks_rva);
+		sec.bytes(&[0; 20]);
+
+		let export_rva = sec.rva();
+		let (funcs, names, ords) = (export_rva + 40, export_rva + 52, export_rva + 60);
+		let (alpha, fwd, target) = (export_rva + 64, export_rva + 70, export_rva + 74);
+		sec.dword(0).dword(0).dword(0).dword(0); // characteristics to name
+		sec.dword(1).dword(3).dword(2); // ordinal base, functions, names
+		sec.dword(funcs).dword(names).dword(ords);
+		sec.dword(0x1234).dword(0).dword(target);
+		sec.dword(alpha).dword(fwd);
+		sec.word(0).word(2);
+		sec.bytes(b"Alpha\0Fwd\0NTDLL.RtlAllocateHeap\0");
+		let export_sz = sec.rva() - export_rva;
+
+		sec.bytes(&[0; 4][..(4 - sec.0.len() % 4) % 4]);
+		let res_rva = sec.rva();
+		res_tree(&mut sec);
+		let res_sz = sec.rva() - res_rva;
+		let sec_sz = sec.0.len() as u32;
+		sec.0.resize(sec.0.len().next_multiple_of(0x200), 0);
+
+		let mut img = Buf(Vec::new());
+		img.bytes(b"MZ").bytes(&[0; 0x3a]).dword(0x40);
+		img.bytes(b"PE\0\0")
+			.word(if pe32_plus { 0x8664 } else { 0x014c })
+			.word(1) // sections
+			.dword(0x6000_0000)
+			.dword(0)
+			.dword(0)
+			.word(if pe32_plus { 240 } else { 224 })
+			.word(0x2022);
+		img.word(if pe32_plus { 0x20b } else { 0x10b })
+			.bytes(&[14, 40])
+			.dword(0)
+			.dword(sec.0.len() as _)
+			.dword(0)
+			.dword(0x1010) // entry point
+			.dword(0);
+		if pe32_plus {
+			img.qword(0x1_8000_0000);
+		} else {
+			img.dword(0).dword(0x1000_0000);
+		}
+		img.dword(0x1000).dword(0x200);
+		img.word(6).word(0).word(1).word(2).word(6).word(0).dword(0);
+		img.dword(0x2000)
+			.dword(SEC_OFF as _)
+			.dword(0)
+			.word(2)
+			.word(0x0160);
+		for _ in 0..4 {
+			if pe32_plus {
+				img.qword(0x10_0000);
+			} else {
+				img.dword(0x10_0000);
+			}
+		}
+		img.dword(0).dword(NUM_DIRS as _);
+		img.dword(export_rva).dword(export_sz);
+		img.dword(import_rva).dword(40);
+		img.dword(res_rva).dword(res_sz);
+		img.bytes(&[0; (NUM_DIRS - 3) * 8]);
+
+		img.bytes(b".data\0\0\0")
+			.dword(sec_sz)
+			.dword(SEC_RVA)
+			.dword(sec.0.len() as _)
+			.dword(SEC_OFF as _)
+			.bytes(&[0; 12])
+			.dword(0xc000_0040);
+		img.0.resize(SEC_OFF, 0);
+		img.bytes(&sec.0);
+		img.0
+	}
+
+	/// File offset of an RVA of the test images.
+	fn off(rva: u32) -> usize {
+		rva as usize - SEC_RVA as usize + SEC_OFF
+	}
+
+	fn check(pe32_plus: bool) {
+		let data = image(pe32_plus, version_and_manifest);
+		let pe = PeFile::parse(&data).unwrap();
+		assert_eq!(pe.is_pe32_plus, pe32_plus);
+		assert_eq!(
+			pe.machine,
+			if pe32_plus { co::IMAGE_FILE_MACHINE::AMD64 } else { co::IMAGE_FILE_MACHINE::I386 },
+		);
+		assert_eq!(pe.image_base, if pe32_plus { 0x1_8000_0000 } else { 0x1000_0000 });
+		assert_eq!(pe.linker_version, (14, 40));
+		assert_eq!(pe.address_of_entry_point, 0x1010);
+		assert_eq!(pe.subsystem_version, (6, 0));
+		assert_eq!(pe.subsystem, co::IMAGE_SUBSYSTEM::WINDOWS_GUI);
+		assert_eq!(pe.sections.len(), 1);
+		assert_eq!(pe.sections[0].name, ".data");
+		assert_eq!(pe.section_data(&pe.sections[0]).len(), data.len() - SEC_OFF);
+		assert_eq!(pe.rva_to_offset(SEC_RVA + 4), Some(SEC_OFF + 4));
+		assert_eq!(pe.rva_to_offset(0x3c), Some(0x3c));
+		assert_eq!(pe.rva_to_offset(0x9000), None);
+		assert!(pe.certificate_table().is_none());
+		assert!(pe.delay_imports().unwrap().is_empty());
+
+		let imports = pe.imports().unwrap();
+		assert!(
+			imports
+				== [PeImport {
+					dll_name: "KERNEL32.dll".to_owned(),
+					symbols: vec![
+						PeImportSymbol::Name {
+							hint: 0x15,
+							name: "ExitProcess".to_owned()
+						},
+						PeImportSymbol::Ordinal(7),
+					],
+				}]
+		);
+
+		let exports = pe.exports().unwrap();
+		assert_eq!(exports.len(), 2); // unused ordinal 2 is skipped
+		assert_eq!(exports[0].ordinal, 1);
+		assert_eq!(exports[0].name.as_deref(), Some("Alpha"));
+		assert_eq!(exports[0].rva, 0x1234);
+		assert_eq!(exports[0].forwarder, None);
+		assert_eq!(exports[1].ordinal, 3);
+		assert_eq!(exports[1].name.as_deref(), Some("Fwd"));
+		assert_eq!(exports[1].forwarder.as_deref(), Some("NTDLL.RtlAllocateHeap"));
+
+		let res = pe.resources().unwrap();
+		assert_eq!(res.entries.len(), 2);
+		let version = res
+			.find(&RtStr::Rt(co::RT::VERSION), &IdStr::Id(1))
+			.unwrap();
+		assert_eq!(u16::from(version.lang), 0x0409);
+		assert_eq!(version.data, b"VERSION\0");
+		assert_eq!(pe.manifest().unwrap(), Some(&b"<assembly"[..]));
+	}
+
+	#[test]
+	fn parse_pe32() {
+		check(false);
+	}
+
+	#[test]
+	fn parse_pe32_plus() {
+		check(true);
+	}
+
+	#[test]
+	fn truncated_headers() {
+		for pe32_plus in [false, true] {
+			let data = image(pe32_plus, version_and_manifest);
+			let headers_end = 0x40 + 4 + 20 + if pe32_plus { 240 } else { 224 } + 40;
+			for len in 0..headers_end {
+				assert!(PeFile::parse(&data[..len]).is_err(), "{}", len);
+			}
+			assert!(PeFile::parse(&data[..headers_end]).is_ok());
+		}
+
+		let mut data = image(false, version_and_manifest);
+		data[0x40 + 24] = 0x07; // optional header magic of ROM images
+		assert!(PeFile::parse(&data).is_err());
+	}
+
+	#[test]
+	fn out_of_range_rvas() {
+		let data = image(false, version_and_manifest);
+		let pe = PeFile::parse(&data).unwrap();
+		let dir = |idx: usize| pe.data_directories[idx].virtual_address;
+		let (export_rva, import_rva, res_rva) =
+			(dir(DIR_EXPORT), dir(DIR_IMPORT), dir(DIR_RESOURCE));
+
+		let mut bad = Buf(data.clone());
+		bad.set_dword(off(import_rva) + 12, 0x9000); // DLL name
+		assert!(PeFile::parse(&bad.0).unwrap().imports().is_err());
+
+		let mut bad = Buf(data.clone());
+		bad.set_dword(off(export_rva) + 28, 0xffff_fffc); // function table
+		assert!(PeFile::parse(&bad.0).unwrap().exports().is_err());
+
+		let mut bad = Buf(data.clone());
+		bad.set_dword(off(res_rva) + 128, 0x9000); // resource data
+		assert!(PeFile::parse(&bad.0).unwrap().resources().is_err());
+
+		let mut bad = Buf(data.clone());
+		bad.set_dword(off(res_rva) + 132, 0x7fff_ffff); // resource data size
+		assert!(PeFile::parse(&bad.0).unwrap().resources().is_err());
+		assert!(PeFile::parse(&bad.0).unwrap().manifest().is_err());
+
+		let mut bad = Buf(data);
+		bad.set_dword(0x3c, 0xffff_fff0); // PE header offset
+		assert!(PeFile::parse(&bad.0).is_err());
+	}
+
+	#[test]
+	fn cyclic_resources() {
+		let data = image(false, version_and_manifest);
+		let res_rva = PeFile::parse(&data).unwrap().data_directories[DIR_RESOURCE].virtual_address;
+		for level_off in [0, 32, 80] {
+			let mut bad = Buf(data.clone());
+			bad.set_dword(off(res_rva) + level_off + 20, 0x8000_0000); // points to the root
+			assert!(PeFile::parse(&bad.0).unwrap().resources().is_err());
+		}
+
+		let data = image(true, fan_out);
+		assert!(PeFile::parse(&data).unwrap().resources().is_err()); // too many entries
+	}
+}
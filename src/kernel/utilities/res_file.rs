@@ -0,0 +1,360 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::bytes::*;
+
+/// Size of the fixed part of an entry header, after the type and name.
+const HEADER_TAIL_SZ: usize = 16;
+
+/// Contents of a 32-bit `.res` file, the binary resource container written by
+/// the resource compiler and read by the linker.
+///
+/// This is a pure parser and writer: no system calls are made, so resources can
+/// be generated and validated on any platform. The entries can also be written
+/// to an executable with
+/// [`HUPDATERSRC::UpdateResource`](crate::HUPDATERSRC::UpdateResource).
+///
+/// # Examples
+///
+/// Listing the entries of a `.res` file:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let fin = w::FileMapped::open("C:\\Temp\\app.res", w::FileAccess::ExistingReadOnly)?;
+/// let res = w::ResFile::parse(fin.as_slice())?;
+///
+/// for entry in res.entries.iter() {
+///     println!("{} {} {} - {} bytes",
+///         entry.res_type, entry.name, entry.lang, entry.data.len());
+/// }
+/// # w::SysResult::Ok(())
+/// ```
+///
+/// Writing a `.res` file with a manifest:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let manifest = std::fs::read("C:\\Temp\\app.manifest").unwrap();
+///
+/// let res = w::ResFile {
+///     entries: vec![
+///         w::ResEntry::new(
+///             w::RtStr::Rt(co::RT::MANIFEST),
+///             w::IdStr::Id(1),
+///             w::LANGID::USER_DEFAULT,
+///             manifest,
+///         ),
+///     ],
+/// };
+///
+/// let fout = w::File::open("C:\\Temp\\app.res", w::FileAccess::OpenOrCreateRW)?;
+/// fout.erase_and_write(&res.serialize())?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Default)]
+pub struct ResFile {
+	/// The resources, in file order. The empty entry which marks the file as
+	/// 32-bit is not included.
+	pub entries: Vec<ResEntry>,
+}
+
+/// A single resource of a [`ResFile`](crate::ResFile).
+#[derive(Clone)]
+pub struct ResEntry {
+	/// The resource type.
+	pub res_type: RtStr,
+	/// The resource name.
+	pub name: IdStr,
+	/// The resource language.
+	pub lang: LANGID,
+	/// Memory flags, a combination of the `ResEntry::MEM_*` values. Ignored by
+	/// 32-bit Windows.
+	pub memory_flags: u16,
+	/// Version of the data format, usually zero.
+	pub data_version: u32,
+	/// User-defined version of the resource.
+	pub version: u32,
+	/// User-defined characteristics of the resource.
+	pub characteristics: u32,
+	/// The raw resource data.
+	pub data: Vec<u8>,
+}
+
+impl ResFile {
+	/// Parses the raw contents of a 32-bit `.res` file.
+	///
+	/// Malformed data, including a 16-bit `.res` file, yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(data: &[u8]) -> SysResult<Self> {
+		let mut entries = Vec::<ResEntry>::new();
+		let mut off = 0;
+		let mut is_first = true;
+
+		while off < data.len() {
+			let (entry, next_off) = ResEntry::read(data, off)?;
+			if is_first {
+				// The 32-bit format starts with an empty entry of type zero.
+				if !entry.data.is_empty()
+					|| !matches!(entry.res_type, RtStr::Rt(rt) if rt.raw() == 0)
+				{
+					return Err(co::ERROR::INVALID_DATA);
+				}
+				is_first = false;
+			} else {
+				entries.push(entry);
+			}
+			off = next_off;
+		}
+
+		Ok(Self { entries })
+	}
+
+	/// Serializes the entries into the raw contents of a 32-bit `.res` file,
+	/// including the leading empty entry.
+	#[must_use]
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut buf = Vec::<u8>::new();
+		ResEntry {
+			res_type: RtStr::Rt(unsafe { co::RT::from_raw(0) }),
+			name: IdStr::Id(0),
+			lang: LANGID::default(),
+			memory_flags: 0,
+			data_version: 0,
+			version: 0,
+			characteristics: 0,
+			data: Vec::new(),
+		}
+		.write(&mut buf);
+
+		self.entries.iter().for_each(|entry| entry.write(&mut buf));
+		buf
+	}
+
+	/// Returns the first entry with the given type and name, in any language.
+	/// String identifiers are compared case-insensitively, like
+	/// [`HINSTANCE::FindResource`](crate::HINSTANCE::FindResource) does.
+	#[must_use]
+	pub fn find(&self, res_type: &RtStr, name: &IdStr) -> Option<&ResEntry> {
+		self.entries.iter().find(|entry| entry.is(res_type, name))
+	}
+
+	/// Returns the entry with the given type, name and language.
+	#[must_use]
+	pub fn find_lang(&self, res_type: &RtStr, name: &IdStr, lang: LANGID) -> Option<&ResEntry> {
+		self.entries
+			.iter()
+			.find(|entry| entry.lang == lang && entry.is(res_type, name))
+	}
+}
+
+impl ResEntry {
+	/// Memory flag: the resource can be moved in memory.
+	pub const MEM_MOVEABLE: u16 = 0x0010;
+	/// Memory flag: the resource is read-only.
+	pub const MEM_PURE: u16 = 0x0020;
+	/// Memory flag: the resource is loaded along with the module.
+	pub const MEM_PRELOAD: u16 = 0x0040;
+	/// Memory flag: the resource can be discarded when no longer needed.
+	pub const MEM_DISCARDABLE: u16 = 0x1000;
+
+	/// Constructs a new `ResEntry` with the given data, zero versions and
+	/// characteristics, and memory flags `MEM_MOVEABLE | MEM_PURE |
+	/// MEM_DISCARDABLE`, which are the resource compiler defaults.
+	#[must_use]
+	pub fn new(res_type: RtStr, name: IdStr, lang: LANGID, data: Vec<u8>) -> Self {
+		Self {
+			res_type,
+			name,
+			lang,
+			memory_flags: Self::MEM_MOVEABLE | Self::MEM_PURE | Self::MEM_DISCARDABLE,
+			data_version: 0,
+			version: 0,
+			characteristics: 0,
+			data,
+		}
+	}
+
+	/// Tells whether the entry has the given type and name.
+	#[must_use]
+	fn is(&self, res_type: &RtStr, name: &IdStr) -> bool {
+		let type_eq = match (&self.res_type, res_type) {
+			(RtStr::Rt(a), RtStr::Rt(b)) => a == b,
+			(RtStr::Str(a), RtStr::Str(b)) => eq_ignore_case(a, b),
+			_ => false,
+		};
+		let name_eq = match (&self.name, name) {
+			(IdStr::Id(a), IdStr::Id(b)) => a == b,
+			(IdStr::Str(a), IdStr::Str(b)) => eq_ignore_case(a, b),
+			_ => false,
+		};
+		type_eq && name_eq
+	}
+
+	/// Reads the entry at the given offset, returning it along with the offset
+	/// of the next one.
+	fn read(data: &[u8], off: usize) -> SysResult<(Self, usize)> {
+		let data_sz = read_dword(data, off)? as usize;
+		let header_sz = read_dword(data, off + 4)? as usize;
+		let data_off = off.checked_add(header_sz).ok_or(co::ERROR::INVALID_DATA)?;
+		let data_end = data_off
+			.checked_add(data_sz)
+			.ok_or(co::ERROR::INVALID_DATA)?;
+		if header_sz < 8 + 4 + 4 + HEADER_TAIL_SZ || data_end > data.len() {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let header = &data[..data_off]; // type and name can't overflow the header
+
+		let read_id = |at: usize| -> SysResult<(IdStr, usize)> {
+			let first = read_word(data, at)?;
+			if first == 0xffff {
+				return Ok((IdStr::Id(read_word(data, at + 2)?), at + 4));
+			}
+			let mut wchars = Vec::<u16>::new();
+			let mut cur = at;
+			loop {
+				let ch = read_word(header, cur)?; // fails if not null-terminated
+				cur += 2;
+				if ch == 0 {
+					break;
+				}
+				wchars.push(ch);
+			}
+			Ok((IdStr::Str(WString::from_wchars_slice(&wchars)), cur))
+		};
+
+		let (res_type, name_off) = read_id(off + 8)?;
+		let (name, tail_off) = read_id(name_off)?;
+		let tail_off = align4(tail_off);
+		if tail_off + HEADER_TAIL_SZ > data_off {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+
+		Ok((
+			Self {
+				res_type: match res_type {
+					IdStr::Id(rt) => RtStr::Rt(unsafe { co::RT::from_raw(rt) }),
+					IdStr::Str(ws) => RtStr::Str(ws),
+				},
+				name,
+				data_version: read_dword(data, tail_off)?,
+				memory_flags: read_word(data, tail_off + 4)?,
+				lang: unsafe { LANGID::from_raw(read_word(data, tail_off + 6)?) },
+				version: read_dword(data, tail_off + 8)?,
+				characteristics: read_dword(data, tail_off + 12)?,
+				data: data[data_off..data_end].to_vec(),
+			},
+			align4(data_end),
+		))
+	}
+
+	/// Appends the entry to the buffer, which is assumed to be 4-byte aligned.
+	fn write(&self, buf: &mut Vec<u8>) {
+		let mut header = Vec::<u8>::new();
+		match &self.res_type {
+			RtStr::Rt(rt) => write_ord(&mut header, rt.raw()),
+			RtStr::Str(ws) => write_wstr(&mut header, ws),
+		}
+		match &self.name {
+			IdStr::Id(id) => write_ord(&mut header, *id),
+			IdStr::Str(ws) => write_wstr(&mut header, ws),
+		}
+		header.resize(align4(8 + header.len()) - 8, 0);
+		header.extend_from_slice(&self.data_version.to_le_bytes());
+		header.extend_from_slice(&self.memory_flags.to_le_bytes());
+		header.extend_from_slice(&u16::from(self.lang).to_le_bytes());
+		header.extend_from_slice(&self.version.to_le_bytes());
+		header.extend_from_slice(&self.characteristics.to_le_bytes());
+
+		buf.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+		buf.extend_from_slice(&((8 + header.len()) as u32).to_le_bytes());
+		buf.extend_from_slice(&header);
+		buf.extend_from_slice(&self.data);
+		buf.resize(align4(buf.len()), 0);
+	}
+}
+
+fn write_ord(buf: &mut Vec<u8>, ord: u16) {
+	buf.extend_from_slice(&0xffff_u16.to_le_bytes());
+	buf.extend_from_slice(&ord.to_le_bytes());
+}
+
+fn write_wstr(buf: &mut Vec<u8>, ws: &WString) {
+	ws.as_slice()[..ws.str_len()]
+		.iter()
+		.chain(std::iter::once(&0))
+		.for_each(|ch| buf.extend_from_slice(&ch.to_le_bytes()));
+}
+
+fn eq_ignore_case(a: &WString, b: &WString) -> bool {
+	a.to_string().to_uppercase() == b.to_string().to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[rustfmt::skip]
+	const FIXTURE: &[u8] = &[
+		// leading empty entry
+		0x00, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00,
+		0xff, 0xff, 0x00, 0x00, 0xff, 0xff, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		// RT_MANIFEST, ID 1, en-US, "abc"
+		0x03, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00,
+		0xff, 0xff, 0x18, 0x00, 0xff, 0xff, 0x01, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x30, 0x10, 0x09, 0x04,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		b'a', b'b', b'c', 0x00,
+		// RT_RCDATA, "AB", neutral, 4 bytes
+		0x04, 0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x00,
+		0xff, 0xff, 0x0a, 0x00, b'A', 0x00, b'B', 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00,
+		0x01, 0x02, 0x03, 0x04,
+	];
+
+	#[test]
+	fn parse_fixture() {
+		let res = ResFile::parse(FIXTURE).unwrap();
+		assert_eq!(res.entries.len(), 2);
+
+		let manifest = &res.entries[0];
+		assert!(matches!(manifest.res_type, RtStr::Rt(rt) if rt == co::RT::MANIFEST));
+		assert!(matches!(manifest.name, IdStr::Id(1)));
+		assert_eq!(u16::from(manifest.lang), 0x0409);
+		assert_eq!(manifest.memory_flags, 0x1030);
+		assert_eq!(manifest.data, b"abc");
+
+		let rcdata = res
+			.find(&RtStr::Rt(co::RT::RCDATA), &IdStr::from_str("ab"))
+			.unwrap();
+		assert_eq!(rcdata.memory_flags, 0);
+		assert_eq!(rcdata.data, [1, 2, 3, 4]);
+	}
+
+	#[test]
+	fn round_trip() {
+		let res = ResFile::parse(FIXTURE).unwrap();
+		assert_eq!(res.serialize(), FIXTURE);
+
+		let entry = ResEntry::new(
+			RtStr::Rt(co::RT::MANIFEST),
+			IdStr::Id(1),
+			unsafe { LANGID::from_raw(0x0409) },
+			b"abc".to_vec(),
+		);
+		assert_eq!(entry.memory_flags, 0x1030);
+		let res = ResFile { entries: vec![entry] };
+		assert_eq!(res.serialize(), FIXTURE[..68]);
+	}
+
+	#[test]
+	fn truncated() {
+		assert!(ResFile::parse(&FIXTURE[..FIXTURE.len() - 1]).is_err());
+		assert!(ResFile::parse(&FIXTURE[32..]).is_err()); // no leading empty entry
+	}
+}
@@ -0,0 +1,396 @@
+//! [Standard Compression Scheme for Unicode](https://www.unicode.org/reports/tr6/)
+//! codec, implemented in pure Rust.
+
+use crate::co;
+use crate::decl::*;
+
+/// Offsets of the 8 static windows, used by the SQn tags.
+const STATIC_WINDOWS: [u32; 8] = [0x0000, 0x0080, 0x0100, 0x0300, 0x2000, 0x2080, 0x2100, 0x3000];
+
+/// Initial offsets of the 8 dynamic windows.
+const DYNAMIC_WINDOWS: [u32; 8] = [0x0080, 0x00c0, 0x0400, 0x0600, 0x0900, 0x3040, 0x30a0, 0xff00];
+
+// Single-byte mode tags.
+const SQ0: u8 = 0x01;
+const SQ7: u8 = 0x08;
+const SDX: u8 = 0x0b;
+const SQU: u8 = 0x0e;
+const SCU: u8 = 0x0f;
+const SC0: u8 = 0x10;
+const SC7: u8 = 0x17;
+const SD0: u8 = 0x18;
+const SD7: u8 = 0x1f;
+
+// Unicode mode tags.
+const UC0: u8 = 0xe0;
+const UC7: u8 = 0xe7;
+const UD0: u8 = 0xe8;
+const UD7: u8 = 0xef;
+const UQU: u8 = 0xf0;
+const UDX: u8 = 0xf1;
+const URS: u8 = 0xf2;
+
+/// Decodes SCSU data into UTF-16. Decoding stops at the first null char, if
+/// any.
+///
+/// Malformed input yields
+/// [`co::ERROR::NO_UNICODE_TRANSLATION`](crate::co::ERROR::NO_UNICODE_TRANSLATION).
+#[must_use]
+pub(crate) fn decode(data: &[u8]) -> SysResult<Vec<u16>> {
+	let mut dec = Decoder {
+		src: data,
+		pos: 0,
+		windows: DYNAMIC_WINDOWS,
+		active: 0,
+		unicode_mode: false,
+		out: Vec::with_capacity(data.len()),
+	};
+	dec.run()?;
+	Ok(dec.out)
+}
+
+struct Decoder<'a> {
+	src: &'a [u8],
+	pos: usize,
+	windows: [u32; 8],
+	active: usize,
+	unicode_mode: bool,
+	out: Vec<u16>,
+}
+
+impl<'a> Decoder<'a> {
+	fn run(&mut self) -> SysResult<()> {
+		while self.pos < self.src.len() {
+			let reached_null =
+				if self.unicode_mode { self.step_unicode()? } else { self.step_single_byte()? };
+			if reached_null {
+				break;
+			}
+		}
+		Ok(())
+	}
+
+	fn next_byte(&mut self) -> SysResult<u8> {
+		match self.src.get(self.pos) {
+			Some(b) => {
+				self.pos += 1;
+				Ok(*b)
+			},
+			None => Err(co::ERROR::NO_UNICODE_TRANSLATION), // truncated sequence
+		}
+	}
+
+	fn next_u16(&mut self) -> SysResult<u16> {
+		let hi = self.next_byte()?;
+		let lo = self.next_byte()?;
+		Ok(u16::from_be_bytes([hi, lo]))
+	}
+
+	/// Pushes a code point, returning `true` if it's the terminating null.
+	fn push(&mut self, code_point: u32) -> SysResult<bool> {
+		if code_point == 0x0000 {
+			return Ok(true);
+		}
+		let ch = char::from_u32(code_point).ok_or(co::ERROR::NO_UNICODE_TRANSLATION)?;
+		let mut buf = [0u16; 2];
+		self.out.extend_from_slice(ch.encode_utf16(&mut buf));
+		Ok(false)
+	}
+
+	/// Pushes a raw UTF-16 unit, as quoted or written in Unicode mode; these
+	/// may be halves of surrogate pairs.
+	fn push_unit(&mut self, unit: u16) -> bool {
+		if unit == 0x0000 {
+			return true;
+		}
+		self.out.push(unit);
+		false
+	}
+
+	fn step_single_byte(&mut self) -> SysResult<bool> {
+		let b = self.next_byte()?;
+		match b {
+			0x00 | 0x09 | 0x0a | 0x0d | 0x20..=0x7f => self.push(b as _),
+			SQ0..=SQ7 => {
+				let n = (b - SQ0) as usize;
+				let q = self.next_byte()?;
+				if q < 0x80 {
+					self.push(STATIC_WINDOWS[n] + q as u32)
+				} else {
+					self.push(self.windows[n] + (q - 0x80) as u32)
+				}
+			},
+			SDX => {
+				self.define_extended()?;
+				Ok(false)
+			},
+			SQU => {
+				let unit = self.next_u16()?;
+				Ok(self.push_unit(unit))
+			},
+			SCU => {
+				self.unicode_mode = true;
+				Ok(false)
+			},
+			SC0..=SC7 => {
+				self.active = (b - SC0) as _;
+				Ok(false)
+			},
+			SD0..=SD7 => {
+				self.define_window((b - SD0) as _)?;
+				Ok(false)
+			},
+			0x80..=0xff => self.push(self.windows[self.active] + (b - 0x80) as u32),
+			_ => Err(co::ERROR::NO_UNICODE_TRANSLATION), // reserved tag
+		}
+	}
+
+	fn step_unicode(&mut self) -> SysResult<bool> {
+		let b = self.next_byte()?;
+		match b {
+			UC0..=UC7 => {
+				self.active = (b - UC0) as _;
+				self.unicode_mode = false;
+				Ok(false)
+			},
+			UD0..=UD7 => {
+				self.define_window((b - UD0) as _)?;
+				self.unicode_mode = false;
+				Ok(false)
+			},
+			UQU => {
+				let unit = self.next_u16()?;
+				Ok(self.push_unit(unit))
+			},
+			UDX => {
+				self.define_extended()?;
+				self.unicode_mode = false;
+				Ok(false)
+			},
+			URS => Err(co::ERROR::NO_UNICODE_TRANSLATION), // reserved tag
+			_ => {
+				let lo = self.next_byte()?;
+				Ok(self.push_unit(u16::from_be_bytes([b, lo])))
+			},
+		}
+	}
+
+	/// Handles SDn and UDn tags: defines the offset of window `n` from the next
+	/// byte, and makes it active.
+	fn define_window(&mut self, n: usize) -> SysResult<()> {
+		let x = self.next_byte()?;
+		self.windows[n] = match x {
+			0x01..=0x67 => x as u32 * 0x80,
+			0x68..=0xa7 => x as u32 * 0x80 + 0xac00,
+			0xf9 => 0x00c0,
+			0xfa => 0x0250,
+			0xfb => 0x0370,
+			0xfc => 0x0530,
+			0xfd => 0x3040,
+			0xfe => 0x30a0,
+			0xff => 0xff60,
+			_ => return Err(co::ERROR::NO_UNICODE_TRANSLATION), // reserved offset
+		};
+		self.active = n;
+		Ok(())
+	}
+
+	/// Handles SDX and UDX tags: defines a window in the supplementary planes
+	/// from the next 2 bytes, and makes it active.
+	fn define_extended(&mut self) -> SysResult<()> {
+		let hi = self.next_byte()?;
+		let lo = self.next_byte()?;
+		let n = (hi >> 5) as usize;
+		self.windows[n] = 0x1_0000 + ((((hi & 0x1f) as u32) << 8) | lo as u32) * 0x80;
+		self.active = n;
+		Ok(())
+	}
+}
+
+/// Encodes the chars into SCSU.
+///
+/// This is a simple encoder: it stays in single-byte mode while the chars fit
+/// into dynamic windows, defining new windows as needed, and switches to
+/// Unicode mode for chars which cannot be windowed, like CJK ideographs.
+#[must_use]
+pub(crate) fn encode(chars: &[char]) -> Vec<u8> {
+	let mut enc = Encoder {
+		windows: DYNAMIC_WINDOWS,
+		active: 0,
+		next_to_define: 0,
+		unicode_mode: false,
+		out: Vec::with_capacity(chars.len() * 2),
+	};
+	chars.iter().for_each(|ch| enc.push(*ch as _));
+	enc.out
+}
+
+struct Encoder {
+	windows: [u32; 8],
+	active: usize,
+	next_to_define: usize,
+	unicode_mode: bool,
+	out: Vec<u8>,
+}
+
+impl Encoder {
+	fn push(&mut self, c: u32) {
+		if self.unicode_mode {
+			self.push_unicode_mode(c);
+		} else {
+			self.push_single_byte_mode(c);
+		}
+	}
+
+	fn push_single_byte_mode(&mut self, c: u32) {
+		if is_pass_through(c) {
+			self.out.push(c as _);
+		} else if c < 0x20 {
+			self.out.extend_from_slice(&[SQ0, c as _]); // other C0 controls
+		} else if let Some(n) = self.window_of(c) {
+			if n != self.active {
+				self.out.push(SC0 + n as u8);
+				self.active = n;
+			}
+			self.out.push((c - self.windows[n] + 0x80) as _);
+		} else if let Some(x) = window_offset_byte(c) {
+			let n = self.define_window(window_offset(x));
+			self.out.extend_from_slice(&[SD0 + n as u8, x]);
+			self.out.push((c - self.windows[n] + 0x80) as _);
+		} else if c >= 0x1_0000 {
+			let n = self.define_window(0x1_0000 + ((c - 0x1_0000) & !0x7f));
+			let (hi, lo) = extended_window_bytes(n, self.windows[n]);
+			self.out.extend_from_slice(&[SDX, hi, lo]);
+			self.out.push((c - self.windows[n] + 0x80) as _);
+		} else {
+			self.out.push(SCU); // not windowable, switch to Unicode mode
+			self.unicode_mode = true;
+			self.push_unicode_mode(c);
+		}
+	}
+
+	fn push_unicode_mode(&mut self, c: u32) {
+		if c < 0x1_0000 && !is_windowable(c) {
+			let [hi, lo] = (c as u16).to_be_bytes();
+			if (UC0..=URS).contains(&hi) {
+				self.out.push(UQU); // high byte would be taken as a tag
+			}
+			self.out.extend_from_slice(&[hi, lo]);
+		} else if c >= 0x1_0000 && self.window_of(c).is_none() {
+			let mut buf = [0u16; 2];
+			for unit in char::from_u32(c).unwrap().encode_utf16(&mut buf) {
+				self.out.extend_from_slice(&unit.to_be_bytes()); // surrogates are D8-DF
+			}
+		} else {
+			let n = self.window_of(c).unwrap_or(self.active); // ASCII fits any window
+			self.out.push(UC0 + n as u8); // back to single-byte mode
+			self.active = n;
+			self.unicode_mode = false;
+			self.push_single_byte_mode(c);
+		}
+	}
+
+	/// Returns the dynamic window which contains the code point, if any,
+	/// preferring the active one.
+	fn window_of(&self, c: u32) -> Option<usize> {
+		let fits = |n: usize| c >= self.windows[n] && c < self.windows[n] + 0x80;
+		if fits(self.active) { Some(self.active) } else { (0..8).find(|n| fits(*n)) }
+	}
+
+	/// Redefines the least recently defined window, making it active.
+	fn define_window(&mut self, offset: u32) -> usize {
+		let n = self.next_to_define;
+		self.next_to_define = (n + 1) % 8;
+		self.windows[n] = offset;
+		self.active = n;
+		n
+	}
+}
+
+/// Chars which are written as they are in single-byte mode.
+#[must_use]
+const fn is_pass_through(c: u32) -> bool {
+	matches!(c, 0x00 | 0x09 | 0x0a | 0x0d | 0x20..=0x7f)
+}
+
+/// Whether the code point can be reached by a dynamic window.
+#[must_use]
+const fn is_windowable(c: u32) -> bool {
+	!matches!(c, 0x3400..=0xdfff) // CJK, Hangul and surrogates
+}
+
+/// Returns the byte which defines a window containing the BMP code point, if
+/// any.
+#[must_use]
+fn window_offset_byte(c: u32) -> Option<u8> {
+	match c {
+		0x3040..=0x309f => Some(0xfd), // Hiragana
+		0x30a0..=0x30ff => Some(0xfe), // Katakana
+		0xff60..=0xff9f => Some(0xff), // halfwidth Katakana
+		0x0080..=0x33ff => Some((c / 0x80) as _),
+		0xe000..=0xffff => Some(((c - 0xac00) / 0x80) as _),
+		_ => None,
+	}
+}
+
+/// Returns the window offset defined by the byte.
+#[must_use]
+const fn window_offset(x: u8) -> u32 {
+	match x {
+		0xfd => 0x3040,
+		0xfe => 0x30a0,
+		0xff => 0xff60,
+		0x01..=0x67 => x as u32 * 0x80,
+		_ => x as u32 * 0x80 + 0xac00,
+	}
+}
+
+/// Returns the 2 bytes which follow SDX and UDX tags.
+#[must_use]
+const fn extended_window_bytes(n: usize, offset: u32) -> (u8, u8) {
+	let v = (offset - 0x1_0000) / 0x80;
+	((((n as u32) << 5) | (v >> 8)) as u8, v as u8)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn utf16(s: &str) -> Vec<u16> {
+		s.encode_utf16().collect()
+	}
+
+	#[test]
+	fn uts6_samples() {
+		// Samples from section 9 of Unicode Technical Standard #6.
+		let german = [0xd6, 0x6c, 0x20, 0x66, 0x6c, 0x69, 0x65, 0xdf, 0x74];
+		assert_eq!(decode(&german).unwrap(), utf16("\u{d6}l flie\u{df}t"));
+		assert_eq!(encode(&"\u{d6}l flie\u{df}t".chars().collect::<Vec<_>>()), german);
+
+		let russian = [0x12, 0x9c, 0xbe, 0xc1, 0xba, 0xb2, 0xb0];
+		let moskva = "\u{41c}\u{43e}\u{441}\u{43a}\u{432}\u{430}";
+		assert_eq!(decode(&russian).unwrap(), utf16(moskva));
+		assert_eq!(encode(&moskva.chars().collect::<Vec<_>>()), russian);
+	}
+
+	#[test]
+	fn round_trip() {
+		for text in [
+			"",
+			"plain ASCII\r\n\ttext",
+			"caf\u{e9} \u{3b1}\u{3b2}\u{3b3} \u{5d0}\u{5d1}",
+			"\u{65e5}\u{672c}\u{8a9e}\u{306e}\u{30c6}\u{30ad}\u{30b9}\u{30c8}",
+			"\u{d55c}\u{ad6d}\u{c5b4} and \u{1f600}\u{1f680} \u{10348}",
+			"\u{e000}\u{f0ff} \u{1}\u{7f}",
+		] {
+			let enc = encode(&text.chars().collect::<Vec<_>>());
+			assert_eq!(decode(&enc).unwrap(), utf16(text), "{:?}", text);
+		}
+	}
+
+	#[test]
+	fn malformed() {
+		assert!(decode(&[SQU, 0x30]).is_err()); // truncated quoted char
+		assert!(decode(&[SDX, 0x00]).is_err()); // truncated window definition
+	}
+}
@@ -0,0 +1,435 @@
+use std::collections::BTreeMap;
+
+use crate::co;
+use crate::decl::*;
+
+/// Number of strings in each `RT_STRING` block.
+const BLOCK_LEN: u16 = 16;
+
+/// Strings of a single language, as stored in
+/// [`RT::STRING`](crate::co::RT::STRING) resources.
+///
+/// Strings are grouped in blocks of 16 consecutive IDs, where block `n` holds
+/// the IDs `(n - 1) * 16` to `(n - 1) * 16 + 15`. Since an empty string marks
+/// an absent ID, empty strings are not stored.
+///
+/// This is a pure encoder and decoder: no system calls are made.
+///
+/// # Examples
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let mut table = w::StringTable::default();
+/// table.strings.insert(101, "Open file".to_owned());
+/// table.strings.insert(102, "Save file".to_owned());
+///
+/// for (block_id, data) in table.serialize_blocks()? {
+///     println!("Block {}: {} bytes", block_id, data.len());
+/// }
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct StringTable {
+	/// The strings, indexed by their IDs.
+	pub strings: BTreeMap<u16, String>,
+}
+
+impl StringTable {
+	/// Returns the ID of the block which holds the given string ID, which is
+	/// the resource name of the block.
+	#[must_use]
+	pub const fn block_id(string_id: u16) -> u16 {
+		(string_id / BLOCK_LEN) + 1
+	}
+
+	/// Returns the string with the given ID, if any.
+	#[must_use]
+	pub fn get(&self, string_id: u16) -> Option<&str> {
+		self.strings.get(&string_id).map(|s| s.as_str())
+	}
+
+	/// Decodes the raw data of a block, as returned by
+	/// [`HINSTANCE::LockResource`](crate::HINSTANCE::LockResource), and
+	/// inserts its strings into the table.
+	///
+	/// Malformed data, or a zero block ID, yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	pub fn parse_block(&mut self, block_id: u16, blob: &[u8]) -> SysResult<()> {
+		if block_id == 0 || block_id > Self::block_id(u16::MAX) {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let first_id = (block_id - 1) * BLOCK_LEN;
+
+		let mut off = 0;
+		let mut block = Vec::<(u16, String)>::with_capacity(BLOCK_LEN as _);
+		for idx in 0..BLOCK_LEN {
+			let len = blob
+				.get(off..off + 2)
+				.map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+				.ok_or(co::ERROR::INVALID_DATA)?;
+			let chars = blob
+				.get(off + 2..off + 2 + len * 2)
+				.ok_or(co::ERROR::INVALID_DATA)?;
+			off += 2 + len * 2;
+
+			if len > 0 {
+				let wchars = chars
+					.chunks_exact(2)
+					.map(|b| u16::from_le_bytes([b[0], b[1]]))
+					.collect::<Vec<_>>();
+				block.push((first_id + idx, String::from_utf16_lossy(&wchars)));
+			}
+		}
+
+		self.strings.extend(block); // only after the whole block is valid
+		Ok(())
+	}
+
+	/// Encodes the strings into blocks, returning the block IDs along with
+	/// their raw data, which can be written as
+	/// [`RT::STRING`](crate::co::RT::STRING) resources.
+	///
+	/// A string longer than 65535 UTF-16 code units yields
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER).
+	#[must_use]
+	pub fn serialize_blocks(&self) -> SysResult<Vec<(u16, Vec<u8>)>> {
+		let mut blocks = Vec::<(u16, Vec<u8>)>::new();
+		let mut block_ids = self
+			.strings
+			.iter()
+			.filter(|(_, s)| !s.is_empty())
+			.map(|(id, _)| Self::block_id(*id))
+			.collect::<Vec<_>>();
+		block_ids.dedup(); // the map is sorted, so are the blocks
+
+		for block_id in block_ids {
+			let first_id = (block_id - 1) * BLOCK_LEN;
+			let mut data = Vec::<u8>::new();
+			for idx in 0..BLOCK_LEN {
+				let wchars = self
+					.strings
+					.get(&(first_id + idx))
+					.map(|s| s.encode_utf16().collect::<Vec<_>>())
+					.unwrap_or_default();
+				let num_chars =
+					u16::try_from(wchars.len()).map_err(|_| co::ERROR::INVALID_PARAMETER)?;
+				data.extend_from_slice(&num_chars.to_le_bytes());
+				wchars
+					.iter()
+					.for_each(|ch| data.extend_from_slice(&ch.to_le_bytes()));
+			}
+			blocks.push((block_id, data));
+		}
+		Ok(blocks)
+	}
+}
+
+/// Localized strings of a module, one [`StringTable`](crate::StringTable)
+/// for each language.
+///
+/// Lookups fall back along a chain of related languages, see
+/// [`fallback_chain`](crate::StringCatalog::fallback_chain).
+///
+/// # Examples
+///
+/// Loading the strings of a module, and exporting them for translation:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let hexe = w::HINSTANCE::LoadLibrary("app.exe")?;
+/// let catalog = w::StringCatalog::load(&hexe)?;
+///
+/// let pt_br = w::LANGID::new(co::LANG::PORTUGUESE, co::SUBLANG::PORTUGUESE_BRAZILIAN);
+/// if let Some(s) = catalog.get(101, pt_br) {
+///     println!("{}", s);
+/// }
+///
+/// let fout = w::File::open("C:\\Temp\\strings.txt", w::FileAccess::OpenOrCreateRW)?;
+/// fout.erase_and_write(catalog.export_text().as_bytes())?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct StringCatalog {
+	/// The string tables, indexed by their languages.
+	pub tables: BTreeMap<LANGID, StringTable>,
+	/// Language used when no language of the chain has the string.
+	///
+	/// Defaults to `None`.
+	pub fallback: Option<LANGID>,
+}
+
+impl StringCatalog {
+	/// Loads all the [`RT::STRING`](crate::co::RT::STRING) blocks of the
+	/// module, in all languages.
+	///
+	/// A module without strings yields an empty catalog.
+	#[must_use]
+	pub fn load(hinst: &HINSTANCE) -> SysResult<Self> {
+		let mut block_ids = Vec::<u16>::new();
+		match hinst.EnumResourceNames(RtStr::Rt(co::RT::STRING), |name| {
+			if let IdStr::Id(block_id) = name {
+				block_ids.push(block_id);
+			}
+			true
+		}) {
+			Err(e) if e == co::ERROR::RESOURCE_TYPE_NOT_FOUND => return Ok(Self::default()),
+			res => res?,
+		}
+
+		let mut catalog = Self::default();
+		for block_id in block_ids {
+			let mut langs = Vec::<LANGID>::new();
+			hinst.EnumResourceLanguages(
+				RtStr::Rt(co::RT::STRING),
+				IdStr::Id(block_id),
+				|lang| {
+					langs.push(lang);
+					true
+				},
+			)?;
+
+			for lang in langs {
+				let hrsrc = hinst.FindResourceEx(
+					IdStr::Id(block_id),
+					RtStr::Rt(co::RT::STRING),
+					Some(lang),
+				)?;
+				let hmem = hinst.LoadResource(&hrsrc)?;
+				let blob = hinst.LockResource(&hrsrc, &hmem)?;
+				catalog
+					.tables
+					.entry(lang)
+					.or_default()
+					.parse_block(block_id, blob)?;
+			}
+		}
+		Ok(catalog)
+	}
+
+	/// Builds the catalog from the [`RT::STRING`](crate::co::RT::STRING)
+	/// entries of a [`ResFile`](crate::ResFile). Other entries are ignored.
+	#[must_use]
+	pub fn from_res_file(res: &ResFile) -> SysResult<Self> {
+		let mut catalog = Self::default();
+		for entry in res.entries.iter() {
+			match (&entry.res_type, &entry.name) {
+				(RtStr::Rt(rt), IdStr::Id(block_id)) if *rt == co::RT::STRING => {
+					catalog
+						.tables
+						.entry(entry.lang)
+						.or_default()
+						.parse_block(*block_id, &entry.data)?;
+				},
+				_ => {},
+			}
+		}
+		Ok(catalog)
+	}
+
+	/// Returns the [`ResFile`](crate::ResFile) entries of all the blocks of
+	/// all languages.
+	///
+	/// A string longer than 65535 UTF-16 code units yields
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER).
+	#[must_use]
+	pub fn to_res_entries(&self) -> SysResult<Vec<ResEntry>> {
+		let mut entries = Vec::<ResEntry>::new();
+		for (lang, table) in self.tables.iter() {
+			for (block_id, data) in table.serialize_blocks()? {
+				entries.push(ResEntry::new(
+					RtStr::Rt(co::RT::STRING),
+					IdStr::Id(block_id),
+					*lang,
+					data,
+				));
+			}
+		}
+		Ok(entries)
+	}
+
+	/// Returns the languages which are searched for a string requested in the
+	/// given language, in order, keeping only those present in the catalog:
+	///
+	/// 1. the language itself;
+	/// 2. the same primary language with
+	///    [`SUBLANG::DEFAULT`](crate::co::SUBLANG::DEFAULT);
+	/// 3. the same primary language with
+	///    [`SUBLANG::NEUTRAL`](crate::co::SUBLANG::NEUTRAL);
+	/// 4. any other sublanguage of the same primary language;
+	/// 5. [`LANGID::NEUTRAL`](crate::LANGID::NEUTRAL);
+	/// 6. the [`fallback`](crate::StringCatalog::fallback) language.
+	#[must_use]
+	pub fn fallback_chain(&self, lang: LANGID) -> Vec<LANGID> {
+		let primary = lang.primary_lang_id();
+		let mut candidates = vec![
+			lang,
+			LANGID::new(primary, co::SUBLANG::DEFAULT),
+			LANGID::new(primary, co::SUBLANG::NEUTRAL),
+		];
+		candidates.extend(
+			self.tables
+				.keys()
+				.filter(|table_lang| table_lang.primary_lang_id() == primary),
+		);
+		candidates.push(LANGID::NEUTRAL);
+		candidates.extend(self.fallback);
+
+		let mut chain = Vec::<LANGID>::with_capacity(candidates.len());
+		for candidate in candidates {
+			if self.tables.contains_key(&candidate) && !chain.contains(&candidate) {
+				chain.push(candidate);
+			}
+		}
+		chain
+	}
+
+	/// Returns the string with the given ID in the given language, following
+	/// the [`fallback_chain`](crate::StringCatalog::fallback_chain).
+	#[must_use]
+	pub fn get(&self, string_id: u16, lang: LANGID) -> Option<&str> {
+		self.fallback_chain(lang)
+			.iter()
+			.find_map(|lang| self.tables[lang].get(string_id))
+	}
+
+	/// Exports the strings into a plain text format meant for translators.
+	///
+	/// Each language starts with a `[0xLLLL]` line with the `LANGID` in
+	/// hexadecimal, followed by one `id=text` line per string. In the text,
+	/// backslashes, line breaks and tabs are escaped as `\\`, `\n`, `\r` and
+	/// `\t`. Blank lines and lines starting with `#` are ignored by
+	/// [`import_text`](crate::StringCatalog::import_text).
+	#[must_use]
+	pub fn export_text(&self) -> String {
+		let mut text = String::new();
+		for (lang, table) in self.tables.iter() {
+			if !text.is_empty() {
+				text.push('\n');
+			}
+			text.push_str(&format!("[{:#06x}]\n", u16::from(*lang)));
+			for (id, s) in table.strings.iter() {
+				text.push_str(&format!("{}={}\n", id, escape(s)));
+			}
+		}
+		text
+	}
+
+	/// Imports the strings from the text format written by
+	/// [`export_text`](crate::StringCatalog::export_text).
+	///
+	/// Malformed text, including strings before the first language line,
+	/// yields [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn import_text(text: &str) -> SysResult<Self> {
+		let mut catalog = Self::default();
+		let mut cur_lang: Option<LANGID> = None;
+
+		for line in text.lines() {
+			let trimmed = line.trim_start();
+			if trimmed.is_empty() || trimmed.starts_with('#') {
+				continue;
+			}
+
+			if let Some(lang) = trimmed
+				.trim_end()
+				.strip_prefix('[')
+				.and_then(|s| s.strip_suffix(']'))
+			{
+				let lang = parse_u16(lang.trim()).ok_or(co::ERROR::INVALID_DATA)?;
+				let lang = unsafe { LANGID::from_raw(lang) };
+				catalog.tables.entry(lang).or_default();
+				cur_lang = Some(lang);
+			} else {
+				let lang = cur_lang.ok_or(co::ERROR::INVALID_DATA)?;
+				let (id, s) = trimmed.split_once('=').ok_or(co::ERROR::INVALID_DATA)?;
+				let id = parse_u16(id.trim()).ok_or(co::ERROR::INVALID_DATA)?;
+				catalog
+					.tables
+					.entry(lang)
+					.or_default()
+					.strings
+					.insert(id, unescape(s)?);
+			}
+		}
+		Ok(catalog)
+	}
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal number.
+#[must_use]
+fn parse_u16(s: &str) -> Option<u16> {
+	match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+		Some(hex) => u16::from_str_radix(hex, 16).ok(),
+		None => s.parse().ok(),
+	}
+}
+
+#[must_use]
+fn escape(s: &str) -> String {
+	let mut escaped = String::with_capacity(s.len());
+	for ch in s.chars() {
+		match ch {
+			'\\' => escaped.push_str("\\\\"),
+			'\n' => escaped.push_str("\\n"),
+			'\r' => escaped.push_str("\\r"),
+			'\t' => escaped.push_str("\\t"),
+			ch => escaped.push(ch),
+		}
+	}
+	escaped
+}
+
+fn unescape(s: &str) -> SysResult<String> {
+	let mut unescaped = String::with_capacity(s.len());
+	let mut chars = s.chars();
+	while let Some(ch) = chars.next() {
+		if ch == '\\' {
+			unescaped.push(match chars.next() {
+				Some('\\') => '\\',
+				Some('n') => '\n',
+				Some('r') => '\r',
+				Some('t') => '\t',
+				_ => return Err(co::ERROR::INVALID_DATA),
+			});
+		} else {
+			unescaped.push(ch);
+		}
+	}
+	Ok(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trip() {
+		let mut table = StringTable::default();
+		table.strings.insert(1, "One".to_owned());
+		table.strings.insert(17, "Seventeen".to_owned());
+		table.strings.insert(18, String::new()); // not stored
+
+		let blocks = table.serialize_blocks().unwrap();
+		assert_eq!(blocks.iter().map(|(id, _)| *id).collect::<Vec<_>>(), [1, 2]);
+		assert_eq!(&blocks[0].1[..10], [0, 0, 3, 0, b'O', 0, b'n', 0, b'e', 0]);
+
+		let mut parsed = StringTable::default();
+		for (block_id, data) in blocks.iter() {
+			parsed.parse_block(*block_id, data).unwrap();
+		}
+		table.strings.remove(&18);
+		assert!(parsed == table);
+	}
+
+	#[test]
+	fn too_long() {
+		let mut table = StringTable::default();
+		table.strings.insert(5, "a".repeat(0xffff));
+		assert!(table.serialize_blocks().is_ok());
+
+		table.strings.insert(5, "a".repeat(0x1_0000));
+		assert_eq!(table.serialize_blocks().err(), Some(co::ERROR::INVALID_PARAMETER));
+	}
+}
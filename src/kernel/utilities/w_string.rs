@@ -3,6 +3,7 @@ use std::cmp::Ordering;
 use crate::co;
 use crate::decl::*;
 use crate::kernel::ffi;
+use crate::kernel::utilities::{bocu1, scsu};
 
 /// Stores a `[u16]` buffer for a null-terminated
 /// [Unicode UTF-16](https://learn.microsoft.com/en-us/windows/win32/intl/unicode-in-the-windows-api)
@@ -232,6 +233,55 @@ impl WString {
 		self.buf.is_allocated()
 	}
 
+	/// Serializes the string into raw bytes of the given encoding by calling
+	/// [`Encoding::encode`](crate::Encoding::encode), optionally prepending the
+	/// [BOM](https://en.wikipedia.org/wiki/Byte_order_mark). The terminating
+	/// null, and anything after it, is not serialized.
+	///
+	/// This is the inverse of [`WString::parse`](crate::WString::parse).
+	///
+	/// # Examples
+	///
+	/// Saving a file back in the same encoding it was loaded with:
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let (encoding, has_bom, text) = {
+	///     let file_in = w::FileMapped::open(
+	///         "C:\\Temp\\foo.txt",
+	///         w::FileAccess::ExistingReadOnly,
+	///     )?;
+	///     let (encoding, sz_bom) = w::Encoding::guess(file_in.as_slice());
+	///     let text = w::WString::parse(file_in.as_slice())?.to_string();
+	///     (encoding, sz_bom > 0, text)
+	/// }; // file is unmapped here
+	///
+	/// let new_text = w::WString::from_str(text.replace("foo", "bar"));
+	/// let raw_bytes = new_text.encode(encoding, has_bom, w::EncodeFallback::Error)?;
+	///
+	/// let file_out = w::File::open(
+	///     "C:\\Temp\\foo.txt",
+	///     w::FileAccess::ExistingRW,
+	/// )?;
+	/// file_out.erase_and_write(&raw_bytes)?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn encode(
+		&self,
+		encoding: Encoding,
+		with_bom: bool,
+		fallback: EncodeFallback,
+	) -> SysResult<Vec<u8>> {
+		let str_len = self
+			.as_slice()
+			.iter()
+			.take_while(|ch| **ch != 0x0000)
+			.count();
+		encoding.encode(&self.as_slice()[..str_len], with_bom, fallback)
+	}
+
 	/// Converts into [`String`](std::string::String) by calling
 	/// [`String::from_utf16`](std::string::String::from_utf16). An uncallocated
 	/// will simply be converted into an empty string.
@@ -273,11 +323,17 @@ impl WString {
 	/// Guesses the encoding with [`Encoding::guess`](crate::Encoding::guess)
 	/// and parses the data as a string.
 	///
+	/// Except for UTF-8, which is converted with
+	/// [`MultiByteToWideChar`](crate::MultiByteToWideChar), all encodings are
+	/// decoded in pure Rust; ANSI and Windows-1252 with the built-in table of
+	/// [`co::CP::decode`](crate::co::CP::decode). Malformed data yields
+	/// [`co::ERROR::NO_UNICODE_TRANSLATION`](crate::co::ERROR::NO_UNICODE_TRANSLATION).
+	///
 	/// If you're sure the data has UTF-8 encoding, you can also use the
 	/// built-in [`String::from_utf8`](std::string::String::from_utf8).
 	///
-	/// To serialize the string back into UTF-8 bytes, use the built-in
-	/// [`String::into_bytes`](std::string::String::into_bytes).
+	/// To serialize the string back into raw bytes, use
+	/// [`WString::encode`](crate::WString::encode).
 	///
 	/// # Examples
 	///
@@ -303,27 +359,27 @@ impl WString {
 		}
 
 		let (encoding, sz_bom) = Encoding::guess(data);
-		data = &data[sz_bom..]; // skip BOM, if any
+		if encoding != Encoding::Bocu1 {
+			data = &data[sz_bom..]; // skip BOM, if any; BOCU-1 needs it to set its state
+		}
 
 		Ok(Self::from_wchars_slice(&match encoding {
-			Encoding::Ansi => Self::parse_ansi(data),
-			Encoding::Win1252 => MultiByteToWideChar(co::CP::WINDOWS_1252, co::MBC::NoValue, data)?,
+			Encoding::Ansi => Self::parse_ansi(data)?,
+			Encoding::Win1252 => co::CP::WINDOWS_1252.decode(data)?,
 			Encoding::Utf8 => MultiByteToWideChar(co::CP::UTF8, co::MBC::NoValue, data)?,
 			Encoding::Utf16be => Self::parse_utf16(data, true),
 			Encoding::Utf16le => Self::parse_utf16(data, false),
-			Encoding::Utf32be
-			| Encoding::Utf32le
-			| Encoding::Scsu
-			| Encoding::Bocu1
-			| Encoding::Unknown => panic!("Encoding {} not implemented.", encoding),
+			Encoding::Utf32be => Self::parse_utf32(data, true)?,
+			Encoding::Utf32le => Self::parse_utf32(data, false)?,
+			Encoding::Scsu => scsu::decode(data)?,
+			Encoding::Bocu1 => bocu1::decode(data)?,
+			Encoding::Unknown => return Err(co::ERROR::NO_UNICODE_TRANSLATION),
 		}))
 	}
 
-	fn parse_ansi(data: &[u8]) -> Vec<u16> {
-		data.iter()
-			.take_while(|ch| **ch != 0x0000) // ignore terminating null, if any
-			.map(|ch| *ch as u16) // raw u8 to u16 conversion
-			.collect()
+	fn parse_ansi(data: &[u8]) -> SysResult<Vec<u16>> {
+		let str_len = data.iter().take_while(|ch| **ch != 0x00).count(); // ignore terminating null, if any
+		co::CP::WINDOWS_1252.decode(&data[..str_len]) // ASCII is a subset of Windows-1252
 	}
 
 	fn parse_utf16(data: &[u8], is_big_endian: bool) -> Vec<u16> {
@@ -344,6 +400,28 @@ impl WString {
 			})
 			.collect()
 	}
+
+	fn parse_utf32(data: &[u8], is_big_endian: bool) -> SysResult<Vec<u16>> {
+		if !data.len().is_multiple_of(4) {
+			return Err(co::ERROR::NO_UNICODE_TRANSLATION); // truncated last char
+		}
+
+		let mut buf = Vec::with_capacity(data.len() / 2);
+		for ch4 in data.chunks(4) {
+			let code_point = if is_big_endian {
+				u32::from_be_bytes(ch4.try_into().unwrap())
+			} else {
+				u32::from_le_bytes(ch4.try_into().unwrap())
+			};
+			if code_point == 0x0000 {
+				break; // ignore terminating null, if any
+			}
+			let ch = char::from_u32(code_point).ok_or(co::ERROR::NO_UNICODE_TRANSLATION)?;
+			let mut ch16 = [0u16; 2];
+			buf.extend_from_slice(ch.encode_utf16(&mut ch16));
+		}
+		Ok(buf)
+	}
 }
 
 #[derive(PartialEq, Eq)]
@@ -431,10 +509,10 @@ impl Buffer {
 			return Self::Unallocated; // no elements yield an empty buffer
 		}
 
-		let tot_chars = v.iter() // number of chars of all strings, including terminating nulls
-			.fold(0, |tot, s| tot + s.as_ref().chars().count() + 1) // include terminating null
+		let tot_units = v.iter() // number of UTF-16 units of all strings, including terminating nulls
+			.fold(0, |tot, s| tot + s.as_ref().encode_utf16().count() + 1) // include terminating null
 			+ 1; // double terminating null
-		let mut new_self = Self::new_alloc_buf(tot_chars, ForceHeap::No);
+		let mut new_self = Self::new_alloc_buf(tot_units, ForceHeap::No);
 		v.iter()
 			.map(|s| {
 				s.as_ref().encode_utf16().chain(std::iter::once(0x0000)) // append terminating null on each string
@@ -0,0 +1,452 @@
+//! Minimal XML reader and writer, used by the typed document models, like
+//! application manifests and task definitions.
+//!
+//! Only elements, attributes and text are kept. Namespace prefixes are
+//! stripped, so elements and attributes are matched by their local names.
+
+use crate::co;
+use crate::decl::*;
+
+/// Limit of element nesting accepted by the parser.
+const MAX_DEPTH: usize = 256;
+
+/// An XML element, with its attributes, child elements and text.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub(crate) struct XmlElem {
+	pub(crate) name: String,
+	pub(crate) attrs: Vec<(String, String)>,
+	pub(crate) children: Vec<XmlElem>,
+	/// Concatenated text content, with surrounding whitespace trimmed.
+	pub(crate) text: String,
+}
+
+impl XmlElem {
+	/// Creates an empty element.
+	#[must_use]
+	pub(crate) fn new(name: &str) -> Self {
+		Self {
+			name: name.to_owned(),
+			..Default::default()
+		}
+	}
+
+	/// Creates an element with the given text.
+	#[must_use]
+	pub(crate) fn with_text(name: &str, text: &str) -> Self {
+		Self {
+			name: name.to_owned(),
+			text: text.to_owned(),
+			..Default::default()
+		}
+	}
+
+	/// Appends an attribute, returning the element.
+	#[must_use]
+	pub(crate) fn attr(mut self, name: &str, val: &str) -> Self {
+		self.attrs.push((name.to_owned(), val.to_owned()));
+		self
+	}
+
+	/// Appends a child element, returning the element.
+	#[must_use]
+	pub(crate) fn child(mut self, child: XmlElem) -> Self {
+		self.children.push(child);
+		self
+	}
+
+	/// Returns the value of the attribute, if present.
+	#[must_use]
+	pub(crate) fn get_attr(&self, name: &str) -> Option<&str> {
+		self.attrs
+			.iter()
+			.find(|(n, _)| n == name)
+			.map(|(_, v)| v.as_str())
+	}
+
+	/// Returns the first child element with the given name.
+	#[must_use]
+	pub(crate) fn find(&self, name: &str) -> Option<&XmlElem> {
+		self.children.iter().find(|c| c.name == name)
+	}
+
+	/// Returns all child elements with the given name.
+	pub(crate) fn find_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElem> + 'a {
+		self.children.iter().filter(move |c| c.name == name)
+	}
+
+	/// Follows a path of child element names, returning the first match.
+	#[must_use]
+	pub(crate) fn find_path(&self, path: &[&str]) -> Option<&XmlElem> {
+		path.iter().try_fold(self, |elem, name| elem.find(name))
+	}
+
+	/// Returns the text of the first child element with the given name.
+	#[must_use]
+	pub(crate) fn child_text(&self, name: &str) -> Option<&str> {
+		self.find(name).map(|c| c.text.as_str())
+	}
+
+	/// Decodes the raw bytes of a document – UTF-8, or UTF-16 with BOM – and
+	/// parses it, returning the root element.
+	///
+	/// Malformed documents yield
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub(crate) fn parse_bytes(data: &[u8]) -> SysResult<Self> {
+		let text = if let Some(utf16) = data.strip_prefix(&[0xff, 0xfe]) {
+			let wchars = utf16
+				.chunks_exact(2)
+				.map(|ch| u16::from_le_bytes([ch[0], ch[1]]))
+				.collect::<Vec<_>>();
+			String::from_utf16(&wchars).map_err(|_| co::ERROR::INVALID_DATA)?
+		} else if let Some(utf16) = data.strip_prefix(&[0xfe, 0xff]) {
+			let wchars = utf16
+				.chunks_exact(2)
+				.map(|ch| u16::from_be_bytes([ch[0], ch[1]]))
+				.collect::<Vec<_>>();
+			String::from_utf16(&wchars).map_err(|_| co::ERROR::INVALID_DATA)?
+		} else {
+			String::from_utf8(data.to_vec()).map_err(|_| co::ERROR::INVALID_DATA)?
+		};
+		Self::parse(&text)
+	}
+
+	/// Parses a document, returning the root element.
+	///
+	/// Malformed documents yield
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub(crate) fn parse(text: &str) -> SysResult<Self> {
+		let mut rd = Reader {
+			src: text.trim_start_matches('\u{feff}'),
+			pos: 0,
+		};
+		rd.skip_misc()?;
+		let root = rd.element(0)?;
+		rd.skip_misc()?;
+		if rd.pos != rd.src.len() {
+			return Err(co::ERROR::INVALID_DATA); // content after root
+		}
+		Ok(root)
+	}
+
+	/// Renders the document as indented XML, with a declaration of the given
+	/// encoding. The returned string must be encoded accordingly by the caller.
+	#[must_use]
+	pub(crate) fn render(&self, encoding: &str, standalone: bool) -> String {
+		let mut out = format!("<?xml version=\"1.0\" encoding=\"{}\"", encoding);
+		if standalone {
+			out.push_str(" standalone=\"yes\"");
+		}
+		out.push_str("?>\r\n");
+		self.render_elem(&mut out, 0);
+		out
+	}
+
+	fn render_elem(&self, out: &mut String, depth: usize) {
+		(0..depth).for_each(|_| out.push_str("  "));
+		out.push('<');
+		out.push_str(&self.name);
+		for (name, val) in self.attrs.iter() {
+			out.push(' ');
+			out.push_str(name);
+			out.push_str("=\"");
+			escape(out, val, true);
+			out.push('"');
+		}
+
+		if self.children.is_empty() && self.text.is_empty() {
+			out.push_str("/>\r\n");
+		} else if self.children.is_empty() {
+			out.push('>');
+			escape(out, &self.text, false);
+			out.push_str("</");
+			out.push_str(&self.name);
+			out.push_str(">\r\n");
+		} else {
+			out.push_str(">\r\n");
+			self.children
+				.iter()
+				.for_each(|child| child.render_elem(out, depth + 1));
+			(0..depth).for_each(|_| out.push_str("  "));
+			out.push_str("</");
+			out.push_str(&self.name);
+			out.push_str(">\r\n");
+		}
+	}
+}
+
+/// Appends the text to the buffer, escaping markup characters.
+fn escape(out: &mut String, text: &str, is_attr: bool) {
+	for ch in text.chars() {
+		match ch {
+			'<' => out.push_str("&lt;"),
+			'>' => out.push_str("&gt;"),
+			'&' => out.push_str("&amp;"),
+			'"' if is_attr => out.push_str("&quot;"),
+			ch => out.push(ch),
+		}
+	}
+}
+
+/// Returns the name without its namespace prefix.
+fn local_name(name: &str) -> &str {
+	name.rsplit(':').next().unwrap_or(name)
+}
+
+struct Reader<'a> {
+	src: &'a str,
+	pos: usize,
+}
+
+impl<'a> Reader<'a> {
+	fn rest(&self) -> &'a str {
+		&self.src[self.pos..]
+	}
+
+	fn skip_ws(&mut self) {
+		let rest = self.rest();
+		self.pos += rest.len() - rest.trim_start().len();
+	}
+
+	/// Skips everything up to and including the terminator.
+	fn skip_past(&mut self, terminator: &str) -> SysResult<()> {
+		let idx = self
+			.rest()
+			.find(terminator)
+			.ok_or(co::ERROR::INVALID_DATA)?;
+		self.pos += idx + terminator.len();
+		Ok(())
+	}
+
+	/// Skips whitespace, comments, processing instructions and declarations.
+	fn skip_misc(&mut self) -> SysResult<()> {
+		loop {
+			self.skip_ws();
+			let rest = self.rest();
+			if rest.starts_with("<?") {
+				self.skip_past("?>")?;
+			} else if rest.starts_with("<!--") {
+				self.skip_past("-->")?;
+			} else if rest.starts_with("<!") && !rest.starts_with("<![CDATA[") {
+				self.skip_past(">")?;
+			} else {
+				return Ok(());
+			}
+		}
+	}
+
+	fn name(&mut self) -> SysResult<&'a str> {
+		let rest = self.rest();
+		let len = rest
+			.find(|ch: char| ch.is_whitespace() || matches!(ch, '=' | '/' | '>' | '<'))
+			.unwrap_or(rest.len());
+		if len == 0 {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		self.pos += len;
+		Ok(&rest[..len])
+	}
+
+	/// Reads an element, whose `<` is at the current position.
+	fn element(&mut self, depth: usize) -> SysResult<XmlElem> {
+		if depth > MAX_DEPTH || !self.rest().starts_with('<') {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		self.pos += 1;
+		let raw_name = self.name()?;
+		let mut elem = XmlElem::new(local_name(raw_name));
+
+		loop {
+			self.skip_ws();
+			let rest = self.rest();
+			if rest.starts_with("/>") {
+				self.pos += 2;
+				return Ok(elem);
+			} else if rest.starts_with('>') {
+				self.pos += 1;
+				break;
+			}
+
+			let attr_name = self.name()?;
+			self.skip_ws();
+			if !self.rest().starts_with('=') {
+				return Err(co::ERROR::INVALID_DATA);
+			}
+			self.pos += 1;
+			self.skip_ws();
+			let quote = self.rest().chars().next().ok_or(co::ERROR::INVALID_DATA)?;
+			if quote != '"' && quote != '\'' {
+				return Err(co::ERROR::INVALID_DATA);
+			}
+			self.pos += 1;
+			let len = self.rest().find(quote).ok_or(co::ERROR::INVALID_DATA)?;
+			let val = unescape(&self.rest()[..len])?;
+			self.pos += len + 1;
+
+			if attr_name != "xmlns" && !attr_name.starts_with("xmlns:") {
+				elem.attrs.push((local_name(attr_name).to_owned(), val));
+			}
+		}
+
+		let mut text = String::new();
+		loop {
+			let rest = self.rest();
+			if rest.starts_with("</") {
+				self.pos += 2;
+				if self.name()? != raw_name {
+					return Err(co::ERROR::INVALID_DATA); // mismatched closing tag
+				}
+				self.skip_ws();
+				if !self.rest().starts_with('>') {
+					return Err(co::ERROR::INVALID_DATA);
+				}
+				self.pos += 1;
+				elem.text = text.trim().to_owned();
+				return Ok(elem);
+			} else if rest.starts_with("<![CDATA[") {
+				self.pos += 9;
+				let len = self.rest().find("]]>").ok_or(co::ERROR::INVALID_DATA)?;
+				text.push_str(&self.rest()[..len]);
+				self.pos += len + 3;
+			} else if rest.starts_with("<!--") {
+				self.skip_past("-->")?;
+			} else if rest.starts_with("<?") {
+				self.skip_past("?>")?;
+			} else if rest.starts_with('<') {
+				elem.children.push(self.element(depth + 1)?);
+			} else if rest.is_empty() {
+				return Err(co::ERROR::INVALID_DATA); // unclosed element
+			} else {
+				let len = rest.find('<').unwrap_or(rest.len());
+				text.push_str(&unescape(&rest[..len])?);
+				self.pos += len;
+			}
+		}
+	}
+}
+
+/// Replaces the entity and character references.
+fn unescape(raw: &str) -> SysResult<String> {
+	let mut out = String::with_capacity(raw.len());
+	let mut rest = raw;
+	while let Some(amp) = rest.find('&') {
+		out.push_str(&rest[..amp]);
+		rest = &rest[amp + 1..];
+		let semi = rest.find(';').ok_or(co::ERROR::INVALID_DATA)?;
+		let entity = &rest[..semi];
+		out.push(match entity {
+			"lt" => '<',
+			"gt" => '>',
+			"amp" => '&',
+			"quot" => '"',
+			"apos" => '\'',
+			_ => {
+				let code = if let Some(hex) = entity
+					.strip_prefix("#x")
+					.or_else(|| entity.strip_prefix("#X"))
+				{
+					u32::from_str_radix(hex, 16)
+				} else if let Some(dec) = entity.strip_prefix('#') {
+					dec.parse::<u32>()
+				} else {
+					return Err(co::ERROR::INVALID_DATA);
+				};
+				code.ok()
+					.and_then(char::from_u32)
+					.ok_or(co::ERROR::INVALID_DATA)?
+			},
+		});
+		rest = &rest[semi + 1..];
+	}
+	out.push_str(rest);
+	Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn entities() {
+		let root = XmlElem::parse("<a v='&quot;x&apos; &amp; &#65;'>&lt;b&gt; &#x263a;&#X42;</a>")
+			.unwrap();
+		assert_eq!(root.get_attr("v"), Some("\"x' & A"));
+		assert_eq!(root.text, "<b> \u{263a}B");
+
+		assert!(XmlElem::parse("<a>&nbsp;</a>").is_err());
+		assert!(XmlElem::parse("<a>&amp</a>").is_err());
+		assert!(XmlElem::parse("<a>&#xd800;</a>").is_err());
+	}
+
+	#[test]
+	fn cdata_and_comments() {
+		let root = XmlElem::parse(
+			"<?xml version='1.0'?>\r\n\
+			<!-- leading --><!DOCTYPE a>\r\n\
+			<a>one <!-- <b/> --><![CDATA[<two> & ]]>three<?pi x?></a>\r\n\
+			<!-- trailing -->",
+		)
+		.unwrap();
+		assert_eq!(root.name, "a");
+		assert!(root.children.is_empty());
+		assert_eq!(root.text, "one <two> & three");
+
+		assert!(XmlElem::parse("<a><![CDATA[x</a>").is_err());
+		assert!(XmlElem::parse("<a><!-- x</a>").is_err());
+	}
+
+	#[test]
+	fn namespaces() {
+		let root = XmlElem::parse(
+			"<asm:assembly xmlns:asm='urn:a' xmlns='urn:b' asm:version='1'>\
+			<asmv3:application xmlns:asmv3='urn:c'><x:item/></asmv3:application>\
+			</asm:assembly>",
+		)
+		.unwrap();
+		assert_eq!(root.name, "assembly");
+		assert_eq!(root.attrs, [("version".to_owned(), "1".to_owned())]);
+		assert!(root.find_path(&["application", "item"]).is_some());
+		assert!(root.find("application").unwrap().attrs.is_empty());
+	}
+
+	#[test]
+	fn malformed() {
+		for text in [
+			"",
+			"<a>",
+			"<a></b>",
+			"<a><b></a></b>",
+			"<x:a></y:a>",
+			"<a x></a>",
+			"<a x=1/>",
+			"<a x='1/>",
+			"<a/><b/>",
+			"text",
+		] {
+			assert!(XmlElem::parse(text).is_err(), "{}", text);
+		}
+
+		let deep = "<a>".repeat(MAX_DEPTH + 2) + &"</a>".repeat(MAX_DEPTH + 2);
+		assert!(XmlElem::parse(&deep).is_err());
+	}
+
+	#[test]
+	fn render_round_trip() {
+		let root = XmlElem::new("root")
+			.attr("a", "<\"&'>")
+			.child(XmlElem::with_text("text", "1 < 2 & 3 > \"2\""))
+			.child(XmlElem::new("empty"))
+			.child(XmlElem::new("nested").child(XmlElem::new("leaf").attr("x", "y")));
+		let xml = root.render("UTF-8", true);
+		assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>"));
+		assert!(XmlElem::parse(&xml).unwrap() == root);
+
+		let utf16 = [0xff, 0xfe]
+			.into_iter()
+			.chain(xml.encode_utf16().flat_map(|ch| ch.to_le_bytes()))
+			.collect::<Vec<_>>();
+		assert!(XmlElem::parse_bytes(&utf16).unwrap() == root);
+		assert!(XmlElem::parse_bytes(&[0xff, 0xfe, 0x00, 0xd8]).is_err());
+	}
+}
@@ -317,3 +317,13 @@ pub mod prelude {
 	#[cfg(feature = "taskschd")] pub use super::taskschd::traits::*;
 	#[cfg(all(feature = "gdi", feature = "mf"))] pub use super::gdi_mf::traits::*;
 }
+
+#[cfg(feature = "ole")]
+pub mod vt {
+	//! Virtual tables of the base COM interfaces, to be used when declaring
+	//! custom COM interfaces with
+	//! [`declare_com_interface!`](crate::declare_com_interface).
+
+	pub use super::ole::vts::IUnknownVT;
+	#[cfg(feature = "oleaut")] pub use super::oleaut::vts::IDispatchVT;
+}
@@ -88,31 +88,82 @@ macro_rules! const_impl_bitflag {
 	};
 }
 
-/// Implements Debug and Display for numeric newtype constants.
+/// Implements Debug and Display for numeric newtype constants, printing the
+/// symbolic names of the values, like `WM::CLOSE`. Bitflags are decomposed,
+/// like `WS::CAPTION | WS::SYSMENU | 0x4`.
+///
+/// Requires `const_impl_names!`.
 macro_rules! const_impl_debug_display {
-	( $name:ident ) => {
+	( $name:ident $kind:ident ) => {
 		impl std::fmt::Display for $name {
 			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-				if self.0 as usize > 0xffff {
-					write!(f, "{}({:#010x})", stringify!($name), self.0)
-				} else {
-					write!(f, "{}({:#06x})", stringify!($name), self.0)
-				}
+				crate::kernel::privs::fmt_const(
+					f,
+					stringify!($name),
+					Self::ALL_NAMES,
+					self.0 as _,
+					std::mem::size_of::<Self>() as _,
+					const_impl_names!(@bitflag $kind),
+				)
 			}
 		}
 		impl std::fmt::Debug for $name {
 			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+				std::fmt::Display::fmt(self, f)?;
 				if self.0 as usize > 0xffff {
-					write!(f, "{}({:#010x} {})", stringify!($name), self.0, self.0)
+					write!(f, " ({:#010x} {})", self.0, self.0)
 				} else {
-					write!(f, "{}({:#06x} {})", stringify!($name), self.0, self.0)
+					write!(f, " ({:#06x} {})", self.0, self.0)
 				}
 			}
 		}
 	};
 }
 
-/// Writes multiple pub values of a numeric newtype constant.
+/// Implements FromStr for numeric newtype constants, parsing the symbolic
+/// names written by `const_impl_debug_display!`.
+///
+/// The name tables are written by `const_values_pub!`; the default one is
+/// `NAMES`, other ones are needed when the values are split across features.
+macro_rules! const_impl_names {
+	(@bitflag ordinary) => { false };
+	(@bitflag bitflag) => { true };
+
+	( $name:ident $kind:ident ) => {
+		const_impl_names!($name $kind [NAMES]);
+	};
+
+	(
+		$name:ident $kind:ident [ $( $( #[$cfg:meta] )* $table:ident ),* ]
+	) => {
+		impl $name {
+			/// All the name tables of this constant type.
+			const ALL_NAMES: &'static crate::kernel::privs::ConstNames = &[
+				$( $( #[$cfg] )* Self::$table, )*
+			];
+		}
+
+		impl std::str::FromStr for $name {
+			type Err = crate::co::ERROR;
+
+			fn from_str(s: &str) -> Result<Self, Self::Err> {
+				crate::kernel::privs::parse_const(
+					stringify!($name),
+					Self::ALL_NAMES,
+					s,
+					std::mem::size_of::<Self>() as _,
+					const_impl_names!(@bitflag $kind),
+				)
+				.map(|v| unsafe { Self::from_raw(v as _) })
+				.ok_or(crate::co::ERROR::INVALID_DATA)
+			}
+		}
+	};
+}
+
+/// Writes multiple pub values of a numeric newtype constant, along with the
+/// table of their names, used by `const_impl_names!`. If the values are split
+/// across features, each declaration must have its own table name.
 macro_rules! const_values_pub {
 	(
 		$name:ident;
@@ -120,12 +171,33 @@ macro_rules! const_values_pub {
 			$( #[$valdoc:meta] )*
 			$valname:ident $val:expr
 		)*
+	) => {
+		const_values_pub! {
+			$name NAMES;
+			$(
+				$( #[$valdoc] )*
+				$valname $val
+			)*
+		}
+	};
+
+	(
+		$name:ident $table:ident;
+		$(
+			$( #[$valdoc:meta] )*
+			$valname:ident $val:expr
+		)*
 	) => {
 		impl $name {
 			$(
 				$( #[$valdoc] )*
 				pub const $valname: Self = unsafe { Self::from_raw($val) };
 			)*
+
+			/// Names of the values above.
+			pub(crate) const $table: &'static [(&'static str, u64)] = &[
+				$( (stringify!($valname), Self::$valname.raw() as _), )*
+			];
 		}
 	};
 }
@@ -161,7 +233,8 @@ macro_rules! const_ordinary {
 			$name: $ntype;
 			$( #[$doc] )*
 		}
-		const_impl_debug_display!($name);
+		const_impl_debug_display!($name ordinary);
+		const_impl_names!($name ordinary);
 		const_values_pub! {
 			$name;
 			$(
@@ -190,7 +263,8 @@ macro_rules! const_bitflag {
 			/// This is a bitflag constant.
 		}
 		const_impl_bitflag!($name);
-		const_impl_debug_display!($name);
+		const_impl_debug_display!($name bitflag);
+		const_impl_names!($name bitflag);
 		const_values_pub! {
 			$name;
 			$(
@@ -202,10 +276,10 @@ macro_rules! const_bitflag {
 }
 
 /// Complete declaration of a constant for a window message, convertible to the
-/// co::WM.
+/// co::WM. Name tables of values declared elsewhere can be given in brackets.
 macro_rules! const_wm {
 	(
-		$name:ident;
+		$name:ident $( [ $( $( #[$cfg:meta] )* $extra:ident ),* ] )?;
 		$( #[$doc:meta] )*
 		=>
 		$(
@@ -220,7 +294,8 @@ macro_rules! const_wm {
 			/// This is a window message, convertible to [`WM`](crate::co::WM).
 		}
 		const_impl_bitflag!($name);
-		const_impl_debug_display!($name);
+		const_impl_debug_display!($name ordinary);
+		const_impl_names!($name ordinary [NAMES $( $( , $( #[$cfg] )* $extra )* )?]);
 		const_values_pub! {
 			$name;
 			$(
@@ -256,7 +331,8 @@ macro_rules! const_cmd {
 			/// code, convertible to [`CMD`](crate::co::CMD).
 		}
 		const_impl_bitflag!($name);
-		const_impl_debug_display!($name);
+		const_impl_debug_display!($name ordinary);
+		const_impl_names!($name ordinary);
 		const_values_pub! {
 			$name;
 			$(
@@ -292,7 +368,8 @@ macro_rules! const_nm {
 			/// code, convertible to/from [`NmhdrCode`](crate::NmhdrCode).
 		}
 		const_impl_bitflag!($name);
-		const_impl_debug_display!($name);
+		const_impl_debug_display!($name ordinary);
+		const_impl_names!($name ordinary);
 		const_values_pub! {
 			$name;
 			$(
@@ -339,7 +416,8 @@ macro_rules! const_ws {
 			/// This is a window style, convertible to [`WS`](crate::co::WS).
 		}
 		const_impl_bitflag!($name);
-		const_impl_debug_display!($name);
+		const_impl_debug_display!($name bitflag);
+		const_impl_names!($name bitflag);
 		const_values_pub! {
 			$name;
 			$(
@@ -380,7 +458,8 @@ macro_rules! const_wsex {
 			/// [`WS_EX`](crate::co::WS_EX).
 		}
 		const_impl_bitflag!($name);
-		const_impl_debug_display!($name);
+		const_impl_debug_display!($name bitflag);
+		const_impl_names!($name bitflag);
 		const_values_pub! {
 			$name;
 			$(
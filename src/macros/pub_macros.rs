@@ -67,3 +67,175 @@ macro_rules! seq_ids {
 		seq_ids!($next_val + 1, $( $others )*);
 	};
 }
+
+/// Declares a custom COM interface, which is not provided by WinSafe.
+///
+/// The macro generates:
+///
+/// * the interface type, which releases the COM object when it goes out of
+///   scope, and implements [`ole_IUnknown`](crate::prelude::ole_IUnknown), so
+///   it can be used with
+///   [`QueryInterface`](crate::prelude::ole_IUnknown::QueryInterface) and
+///   [`CoCreateInstance`](crate::CoCreateInstance);
+/// * the virtual table struct, whose first field – `parent` – is the virtual
+///   table of the parent interface, like
+///   [`IUnknownVT`](crate::vt::IUnknownVT) or
+///   [`IDispatchVT`](crate::vt::IDispatchVT);
+/// * optionally, empty implementations of the given traits, which provide the
+///   methods of the parent interfaces, and the safe wrappers you write for
+///   the new interface.
+///
+/// The virtual table methods are called through
+/// [`ole_IUnknown::vt`](crate::prelude::ole_IUnknown::vt).
+///
+/// # Examples
+///
+/// Declaring
+/// [`IOleWindow`](https://learn.microsoft.com/en-us/windows/win32/api/oleidl/nn-oleidl-iolewindow):
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co, declare_com_interface};
+///
+/// declare_com_interface! {
+///     /// `IOleWindow` COM interface.
+///     pub IOleWindow: "00000114-0000-0000-c000-000000000046";
+///
+///     /// `IOleWindow` virtual table.
+///     pub IOleWindowVT: w::vt::IUnknownVT {
+///         GetWindow: extern "system" fn(*mut std::ffi::c_void, *mut w::HWND) -> co::HRESULT,
+///         ContextSensitiveHelp: extern "system" fn(*mut std::ffi::c_void, i32) -> co::HRESULT,
+///     }
+///
+///     impl my_IOleWindow;
+/// }
+///
+/// /// Safe wrappers for the `IOleWindow` methods.
+/// pub trait my_IOleWindow: ole_IUnknown {
+///     fn GetWindow(&self) -> w::HrResult<w::HWND> {
+///         let mut hwnd = w::HWND::NULL;
+///         unsafe { (self.vt::<IOleWindowVT>().GetWindow)(self.ptr(), &mut hwnd) }
+///             .to_hrresult()
+///             .map(|_| hwnd)
+///     }
+///
+///     fn ContextSensitiveHelp(&self, enter_mode: bool) -> w::HrResult<()> {
+///         unsafe {
+///             (self.vt::<IOleWindowVT>().ContextSensitiveHelp)(self.ptr(), enter_mode as _)
+///         }
+///         .to_hrresult()
+///     }
+/// }
+///
+/// let obj: w::IUnknown; // initialized somewhere
+/// # let obj = unsafe { w::IUnknown::null() };
+/// let ole_wnd = obj.QueryInterface::<IOleWindow>()?;
+/// let hwnd = ole_wnd.GetWindow()?;
+/// # w::HrResult::Ok(())
+/// ```
+///
+/// An interface derived from `IDispatch` must also implement
+/// [`oleaut_IDispatch`](crate::prelude::oleaut_IDispatch), and an interface
+/// derived from another custom interface must implement the traits of its
+/// parent:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co, declare_com_interface};
+///
+/// declare_com_interface! {
+///     pub IFoo: "a7cd1a4e-4b9c-4d3a-9f0c-1c3e2b7a5d10";
+///     pub IFooVT: w::vt::IDispatchVT {
+///         Foo: extern "system" fn(*mut std::ffi::c_void) -> co::HRESULT,
+///     }
+///     impl oleaut_IDispatch, my_IFoo;
+/// }
+///
+/// declare_com_interface! {
+///     pub IBar: "5f0e3d2c-1b8a-4e6f-a3d9-7c2b1e0f4a86";
+///     pub IBarVT: IFooVT {
+///         Bar: extern "system" fn(*mut std::ffi::c_void) -> co::HRESULT,
+///     }
+///     impl oleaut_IDispatch, my_IFoo, my_IBar;
+/// }
+///
+/// pub trait my_IFoo: oleaut_IDispatch {
+///     fn Foo(&self) -> w::HrResult<()> {
+///         unsafe { (self.vt::<IFooVT>().Foo)(self.ptr()) }.to_hrresult()
+///     }
+/// }
+///
+/// pub trait my_IBar: my_IFoo {
+///     fn Bar(&self) -> w::HrResult<()> {
+///         unsafe { (self.vt::<IBarVT>().Bar)(self.ptr()) }.to_hrresult()
+///     }
+/// }
+/// ```
+#[cfg(feature = "ole")]
+#[macro_export]
+macro_rules! declare_com_interface {
+	(
+		$( #[$doc:meta] )*
+		$vis:vis $name:ident : $guid:expr;
+
+		$( #[$vt_doc:meta] )*
+		$vt_vis:vis $vt:ident : $parent:ty {
+			$(
+				$( #[$field_doc:meta] )*
+				$field:ident : $field_ty:ty
+			),* $(,)?
+		}
+
+		$( impl $( $tr:path ),+ ; )?
+	) => {
+		$( #[$doc] )*
+		#[repr(transparent)]
+		$vis struct $name(*mut std::ffi::c_void);
+
+		unsafe impl Send for $name {}
+
+		impl Drop for $name {
+			fn drop(&mut self) {
+				if !self.0.is_null() {
+					use $crate::prelude::ole_IUnknown;
+					unsafe { (self.vt::<$crate::vt::IUnknownVT>().Release)(self.0); }
+				}
+			}
+		}
+
+		impl Clone for $name {
+			fn clone(&self) -> Self {
+				use $crate::prelude::ole_IUnknown;
+				unsafe { (self.vt::<$crate::vt::IUnknownVT>().AddRef)(self.0); }
+				Self(self.0)
+			}
+		}
+
+		impl $crate::prelude::ole_IUnknown for $name {
+			const IID: $crate::co::IID = unsafe { $crate::co::IID::from_raw($guid) };
+
+			unsafe fn from_ptr(p: *mut std::ffi::c_void) -> Self {
+				Self(p)
+			}
+
+			unsafe fn as_mut(&mut self) -> &mut *mut std::ffi::c_void {
+				&mut self.0
+			}
+
+			fn ptr(&self) -> *mut std::ffi::c_void {
+				self.0
+			}
+		}
+
+		$( #[$vt_doc] )*
+		#[repr(C)]
+		#[allow(non_snake_case)]
+		$vt_vis struct $vt {
+			pub parent: $parent,
+			$(
+				$( #[$field_doc] )*
+				pub $field: $field_ty,
+			)*
+		}
+
+		$( $( impl $tr for $name {} )+ )?
+	};
+}
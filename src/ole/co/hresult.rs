@@ -1,6 +1,7 @@
 #![allow(non_snake_case)]
 
 use crate::co;
+use crate::decl::*;
 use crate::prelude::*;
 
 const_basic_decl! { HRESULT: u32;
@@ -29,11 +30,15 @@ const_basic_decl! { HRESULT: u32;
 	/// Will print:
 	///
 	/// ```text
-	/// [0x80070057 2147942487] The parameter is incorrect.
+	/// HRESULT::E_INVALIDARG [0x80070057] The parameter is incorrect.
 	/// ```
 	///
-	/// The numbers inside the brackets are the system error code, in
-	/// hexadecimal and decimal formats for convenience.
+	/// The number inside the brackets is the system error code. The
+	/// [`Debug`](std::fmt::Debug) output shows it in hexadecimal and decimal
+	/// formats for convenience.
+	///
+	/// The symbolic name can be parsed back with
+	/// [`FromStr`](std::str::FromStr).
 	///
 	/// An [`ERROR`](crate::co::ERROR) can be seamlessly converted into an
 	/// `HRESULT`. This operation is equivalent to the
@@ -57,6 +62,9 @@ impl std::error::Error for HRESULT {
 
 impl std::fmt::Display for HRESULT {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if let Some(name) = crate::kernel::privs::const_name(Self::ALL_NAMES, self.0 as _) {
+			write!(f, "HRESULT::{} ", name)?;
+		}
 		if self.0 > 0xffff {
 			write!(f, "[{:#010x}] {}", self.0, self.FormatMessage().trim_end())
 		} else {
@@ -66,14 +74,26 @@ impl std::fmt::Display for HRESULT {
 }
 impl std::fmt::Debug for HRESULT {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match crate::kernel::privs::const_name(Self::ALL_NAMES, self.0 as _) {
+			Some(name) => write!(f, "HRESULT::{} ", name)?,
+			None => write!(f, "HRESULT")?,
+		}
 		if self.0 > 0xffff {
-			write!(f, "HRESULT({:#010x} {}) {}", self.0, self.0, self.FormatMessage().trim_end())
+			write!(f, "({:#010x} {}) {}", self.0, self.0, self.FormatMessage().trim_end())
 		} else {
-			write!(f, "HRESULT({:#06x} {}) {}", self.0, self.0, self.FormatMessage().trim_end())
+			write!(f, "({:#06x} {}) {}", self.0, self.0, self.FormatMessage().trim_end())
 		}
 	}
 }
 
+const_impl_names!(HRESULT ordinary [
+	NAMES,
+	#[cfg(feature = "dshow")] NAMES_DSHOW,
+	#[cfg(feature = "dxgi")] NAMES_DXGI,
+	#[cfg(feature = "mf")] NAMES_MF,
+	#[cfg(feature = "oleaut")] NAMES_OLEAUT
+]);
+
 impl SystemError for HRESULT {}
 
 impl co::ERROR {
@@ -128,6 +148,28 @@ impl HRESULT {
 	pub const fn SUCCEEDED(self) -> bool {
 		(self.0 as i32) >= 0
 	}
+
+	/// Returns `Ok(())` if the value is
+	/// [`HRESULT::S_OK`](crate::co::HRESULT::S_OK), otherwise `Err(self)`.
+	///
+	/// This is useful when calling the methods of a virtual table directly, like
+	/// when declaring a custom COM interface with
+	/// [`declare_com_interface!`](crate::declare_com_interface).
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// assert_eq!(co::HRESULT::S_OK.to_hrresult(), Ok(()));
+	/// assert_eq!(co::HRESULT::E_FAIL.to_hrresult(), Err(co::HRESULT::E_FAIL));
+	/// ```
+	pub const fn to_hrresult(self) -> HrResult<()> {
+		match self {
+			co::HRESULT::S_OK => Ok(()),
+			hr => Err(hr),
+		}
+	}
 }
 
 // https://learn.microsoft.com/en-us/windows/win32/com/com-error-codes-1
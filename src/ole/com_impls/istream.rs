@@ -0,0 +1,644 @@
+#![allow(non_camel_case_types, non_snake_case)]
+
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::mem::ManuallyDrop;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::co;
+use crate::decl::*;
+use crate::kernel::ffi_types::*;
+use crate::ole::{privs::*, vts::*};
+use crate::prelude::*;
+
+impl IStream {
+	/// Creates a custom [`IStream`](crate::IStream) implementation which
+	/// stores its data in a `Vec`, starting with the given contents.
+	///
+	/// Unlike [`SHCreateMemStream`](crate::SHCreateMemStream), the whole
+	/// stream is implemented in Rust.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let stream = w::IStream::from_vec(vec![1, 2, 3]);
+	/// stream.Seek(0, co::STREAM_SEEK::END)?;
+	/// stream.Write(&[4, 5])?;
+	/// # w::HrResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn from_vec(data: Vec<u8>) -> IStream {
+		IStreamImpl::new_stream(VecBackend(data))
+	}
+
+	/// Creates a custom [`IStream`](crate::IStream) implementation which
+	/// reads and writes the given [`File`](crate::File), starting at the
+	/// beginning of the file.
+	///
+	/// The file is closed when the last reference to the stream is released.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let f = w::File::open("C:\\Temp\\foo.bin", w::FileAccess::ExistingRW)?;
+	/// let stream = w::IStream::from_file(f);
+	/// # w::AnyResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn from_file(file: File) -> IStream {
+		IStreamImpl::new_stream(FileBackend(file))
+	}
+
+	/// Creates a custom, read-only [`IStream`](crate::IStream) implementation
+	/// over the contents of the given [`FileMapped`](crate::FileMapped).
+	///
+	/// Writing to the stream fails with
+	/// [`co::HRESULT::STG_E_ACCESSDENIED`](crate::co::HRESULT::STG_E_ACCESSDENIED).
+	#[must_use]
+	pub fn from_file_mapped(file: FileMapped) -> IStream {
+		IStreamImpl::new_stream(FileMappedBackend(file))
+	}
+
+	/// Creates a custom [`IStream`](crate::IStream) implementation on top of
+	/// any object which implements [`Read`](std::io::Read),
+	/// [`Write`](std::io::Write) and [`Seek`](std::io::Seek).
+	///
+	/// Since there's no generic way to truncate such an object,
+	/// [`IStream::SetSize`](crate::prelude::ole_IStream::SetSize) can only
+	/// grow it, by writing zeros at the end.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let f = std::fs::File::options()
+	///     .read(true)
+	///     .write(true)
+	///     .open("C:\\Temp\\foo.bin")?;
+	/// let stream = w::IStream::from_rw(f);
+	/// # w::AnyResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn from_rw<T>(rw: T) -> IStream
+	where
+		T: Read + Write + Seek + Send + 'static,
+	{
+		IStreamImpl::new_stream(RwBackend(rw))
+	}
+}
+
+/// Storage behind a custom `IStream` implementation. Each stream object keeps
+/// its own seek pointer, so all operations take an absolute offset.
+trait StreamBackend: Send {
+	fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> HrResult<usize>;
+	fn write_at(&mut self, offset: u64, data: &[u8]) -> HrResult<usize>;
+	fn size(&mut self) -> HrResult<u64>;
+	fn set_size(&mut self, new_size: u64) -> HrResult<()>;
+
+	fn flush(&mut self) -> HrResult<()> {
+		Ok(())
+	}
+
+	fn mode(&self) -> co::STGM {
+		co::STGM::READWRITE
+	}
+
+	/// Creation, last access and last write times.
+	fn times(&self) -> Option<(FILETIME, FILETIME, FILETIME)> {
+		None
+	}
+}
+
+/// Copies from the slice at the given offset, returning the number of bytes
+/// copied, which is zero past the end.
+fn read_slice(src: &[u8], offset: u64, buf: &mut [u8]) -> usize {
+	let src = usize::try_from(offset)
+		.ok()
+		.and_then(|off| src.get(off..))
+		.unwrap_or_default();
+	let n = src.len().min(buf.len());
+	buf[..n].copy_from_slice(&src[..n]);
+	n
+}
+
+struct VecBackend(Vec<u8>);
+
+impl StreamBackend for VecBackend {
+	fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> HrResult<usize> {
+		Ok(read_slice(&self.0, offset, buf))
+	}
+
+	fn write_at(&mut self, offset: u64, data: &[u8]) -> HrResult<usize> {
+		let end = offset
+			.checked_add(data.len() as _)
+			.and_then(|end| usize::try_from(end).ok())
+			.ok_or(co::HRESULT::STG_E_MEDIUMFULL)?;
+		if end > self.0.len() {
+			self.0.resize(end, 0x00);
+		}
+		self.0[end - data.len()..end].copy_from_slice(data);
+		Ok(data.len())
+	}
+
+	fn size(&mut self) -> HrResult<u64> {
+		Ok(self.0.len() as _)
+	}
+
+	fn set_size(&mut self, new_size: u64) -> HrResult<()> {
+		let new_size = usize::try_from(new_size).map_err(|_| co::HRESULT::STG_E_MEDIUMFULL)?;
+		self.0.resize(new_size, 0x00);
+		Ok(())
+	}
+}
+
+struct FileBackend(File);
+
+impl StreamBackend for FileBackend {
+	fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> HrResult<usize> {
+		self.0
+			.set_pointer_offset(offset)
+			.map_err(|_| co::HRESULT::STG_E_SEEKERROR)?;
+		let len = buf.len().min(u32::MAX as _);
+		self.0
+			.read_buffer(&mut buf[..len])
+			.map(|n| n as _)
+			.map_err(|e| e.to_hresult())
+	}
+
+	fn write_at(&mut self, offset: u64, data: &[u8]) -> HrResult<usize> {
+		self.0
+			.set_pointer_offset(offset)
+			.map_err(|_| co::HRESULT::STG_E_SEEKERROR)?;
+		self.0
+			.write(data)
+			.map(|_| data.len())
+			.map_err(|e| e.to_hresult())
+	}
+
+	fn size(&mut self) -> HrResult<u64> {
+		self.0.size().map_err(|e| e.to_hresult())
+	}
+
+	fn set_size(&mut self, new_size: u64) -> HrResult<()> {
+		self.0.set_size(new_size).map_err(|e| e.to_hresult())
+	}
+
+	fn times(&self) -> Option<(FILETIME, FILETIME, FILETIME)> {
+		self.0.hfile().GetFileTime().ok()
+	}
+}
+
+struct FileMappedBackend(FileMapped);
+
+impl StreamBackend for FileMappedBackend {
+	fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> HrResult<usize> {
+		Ok(read_slice(self.0.as_slice(), offset, buf))
+	}
+
+	fn write_at(&mut self, _: u64, _: &[u8]) -> HrResult<usize> {
+		Err(co::HRESULT::STG_E_ACCESSDENIED)
+	}
+
+	fn size(&mut self) -> HrResult<u64> {
+		Ok(self.0.size())
+	}
+
+	fn set_size(&mut self, _: u64) -> HrResult<()> {
+		Err(co::HRESULT::STG_E_ACCESSDENIED)
+	}
+
+	fn mode(&self) -> co::STGM {
+		co::STGM::READ
+	}
+
+	fn times(&self) -> Option<(FILETIME, FILETIME, FILETIME)> {
+		self.0.hfile().GetFileTime().ok()
+	}
+}
+
+struct RwBackend<T>(T);
+
+impl<T> StreamBackend for RwBackend<T>
+where
+	T: Read + Write + Seek + Send,
+{
+	fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> HrResult<usize> {
+		self.0
+			.seek(SeekFrom::Start(offset))
+			.map_err(|_| co::HRESULT::STG_E_SEEKERROR)?;
+		let mut total = 0;
+		while total < buf.len() {
+			match self.0.read(&mut buf[total..]) {
+				Ok(0) => break,
+				Ok(n) => total += n,
+				Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {},
+				Err(_) => return Err(co::HRESULT::STG_E_READFAULT),
+			}
+		}
+		Ok(total)
+	}
+
+	fn write_at(&mut self, offset: u64, data: &[u8]) -> HrResult<usize> {
+		self.0
+			.seek(SeekFrom::Start(offset))
+			.map_err(|_| co::HRESULT::STG_E_SEEKERROR)?;
+		self.0
+			.write_all(data)
+			.map(|_| data.len())
+			.map_err(|_| co::HRESULT::STG_E_WRITEFAULT)
+	}
+
+	fn size(&mut self) -> HrResult<u64> {
+		self.0
+			.seek(SeekFrom::End(0))
+			.map_err(|_| co::HRESULT::STG_E_SEEKERROR)
+	}
+
+	fn set_size(&mut self, new_size: u64) -> HrResult<()> {
+		let cur_size = self.size()?;
+		if new_size < cur_size {
+			return Err(co::HRESULT::STG_E_INVALIDFUNCTION); // cannot truncate
+		}
+
+		let zeros = [0u8; 4096];
+		let mut remaining = new_size - cur_size;
+		while remaining > 0 {
+			let n = remaining.min(zeros.len() as _);
+			self.0
+				.write_all(&zeros[..n as usize])
+				.map_err(|_| co::HRESULT::STG_E_WRITEFAULT)?;
+			remaining -= n;
+		}
+		Ok(())
+	}
+
+	fn flush(&mut self) -> HrResult<()> {
+		self.0.flush().map_err(|_| co::HRESULT::STG_E_WRITEFAULT)
+	}
+}
+
+/// The COM object itself. Unlike the other user-defined implementations, the
+/// object is referenced directly by the COM pointer, so it can be handed out
+/// by `Clone` and `QueryInterface`; the first field is therefore a pointer to
+/// a static VT.
+#[repr(C)]
+struct IStreamImpl {
+	vt: &'static IStreamVT,
+	counter: AtomicU32,
+	pos: AtomicU64,
+	backend: Arc<Mutex<dyn StreamBackend>>,
+}
+
+static ISTREAM_VT: IStreamVT = IStreamVT {
+	ISequentialStreamVT: ISequentialStreamVT {
+		IUnknownVT: IUnknownVT {
+			QueryInterface: IStreamImpl::QueryInterface,
+			AddRef: IStreamImpl::AddRef,
+			Release: IStreamImpl::Release,
+		},
+		Read: IStreamImpl::Read,
+		Write: IStreamImpl::Write,
+	},
+	Seek: IStreamImpl::Seek,
+	SetSize: IStreamImpl::SetSize,
+	CopyTo: IStreamImpl::CopyTo,
+	Commit: IStreamImpl::Commit,
+	Revert: IStreamImpl::Revert,
+	LockRegion: IStreamImpl::LockRegion,
+	UnlockRegion: IStreamImpl::UnlockRegion,
+	Stat: IStreamImpl::Stat,
+	Clone: IStreamImpl::Clone,
+};
+
+impl IStreamImpl {
+	#[must_use]
+	fn new_stream(backend: impl StreamBackend + 'static) -> IStream {
+		Self::alloc(Arc::new(Mutex::new(backend)), 0)
+	}
+
+	#[must_use]
+	fn alloc(backend: Arc<Mutex<dyn StreamBackend>>, pos: u64) -> IStream {
+		let box_impl = Box::new(Self {
+			vt: &ISTREAM_VT,
+			counter: AtomicU32::new(1),
+			pos: AtomicU64::new(pos),
+			backend,
+		});
+		unsafe { IStream::from_ptr(Box::into_raw(box_impl) as _) } // Release() frees it
+	}
+
+	#[must_use]
+	fn of<'a>(p: COMPTR) -> &'a Self {
+		unsafe { &*(p as *const Self) }
+	}
+
+	fn lock(&self) -> HrResult<MutexGuard<'_, dyn StreamBackend + 'static>> {
+		self.backend.lock().map_err(|_| co::HRESULT::E_UNEXPECTED)
+	}
+
+	fn QueryInterface(p: COMPTR, riid: PCVOID, ppv: *mut COMPTR) -> HRES {
+		if ppv.is_null() {
+			return co::HRESULT::E_POINTER.raw();
+		}
+		let iid = co::IID::from(unsafe { *(riid as *const GUID) });
+		if iid == IUnknown::IID || iid == ISequentialStream::IID || iid == IStream::IID {
+			Self::AddRef(p);
+			unsafe { *ppv = p };
+			co::HRESULT::S_OK.raw()
+		} else {
+			unsafe { *ppv = std::ptr::null_mut() };
+			co::HRESULT::E_NOINTERFACE.raw()
+		}
+	}
+
+	fn AddRef(p: COMPTR) -> u32 {
+		Self::of(p).counter.fetch_add(1, Ordering::Relaxed) + 1
+	}
+
+	fn Release(p: COMPTR) -> u32 {
+		let count = Self::of(p).counter.fetch_sub(1, Ordering::Release) - 1;
+		if count == 0 {
+			std::sync::atomic::fence(Ordering::Acquire);
+			let _ = unsafe { Box::from_raw(p as *mut Self) }; // free the memory block
+		}
+		count
+	}
+
+	fn Read(p: COMPTR, pv: PVOID, cb: u32, pcbRead: *mut u32) -> HRES {
+		let obj = Self::of(p);
+		if pv.is_null() && cb != 0 {
+			return co::HRESULT::STG_E_INVALIDPOINTER.raw();
+		}
+		hrresult_to_hres((|| {
+			let buf = match cb {
+				0 => &mut [],
+				cb => unsafe { std::slice::from_raw_parts_mut(pv as *mut u8, cb as _) },
+			};
+			let mut backend = obj.lock()?;
+			let pos = obj.pos.load(Ordering::Relaxed);
+			let n = backend.read_at(pos, buf)?;
+			obj.pos.store(pos + n as u64, Ordering::Relaxed);
+			if !pcbRead.is_null() {
+				unsafe { *pcbRead = n as _ };
+			}
+			Ok(())
+		})())
+	}
+
+	fn Write(p: COMPTR, pv: PCVOID, cb: u32, pcbWritten: *mut u32) -> HRES {
+		let obj = Self::of(p);
+		if pv.is_null() && cb != 0 {
+			return co::HRESULT::STG_E_INVALIDPOINTER.raw();
+		}
+		hrresult_to_hres((|| {
+			let data = match cb {
+				0 => &[],
+				cb => unsafe { std::slice::from_raw_parts(pv as *const u8, cb as _) },
+			};
+			let mut backend = obj.lock()?;
+			let pos = obj.pos.load(Ordering::Relaxed);
+			let n = backend.write_at(pos, data)?;
+			obj.pos.store(pos + n as u64, Ordering::Relaxed);
+			if !pcbWritten.is_null() {
+				unsafe { *pcbWritten = n as _ };
+			}
+			Ok(())
+		})())
+	}
+
+	fn Seek(p: COMPTR, dlibMove: i64, dwOrigin: u32, plibNewPosition: *mut u64) -> HRES {
+		let obj = Self::of(p);
+		hrresult_to_hres((|| {
+			let mut backend = obj.lock()?;
+			let base = match unsafe { co::STREAM_SEEK::from_raw(dwOrigin) } {
+				co::STREAM_SEEK::SET => 0,
+				co::STREAM_SEEK::CUR => obj.pos.load(Ordering::Relaxed),
+				co::STREAM_SEEK::END => backend.size()?,
+				_ => return Err(co::HRESULT::STG_E_INVALIDFUNCTION),
+			};
+			let new_pos = base
+				.checked_add_signed(dlibMove)
+				.ok_or(co::HRESULT::STG_E_INVALIDFUNCTION)?;
+			obj.pos.store(new_pos, Ordering::Relaxed);
+			if !plibNewPosition.is_null() {
+				unsafe { *plibNewPosition = new_pos };
+			}
+			Ok(())
+		})())
+	}
+
+	fn SetSize(p: COMPTR, libNewSize: u64) -> HRES {
+		let obj = Self::of(p);
+		hrresult_to_hres(
+			obj.lock()
+				.and_then(|mut backend| backend.set_size(libNewSize)),
+		)
+	}
+
+	fn CopyTo(p: COMPTR, pstm: COMPTR, cb: u64, pcbRead: *mut u64, pcbWritten: *mut u64) -> HRES {
+		let obj = Self::of(p);
+		let dest = ManuallyDrop::new(unsafe { IStream::from_ptr(pstm) });
+		let (mut total_read, mut total_written) = (0u64, 0u64);
+		let mut buf = vec![0u8; 0x1_0000];
+
+		let res = (|| {
+			while total_read < cb {
+				let chunk = (cb - total_read).min(buf.len() as _) as usize;
+				let n = {
+					let mut backend = obj.lock()?; // released before writing to dest
+					let pos = obj.pos.load(Ordering::Relaxed);
+					let n = backend.read_at(pos, &mut buf[..chunk])?;
+					obj.pos.store(pos + n as u64, Ordering::Relaxed);
+					n
+				};
+				if n == 0 {
+					break; // end of stream
+				}
+				total_read += n as u64;
+
+				let mut data = &buf[..n];
+				while !data.is_empty() {
+					let written = dest.Write(data)? as usize;
+					if written == 0 {
+						return Err(co::HRESULT::STG_E_MEDIUMFULL);
+					}
+					total_written += written as u64;
+					data = &data[written..];
+				}
+			}
+			Ok(())
+		})();
+
+		if !pcbRead.is_null() {
+			unsafe { *pcbRead = total_read };
+		}
+		if !pcbWritten.is_null() {
+			unsafe { *pcbWritten = total_written };
+		}
+		hrresult_to_hres(res)
+	}
+
+	fn Commit(p: COMPTR, _grfCommitFlags: u32) -> HRES {
+		let obj = Self::of(p);
+		hrresult_to_hres(obj.lock().and_then(|mut backend| backend.flush()))
+	}
+
+	fn Revert(_p: COMPTR) -> HRES {
+		co::HRESULT::S_OK.raw() // not transacted, nothing to revert
+	}
+
+	fn LockRegion(_p: COMPTR, _libOffset: u64, _cb: u64, _dwLockType: u32) -> HRES {
+		co::HRESULT::STG_E_INVALIDFUNCTION.raw()
+	}
+
+	fn UnlockRegion(_p: COMPTR, _libOffset: u64, _cb: u64, _dwLockType: u32) -> HRES {
+		co::HRESULT::STG_E_INVALIDFUNCTION.raw()
+	}
+
+	fn Stat(p: COMPTR, pstatstg: PVOID, _grfStatFlag: u32) -> HRES {
+		let obj = Self::of(p);
+		if pstatstg.is_null() {
+			return co::HRESULT::STG_E_INVALIDPOINTER.raw();
+		}
+		hrresult_to_hres((|| {
+			let backend = &mut *obj.lock()?;
+			let stat = unsafe { &mut *(pstatstg as *mut STATSTG) };
+			*stat = STATSTG::default(); // no name is ever returned
+			stat.r#type = co::STGTY::STREAM;
+			stat.cbSize = backend.size()?;
+			stat.grfMode = backend.mode();
+			if let Some((creation, access, write)) = backend.times() {
+				stat.ctime = creation;
+				stat.atime = access;
+				stat.mtime = write;
+			}
+			Ok(())
+		})())
+	}
+
+	fn Clone(p: COMPTR, ppstm: *mut COMPTR) -> HRES {
+		if ppstm.is_null() {
+			return co::HRESULT::STG_E_INVALIDPOINTER.raw();
+		}
+		let obj = Self::of(p);
+		let mut cloned = Self::alloc(obj.backend.clone(), obj.pos.load(Ordering::Relaxed));
+		unsafe { *ppstm = cloned.leak() }; // ownership goes to the caller
+		co::HRESULT::S_OK.raw()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn read_all(stream: &IStream) -> Vec<u8> {
+		stream.Seek(0, co::STREAM_SEEK::SET).unwrap();
+		let mut buf = vec![0u8; 64];
+		let n = stream.Read(&mut buf).unwrap();
+		buf.truncate(n as _);
+		buf
+	}
+
+	#[test]
+	fn read_write() {
+		let stream = IStream::from_vec(vec![1, 2, 3, 4, 5]);
+		let mut buf = [0u8; 3];
+		assert_eq!(stream.Read(&mut buf).unwrap(), 3);
+		assert_eq!(buf, [1, 2, 3]);
+		assert_eq!(stream.Read(&mut buf).unwrap(), 2); // short read at the end
+		assert_eq!(buf[..2], [4, 5]);
+		assert_eq!(stream.Read(&mut buf).unwrap(), 0);
+
+		assert_eq!(stream.Write(&[6, 7]).unwrap(), 2); // appends
+		assert_eq!(stream.Seek(1, co::STREAM_SEEK::SET).unwrap(), 1);
+		assert_eq!(stream.Write(&[9]).unwrap(), 1); // overwrites
+		assert_eq!(stream.Write(&[]).unwrap(), 0);
+		assert_eq!(read_all(&stream), [1, 9, 3, 4, 5, 6, 7]);
+	}
+
+	#[test]
+	fn seek() {
+		let stream = IStream::from_vec(vec![0; 10]);
+		assert_eq!(stream.Seek(4, co::STREAM_SEEK::SET).unwrap(), 4);
+		assert_eq!(stream.Seek(3, co::STREAM_SEEK::CUR).unwrap(), 7);
+		assert_eq!(stream.Seek(-5, co::STREAM_SEEK::CUR).unwrap(), 2);
+		assert_eq!(stream.Seek(-3, co::STREAM_SEEK::END).unwrap(), 7);
+		assert_eq!(stream.Seek(0, co::STREAM_SEEK::END).unwrap(), 10);
+
+		assert!(stream.Seek(-11, co::STREAM_SEEK::END) == Err(co::HRESULT::STG_E_INVALIDFUNCTION));
+		assert!(stream.Seek(-1, co::STREAM_SEEK::SET) == Err(co::HRESULT::STG_E_INVALIDFUNCTION));
+		assert!(
+			stream.Seek(0, unsafe { co::STREAM_SEEK::from_raw(3) })
+				== Err(co::HRESULT::STG_E_INVALIDFUNCTION)
+		);
+		assert_eq!(stream.Seek(0, co::STREAM_SEEK::CUR).unwrap(), 10); // unchanged by errors
+
+		assert_eq!(stream.Seek(2, co::STREAM_SEEK::END).unwrap(), 12); // past the end
+		stream.Write(&[1]).unwrap();
+		assert_eq!(read_all(&stream), [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+	}
+
+	#[test]
+	fn set_size() {
+		let stream = IStream::from_vec(vec![1, 2, 3]);
+		stream.SetSize(5).unwrap();
+		assert_eq!(read_all(&stream), [1, 2, 3, 0, 0]);
+		stream.SetSize(2).unwrap();
+		assert_eq!(read_all(&stream), [1, 2]);
+		assert_eq!(stream.Seek(0, co::STREAM_SEEK::CUR).unwrap(), 2);
+	}
+
+	#[test]
+	fn clone() {
+		let stream = IStream::from_vec(vec![1, 2, 3, 4]);
+		stream.Seek(1, co::STREAM_SEEK::SET).unwrap();
+		let cloned = stream.Clone().unwrap();
+		assert_eq!(cloned.Seek(0, co::STREAM_SEEK::CUR).unwrap(), 1); // starts at the same position
+
+		let mut buf = [0u8; 2];
+		cloned.Read(&mut buf).unwrap();
+		assert_eq!(buf, [2, 3]);
+		assert_eq!(stream.Seek(0, co::STREAM_SEEK::CUR).unwrap(), 1); // own seek pointer
+
+		cloned.Write(&[8]).unwrap();
+		drop(cloned); // data outlives the clone
+		assert_eq!(read_all(&stream), [1, 2, 3, 8]); // shared data
+	}
+
+	#[test]
+	fn copy_to() {
+		let src = IStream::from_vec((0..100).collect());
+		let dest = IStream::from_vec(vec![0xff; 3]);
+		src.Seek(10, co::STREAM_SEEK::SET).unwrap();
+		dest.Seek(1, co::STREAM_SEEK::SET).unwrap();
+
+		assert_eq!(src.CopyTo(&dest, 5).unwrap(), (5, 5));
+		assert_eq!(src.Seek(0, co::STREAM_SEEK::CUR).unwrap(), 15);
+		assert_eq!(dest.Seek(0, co::STREAM_SEEK::CUR).unwrap(), 6);
+		assert_eq!(read_all(&dest), [0xff, 10, 11, 12, 13, 14]);
+
+		src.Seek(97, co::STREAM_SEEK::SET).unwrap();
+		dest.Seek(0, co::STREAM_SEEK::END).unwrap();
+		assert_eq!(src.CopyTo(&dest, u64::MAX).unwrap(), (3, 3)); // stops at the end
+		assert_eq!(read_all(&dest), [0xff, 10, 11, 12, 13, 14, 97, 98, 99]);
+	}
+
+	#[test]
+	fn stat() {
+		let stream = IStream::from_vec(vec![0; 7]);
+		let (stat, name) = stream.Stat(co::STATFLAG::NONAME).unwrap();
+		assert!(name.is_empty());
+		assert_eq!(stat.r#type, co::STGTY::STREAM);
+		assert_eq!(stat.cbSize, 7);
+		assert_eq!(stat.grfMode, co::STGM::READWRITE);
+
+		stream.SetSize(12).unwrap();
+		assert_eq!(stream.Stat(co::STATFLAG::DEFAULT).unwrap().0.cbSize, 12);
+		assert!(stream.Commit(co::STGC::DEFAULT).is_ok());
+	}
+}
@@ -1,4 +1,5 @@
 mod idroptarget;
+mod istream;
 
 pub mod decl {
 	pub use super::idroptarget::IDropTarget;
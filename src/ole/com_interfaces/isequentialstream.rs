@@ -51,7 +51,7 @@ pub trait ole_ISequentialStream: ole_IUnknown {
 	fn Write(&self, data: &[u8]) -> HrResult<u32> {
 		let mut num_written = 0u32;
 		HrRet(unsafe {
-			(vt::<ISequentialStreamVT>(self).Read)(
+			(vt::<ISequentialStreamVT>(self).Write)(
 				self.ptr(),
 				vec_ptr(data) as _,
 				data.len() as _,
@@ -2,6 +2,7 @@
 
 use crate::co;
 use crate::decl::*;
+use crate::kernel::privs::*;
 use crate::ole::{privs::*, vts::*};
 use crate::prelude::*;
 
@@ -31,8 +32,56 @@ com_interface! { IStream: "0000000c-0000-0000-c000-000000000046";
 impl ole_ISequentialStream for IStream {}
 impl ole_IStream for IStream {}
 
+impl std::io::Read for IStream {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		let len = buf.len().min(u32::MAX as _);
+		ole_ISequentialStream::Read(self, &mut buf[..len])
+			.map(|num_read| num_read as _)
+			.map_err(std::io::Error::other)
+	}
+}
+
+impl std::io::Write for IStream {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		let len = buf.len().min(u32::MAX as _);
+		ole_ISequentialStream::Write(self, &buf[..len])
+			.map(|num_written| num_written as _)
+			.map_err(std::io::Error::other)
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		self.Commit(co::STGC::DEFAULT)
+			.map_err(std::io::Error::other)
+	}
+}
+
+impl std::io::Seek for IStream {
+	fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+		let (displacement, origin) = match pos {
+			std::io::SeekFrom::Start(off) => (
+				i64::try_from(off).map_err(|_| {
+					std::io::Error::from(std::io::ErrorKind::InvalidInput)
+				})?,
+				co::STREAM_SEEK::SET,
+			),
+			std::io::SeekFrom::Current(off) => (off, co::STREAM_SEEK::CUR),
+			std::io::SeekFrom::End(off) => (off, co::STREAM_SEEK::END),
+		};
+		self.Seek(displacement, origin)
+			.map_err(std::io::Error::other)
+	}
+}
+
 /// [`IStream`](crate::IStream) methods from `ole` feature.
 pub trait ole_IStream: ole_ISequentialStream {
+	fn_com_interface_get! { Clone: IStreamVT => IStream;
+		/// [`IStream::Clone`](https://learn.microsoft.com/en-us/windows/win32/api/objidl/nf-objidl-istream-clone)
+		/// method.
+		///
+		/// The new stream shares the same underlying data, but has its own seek
+		/// pointer, initially at the same position.
+	}
+
 	/// [`IStream::Commit`](https://learn.microsoft.com/en-us/windows/win32/api/objidl/nf-objidl-istream-commit)
 	/// method.
 	fn Commit(&self, flags: co::STGC) -> HrResult<()> {
@@ -95,6 +144,36 @@ pub trait ole_IStream: ole_ISequentialStream {
 		HrRet(unsafe { (vt::<IStreamVT>(self).SetSize)(self.ptr(), new_size) }).to_hrresult()
 	}
 
+	/// [`IStream::Stat`](https://learn.microsoft.com/en-us/windows/win32/api/objidl/nf-objidl-istream-stat)
+	/// method.
+	///
+	/// Returns the statistics along with the name of the stream, which will be
+	/// empty if [`co::STATFLAG::NONAME`](crate::co::STATFLAG::NONAME) is passed,
+	/// or if the stream has no name.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let stream = w::SHCreateMemStream(&[1, 2, 3])?;
+	/// let (stat, _) = stream.Stat(co::STATFLAG::NONAME)?;
+	/// println!("Size: {}", stat.cbSize);
+	/// # w::HrResult::Ok(())
+	/// ```
+	#[must_use]
+	fn Stat(&self, flag: co::STATFLAG) -> HrResult<(STATSTG, String)> {
+		let mut stat = STATSTG::default();
+		HrRet(unsafe {
+			(vt::<IStreamVT>(self).Stat)(self.ptr(), pvoid(&mut stat), flag.raw())
+		})
+		.to_hrresult()
+		.map(|_| {
+			let name = stat.take_name();
+			(stat, name)
+		})
+	}
+
 	/// [`IStream::UnlockRegion`](https://learn.microsoft.com/en-us/windows/win32/api/objidl/nf-objidl-istream-unlockregion)
 	/// method.
 	fn UnlockRegion(&self, offset: u64, length: u64, lock_type: co::LOCKTYPE) -> HrResult<()> {
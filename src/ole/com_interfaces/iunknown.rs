@@ -105,6 +105,22 @@ pub trait ole_IUnknown: Clone {
 		p
 	}
 
+	/// Returns a reference to the underlying COM virtual table, cast to the
+	/// given type.
+	///
+	/// This method is used to call the virtual table methods of custom COM
+	/// interfaces, declared with
+	/// [`declare_com_interface!`](crate::declare_com_interface).
+	///
+	/// # Safety
+	///
+	/// The type must be the virtual table of this interface, or of one of its
+	/// ancestors.
+	#[must_use]
+	unsafe fn vt<T>(&self) -> &T {
+		unsafe { vt::<T>(self) }
+	}
+
 	/// [`IUnknown::QueryInterface`](https://learn.microsoft.com/en-us/windows/win32/api/unknwn/nf-unknwn-iunknown-queryinterface(refiid_void))
 	/// method.
 	#[must_use]
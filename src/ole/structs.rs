@@ -6,6 +6,7 @@ use crate::co;
 use crate::decl::*;
 use crate::guard::*;
 use crate::kernel::ffi_types::*;
+use crate::ole::privs::*;
 
 /// [`BIND_OPTS3`](https://learn.microsoft.com/en-us/windows/win32/api/objidl/ns-objidl-bind_opts3-r1)
 /// struct.
@@ -248,6 +249,44 @@ impl SNB {
 	}
 }
 
+/// [`STATSTG`](https://learn.microsoft.com/en-us/windows/win32/api/objidl/ns-objidl-statstg)
+/// struct.
+///
+/// The name of the element is returned separately by
+/// [`IStream::Stat`](crate::prelude::ole_IStream::Stat), therefore the
+/// `pwcsName` field is not exposed.
+#[repr(C)]
+pub struct STATSTG {
+	pwcsName: *mut u16,
+	pub r#type: co::STGTY,
+	pub cbSize: u64,
+	pub mtime: FILETIME,
+	pub ctime: FILETIME,
+	pub atime: FILETIME,
+	pub grfMode: co::STGM,
+	pub grfLocksSupported: co::LOCKTYPE,
+	pub clsid: GUID,
+	pub grfStateBits: u32,
+	reserved: u32,
+}
+
+impl_default!(STATSTG);
+
+impl STATSTG {
+	/// Converts the `pwcsName` field to `String`, releasing the allocated
+	/// memory.
+	#[must_use]
+	pub(crate) fn take_name(&mut self) -> String {
+		if self.pwcsName.is_null() {
+			String::new()
+		} else {
+			let name = htaskmem_ptr_to_str(self.pwcsName);
+			self.pwcsName = std::ptr::null_mut();
+			name
+		}
+	}
+}
+
 /// [`STGMEDIUM`](https://learn.microsoft.com/en-us/windows/win32/api/objidl/ns-objidl-ustgmedium-r1)
 /// struct.
 #[repr(C)]
@@ -0,0 +1,1029 @@
+use std::cmp::Ordering;
+
+use crate::co;
+use crate::decl::*;
+use crate::kernel::bytes::*;
+
+const SIGNATURE: [u8; 8] = [0xd0, 0xcf, 0x11, 0xe0, 0xa1, 0xb1, 0x1a, 0xe1];
+const HEADER_DIFAT_LEN: usize = 109;
+const DIR_ENTRY_SIZE: usize = 128;
+const MINI_SECTOR_SIZE: usize = 64;
+/// Streams smaller than this are stored in the mini stream.
+const MINI_STREAM_CUTOFF: u64 = 4096;
+/// Limit of the stream size in version 3 files.
+const MAX_V3_STREAM_SIZE: u64 = 0x8000_0000;
+const MAX_NAME_LEN: usize = 31;
+
+const MAXREGSECT: u32 = 0xffff_fffa;
+const DIFSECT: u32 = 0xffff_fffc;
+const FATSECT: u32 = 0xffff_fffd;
+const ENDOFCHAIN: u32 = 0xffff_fffe;
+const FREESECT: u32 = 0xffff_ffff;
+const NOSTREAM: u32 = 0xffff_ffff;
+
+const TYPE_STORAGE: u8 = 1;
+const TYPE_STREAM: u8 = 2;
+const TYPE_ROOT: u8 = 5;
+
+/// Pure Rust reader and writer of
+/// [Compound File Binary](https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-cfb/53989ce4-7b05-4f8d-829b-d08d6148375b)
+/// files, also known as structured storage – the format of `.msi`, legacy
+/// Office documents and `Thumbs.db` files, among others.
+///
+/// This is the format written by [`IStorage`](crate::IStorage), and the
+/// methods mirror its own. Elements are addressed by paths of storage names
+/// separated by `/`, and the empty path is the root storage. Names are
+/// compared case-insensitively, as in `IStorage`.
+///
+/// The data can come from a byte slice or a
+/// [`FileMapped`](crate::FileMapped), and is only read when a stream is
+/// opened. Created and modified streams are kept in memory until the file is
+/// serialized.
+///
+/// # Examples
+///
+/// Listing the streams of an `.msi` file:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let fin = w::FileMapped::open("C:\\Temp\\setup.msi", w::FileAccess::ExistingReadOnly)?;
+/// let cf = w::CompoundFile::parse(fin.as_slice())?;
+///
+/// for stat in cf.enum_elements("")?.iter() {
+///     if stat.kind == co::STGTY::STREAM {
+///         println!("{}: {} bytes", stat.name, stat.size);
+///     }
+/// }
+///
+/// let summary = cf.open_stream("\u{5}SummaryInformation")?;
+/// # w::SysResult::Ok(())
+/// ```
+///
+/// Writing a new file:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let mut cf = w::CompoundFile::new();
+/// cf.create_storage("Data")?;
+/// cf.create_stream("Data/Contents", b"Hello")?;
+///
+/// std::fs::write("C:\\Temp\\file.bin", cf.serialize()?)?;
+/// # w::AnyResult::Ok(())
+/// ```
+#[derive(Clone)]
+pub struct CompoundFile<'a> {
+	src: &'a [u8],
+	src_sector_size: usize,
+	fat: Vec<u32>,
+	mini_fat: Vec<u32>,
+	mini_stream_sectors: Vec<u32>,
+	version: u16,
+	nodes: Vec<Node>, // root is the first one; removed nodes are left orphaned
+}
+
+/// Metadata of an element of a [`CompoundFile`](crate::CompoundFile), like
+/// the [`STATSTG`](https://learn.microsoft.com/en-us/windows/win32/api/objidl/ns-objidl-statstg)
+/// struct.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CompoundFileStat {
+	/// Name of the element.
+	pub name: String,
+	/// Either [`co::STGTY::STORAGE`](crate::co::STGTY::STORAGE) or
+	/// [`co::STGTY::STREAM`](crate::co::STGTY::STREAM).
+	pub kind: co::STGTY,
+	/// Size of the stream, in bytes; zero for storages.
+	pub size: u64,
+	/// CLSID of the storage; null for streams.
+	pub clsid: GUID,
+	/// State bits of the storage.
+	pub state_bits: u32,
+	/// Creation time of the storage; zero for streams.
+	pub creation_time: FILETIME,
+	/// Last modification time of the storage; zero for streams.
+	pub modification_time: FILETIME,
+}
+
+#[derive(Clone)]
+struct Node {
+	name: String,
+	kind: co::STGTY,
+	clsid: GUID,
+	state_bits: u32,
+	creation_time: FILETIME,
+	modification_time: FILETIME,
+	data: NodeData,
+	children: Vec<usize>, // sorted by compare_names()
+}
+
+#[derive(Clone)]
+enum NodeData {
+	/// Stream still in the source data.
+	Source {
+		start: u32,
+		size: u64,
+	},
+	Owned(Vec<u8>),
+}
+
+/// A directory entry, as read from or written to the file.
+#[derive(Clone, Default)]
+struct DirEntry {
+	name: Vec<u16>,
+	kind: u8,
+	is_red: bool,
+	left: u32,
+	right: u32,
+	child: u32,
+	clsid: GUID,
+	state_bits: u32,
+	creation_time: u64,
+	modification_time: u64,
+	start: u32,
+	size: u64,
+}
+
+impl<'a> Default for CompoundFile<'a> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<'a> CompoundFile<'a> {
+	/// Creates an empty version 3 file, with 512-byte sectors.
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			src: &[],
+			src_sector_size: 512,
+			fat: Vec::new(),
+			mini_fat: Vec::new(),
+			mini_stream_sectors: Vec::new(),
+			version: 3,
+			nodes: vec![Node::new("Root Entry", co::STGTY::STORAGE)],
+		}
+	}
+
+	/// Parses the header and the directory of a compound file. The streams
+	/// are read when opened.
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(data: &'a [u8]) -> SysResult<Self> {
+		if data.get(..8) != Some(&SIGNATURE[..]) {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let version = read_word(data, 0x1a)?;
+		let sector_size = match (version, read_word(data, 0x1e)?) {
+			(3, 9) => 512,
+			(4, 12) => 4096,
+			_ => return Err(co::ERROR::INVALID_DATA),
+		};
+		if read_word(data, 0x20)? != 6 {
+			return Err(co::ERROR::INVALID_DATA); // mini sectors must have 64 bytes
+		}
+
+		let mut cf = Self {
+			src: data,
+			src_sector_size: sector_size,
+			version,
+			..Self::new()
+		};
+		let max_sectors = data.len() / sector_size;
+
+		let num_fat = read_dword(data, 0x2c)? as usize;
+		let mut fat_sectors = (0..HEADER_DIFAT_LEN)
+			.map(|i| read_dword(data, 0x4c + i * 4))
+			.collect::<SysResult<Vec<_>>>()?;
+		let mut difat = read_dword(data, 0x44)?;
+		let mut num_difat = 0;
+		while difat <= MAXREGSECT {
+			num_difat += 1;
+			if num_difat > max_sectors {
+				return Err(co::ERROR::INVALID_DATA); // cyclic DIFAT chain
+			}
+			let sector = cf.sector(difat)?;
+			let ids = sector_size / 4 - 1;
+			for i in 0..ids {
+				fat_sectors.push(read_dword(sector, i * 4)?);
+			}
+			difat = read_dword(sector, ids * 4)?;
+		}
+		for &fat_sector in fat_sectors.iter().take(num_fat) {
+			let sector = cf.sector(fat_sector)?;
+			cf.fat.extend(
+				sector
+					.chunks_exact(4)
+					.map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+			);
+		}
+
+		let mini_fat = if read_dword(data, 0x40)? == 0 {
+			Vec::new()
+		} else {
+			cf.read_chain(read_dword(data, 0x3c)?, None)?
+		};
+		cf.mini_fat = mini_fat
+			.chunks_exact(4)
+			.map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+			.collect();
+
+		let dir_data = cf.read_chain(read_dword(data, 0x30)?, None)?;
+		let entries = dir_data
+			.chunks_exact(DIR_ENTRY_SIZE)
+			.map(|raw| DirEntry::parse(raw, version))
+			.collect::<SysResult<Vec<_>>>()?;
+		let root = entries.first().ok_or(co::ERROR::INVALID_DATA)?;
+		if root.kind != TYPE_ROOT {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		if root.size > 0 {
+			cf.mini_stream_sectors = cf.chain(root.start)?;
+		}
+		cf.nodes[0] = Node::from_entry(root);
+
+		// Walk the red-black trees of each storage, guarding against cycles.
+		let mut visited = vec![false; entries.len()];
+		visited[0] = true;
+		let mut pending = vec![(root.child, 0usize)];
+		while let Some((tree_root, parent)) = pending.pop() {
+			let mut stack = vec![tree_root];
+			while let Some(idx) = stack.pop() {
+				if idx == NOSTREAM {
+					continue;
+				}
+				let entry = entries.get(idx as usize).ok_or(co::ERROR::INVALID_DATA)?;
+				if std::mem::replace(&mut visited[idx as usize], true) {
+					return Err(co::ERROR::INVALID_DATA);
+				}
+				stack.push(entry.left);
+				stack.push(entry.right);
+
+				if entry.kind == TYPE_STORAGE || entry.kind == TYPE_STREAM {
+					let node_idx = cf.nodes.len();
+					cf.nodes.push(Node::from_entry(entry));
+					cf.nodes[parent].children.push(node_idx);
+					if entry.kind == TYPE_STORAGE {
+						pending.push((entry.child, node_idx));
+					}
+				}
+			}
+		}
+		for idx in 0..cf.nodes.len() {
+			let mut children = std::mem::take(&mut cf.nodes[idx].children);
+			children.sort_by(|a, b| compare_names(&cf.nodes[*a].name, &cf.nodes[*b].name));
+			cf.nodes[idx].children = children;
+		}
+		Ok(cf)
+	}
+
+	/// Returns the major version of the format: 3, with 512-byte sectors,
+	/// or 4, with 4096-byte sectors.
+	#[must_use]
+	pub const fn version(&self) -> u16 {
+		self.version
+	}
+
+	/// Sets the major version used when serializing.
+	///
+	/// Versions other than 3 and 4 yield
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER).
+	pub fn set_version(&mut self, version: u16) -> SysResult<()> {
+		match version {
+			3 | 4 => {
+				self.version = version;
+				Ok(())
+			},
+			_ => Err(co::ERROR::INVALID_PARAMETER),
+		}
+	}
+
+	/// Creates a storage, like
+	/// [`IStorage::CreateStorage`](crate::prelude::ole_IStorage::CreateStorage).
+	///
+	/// Returns [`co::ERROR::ALREADY_EXISTS`](crate::co::ERROR::ALREADY_EXISTS)
+	/// if an element with the name exists.
+	pub fn create_storage(&mut self, path: &str) -> SysResult<()> {
+		let (parent, name) = self.find_parent(path)?;
+		if self.find_child(parent, name).is_some() {
+			return Err(co::ERROR::ALREADY_EXISTS);
+		}
+		self.insert_child(parent, Node::new(name, co::STGTY::STORAGE));
+		Ok(())
+	}
+
+	/// Creates a stream with the given contents, like
+	/// [`IStorage::CreateStream`](crate::prelude::ole_IStorage::CreateStream)
+	/// with [`co::STGM::CREATE`](crate::co::STGM::CREATE). An existing
+	/// stream is overwritten.
+	///
+	/// Returns [`co::ERROR::ALREADY_EXISTS`](crate::co::ERROR::ALREADY_EXISTS)
+	/// if a storage with the name exists.
+	pub fn create_stream(&mut self, path: &str, data: &[u8]) -> SysResult<()> {
+		let (parent, name) = self.find_parent(path)?;
+		match self.find_child(parent, name) {
+			Some(idx) if self.nodes[idx].kind == co::STGTY::STREAM => {
+				self.nodes[idx].data = NodeData::Owned(data.to_vec());
+			},
+			Some(_) => return Err(co::ERROR::ALREADY_EXISTS),
+			None => {
+				let mut node = Node::new(name, co::STGTY::STREAM);
+				node.data = NodeData::Owned(data.to_vec());
+				self.insert_child(parent, node);
+			},
+		}
+		Ok(())
+	}
+
+	/// Removes a storage, with all its elements, or a stream, like
+	/// [`IStorage::DestroyElement`](crate::prelude::ole_IStorage::DestroyElement).
+	pub fn destroy_element(&mut self, path: &str) -> SysResult<()> {
+		let (parent, name) = self.find_parent(path)?;
+		let idx = self
+			.find_child(parent, name)
+			.ok_or(co::ERROR::FILE_NOT_FOUND)?;
+		self.nodes[parent].children.retain(|c| *c != idx);
+		Ok(())
+	}
+
+	/// Returns the metadata of the elements of a storage, sorted as in the
+	/// file directory, like
+	/// [`IStorage::EnumElements`](https://learn.microsoft.com/en-us/windows/win32/api/objidl/nf-objidl-istorage-enumelements).
+	#[must_use]
+	pub fn enum_elements(&self, path: &str) -> SysResult<Vec<CompoundFileStat>> {
+		let idx = self.find_storage(path)?;
+		Ok(self.nodes[idx]
+			.children
+			.iter()
+			.map(|c| self.node_stat(*c))
+			.collect())
+	}
+
+	/// Reads the whole contents of a stream, like
+	/// [`IStorage::OpenStream`](crate::prelude::ole_IStorage::OpenStream)
+	/// followed by a read.
+	#[must_use]
+	pub fn open_stream(&self, path: &str) -> SysResult<Vec<u8>> {
+		let idx = self.find(path)?;
+		let node = &self.nodes[idx];
+		if node.kind != co::STGTY::STREAM {
+			return Err(co::ERROR::FILE_NOT_FOUND);
+		}
+		match &node.data {
+			NodeData::Owned(data) => Ok(data.clone()),
+			NodeData::Source { start, size } => self.read_stream(*start, *size),
+		}
+	}
+
+	/// Renames an element, like
+	/// [`IStorage::RenameElement`](crate::prelude::ole_IStorage::RenameElement).
+	pub fn rename_element(&mut self, path: &str, new_name: &str) -> SysResult<()> {
+		validate_name(new_name)?;
+		let (parent, name) = self.find_parent(path)?;
+		let idx = self
+			.find_child(parent, name)
+			.ok_or(co::ERROR::FILE_NOT_FOUND)?;
+		if self
+			.find_child(parent, new_name)
+			.is_some_and(|other| other != idx)
+		{
+			return Err(co::ERROR::ALREADY_EXISTS);
+		}
+		self.nodes[parent].children.retain(|c| *c != idx);
+		self.nodes[idx].name = new_name.to_owned();
+		self.insert_index(parent, idx);
+		Ok(())
+	}
+
+	/// Sets the CLSID of a storage, like
+	/// [`IStorage::SetClass`](crate::prelude::ole_IStorage::SetClass).
+	pub fn set_class(&mut self, path: &str, clsid: &GUID) -> SysResult<()> {
+		let idx = self.find_storage(path)?;
+		self.nodes[idx].clsid = *clsid;
+		Ok(())
+	}
+
+	/// Sets the creation and modification times of a storage, like
+	/// [`IStorage::SetElementTimes`](crate::prelude::ole_IStorage::SetElementTimes).
+	/// The times of streams are not stored in the file.
+	pub fn set_element_times(
+		&mut self,
+		path: &str,
+		creation: Option<&FILETIME>,
+		modification: Option<&FILETIME>,
+	) -> SysResult<()> {
+		let idx = self.find_storage(path)?;
+		if let Some(creation) = creation {
+			self.nodes[idx].creation_time = *creation;
+		}
+		if let Some(modification) = modification {
+			self.nodes[idx].modification_time = *modification;
+		}
+		Ok(())
+	}
+
+	/// Sets the state bits of a storage, like
+	/// [`IStorage::SetStateBits`](crate::prelude::ole_IStorage::SetStateBits).
+	pub fn set_state_bits(&mut self, path: &str, state_bits: u32, mask: u32) -> SysResult<()> {
+		let idx = self.find_storage(path)?;
+		let node = &mut self.nodes[idx];
+		node.state_bits = (node.state_bits & !mask) | (state_bits & mask);
+		Ok(())
+	}
+
+	/// Returns the metadata of an element.
+	#[must_use]
+	pub fn stat(&self, path: &str) -> SysResult<CompoundFileStat> {
+		self.find(path).map(|idx| self.node_stat(idx))
+	}
+
+	/// Serializes the file, writing all storages and streams.
+	///
+	/// A stream too large for a version 3 file yields
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER).
+	/// Errors reading the source data are also returned.
+	#[must_use]
+	pub fn serialize(&self) -> SysResult<Vec<u8>> {
+		let ss = if self.version == 4 { 4096 } else { 512 };
+		let per_sector = ss / 4;
+
+		// Directory order: breadth first, so each storage precedes its elements.
+		let mut order = vec![0usize];
+		let mut i = 0;
+		while i < order.len() {
+			order.extend_from_slice(&self.nodes[order[i]].children);
+			i += 1;
+		}
+		let mut dir_idx = vec![0u32; self.nodes.len()];
+		order
+			.iter()
+			.enumerate()
+			.for_each(|(d, n)| dir_idx[*n] = d as u32);
+
+		let mut entries = Vec::<DirEntry>::with_capacity(order.len());
+		let mut big_streams = Vec::<(usize, Vec<u8>)>::new(); // directory index, data
+		let mut mini_stream = Vec::<u8>::new();
+		let mut mini_fat = Vec::<u32>::new();
+		for (d, &n) in order.iter().enumerate() {
+			let node = &self.nodes[n];
+			let mut entry = DirEntry {
+				name: node.name.encode_utf16().collect(),
+				kind: if d == 0 {
+					TYPE_ROOT
+				} else if node.kind == co::STGTY::STORAGE {
+					TYPE_STORAGE
+				} else {
+					TYPE_STREAM
+				},
+				clsid: node.clsid,
+				state_bits: node.state_bits,
+				creation_time: node.creation_time.into(),
+				modification_time: node.modification_time.into(),
+				start: ENDOFCHAIN,
+				..Default::default()
+			};
+
+			if node.kind == co::STGTY::STREAM {
+				let data = match &node.data {
+					NodeData::Owned(data) => data.clone(),
+					NodeData::Source { start, size } => self.read_stream(*start, *size)?,
+				};
+				entry.size = data.len() as u64;
+				if self.version == 3 && entry.size > MAX_V3_STREAM_SIZE {
+					return Err(co::ERROR::INVALID_PARAMETER);
+				} else if entry.size >= MINI_STREAM_CUTOFF {
+					big_streams.push((d, data));
+				} else if entry.size > 0 {
+					entry.start = mini_fat.len() as u32;
+					let count = data.len().div_ceil(MINI_SECTOR_SIZE);
+					push_chain(&mut mini_fat, entry.start, count);
+					mini_stream.extend_from_slice(&data);
+					mini_stream.resize(mini_stream.len().next_multiple_of(MINI_SECTOR_SIZE), 0);
+				}
+			}
+			entries.push(entry);
+		}
+
+		for &n in order.iter() {
+			let mut children = self.nodes[n]
+				.children
+				.iter()
+				.map(|c| dir_idx[*c])
+				.collect::<Vec<_>>();
+			children.sort_by(|a, b| {
+				compare_utf16(&entries[*a as usize].name, &entries[*b as usize].name)
+			});
+			let max_depth = children.len().checked_ilog2().unwrap_or_default() as usize;
+			entries[dir_idx[n] as usize].child =
+				link_siblings(&children, 0, max_depth, &mut entries);
+		}
+
+		// Sector layout: directory, mini FAT, mini stream, big streams, FAT and
+		// DIFAT.
+		let num_dir = (entries.len() * DIR_ENTRY_SIZE).div_ceil(ss);
+		let num_mini_fat = (mini_fat.len() * 4).div_ceil(ss);
+		let num_mini_stream = mini_stream.len().div_ceil(ss);
+		let num_data = num_dir
+			+ num_mini_fat
+			+ num_mini_stream
+			+ big_streams
+				.iter()
+				.map(|(_, data)| data.len().div_ceil(ss))
+				.sum::<usize>();
+		let (mut num_fat, mut num_difat) = (0, 0);
+		loop {
+			let need_fat = (num_data + num_fat + num_difat).div_ceil(per_sector);
+			let need_difat = need_fat
+				.saturating_sub(HEADER_DIFAT_LEN)
+				.div_ceil(per_sector - 1);
+			if (need_fat, need_difat) == (num_fat, num_difat) {
+				break;
+			}
+			(num_fat, num_difat) = (need_fat, need_difat);
+		}
+
+		let mut fat = Vec::<u32>::with_capacity(num_fat * per_sector);
+		let first_dir = fat.len() as u32;
+		push_chain(&mut fat, first_dir, num_dir);
+		let first_mini_fat = fat.len() as u32;
+		push_chain(&mut fat, first_mini_fat, num_mini_fat);
+		let first_mini_stream = fat.len() as u32;
+		push_chain(&mut fat, first_mini_stream, num_mini_stream);
+		for (d, data) in big_streams.iter() {
+			entries[*d].start = fat.len() as u32;
+			push_chain(&mut fat, entries[*d].start, data.len().div_ceil(ss));
+		}
+		let first_fat = fat.len() as u32;
+		fat.resize(fat.len() + num_fat, FATSECT);
+		let first_difat = fat.len() as u32;
+		fat.resize(fat.len() + num_difat, DIFSECT);
+		fat.resize(num_fat * per_sector, FREESECT);
+
+		if num_mini_stream > 0 {
+			entries[0].start = first_mini_stream;
+			entries[0].size = mini_stream.len() as u64;
+		}
+
+		let mut buf = Vec::<u8>::with_capacity((1 + fat.len()) * ss);
+		buf.extend_from_slice(&SIGNATURE);
+		buf.resize(0x18, 0); // CLSID
+		for val in [0x3e, self.version, 0xfffe, if ss == 4096 { 12 } else { 9 }, 6] {
+			buf.extend_from_slice(&u16::to_le_bytes(val));
+		}
+		buf.resize(0x28, 0); // reserved
+		for val in [
+			if self.version == 4 { num_dir as u32 } else { 0 },
+			num_fat as u32,
+			first_dir,
+			0, // transaction signature
+			MINI_STREAM_CUTOFF as u32,
+			if num_mini_fat > 0 { first_mini_fat } else { ENDOFCHAIN },
+			num_mini_fat as u32,
+			if num_difat > 0 { first_difat } else { ENDOFCHAIN },
+			num_difat as u32,
+		] {
+			buf.extend_from_slice(&val.to_le_bytes());
+		}
+		for i in 0..HEADER_DIFAT_LEN {
+			let id = if i < num_fat { first_fat + i as u32 } else { FREESECT };
+			buf.extend_from_slice(&id.to_le_bytes());
+		}
+		buf.resize(ss, 0);
+
+		for entry in entries.iter() {
+			entry.serialize(&mut buf);
+		}
+		while !buf.len().is_multiple_of(ss) {
+			DirEntry::default().serialize(&mut buf); // unused entries
+		}
+		for id in mini_fat.iter() {
+			buf.extend_from_slice(&id.to_le_bytes());
+		}
+		pad_sector(&mut buf, ss, 0xff);
+		buf.extend_from_slice(&mini_stream);
+		pad_sector(&mut buf, ss, 0);
+		for (_, data) in big_streams.iter() {
+			buf.extend_from_slice(data);
+			pad_sector(&mut buf, ss, 0);
+		}
+		for id in fat.iter() {
+			buf.extend_from_slice(&id.to_le_bytes());
+		}
+		for d in 0..num_difat {
+			let ids = per_sector - 1;
+			for i in 0..ids {
+				let fat_idx = HEADER_DIFAT_LEN + d * ids + i;
+				let id = if fat_idx < num_fat { first_fat + fat_idx as u32 } else { FREESECT };
+				buf.extend_from_slice(&id.to_le_bytes());
+			}
+			let next = if d + 1 < num_difat { first_difat + d as u32 + 1 } else { ENDOFCHAIN };
+			buf.extend_from_slice(&next.to_le_bytes());
+		}
+		Ok(buf)
+	}
+
+	fn node_stat(&self, idx: usize) -> CompoundFileStat {
+		let node = &self.nodes[idx];
+		CompoundFileStat {
+			name: node.name.clone(),
+			kind: node.kind,
+			size: match &node.data {
+				NodeData::Owned(data) => data.len() as u64,
+				NodeData::Source { size, .. } => *size,
+			},
+			clsid: node.clsid,
+			state_bits: node.state_bits,
+			creation_time: node.creation_time,
+			modification_time: node.modification_time,
+		}
+	}
+
+	/// Returns the index of the node at the path.
+	fn find(&self, path: &str) -> SysResult<usize> {
+		if path.is_empty() {
+			return Ok(0);
+		}
+		let (parent, name) = self.find_parent(path)?;
+		self.find_child(parent, name)
+			.ok_or(co::ERROR::FILE_NOT_FOUND)
+	}
+
+	/// Returns the index of the storage at the path.
+	fn find_storage(&self, path: &str) -> SysResult<usize> {
+		let idx = self.find(path)?;
+		if self.nodes[idx].kind == co::STGTY::STORAGE {
+			Ok(idx)
+		} else {
+			Err(co::ERROR::PATH_NOT_FOUND)
+		}
+	}
+
+	/// Returns the index of the parent storage and the validated element name.
+	fn find_parent<'p>(&self, path: &'p str) -> SysResult<(usize, &'p str)> {
+		let (parent_path, name) = path.rsplit_once('/').unwrap_or(("", path));
+		validate_name(name)?;
+		let parent = self
+			.find(parent_path)
+			.map_err(|_| co::ERROR::PATH_NOT_FOUND)?;
+		if self.nodes[parent].kind == co::STGTY::STORAGE {
+			Ok((parent, name))
+		} else {
+			Err(co::ERROR::PATH_NOT_FOUND)
+		}
+	}
+
+	fn find_child(&self, parent: usize, name: &str) -> Option<usize> {
+		self.nodes[parent]
+			.children
+			.iter()
+			.copied()
+			.find(|c| compare_names(&self.nodes[*c].name, name) == Ordering::Equal)
+	}
+
+	fn insert_child(&mut self, parent: usize, node: Node) {
+		self.nodes.push(node);
+		self.insert_index(parent, self.nodes.len() - 1);
+	}
+
+	fn insert_index(&mut self, parent: usize, idx: usize) {
+		let pos = self.nodes[parent].children.partition_point(|c| {
+			compare_names(&self.nodes[*c].name, &self.nodes[idx].name) == Ordering::Less
+		});
+		self.nodes[parent].children.insert(pos, idx);
+	}
+
+	/// Returns a sector of the source data; the last one may be truncated.
+	fn sector(&self, id: u32) -> SysResult<&'a [u8]> {
+		let start = (id as usize + 1) * self.src_sector_size;
+		if id > MAXREGSECT || start >= self.src.len() {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		Ok(&self.src[start..self.src.len().min(start + self.src_sector_size)])
+	}
+
+	/// Returns the sector IDs of a FAT chain.
+	fn chain(&self, start: u32) -> SysResult<Vec<u32>> {
+		let mut ids = Vec::<u32>::new();
+		let mut id = start;
+		while id != ENDOFCHAIN {
+			if ids.len() >= self.fat.len() {
+				return Err(co::ERROR::INVALID_DATA); // cyclic chain
+			}
+			ids.push(id);
+			id = *self.fat.get(id as usize).ok_or(co::ERROR::INVALID_DATA)?;
+		}
+		Ok(ids)
+	}
+
+	/// Reads the sectors of a FAT chain, up to the given length.
+	fn read_chain(&self, start: u32, len: Option<usize>) -> SysResult<Vec<u8>> {
+		let mut buf = Vec::<u8>::new();
+		for id in self.chain(start)? {
+			if len.is_some_and(|len| buf.len() >= len) {
+				break;
+			}
+			buf.extend_from_slice(self.sector(id)?);
+		}
+		if let Some(len) = len {
+			if buf.len() < len {
+				return Err(co::ERROR::INVALID_DATA);
+			}
+			buf.truncate(len);
+		}
+		Ok(buf)
+	}
+
+	/// Reads a stream from the source data, either from the FAT or from the
+	/// mini stream.
+	fn read_stream(&self, start: u32, size: u64) -> SysResult<Vec<u8>> {
+		if size == 0 {
+			return Ok(Vec::new());
+		} else if size > self.src.len() as u64 {
+			return Err(co::ERROR::INVALID_DATA);
+		} else if size >= MINI_STREAM_CUTOFF {
+			return self.read_chain(start, Some(size as usize));
+		}
+
+		let mut buf = Vec::<u8>::with_capacity(size as usize);
+		let mut id = start;
+		while buf.len() < size as usize {
+			let next = *self
+				.mini_fat
+				.get(id as usize)
+				.ok_or(co::ERROR::INVALID_DATA)?;
+			let offset = id as usize * MINI_SECTOR_SIZE;
+			let sector_id = *self
+				.mini_stream_sectors
+				.get(offset / self.src_sector_size)
+				.ok_or(co::ERROR::INVALID_DATA)?;
+			let within = offset % self.src_sector_size;
+			let mini_sector = self
+				.sector(sector_id)?
+				.get(within..within + MINI_SECTOR_SIZE)
+				.ok_or(co::ERROR::INVALID_DATA)?;
+			buf.extend_from_slice(mini_sector);
+			id = next;
+		}
+		buf.truncate(size as usize);
+		Ok(buf)
+	}
+}
+
+impl Node {
+	fn new(name: &str, kind: co::STGTY) -> Self {
+		Self {
+			name: name.to_owned(),
+			kind,
+			clsid: GUID::default(),
+			state_bits: 0,
+			creation_time: FILETIME::default(),
+			modification_time: FILETIME::default(),
+			data: NodeData::Owned(Vec::new()),
+			children: Vec::new(),
+		}
+	}
+
+	fn from_entry(entry: &DirEntry) -> Self {
+		let is_stream = entry.kind == TYPE_STREAM;
+		Self {
+			name: String::from_utf16_lossy(&entry.name),
+			kind: if is_stream { co::STGTY::STREAM } else { co::STGTY::STORAGE },
+			clsid: entry.clsid,
+			state_bits: entry.state_bits,
+			creation_time: FILETIME::from(entry.creation_time),
+			modification_time: FILETIME::from(entry.modification_time),
+			data: if is_stream {
+				NodeData::Source { start: entry.start, size: entry.size }
+			} else {
+				NodeData::Owned(Vec::new())
+			},
+			children: Vec::new(),
+		}
+	}
+}
+
+impl DirEntry {
+	fn parse(raw: &[u8], version: u16) -> SysResult<Self> {
+		let name_len = (read_word(raw, 0x40)? as usize / 2).clamp(1, 32) - 1; // without null
+		let size = read_qword(raw, 0x78)?;
+		Ok(Self {
+			name: (0..name_len)
+				.map(|i| read_word(raw, i * 2))
+				.collect::<SysResult<_>>()?,
+			kind: raw[0x42],
+			is_red: raw[0x43] == 0,
+			left: read_dword(raw, 0x44)?,
+			right: read_dword(raw, 0x48)?,
+			child: read_dword(raw, 0x4c)?,
+			clsid: GUID::from_le_bytes(raw[0x50..0x60].try_into().unwrap()),
+			state_bits: read_dword(raw, 0x60)?,
+			creation_time: read_qword(raw, 0x64)?,
+			modification_time: read_qword(raw, 0x6c)?,
+			start: read_dword(raw, 0x74)?,
+			size: if version == 3 { size & 0xffff_ffff } else { size }, // high part may be garbage
+		})
+	}
+
+	fn serialize(&self, buf: &mut Vec<u8>) {
+		let start = buf.len();
+		self.name
+			.iter()
+			.for_each(|ch| buf.extend_from_slice(&ch.to_le_bytes()));
+		buf.resize(start + 0x40, 0);
+		let name_len = if self.kind == 0 { 0 } else { (self.name.len() as u16 + 1) * 2 };
+		buf.extend_from_slice(&name_len.to_le_bytes());
+		buf.push(self.kind);
+		buf.push(if self.is_red { 0 } else { 1 });
+		for id in [self.left, self.right, self.child] {
+			buf.extend_from_slice(&(if self.kind == 0 { NOSTREAM } else { id }).to_le_bytes());
+		}
+		buf.extend_from_slice(&self.clsid.to_le_bytes());
+		buf.extend_from_slice(&self.state_bits.to_le_bytes());
+		buf.extend_from_slice(&self.creation_time.to_le_bytes());
+		buf.extend_from_slice(&self.modification_time.to_le_bytes());
+		buf.extend_from_slice(&(if self.kind == 0 { 0 } else { self.start }).to_le_bytes());
+		buf.extend_from_slice(&self.size.to_le_bytes());
+	}
+}
+
+/// Links the sorted siblings as a balanced binary tree, returning its root.
+/// The nodes of the deepest level are red, so the black height is the same
+/// for all paths.
+fn link_siblings(ids: &[u32], depth: usize, max_depth: usize, entries: &mut [DirEntry]) -> u32 {
+	if ids.is_empty() {
+		return NOSTREAM;
+	}
+	let mid = ids.len() / 2;
+	let left = link_siblings(&ids[..mid], depth + 1, max_depth, entries);
+	let right = link_siblings(&ids[mid + 1..], depth + 1, max_depth, entries);
+	let entry = &mut entries[ids[mid] as usize];
+	entry.left = left;
+	entry.right = right;
+	entry.is_red = depth == max_depth && depth > 0;
+	ids[mid]
+}
+
+/// Appends a chain of consecutive sectors to a FAT.
+fn push_chain(fat: &mut Vec<u32>, start: u32, count: usize) {
+	(1..count).for_each(|i| fat.push(start + i as u32));
+	if count > 0 {
+		fat.push(ENDOFCHAIN);
+	}
+}
+
+fn pad_sector(buf: &mut Vec<u8>, ss: usize, fill: u8) {
+	buf.resize(buf.len().next_multiple_of(ss), fill);
+}
+
+fn validate_name(name: &str) -> SysResult<()> {
+	if name.is_empty()
+		|| name.encode_utf16().count() > MAX_NAME_LEN
+		|| name.contains(['/', '\\', ':', '!'])
+	{
+		Err(co::ERROR::INVALID_NAME)
+	} else {
+		Ok(())
+	}
+}
+
+fn compare_names(a: &str, b: &str) -> Ordering {
+	compare_utf16(&a.encode_utf16().collect::<Vec<_>>(), &b.encode_utf16().collect::<Vec<_>>())
+}
+
+/// Compares names as the directory does: shorter names first, then by the
+/// uppercase chars.
+fn compare_utf16(a: &[u16], b: &[u16]) -> Ordering {
+	a.len()
+		.cmp(&b.len())
+		.then_with(|| a.iter().map(upper).cmp(b.iter().map(upper)))
+}
+
+fn upper(ch: &u16) -> u16 {
+	char::from_u32(*ch as u32)
+		.and_then(|c| {
+			let mut up = c.to_uppercase();
+			match (up.next(), up.next()) {
+				(Some(u), None) if (u as u32) <= 0xffff => Some(u as u16),
+				_ => None,
+			}
+		})
+		.unwrap_or(*ch)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Builds a minimal version 3 file, as described in the specification:
+	/// header, FAT, directory, mini FAT and mini stream sectors, with a single
+	/// 5-byte stream named `A`.
+	fn fixture() -> Vec<u8> {
+		let mut data = vec![0u8; 512 * 5];
+		let put = |data: &mut [u8], at: usize, val: &[u8]| {
+			data[at..at + val.len()].copy_from_slice(val);
+		};
+
+		put(&mut data, 0, &SIGNATURE);
+		put(&mut data, 0x18, &0x003e_u16.to_le_bytes()); // minor version
+		put(&mut data, 0x1a, &3u16.to_le_bytes()); // major version
+		put(&mut data, 0x1c, &0xfffe_u16.to_le_bytes()); // byte order
+		put(&mut data, 0x1e, &9u16.to_le_bytes()); // sector shift
+		put(&mut data, 0x20, &6u16.to_le_bytes()); // mini sector shift
+		put(&mut data, 0x2c, &1u32.to_le_bytes()); // FAT sectors
+		put(&mut data, 0x30, &1u32.to_le_bytes()); // first directory sector
+		put(&mut data, 0x38, &4096u32.to_le_bytes()); // mini stream cutoff
+		put(&mut data, 0x3c, &2u32.to_le_bytes()); // first mini FAT sector
+		put(&mut data, 0x40, &1u32.to_le_bytes()); // mini FAT sectors
+		put(&mut data, 0x44, &ENDOFCHAIN.to_le_bytes()); // first DIFAT sector
+		put(&mut data, 0x4c, &0u32.to_le_bytes()); // DIFAT[0]
+		(1..HEADER_DIFAT_LEN).for_each(|i| put(&mut data, 0x4c + i * 4, &FREESECT.to_le_bytes()));
+
+		let fat = 512;
+		put(&mut data, fat, &FATSECT.to_le_bytes());
+		(1..4).for_each(|i| put(&mut data, fat + i * 4, &ENDOFCHAIN.to_le_bytes()));
+		(4..128).for_each(|i| put(&mut data, fat + i * 4, &FREESECT.to_le_bytes()));
+
+		let dir = 512 * 2;
+		for (i, (name, kind, child, start, size)) in [
+			("Root Entry", TYPE_ROOT, 1, 3, 64u64),
+			("A", TYPE_STREAM, NOSTREAM, 0, 5),
+			("", 0, NOSTREAM, 0, 0),
+			("", 0, NOSTREAM, 0, 0),
+		]
+		.into_iter()
+		.enumerate()
+		{
+			let entry = dir + i * DIR_ENTRY_SIZE;
+			let wname = name.encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<_>>();
+			put(&mut data, entry, &wname);
+			if !name.is_empty() {
+				put(&mut data, entry + 64, &(wname.len() as u16 + 2).to_le_bytes());
+				data[entry + 67] = 1; // black
+			}
+			data[entry + 66] = kind;
+			put(&mut data, entry + 68, &NOSTREAM.to_le_bytes()); // left
+			put(&mut data, entry + 72, &NOSTREAM.to_le_bytes()); // right
+			put(&mut data, entry + 76, &child.to_le_bytes());
+			put(&mut data, entry + 116, &(start as u32).to_le_bytes());
+			put(&mut data, entry + 120, &size.to_le_bytes());
+		}
+
+		let mini_fat = 512 * 3;
+		put(&mut data, mini_fat, &ENDOFCHAIN.to_le_bytes());
+		(1..128).for_each(|i| put(&mut data, mini_fat + i * 4, &FREESECT.to_le_bytes()));
+
+		put(&mut data, 512 * 4, b"Hello");
+		data
+	}
+
+	#[test]
+	fn parse_fixture() {
+		let data = fixture();
+		let cf = CompoundFile::parse(&data).unwrap();
+		assert_eq!(cf.version(), 3);
+
+		let elems = cf.enum_elements("").unwrap();
+		assert_eq!(elems.len(), 1);
+		assert_eq!(elems[0].name, "A");
+		assert_eq!(elems[0].kind, co::STGTY::STREAM);
+		assert_eq!(elems[0].size, 5);
+		assert_eq!(cf.open_stream("a").unwrap(), b"Hello");
+	}
+
+	#[test]
+	fn round_trip() {
+		let big = (0..10_000u32).map(|n| n as u8).collect::<Vec<_>>();
+		for version in [3, 4] {
+			let data = fixture();
+			let mut cf = CompoundFile::parse(&data).unwrap();
+			cf.set_version(version).unwrap();
+			cf.create_storage("Data").unwrap();
+			cf.create_stream("Data/Big", &big).unwrap();
+			cf.create_stream("Data/Small", b"small").unwrap();
+			cf.rename_element("A", "B").unwrap();
+
+			let blob = cf.serialize().unwrap();
+			assert_eq!(blob[..8], SIGNATURE);
+			assert_eq!(read_word(&blob, 0x1a).unwrap(), version);
+			assert_eq!(blob.len() % if version == 3 { 512 } else { 4096 }, 0);
+
+			let parsed = CompoundFile::parse(&blob).unwrap();
+			assert_eq!(parsed.open_stream("B").unwrap(), b"Hello");
+			assert_eq!(parsed.open_stream("Data/Big").unwrap(), big);
+			assert_eq!(parsed.open_stream("Data/Small").unwrap(), b"small");
+			assert!(parsed.open_stream("A").is_err());
+			assert_eq!(parsed.enum_elements("Data").unwrap().len(), 2);
+			assert_eq!(parsed.serialize().unwrap(), blob);
+		}
+	}
+
+	#[test]
+	fn malformed() {
+		let mut data = fixture();
+		assert!(CompoundFile::parse(&data[..512]).is_err()); // no sectors
+		data[512 * 2 + 66] = TYPE_STREAM; // root entry with the wrong type
+		assert!(CompoundFile::parse(&data).is_err());
+	}
+}
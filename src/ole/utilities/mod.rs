@@ -0,0 +1,3 @@
+mod compound_file;
+
+pub use compound_file::{CompoundFile, CompoundFileStat};
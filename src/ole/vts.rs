@@ -155,6 +155,8 @@ pub struct IStreamVT {
 	pub Clone: fn(COMPTR, *mut COMPTR) -> HRES,
 }
 
+/// [`IUnknown`](crate::IUnknown) virtual table, the base of all other COM
+/// virtual tables.
 #[repr(C)]
 pub struct IUnknownVT {
 	pub QueryInterface: fn(COMPTR, PCVOID, *mut COMPTR) -> HRES,
@@ -1,5 +1,5 @@
 use crate::co::*;
 
-const_values_pub! { HRESULT;
+const_values_pub! { HRESULT NAMES_OLEAUT;
 	CTL_E_INVALIDPICTURE 0x800a_01e1
 }
@@ -3,6 +3,7 @@
 use crate::kernel::ffi_types::*;
 use crate::ole::vts::*;
 
+/// [`IDispatch`](crate::IDispatch) virtual table.
 #[repr(C)]
 pub struct IDispatchVT {
 	pub IUnknownVT: IUnknownVT,
@@ -6,6 +6,7 @@ mod enums;
 mod funcs;
 mod handles;
 mod structs;
+mod utilities;
 
 pub mod co;
 pub(in crate::shell) mod ffi;
@@ -22,6 +23,7 @@ pub mod decl {
 	pub use super::funcs::*;
 	pub use super::handles::decl::*;
 	pub use super::structs::*;
+	pub use super::utilities::*;
 }
 
 pub mod traits {
@@ -0,0 +1,647 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::bytes::*;
+use crate::prelude::*;
+
+/// Signature of the extension block of file system items, which holds the
+/// creation and access dates, and the long name.
+const SIG_FS_EXTENSION: u32 = 0xbeef_0004;
+/// Version of the file system extension block written by Windows 8 and later.
+const FS_EXTENSION_VERSION: u16 = 9;
+
+/// Owned [`ITEMIDLIST`](crate::ITEMIDLIST), a sequence of `SHITEMID` items
+/// which identifies an object in the shell namespace.
+///
+/// The data is kept in its native layout, including the terminating zero, so
+/// it can be passed to the shell as a [`PIDL`](crate::PIDL). Two lists are
+/// equal if their raw items are equal, which doesn't account for the
+/// different ways the same object can be identified.
+///
+/// The common item kinds can be decoded with
+/// [`IdListItem::parse`](crate::IdListItem::parse), without calling the shell.
+///
+/// # Examples
+///
+/// Listing the items of a folder `ITEMIDLIST`:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let _com_guard = w::CoInitializeEx(
+///     co::COINIT::APARTMENTTHREADED | co::COINIT::DISABLE_OLE1DDE)?;
+///
+/// let f = w::SHCreateItemFromParsingName::<w::IShellItem>(
+///     "C:\\Temp",
+///     None::<&w::IBindCtx>,
+/// )?;
+///
+/// let id_list = w::IdList::from_object(&f)?;
+/// for item in id_list.decode()?.iter() {
+///     match item {
+///         w::IdListItem::Root { clsid, .. } => println!("Root {}", clsid),
+///         w::IdListItem::Drive { path } => println!("Drive {}", path),
+///         w::IdListItem::FileSystem(fs) => println!("{}", fs.name()),
+///         _ => println!("Other"),
+///     }
+/// }
+/// # w::AnyResult::Ok(())
+/// ```
+///
+/// Building an `ITEMIDLIST` and creating an
+/// [`IShellItem`](crate::IShellItem) from it:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let _com_guard = w::CoInitializeEx(
+///     co::COINIT::APARTMENTTHREADED | co::COINIT::DISABLE_OLE1DDE)?;
+///
+/// let mut id_list = w::IdList::new();
+/// id_list.push_item(&w::IdListItem::Root {
+///     sort_index: 0x50,
+///     clsid: w::GUID::from_str("20d04fe0-3aea-1069-a2d8-08002b30309d"), // This PC
+/// })?;
+/// id_list.push_item(&w::IdListItem::Drive { path: "C:\\".to_owned() })?;
+///
+/// let item = id_list.to_shell_item::<w::IShellItem>()?;
+/// println!("{}", item.GetDisplayName(co::SIGDN::FILESYSPATH)?);
+/// # w::AnyResult::Ok(())
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct IdList {
+	data: Vec<u8>, // always ends with the terminating zero
+}
+
+impl Default for IdList {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl IdList {
+	/// Creates an empty `ITEMIDLIST`, which identifies the desktop.
+	#[must_use]
+	pub fn new() -> Self {
+		Self { data: vec![0, 0] }
+	}
+
+	/// Parses a serialized `ITEMIDLIST`, which must end with the terminating
+	/// zero; any data after it is ignored.
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(data: &[u8]) -> SysResult<Self> {
+		let mut pos = 0;
+		loop {
+			let cb = read_word(data, pos)? as usize;
+			if cb == 0 {
+				return Ok(Self { data: data[..pos + 2].to_vec() });
+			} else if cb < 2 {
+				return Err(co::ERROR::INVALID_DATA);
+			}
+			pos += cb;
+		}
+	}
+
+	/// Copies the `ITEMIDLIST` pointed to by the [`PIDL`](crate::PIDL). A null
+	/// `PIDL` yields an empty list.
+	///
+	/// # Safety
+	///
+	/// The `PIDL` must be null or point to a valid `ITEMIDLIST`, ended by the
+	/// terminating zero, otherwise the items will be read out of bounds.
+	#[must_use]
+	pub unsafe fn from_pidl(pidl: &PIDL) -> Self {
+		let p = pidl.ptr() as *const u8;
+		if p.is_null() {
+			return Self::new();
+		}
+
+		let mut len = 0;
+		loop {
+			let cb = unsafe { u16::from_le_bytes([*p.add(len), *p.add(len + 1)]) } as usize;
+			if cb == 0 {
+				break;
+			}
+			len += cb;
+		}
+		Self {
+			data: unsafe { std::slice::from_raw_parts(p, len + 2) }.to_vec(),
+		}
+	}
+
+	/// Retrieves the `ITEMIDLIST` of a shell object, like an
+	/// [`IShellItem`](crate::IShellItem), with
+	/// [`SHGetIDListFromObject`](crate::SHGetIDListFromObject).
+	#[must_use]
+	pub fn from_object(obj: &impl ole_IUnknown) -> HrResult<Self> {
+		SHGetIDListFromObject(obj).map(|pidl| unsafe { Self::from_pidl(&pidl) })
+	}
+
+	/// Creates a shell item from the `ITEMIDLIST` with
+	/// [`SHCreateItemFromIDList`](crate::SHCreateItemFromIDList).
+	#[must_use]
+	pub fn to_shell_item<T>(&self) -> HrResult<T>
+	where
+		T: shell_IShellItem,
+	{
+		let pidl = unsafe { PIDL::from_ptr(self.data.as_ptr() as _) };
+		SHCreateItemFromIDList(&pidl)
+	}
+
+	/// Returns the serialized `ITEMIDLIST`, including the terminating zero.
+	#[must_use]
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.data
+	}
+
+	/// Returns the number of items.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.items().count()
+	}
+
+	/// Returns `true` if there are no items, which identifies the desktop.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.data.len() == 2
+	}
+
+	/// Returns an iterator over the data of each item, without its `cb`
+	/// size field.
+	#[must_use]
+	pub fn items(&self) -> impl Iterator<Item = &[u8]> + '_ {
+		let mut pos = 0;
+		std::iter::from_fn(move || {
+			let cb = u16::from_le_bytes([self.data[pos], self.data[pos + 1]]) as usize;
+			if cb == 0 {
+				None
+			} else {
+				let item = &self.data[pos + 2..pos + cb];
+				pos += cb;
+				Some(item)
+			}
+		})
+	}
+
+	/// Returns the data of the last item, which identifies the object within
+	/// its parent folder.
+	#[must_use]
+	pub fn last(&self) -> Option<&[u8]> {
+		self.items().last()
+	}
+
+	/// Returns the `ITEMIDLIST` of the parent folder, or `None` if the list is
+	/// empty.
+	#[must_use]
+	pub fn parent(&self) -> Option<Self> {
+		let count = self.len();
+		if count == 0 { None } else { Some(self.split_at(count - 1).0) }
+	}
+
+	/// Splits the list into the first `count` items and the remaining ones,
+	/// like an absolute folder `ITEMIDLIST` and a child `ITEMIDLIST` relative
+	/// to it.
+	///
+	/// # Panics
+	///
+	/// Panics if `count` is greater than the number of items.
+	#[must_use]
+	pub fn split_at(&self, count: usize) -> (Self, Self) {
+		let offset = self.item_offset(count).expect("Item count out of bounds.");
+		let mut head = self.data[..offset].to_vec();
+		head.extend_from_slice(&[0, 0]);
+		(Self { data: head }, Self { data: self.data[offset..].to_vec() })
+	}
+
+	/// Returns `true` if the items of `other` are the first items of this list.
+	#[must_use]
+	pub fn starts_with(&self, other: &Self) -> bool {
+		let prefix = &other.data[..other.data.len() - 2];
+		self.data.starts_with(prefix) && self.item_offset(other.len()) == Some(prefix.len())
+	}
+
+	/// Appends an item, given its data without the `cb` size field.
+	///
+	/// Data longer than 65,533 bytes yields
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER).
+	pub fn push(&mut self, item: &[u8]) -> SysResult<()> {
+		let cb = u16::try_from(item.len() + 2).map_err(|_| co::ERROR::INVALID_PARAMETER)?;
+		self.data.truncate(self.data.len() - 2);
+		self.data.extend_from_slice(&cb.to_le_bytes());
+		self.data.extend_from_slice(item);
+		self.data.extend_from_slice(&[0, 0]);
+		Ok(())
+	}
+
+	/// Encodes and appends an item.
+	///
+	/// Items which cannot be encoded, like
+	/// [`IdListItem::Other`](crate::IdListItem::Other), or strings too long,
+	/// yield [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER).
+	pub fn push_item(&mut self, item: &IdListItem) -> SysResult<()> {
+		self.push(&item.serialize()?)
+	}
+
+	/// Appends all items of another list.
+	pub fn append(&mut self, other: &Self) {
+		self.data.truncate(self.data.len() - 2);
+		self.data.extend_from_slice(&other.data);
+	}
+
+	/// Decodes all items.
+	///
+	/// Malformed items yield
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn decode(&self) -> SysResult<Vec<IdListItem>> {
+		self.items().map(IdListItem::parse).collect()
+	}
+
+	/// Returns the offset of the item at the given index, which is the
+	/// terminating zero if `idx` equals the number of items.
+	fn item_offset(&self, idx: usize) -> Option<usize> {
+		let mut pos = 0;
+		for _ in 0..idx {
+			let cb = u16::from_le_bytes([self.data[pos], self.data[pos + 1]]) as usize;
+			if cb == 0 {
+				return None;
+			}
+			pos += cb;
+		}
+		Some(pos)
+	}
+}
+
+/// A decoded item of an [`IdList`](crate::IdList).
+#[derive(Clone, PartialEq, Eq)]
+pub enum IdListItem {
+	/// A root folder, like This PC or the Recycle Bin, identified by its
+	/// CLSID.
+	Root {
+		/// Order of the folder among the other root folders.
+		sort_index: u8,
+		/// CLSID of the folder.
+		clsid: GUID,
+	},
+	/// A drive, child of This PC.
+	Drive {
+		/// Path of the drive, like `C:\`.
+		path: String,
+	},
+	/// A file or folder in the file system.
+	FileSystem(IdListFsItem),
+	/// A network location.
+	Network {
+		/// Class type of the item, like `0x42` for a server or `0x43` for a
+		/// share.
+		class_type: u8,
+		/// Location, like `\\server\share`.
+		location: String,
+		/// Description of the location.
+		description: Option<String>,
+		/// Comments of the location.
+		comments: Option<String>,
+	},
+	/// Any other item, with its class type – the first data byte.
+	Other(u8),
+}
+
+/// A file or folder item of an [`IdList`](crate::IdList).
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct IdListFsItem {
+	/// Whether the item is a folder.
+	pub is_folder: bool,
+	/// Size of the file, in bytes; only the low 32 bits.
+	pub file_size: u32,
+	/// Attributes of the file; only the low 16 bits.
+	pub file_attributes: co::FILE_ATTRIBUTE,
+	/// Last write time, in local time with a 2-second precision.
+	pub write_time: Option<SYSTEMTIME>,
+	/// Creation time, in local time with a 2-second precision.
+	pub creation_time: Option<SYSTEMTIME>,
+	/// Last access time, in local time with a 2-second precision.
+	pub access_time: Option<SYSTEMTIME>,
+	/// Primary name of the item, usually the 8.3 short name.
+	pub short_name: String,
+	/// Long name of the item, from its extension block.
+	pub long_name: Option<String>,
+}
+
+impl IdListFsItem {
+	/// Returns the long name if present, otherwise the short name.
+	#[must_use]
+	pub fn name(&self) -> &str {
+		self.long_name.as_deref().unwrap_or(&self.short_name)
+	}
+}
+
+impl IdListItem {
+	/// Decodes the data of an item, without its `cb` size field, as returned
+	/// by [`IdList::items`](crate::IdList::items).
+	///
+	/// Malformed items yield
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(item: &[u8]) -> SysResult<Self> {
+		let class_type = item.first().copied().unwrap_or_default();
+		Ok(match class_type {
+			0x1f => Self::Root {
+				sort_index: item.get(1).copied().ok_or(co::ERROR::INVALID_DATA)?,
+				clsid: read_guid(item, 2)?,
+			},
+			0x20..=0x2f if class_type & 0x01 != 0 => Self::Drive { path: read_ansiz(item, 1)? },
+			0x30..=0x3f => Self::FileSystem(Self::parse_fs(item)?),
+			0x40..=0x4f => {
+				let flags = item.get(2).copied().ok_or(co::ERROR::INVALID_DATA)?;
+				let location = read_ansiz(item, 3)?;
+				let mut pos = 3 + location.len() + 1;
+				let mut next_str = |present: bool| -> SysResult<Option<String>> {
+					if !present {
+						return Ok(None);
+					}
+					let s = read_ansiz(item, pos)?;
+					pos += s.len() + 1;
+					Ok(Some(s))
+				};
+				Self::Network {
+					class_type,
+					description: next_str(flags & 0x80 != 0)?,
+					comments: next_str(flags & 0x40 != 0)?,
+					location,
+				}
+			},
+			_ => Self::Other(class_type),
+		})
+	}
+
+	fn parse_fs(item: &[u8]) -> SysResult<IdListFsItem> {
+		let flags = item[0] & 0x0f;
+		let mut fs = IdListFsItem {
+			is_folder: flags & 0x01 != 0,
+			file_size: read_dword(item, 2)?,
+			write_time: fat_to_systemtime(read_dword(item, 6)?),
+			file_attributes: unsafe { co::FILE_ATTRIBUTE::from_raw(read_word(item, 10)? as _) },
+			..Default::default()
+		};
+
+		let mut pos = if flags & 0x04 != 0 {
+			fs.short_name = read_utf16z(item, 12)?;
+			12 + (fs.short_name.encode_utf16().count() + 1) * 2
+		} else {
+			fs.short_name = read_ansiz(item, 12)?;
+			(12 + fs.short_name.len() + 1).next_multiple_of(2)
+		};
+
+		while pos + 8 <= item.len() {
+			let block_len = read_word(item, pos)? as usize;
+			if block_len < 8 {
+				break;
+			}
+			let block = item
+				.get(pos..pos + block_len)
+				.ok_or(co::ERROR::INVALID_DATA)?;
+			if read_dword(block, 4)? == SIG_FS_EXTENSION {
+				let version = read_word(block, 2)?;
+				fs.creation_time = fat_to_systemtime(read_dword(block, 8)?);
+				fs.access_time = fat_to_systemtime(read_dword(block, 12)?);
+				let name_off = match version {
+					9.. => Some(46),
+					8 => Some(42),
+					7 => Some(38),
+					3..=6 => Some(20),
+					_ => None,
+				};
+				if let Some(name_off) = name_off {
+					fs.long_name = Some(read_utf16z(block, name_off)?);
+				}
+				break;
+			}
+			pos += block_len;
+		}
+		Ok(fs)
+	}
+
+	/// Encodes the item, returning its data without the `cb` size field.
+	fn serialize(&self) -> SysResult<Vec<u8>> {
+		let mut buf = Vec::<u8>::new();
+		match self {
+			Self::Root { sort_index, clsid } => {
+				buf.push(0x1f);
+				buf.push(*sort_index);
+				buf.extend_from_slice(&clsid.to_le_bytes());
+			},
+			Self::Drive { path } => {
+				buf.push(0x2f);
+				push_ansiz(&mut buf, path)?;
+				buf.resize(buf.len().max(23), 0); // as written by the shell
+			},
+			Self::FileSystem(fs) => {
+				let short_unicode = !fs.short_name.is_ascii();
+				let mut class_type = if fs.is_folder { 0x31 } else { 0x32 };
+				if short_unicode {
+					class_type |= 0x04;
+				}
+				buf.push(class_type);
+				buf.push(0);
+				buf.extend_from_slice(&fs.file_size.to_le_bytes());
+				buf.extend_from_slice(&systemtime_to_fat(&fs.write_time).to_le_bytes());
+				buf.extend_from_slice(&(fs.file_attributes.raw() as u16).to_le_bytes());
+				if short_unicode {
+					push_utf16z(&mut buf, &fs.short_name);
+				} else {
+					push_ansiz(&mut buf, &fs.short_name)?;
+					buf.resize(buf.len().next_multiple_of(2), 0);
+				}
+
+				if let Some(long_name) = &fs.long_name {
+					let block_off = (buf.len() + 2) as u16; // counted from the cb field
+					let mut block = Vec::<u8>::new();
+					block.extend_from_slice(&[0, 0]); // size, set below
+					block.extend_from_slice(&FS_EXTENSION_VERSION.to_le_bytes());
+					block.extend_from_slice(&SIG_FS_EXTENSION.to_le_bytes());
+					block.extend_from_slice(&systemtime_to_fat(&fs.creation_time).to_le_bytes());
+					block.extend_from_slice(&systemtime_to_fat(&fs.access_time).to_le_bytes());
+					block.extend_from_slice(&46u16.to_le_bytes()); // offset of the long name
+					block.resize(46, 0); // file reference and reserved fields
+					push_utf16z(&mut block, long_name);
+					block.extend_from_slice(&block_off.to_le_bytes());
+					let block_len =
+						u16::try_from(block.len()).map_err(|_| co::ERROR::INVALID_PARAMETER)?;
+					block[..2].copy_from_slice(&block_len.to_le_bytes());
+					buf.append(&mut block);
+				}
+			},
+			Self::Network {
+				class_type,
+				location,
+				description,
+				comments,
+			} => {
+				let mut flags = 0u8;
+				if description.is_some() {
+					flags |= 0x80;
+				}
+				if comments.is_some() {
+					flags |= 0x40;
+				}
+				buf.extend_from_slice(&[*class_type, 0, flags]);
+				push_ansiz(&mut buf, location)?;
+				for s in [description, comments].into_iter().flatten() {
+					push_ansiz(&mut buf, s)?;
+				}
+			},
+			Self::Other(_) => return Err(co::ERROR::INVALID_PARAMETER),
+		}
+		Ok(buf)
+	}
+}
+
+/// Converts a FAT date, in the low word, and time, in the high word.
+fn fat_to_systemtime(dt: u32) -> Option<SYSTEMTIME> {
+	let (date, time) = ((dt & 0xffff) as u16, (dt >> 16) as u16);
+	let (year, month, day) = (1980 + (date >> 9), (date >> 5) & 0x0f, date & 0x1f);
+	if dt == 0 || !(1..=12).contains(&month) || day == 0 {
+		return None;
+	}
+
+	// Sakamoto's algorithm.
+	const OFFS: [u16; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+	let y = if month < 3 { year - 1 } else { year };
+	let day_of_week = (y + y / 4 - y / 100 + y / 400 + OFFS[month as usize - 1] + day) % 7;
+
+	Some(SYSTEMTIME {
+		wYear: year,
+		wMonth: month,
+		wDayOfWeek: day_of_week,
+		wDay: day,
+		wHour: time >> 11,
+		wMinute: (time >> 5) & 0x3f,
+		wSecond: (time & 0x1f) * 2,
+		wMilliseconds: 0,
+	})
+}
+
+fn systemtime_to_fat(st: &Option<SYSTEMTIME>) -> u32 {
+	st.as_ref().map_or(0, |st| {
+		let date = (st.wYear.saturating_sub(1980).min(127) << 9) | (st.wMonth << 5) | st.wDay;
+		let time = (st.wHour << 11) | (st.wMinute << 5) | (st.wSecond / 2);
+		(date as u32) | ((time as u32) << 16)
+	})
+}
+
+/// Appends the string as null-terminated ANSI; only Latin-1 chars are
+/// accepted.
+fn push_ansiz(buf: &mut Vec<u8>, s: &str) -> SysResult<()> {
+	for ch in s.chars() {
+		buf.push(u8::try_from(ch).map_err(|_| co::ERROR::INVALID_PARAMETER)?);
+	}
+	buf.push(0);
+	Ok(())
+}
+
+fn push_utf16z(buf: &mut Vec<u8>, s: &str) {
+	s.encode_utf16()
+		.chain(std::iter::once(0))
+		.for_each(|ch| buf.extend_from_slice(&ch.to_le_bytes()));
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[rustfmt::skip]
+	const FIXTURE: &[u8] = &[
+		// This PC
+		0x14, 0x00, 0x1f, 0x50,
+		0xe0, 0x4f, 0xd0, 0x20, 0xea, 0x3a, 0x69, 0x10,
+		0xa2, 0xd8, 0x08, 0x00, 0x2b, 0x30, 0x30, 0x9d,
+		// C:\
+		0x19, 0x00, 0x2f, b'C', b':', b'\\', 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, // terminator
+	];
+
+	#[test]
+	fn parse_fixture() {
+		let id_list = IdList::parse(FIXTURE).unwrap();
+		assert_eq!(id_list.len(), 2);
+		assert_eq!(id_list.as_bytes(), FIXTURE);
+
+		let items = id_list.decode().unwrap();
+		assert!(
+			items[0]
+				== IdListItem::Root {
+					sort_index: 0x50,
+					clsid: GUID::from_str("20d04fe0-3aea-1069-a2d8-08002b30309d"),
+				}
+		);
+		assert!(items[1] == IdListItem::Drive { path: "C:\\".to_owned() });
+	}
+
+	#[test]
+	fn build_fixture() {
+		let mut id_list = IdList::new();
+		id_list
+			.push_item(&IdListItem::Root {
+				sort_index: 0x50,
+				clsid: GUID::from_str("20d04fe0-3aea-1069-a2d8-08002b30309d"),
+			})
+			.unwrap();
+		id_list
+			.push_item(&IdListItem::Drive { path: "C:\\".to_owned() })
+			.unwrap();
+		assert_eq!(id_list.as_bytes(), FIXTURE);
+
+		let from_pidl = unsafe { IdList::from_pidl(&PIDL::from_ptr(FIXTURE.as_ptr() as _)) };
+		assert!(from_pidl == id_list);
+	}
+
+	#[test]
+	fn file_system_round_trip() {
+		let st = SYSTEMTIME {
+			wYear: 2024,
+			wMonth: 1,
+			wDayOfWeek: 1, // Monday
+			wDay: 15,
+			wHour: 10,
+			wMinute: 20,
+			wSecond: 30,
+			wMilliseconds: 0,
+		};
+		let fs = IdListFsItem {
+			is_folder: false,
+			file_size: 1234,
+			file_attributes: co::FILE_ATTRIBUTE::ARCHIVE,
+			write_time: Some(st),
+			creation_time: Some(st),
+			access_time: None,
+			short_name: "LONGFI~1.TXT".to_owned(),
+			long_name: Some("Long file name \u{e9}.txt".to_owned()),
+		};
+
+		let mut id_list = IdList::parse(FIXTURE).unwrap();
+		id_list.push_item(&IdListItem::FileSystem(fs.clone())).unwrap();
+		let parsed = IdList::parse(id_list.as_bytes()).unwrap();
+		assert!(parsed == id_list);
+		match &parsed.decode().unwrap()[..] {
+			[_, _, IdListItem::FileSystem(decoded)] => assert!(*decoded == fs),
+			_ => panic!("unexpected items"),
+		}
+
+		let (parent, child) = parsed.split_at(2);
+		assert_eq!(parent.as_bytes(), FIXTURE);
+		assert_eq!(child.len(), 1);
+		assert!(parsed.starts_with(&parent));
+		assert!(parsed.parent().unwrap() == parent);
+	}
+
+	#[test]
+	fn malformed() {
+		assert!(IdList::parse(&FIXTURE[..FIXTURE.len() - 1]).is_err());
+		assert!(IdList::parse(&[0x01, 0x00, 0x00, 0x00]).is_err());
+		assert!(IdListItem::parse(&[0x1f, 0x50, 0xe0]).is_err());
+	}
+}
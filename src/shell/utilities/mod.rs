@@ -0,0 +1,5 @@
+mod id_list;
+mod shell_link_file;
+
+pub use id_list::{IdList, IdListFsItem, IdListItem};
+pub use shell_link_file::{ShellLinkFile, ShellLinkInfo, ShellLinkProperty};
@@ -0,0 +1,901 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::bytes::*;
+
+const HEADER_SIZE: usize = 0x4c;
+const LINK_CLSID: GUID = GUID::from_str("00021401-0000-0000-c000-000000000046");
+const FMTID_NAMED: GUID = GUID::from_str("d5cdd505-2e9c-101b-9397-08002b2cf9ae");
+
+const SIG_ENVIRONMENT: u32 = 0xa000_0001;
+const SIG_SPECIAL_FOLDER: u32 = 0xa000_0005;
+const SIG_ICON_ENVIRONMENT: u32 = 0xa000_0007;
+const SIG_PROPERTY_STORE: u32 = 0xa000_0009;
+const SIG_KNOWN_FOLDER: u32 = 0xa000_000b;
+const SPS_VERSION: u32 = 0x5350_5331; // "1SPS"
+
+/// Flags computed from the contents when serializing.
+const STRUCTURAL_FLAGS: co::SLDF = unsafe {
+	co::SLDF::from_raw(
+		co::SLDF::HAS_ID_LIST.raw()
+			| co::SLDF::HAS_LINK_INFO.raw()
+			| co::SLDF::HAS_NAME.raw()
+			| co::SLDF::HAS_RELPATH.raw()
+			| co::SLDF::HAS_WORKINGDIR.raw()
+			| co::SLDF::HAS_ARGS.raw()
+			| co::SLDF::HAS_ICONLOCATION.raw()
+			| co::SLDF::UNICODE.raw()
+			| co::SLDF::HAS_EXP_SZ.raw()
+			| co::SLDF::HAS_EXP_ICON_SZ.raw(),
+	)
+};
+
+/// Pure Rust reader and writer of
+/// [Shell Link](https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-shllink/16cb4ca1-9339-4d0c-a68d-bf1d6cc0f943)
+/// (`.lnk`) files, which doesn't depend on the shell.
+///
+/// A shortcut written by this type is read by
+/// [`IShellLink`](crate::IShellLink) just like one saved by the shell itself.
+/// Strings without a Unicode version in the file are decoded as Latin-1.
+///
+/// # Examples
+///
+/// Reading a shortcut:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let data = std::fs::read("C:\\Temp\\app.lnk")?;
+/// let lnk = w::ShellLinkFile::parse(&data)?;
+/// println!("Target: {}", lnk.target_path().unwrap_or_default());
+/// println!("Arguments: {}", lnk.arguments.unwrap_or_default());
+/// # w::AnyResult::Ok(())
+/// ```
+///
+/// Writing a shortcut:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let mut lnk = w::ShellLinkFile::new("C:\\Program Files\\App\\app.exe");
+/// lnk.arguments = Some("--verbose".to_owned());
+/// lnk.working_dir = Some("C:\\Program Files\\App".to_owned());
+/// std::fs::write("C:\\Temp\\app.lnk", lnk.serialize()?)?;
+/// # w::AnyResult::Ok(())
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct ShellLinkFile {
+	/// Link flags. The flags telling which parts are present, like
+	/// [`co::SLDF::HAS_ARGS`](crate::co::SLDF::HAS_ARGS), are computed when
+	/// serializing, and removed when parsing.
+	pub flags: co::SLDF,
+	/// Attributes of the target.
+	pub file_attributes: co::FILE_ATTRIBUTE,
+	/// Creation time of the target.
+	pub creation_time: FILETIME,
+	/// Last access time of the target.
+	pub access_time: FILETIME,
+	/// Last write time of the target.
+	pub write_time: FILETIME,
+	/// Size of the target, in bytes; only the low 32 bits.
+	pub file_size: u32,
+	/// Index of the icon within the icon location.
+	pub icon_index: i32,
+	/// How the target window is shown.
+	pub show_cmd: co::SW,
+	/// Keyboard shortcut which starts the target.
+	pub hot_key: Option<(co::VK, co::HOTKEYF)>,
+	/// The raw `ITEMIDLIST` of the target, including its terminating zero,
+	/// which can be parsed with [`IdList::parse`](crate::IdList::parse).
+	pub id_list: Option<Vec<u8>>,
+	/// Information used to resolve the target if it's moved.
+	pub link_info: Option<ShellLinkInfo>,
+	/// Description of the shortcut.
+	pub name: Option<String>,
+	/// Path of the target relative to the `.lnk` file.
+	pub relative_path: Option<String>,
+	/// Working directory of the target.
+	pub working_dir: Option<String>,
+	/// Command line arguments passed to the target.
+	pub arguments: Option<String>,
+	/// Path of the icon.
+	pub icon_location: Option<String>,
+	/// Path of the target, with environment variables like
+	/// `%ProgramFiles%\App\app.exe`.
+	pub environment_target: Option<String>,
+	/// Path of the icon, with environment variables.
+	pub icon_environment: Option<String>,
+	/// Known folder of the target, and the offset of its first item in
+	/// `id_list`.
+	pub known_folder: Option<(co::KNOWNFOLDERID, u32)>,
+	/// CSIDL of the special folder of the target, and the offset of its first
+	/// item in `id_list`.
+	pub special_folder: Option<(u32, u32)>,
+	/// Properties of the target, like the application user model ID.
+	pub properties: Vec<ShellLinkProperty>,
+	/// Other extra data blocks, kept verbatim as signature and data.
+	pub extra_blocks: Vec<(u32, Vec<u8>)>,
+}
+
+/// The `LinkInfo` of a [`ShellLinkFile`](crate::ShellLinkFile), with the
+/// local or network location of the target.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ShellLinkInfo {
+	/// Type of the drive of the local volume.
+	pub drive_type: co::DRIVE,
+	/// Serial number of the local volume.
+	pub drive_serial_number: u32,
+	/// Label of the local volume.
+	pub volume_label: String,
+	/// Path of the target on the local volume; if `None`, there is no volume
+	/// information.
+	pub local_base_path: Option<String>,
+	/// Network share of the target, like `\\server\share`.
+	pub net_name: Option<String>,
+	/// Device name mapped to the share, like `Z:`.
+	pub device_name: Option<String>,
+	/// Network provider type, like `0x0002_0000` for Windows networking;
+	/// zero if unknown.
+	pub network_provider_type: u32,
+	/// Path appended to the local base path or the network share.
+	pub common_path_suffix: String,
+}
+
+/// A property from the property store of a
+/// [`ShellLinkFile`](crate::ShellLinkFile).
+#[derive(Clone, PartialEq, Eq)]
+pub struct ShellLinkProperty {
+	/// Format ID of the property set.
+	pub fmtid: GUID,
+	/// Property ID; zero for named properties.
+	pub pid: u32,
+	/// Name of the property, for the named property set.
+	pub name: Option<String>,
+	/// Raw serialized value, starting with its
+	/// [`co::VT`](crate::co::VT) type.
+	pub value: Vec<u8>,
+}
+
+impl ShellLinkFile {
+	/// Returns a shortcut to the given local or UNC path, resolved through its
+	/// `LinkInfo`.
+	#[must_use]
+	pub fn new(target: &str) -> Self {
+		let link_info = if let Some(unc) = target.strip_prefix("\\\\") {
+			let mut parts = unc.splitn(3, '\\');
+			let server = parts.next().unwrap_or_default();
+			let share = parts.next().unwrap_or_default();
+			ShellLinkInfo {
+				net_name: Some(format!("\\\\{}\\{}", server, share)),
+				network_provider_type: 0x0002_0000, // WNNC_NET_LANMAN
+				common_path_suffix: parts.next().unwrap_or_default().to_owned(),
+				..Default::default()
+			}
+		} else {
+			ShellLinkInfo {
+				drive_type: co::DRIVE::FIXED,
+				local_base_path: Some(target.to_owned()),
+				..Default::default()
+			}
+		};
+
+		Self {
+			link_info: Some(link_info),
+			..Default::default()
+		}
+	}
+
+	/// Parses the contents of a `.lnk` file.
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(data: &[u8]) -> SysResult<Self> {
+		if read_dword(data, 0)? as usize != HEADER_SIZE || read_guid(data, 4)? != LINK_CLSID {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let raw_flags = unsafe { co::SLDF::from_raw(read_dword(data, 20)?) };
+		let hot_key = read_word(data, 64)?;
+
+		let mut lnk = Self {
+			flags: raw_flags & !STRUCTURAL_FLAGS,
+			file_attributes: unsafe { co::FILE_ATTRIBUTE::from_raw(read_dword(data, 24)?) },
+			creation_time: FILETIME::from(read_qword(data, 28)?),
+			access_time: FILETIME::from(read_qword(data, 36)?),
+			write_time: FILETIME::from(read_qword(data, 44)?),
+			file_size: read_dword(data, 52)?,
+			icon_index: read_dword(data, 56)? as _,
+			show_cmd: unsafe { co::SW::from_raw(read_dword(data, 60)? as _) },
+			hot_key: if hot_key == 0 {
+				None
+			} else {
+				Some(unsafe {
+					(co::VK::from_raw(hot_key & 0xff), co::HOTKEYF::from_raw(hot_key >> 8))
+				})
+			},
+			..Default::default()
+		};
+		let mut pos = HEADER_SIZE;
+
+		if raw_flags.has(co::SLDF::HAS_ID_LIST) {
+			let len = read_word(data, pos)? as usize;
+			let id_list = data
+				.get(pos + 2..pos + 2 + len)
+				.ok_or(co::ERROR::INVALID_DATA)?;
+			validate_id_list(id_list)?;
+			lnk.id_list = Some(id_list.to_vec());
+			pos += 2 + len;
+		}
+
+		if raw_flags.has(co::SLDF::HAS_LINK_INFO) {
+			let len = read_dword(data, pos)? as usize;
+			let block = data.get(pos..pos + len).ok_or(co::ERROR::INVALID_DATA)?;
+			lnk.link_info = Some(ShellLinkInfo::parse(block)?);
+			pos += len;
+		}
+
+		let is_unicode = raw_flags.has(co::SLDF::UNICODE);
+		for (flag, field) in [
+			(co::SLDF::HAS_NAME, &mut lnk.name),
+			(co::SLDF::HAS_RELPATH, &mut lnk.relative_path),
+			(co::SLDF::HAS_WORKINGDIR, &mut lnk.working_dir),
+			(co::SLDF::HAS_ARGS, &mut lnk.arguments),
+			(co::SLDF::HAS_ICONLOCATION, &mut lnk.icon_location),
+		] {
+			if raw_flags.has(flag) {
+				let count = read_word(data, pos)? as usize;
+				let len = if is_unicode { count * 2 } else { count };
+				let raw = data
+					.get(pos + 2..pos + 2 + len)
+					.ok_or(co::ERROR::INVALID_DATA)?;
+				*field = Some(if is_unicode { decode_utf16(raw) } else { decode_ansi(raw) });
+				pos += 2 + len;
+			}
+		}
+
+		loop {
+			let len = read_dword(data, pos)? as usize;
+			if len < 4 {
+				break; // terminal block
+			}
+			let block = data
+				.get(pos..pos.checked_add(len).ok_or(co::ERROR::INVALID_DATA)?)
+				.ok_or(co::ERROR::INVALID_DATA)?;
+			let sig = read_dword(block, 4)?;
+			match sig {
+				SIG_ENVIRONMENT => lnk.environment_target = Some(read_env_block(block)?),
+				SIG_ICON_ENVIRONMENT => lnk.icon_environment = Some(read_env_block(block)?),
+				SIG_KNOWN_FOLDER => {
+					lnk.known_folder = Some((
+						co::KNOWNFOLDERID::from(read_guid(block, 8)?),
+						read_dword(block, 24)?,
+					));
+				},
+				SIG_SPECIAL_FOLDER => {
+					lnk.special_folder = Some((read_dword(block, 8)?, read_dword(block, 12)?));
+				},
+				SIG_PROPERTY_STORE => {
+					lnk.properties
+						.append(&mut ShellLinkProperty::parse_store(&block[8..])?);
+				},
+				_ => lnk.extra_blocks.push((sig, block[8..].to_vec())),
+			}
+			pos += len;
+		}
+
+		Ok(lnk)
+	}
+
+	/// Serializes the shortcut into the contents of a `.lnk` file.
+	///
+	/// Strings longer than 65,535 characters, or an `ITEMIDLIST` longer than
+	/// 65,535 bytes, yield
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER).
+	#[must_use]
+	pub fn serialize(&self) -> SysResult<Vec<u8>> {
+		let strings = [
+			(co::SLDF::HAS_NAME, &self.name),
+			(co::SLDF::HAS_RELPATH, &self.relative_path),
+			(co::SLDF::HAS_WORKINGDIR, &self.working_dir),
+			(co::SLDF::HAS_ARGS, &self.arguments),
+			(co::SLDF::HAS_ICONLOCATION, &self.icon_location),
+		];
+
+		let mut flags = (self.flags & !STRUCTURAL_FLAGS) | co::SLDF::UNICODE;
+		for (flag, present) in [
+			(co::SLDF::HAS_ID_LIST, self.id_list.is_some()),
+			(co::SLDF::HAS_LINK_INFO, self.link_info.is_some()),
+			(co::SLDF::HAS_EXP_SZ, self.environment_target.is_some()),
+			(co::SLDF::HAS_EXP_ICON_SZ, self.icon_environment.is_some()),
+		] {
+			if present {
+				flags |= flag;
+			}
+		}
+		strings
+			.iter()
+			.filter(|(_, s)| s.is_some())
+			.for_each(|(flag, _)| flags |= *flag);
+
+		let mut buf = Vec::<u8>::with_capacity(HEADER_SIZE * 4);
+		buf.extend_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+		buf.extend_from_slice(&LINK_CLSID.to_le_bytes());
+		buf.extend_from_slice(&flags.raw().to_le_bytes());
+		buf.extend_from_slice(&self.file_attributes.raw().to_le_bytes());
+		for ft in [self.creation_time, self.access_time, self.write_time] {
+			buf.extend_from_slice(&u64::from(ft).to_le_bytes());
+		}
+		buf.extend_from_slice(&self.file_size.to_le_bytes());
+		buf.extend_from_slice(&self.icon_index.to_le_bytes());
+		buf.extend_from_slice(&self.show_cmd.raw().to_le_bytes());
+		let hot_key = self
+			.hot_key
+			.map_or(0, |(vk, mods)| (vk.raw() & 0xff) | (mods.raw() << 8));
+		buf.extend_from_slice(&hot_key.to_le_bytes());
+		buf.resize(HEADER_SIZE, 0); // reserved fields
+
+		if let Some(id_list) = &self.id_list {
+			let len = u16::try_from(id_list.len()).map_err(|_| co::ERROR::INVALID_PARAMETER)?;
+			buf.extend_from_slice(&len.to_le_bytes());
+			buf.extend_from_slice(id_list);
+		}
+
+		if let Some(link_info) = &self.link_info {
+			buf.append(&mut link_info.serialize());
+		}
+
+		for (_, s) in strings.iter() {
+			if let Some(s) = s {
+				let wchars = s.encode_utf16().collect::<Vec<_>>();
+				let count =
+					u16::try_from(wchars.len()).map_err(|_| co::ERROR::INVALID_PARAMETER)?;
+				buf.extend_from_slice(&count.to_le_bytes());
+				wchars
+					.iter()
+					.for_each(|ch| buf.extend_from_slice(&ch.to_le_bytes()));
+			}
+		}
+
+		if let Some(target) = &self.environment_target {
+			write_env_block(&mut buf, SIG_ENVIRONMENT, target);
+		}
+		if let Some(icon) = &self.icon_environment {
+			write_env_block(&mut buf, SIG_ICON_ENVIRONMENT, icon);
+		}
+		if let Some((folder_id, offset)) = &self.known_folder {
+			write_block_header(&mut buf, 0x1c, SIG_KNOWN_FOLDER);
+			buf.extend_from_slice(&folder_id.as_ref().to_le_bytes());
+			buf.extend_from_slice(&offset.to_le_bytes());
+		}
+		if let Some((csidl, offset)) = &self.special_folder {
+			write_block_header(&mut buf, 0x10, SIG_SPECIAL_FOLDER);
+			buf.extend_from_slice(&csidl.to_le_bytes());
+			buf.extend_from_slice(&offset.to_le_bytes());
+		}
+		if !self.properties.is_empty() {
+			let store = ShellLinkProperty::serialize_store(&self.properties);
+			write_block_header(&mut buf, 8 + store.len() as u32, SIG_PROPERTY_STORE);
+			buf.extend_from_slice(&store);
+		}
+		for (sig, data) in self.extra_blocks.iter() {
+			write_block_header(&mut buf, 8 + data.len() as u32, *sig);
+			buf.extend_from_slice(data);
+		}
+		buf.extend_from_slice(&0u32.to_le_bytes()); // terminal block
+
+		Ok(buf)
+	}
+
+	/// Returns the target path stored in the `LinkInfo`, which is the path
+	/// returned by
+	/// [`IShellLink::GetPath`](crate::prelude::shell_IShellLink::GetPath)
+	/// when the shortcut has no `ITEMIDLIST`.
+	#[must_use]
+	pub fn target_path(&self) -> Option<String> {
+		self.link_info.as_ref().and_then(|info| info.target_path())
+	}
+}
+
+impl Default for ShellLinkFile {
+	fn default() -> Self {
+		Self {
+			flags: co::SLDF::DEFAULT,
+			file_attributes: co::FILE_ATTRIBUTE::default(),
+			creation_time: FILETIME::default(),
+			access_time: FILETIME::default(),
+			write_time: FILETIME::default(),
+			file_size: 0,
+			icon_index: 0,
+			show_cmd: co::SW::SHOWNORMAL,
+			hot_key: None,
+			id_list: None,
+			link_info: None,
+			name: None,
+			relative_path: None,
+			working_dir: None,
+			arguments: None,
+			icon_location: None,
+			environment_target: None,
+			icon_environment: None,
+			known_folder: None,
+			special_folder: None,
+			properties: Vec::new(),
+			extra_blocks: Vec::new(),
+		}
+	}
+}
+
+impl ShellLinkInfo {
+	/// Returns the full path of the target, either local or on the network.
+	#[must_use]
+	pub fn target_path(&self) -> Option<String> {
+		let base = self.local_base_path.as_ref().or(self.net_name.as_ref())?;
+		Some(if self.common_path_suffix.is_empty() {
+			base.clone()
+		} else if base.ends_with('\\') {
+			format!("{}{}", base, self.common_path_suffix)
+		} else {
+			format!("{}\\{}", base, self.common_path_suffix)
+		})
+	}
+
+	fn parse(block: &[u8]) -> SysResult<Self> {
+		let header_len = read_dword(block, 4)? as usize;
+		let flags = read_dword(block, 8)?;
+		let unicode_offs = if header_len >= 0x24 {
+			Some((read_dword(block, 0x1c)? as usize, read_dword(block, 0x20)? as usize))
+		} else {
+			None
+		};
+		let mut info = Self::default();
+
+		if flags & 0x1 != 0 {
+			// VolumeIDAndLocalBasePath
+			let vol_off = read_dword(block, 0x0c)? as usize;
+			let vol = block.get(vol_off..).ok_or(co::ERROR::INVALID_DATA)?;
+			info.drive_type = unsafe { co::DRIVE::from_raw(read_dword(vol, 4)?) };
+			info.drive_serial_number = read_dword(vol, 8)?;
+			let label_off = read_dword(vol, 12)? as usize;
+			info.volume_label = if label_off == 0x14 {
+				read_utf16z(vol, read_dword(vol, 16)? as usize)?
+			} else {
+				read_ansiz(vol, label_off)?
+			};
+
+			info.local_base_path = Some(match unicode_offs {
+				Some((off, _)) if off != 0 => read_utf16z(block, off)?,
+				_ => read_ansiz(block, read_dword(block, 0x10)? as usize)?,
+			});
+		}
+
+		if flags & 0x2 != 0 {
+			// CommonNetworkRelativeLinkAndPathSuffix
+			let cnrl_off = read_dword(block, 0x14)? as usize;
+			let cnrl = block.get(cnrl_off..).ok_or(co::ERROR::INVALID_DATA)?;
+			let cnrl_flags = read_dword(cnrl, 4)?;
+			let net_off = read_dword(cnrl, 8)? as usize;
+			let dev_off = read_dword(cnrl, 12)? as usize;
+			if cnrl_flags & 0x2 != 0 {
+				info.network_provider_type = read_dword(cnrl, 16)?;
+			}
+			let has_unicode = net_off > 0x14;
+			info.net_name = Some(if has_unicode {
+				read_utf16z(cnrl, read_dword(cnrl, 0x14)? as usize)?
+			} else {
+				read_ansiz(cnrl, net_off)?
+			});
+			if cnrl_flags & 0x1 != 0 {
+				info.device_name = Some(if has_unicode {
+					read_utf16z(cnrl, read_dword(cnrl, 0x18)? as usize)?
+				} else {
+					read_ansiz(cnrl, dev_off)?
+				});
+			}
+		}
+
+		info.common_path_suffix = match unicode_offs {
+			Some((_, off)) if off != 0 => read_utf16z(block, off)?,
+			_ => match read_dword(block, 0x18)? as usize {
+				0 => String::new(),
+				off => read_ansiz(block, off)?,
+			},
+		};
+		Ok(info)
+	}
+
+	fn serialize(&self) -> Vec<u8> {
+		let needs_unicode = !self
+			.local_base_path
+			.as_deref()
+			.unwrap_or_default()
+			.is_ascii()
+			|| !self.common_path_suffix.is_ascii();
+		let header_len: usize = if needs_unicode { 0x24 } else { 0x1c };
+		let mut body = Vec::<u8>::new(); // everything after the header
+		let offset = |body: &Vec<u8>| (header_len + body.len()) as u32;
+
+		let (mut vol_off, mut base_off, mut cnrl_off) = (0, 0, 0);
+		if let Some(base) = &self.local_base_path {
+			vol_off = offset(&body);
+			let mut vol = Vec::<u8>::new();
+			vol.extend_from_slice(&[0; 4]); // size, set below
+			vol.extend_from_slice(&self.drive_type.raw().to_le_bytes());
+			vol.extend_from_slice(&self.drive_serial_number.to_le_bytes());
+			if self.volume_label.is_ascii() {
+				vol.extend_from_slice(&0x10u32.to_le_bytes());
+				push_ansiz(&mut vol, &self.volume_label);
+			} else {
+				vol.extend_from_slice(&0x14u32.to_le_bytes());
+				vol.extend_from_slice(&0x14u32.to_le_bytes());
+				push_utf16z(&mut vol, &self.volume_label);
+			}
+			let vol_len = vol.len() as u32;
+			vol[..4].copy_from_slice(&vol_len.to_le_bytes());
+			body.append(&mut vol);
+
+			base_off = offset(&body);
+			push_ansiz(&mut body, base);
+		}
+
+		if let Some(net_name) = &self.net_name {
+			cnrl_off = offset(&body);
+			let device = self.device_name.as_deref();
+			let net_unicode = !net_name.is_ascii() || !device.unwrap_or_default().is_ascii();
+			let fixed_len: u32 = if net_unicode { 0x1c } else { 0x14 };
+
+			let mut strs = Vec::<u8>::new();
+			let net_off = fixed_len;
+			push_ansiz(&mut strs, net_name);
+			let dev_off = device.map_or(0, |dev| {
+				let off = fixed_len + strs.len() as u32;
+				push_ansiz(&mut strs, dev);
+				off
+			});
+			let (mut net_off_w, mut dev_off_w) = (0, 0);
+			if net_unicode {
+				net_off_w = fixed_len + strs.len() as u32;
+				push_utf16z(&mut strs, net_name);
+				if let Some(dev) = device {
+					dev_off_w = fixed_len + strs.len() as u32;
+					push_utf16z(&mut strs, dev);
+				}
+			}
+
+			let mut cnrl_flags = 0u32;
+			if device.is_some() {
+				cnrl_flags |= 0x1; // ValidDevice
+			}
+			if self.network_provider_type != 0 {
+				cnrl_flags |= 0x2; // ValidNetType
+			}
+			let mut cnrl = Vec::<u8>::new();
+			cnrl.extend_from_slice(&(fixed_len + strs.len() as u32).to_le_bytes());
+			cnrl.extend_from_slice(&cnrl_flags.to_le_bytes());
+			cnrl.extend_from_slice(&net_off.to_le_bytes());
+			cnrl.extend_from_slice(&dev_off.to_le_bytes());
+			cnrl.extend_from_slice(&self.network_provider_type.to_le_bytes());
+			if net_unicode {
+				cnrl.extend_from_slice(&net_off_w.to_le_bytes());
+				cnrl.extend_from_slice(&dev_off_w.to_le_bytes());
+			}
+			cnrl.append(&mut strs);
+			body.append(&mut cnrl);
+		}
+
+		let suffix_off = offset(&body);
+		push_ansiz(&mut body, &self.common_path_suffix);
+
+		let (mut base_off_w, mut suffix_off_w) = (0, 0);
+		if needs_unicode {
+			if let Some(base) = &self.local_base_path {
+				base_off_w = offset(&body);
+				push_utf16z(&mut body, base);
+			}
+			suffix_off_w = offset(&body);
+			push_utf16z(&mut body, &self.common_path_suffix);
+		}
+
+		let mut flags = 0u32;
+		if self.local_base_path.is_some() {
+			flags |= 0x1;
+		}
+		if self.net_name.is_some() {
+			flags |= 0x2;
+		}
+
+		let mut buf = Vec::<u8>::with_capacity(header_len + body.len());
+		for val in [
+			(header_len + body.len()) as u32,
+			header_len as u32,
+			flags,
+			vol_off,
+			base_off,
+			cnrl_off,
+			suffix_off,
+		] {
+			buf.extend_from_slice(&val.to_le_bytes());
+		}
+		if needs_unicode {
+			buf.extend_from_slice(&base_off_w.to_le_bytes());
+			buf.extend_from_slice(&suffix_off_w.to_le_bytes());
+		}
+		buf.append(&mut body);
+		buf
+	}
+}
+
+impl ShellLinkProperty {
+	/// Creates a `VT_LPWSTR` property, like the
+	/// [application user model ID](https://learn.microsoft.com/en-us/windows/win32/shell/appids),
+	/// whose format ID is `9f4c2855-9f79-4b39-a8d0-e1d42de1d5f3` and
+	/// property ID is 5.
+	#[must_use]
+	pub fn new_str(fmtid: &GUID, pid: u32, val: &str) -> Self {
+		let wchars = val
+			.encode_utf16()
+			.chain(std::iter::once(0))
+			.collect::<Vec<_>>();
+		let mut value = Vec::<u8>::with_capacity(8 + wchars.len() * 2);
+		value.extend_from_slice(&co::VT::LPWSTR.raw().to_le_bytes());
+		value.extend_from_slice(&[0, 0]); // padding
+		value.extend_from_slice(&(wchars.len() as u32).to_le_bytes());
+		wchars
+			.iter()
+			.for_each(|ch| value.extend_from_slice(&ch.to_le_bytes()));
+		value.resize(value.len().next_multiple_of(4), 0);
+		Self { fmtid: *fmtid, pid, name: None, value }
+	}
+
+	/// Returns the type of the value.
+	#[must_use]
+	pub fn value_type(&self) -> co::VT {
+		unsafe { co::VT::from_raw(read_word(&self.value, 0).unwrap_or_default()) }
+	}
+
+	/// Returns the value if it's a `VT_LPWSTR` string.
+	#[must_use]
+	pub fn as_str(&self) -> Option<String> {
+		if self.value_type() != co::VT::LPWSTR {
+			return None;
+		}
+		let count = read_dword(&self.value, 4).ok()? as usize;
+		let raw = self.value.get(8..8 + count.checked_mul(2)?)?;
+		Some(decode_utf16(raw))
+	}
+
+	/// Parses a serialized property store, a sequence of storages.
+	fn parse_store(data: &[u8]) -> SysResult<Vec<Self>> {
+		let mut props = Vec::<Self>::new();
+		let mut pos = 0;
+		loop {
+			let storage_len = read_dword(data, pos)? as usize;
+			if storage_len == 0 {
+				return Ok(props);
+			}
+			let storage = data
+				.get(
+					pos..pos
+						.checked_add(storage_len)
+						.ok_or(co::ERROR::INVALID_DATA)?,
+				)
+				.ok_or(co::ERROR::INVALID_DATA)?;
+			if read_dword(storage, 4)? != SPS_VERSION {
+				return Err(co::ERROR::INVALID_DATA);
+			}
+			let fmtid = read_guid(storage, 8)?;
+
+			let mut vpos = 24;
+			loop {
+				let value_len = read_dword(storage, vpos)? as usize;
+				if value_len == 0 {
+					break;
+				}
+				let entry = storage
+					.get(vpos..vpos.checked_add(value_len).ok_or(co::ERROR::INVALID_DATA)?)
+					.ok_or(co::ERROR::INVALID_DATA)?;
+				props.push(if fmtid == FMTID_NAMED {
+					let name_len = read_dword(entry, 4)? as usize;
+					let name = entry.get(9..9 + name_len).ok_or(co::ERROR::INVALID_DATA)?;
+					Self {
+						fmtid,
+						pid: 0,
+						name: Some(decode_utf16(name)),
+						value: entry[9 + name_len..].to_vec(),
+					}
+				} else {
+					Self {
+						fmtid,
+						pid: read_dword(entry, 4)?,
+						name: None,
+						value: entry.get(9..).ok_or(co::ERROR::INVALID_DATA)?.to_vec(),
+					}
+				});
+				vpos += value_len;
+			}
+			pos += storage_len;
+		}
+	}
+
+	/// Serializes the properties, grouping consecutive ones with the same
+	/// format ID into a storage.
+	fn serialize_store(props: &[Self]) -> Vec<u8> {
+		let mut buf = Vec::<u8>::new();
+		for group in props.chunk_by(|a, b| a.fmtid == b.fmtid) {
+			let mut storage = Vec::<u8>::new();
+			storage.extend_from_slice(&[0; 4]); // size, set below
+			storage.extend_from_slice(&SPS_VERSION.to_le_bytes());
+			storage.extend_from_slice(&group[0].fmtid.to_le_bytes());
+			for prop in group.iter() {
+				let mut name_bytes = Vec::<u8>::new();
+				if let Some(name) = &prop.name {
+					push_utf16z(&mut name_bytes, name);
+				}
+				let value_len = 9 + name_bytes.len() + prop.value.len();
+				storage.extend_from_slice(&(value_len as u32).to_le_bytes());
+				if prop.name.is_some() {
+					storage.extend_from_slice(&(name_bytes.len() as u32).to_le_bytes());
+				} else {
+					storage.extend_from_slice(&prop.pid.to_le_bytes());
+				}
+				storage.push(0); // reserved
+				storage.append(&mut name_bytes);
+				storage.extend_from_slice(&prop.value);
+			}
+			storage.extend_from_slice(&0u32.to_le_bytes()); // terminal value
+			let storage_len = storage.len() as u32;
+			storage[..4].copy_from_slice(&storage_len.to_le_bytes());
+			buf.append(&mut storage);
+		}
+		buf.extend_from_slice(&0u32.to_le_bytes()); // terminal storage
+		buf
+	}
+}
+
+/// Checks that the `ITEMIDLIST` items fit the buffer, and that it ends with
+/// the terminating zero.
+fn validate_id_list(id_list: &[u8]) -> SysResult<()> {
+	let mut pos = 0;
+	loop {
+		let item_len = read_word(id_list, pos)? as usize;
+		if item_len == 0 {
+			return if pos + 2 == id_list.len() { Ok(()) } else { Err(co::ERROR::INVALID_DATA) };
+		} else if item_len < 2 {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		pos += item_len;
+	}
+}
+
+/// Reads an `EnvironmentVariableDataBlock` or `IconEnvironmentDataBlock`,
+/// preferring the Unicode target.
+fn read_env_block(block: &[u8]) -> SysResult<String> {
+	let ansi = block.get(8..8 + 260).ok_or(co::ERROR::INVALID_DATA)?;
+	let wide = block.get(268..268 + 520).ok_or(co::ERROR::INVALID_DATA)?;
+	let wide = read_utf16z(wide, 0)?;
+	Ok(if wide.is_empty() { read_ansiz(ansi, 0)? } else { wide })
+}
+
+fn write_env_block(buf: &mut Vec<u8>, sig: u32, target: &str) {
+	write_block_header(buf, 0x314, sig);
+	let start = buf.len();
+	target
+		.chars()
+		.take(259)
+		.for_each(|ch| buf.push(if ch.is_ascii() { ch as u8 } else { b'?' }));
+	buf.resize(start + 260, 0);
+
+	let start = buf.len();
+	target
+		.encode_utf16()
+		.take(259)
+		.for_each(|ch| buf.extend_from_slice(&ch.to_le_bytes()));
+	buf.resize(start + 520, 0);
+}
+
+fn write_block_header(buf: &mut Vec<u8>, len: u32, sig: u32) {
+	buf.extend_from_slice(&len.to_le_bytes());
+	buf.extend_from_slice(&sig.to_le_bytes());
+}
+
+/// Decodes UTF-16 chars, stopping at the first null.
+fn decode_utf16(raw: &[u8]) -> String {
+	let wchars = raw
+		.chunks_exact(2)
+		.map(|ch| u16::from_le_bytes([ch[0], ch[1]]))
+		.take_while(|ch| *ch != 0)
+		.collect::<Vec<_>>();
+	String::from_utf16_lossy(&wchars)
+}
+
+/// Decodes ANSI chars as Latin-1, stopping at the first null.
+fn decode_ansi(raw: &[u8]) -> String {
+	raw.iter()
+		.take_while(|ch| **ch != 0)
+		.map(|ch| *ch as char)
+		.collect()
+}
+
+/// Appends the string as null-terminated ANSI, replacing non-ASCII chars.
+fn push_ansiz(buf: &mut Vec<u8>, s: &str) {
+	s.chars()
+		.for_each(|ch| buf.push(if ch.is_ascii() { ch as u8 } else { b'?' }));
+	buf.push(0);
+}
+
+fn push_utf16z(buf: &mut Vec<u8>, s: &str) {
+	s.encode_utf16()
+		.chain(std::iter::once(0))
+		.for_each(|ch| buf.extend_from_slice(&ch.to_le_bytes()));
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[rustfmt::skip]
+	const FIXTURE: &[u8] = &[
+		0x4c, 0x00, 0x00, 0x00, // HeaderSize
+		0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, // LinkCLSID
+		0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+		0x84, 0x00, 0x00, 0x00, // HasName | IsUnicode
+		0x20, 0x00, 0x00, 0x00, // FILE_ATTRIBUTE_ARCHIVE
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // CreationTime
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // AccessTime
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // WriteTime
+		0x00, 0x10, 0x00, 0x00, // FileSize
+		0x02, 0x00, 0x00, 0x00, // IconIndex
+		0x03, 0x00, 0x00, 0x00, // SW_SHOWMAXIMIZED
+		0x41, 0x06, // Ctrl+Alt+A
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // reserved
+		0x02, 0x00, b'H', 0x00, b'i', 0x00, // NAME_STRING
+		0x00, 0x00, 0x00, 0x00, // TerminalBlock
+	];
+
+	#[test]
+	fn parse_fixture() {
+		let lnk = ShellLinkFile::parse(FIXTURE).unwrap();
+		assert_eq!(lnk.flags, co::SLDF::DEFAULT); // structural flags are computed
+		assert_eq!(lnk.file_attributes, co::FILE_ATTRIBUTE::ARCHIVE);
+		assert_eq!(lnk.file_size, 0x1000);
+		assert_eq!(lnk.icon_index, 2);
+		assert_eq!(lnk.show_cmd, co::SW::SHOWMAXIMIZED);
+		assert_eq!(lnk.hot_key, Some((co::VK::CHAR_A, co::HOTKEYF::CONTROL | co::HOTKEYF::ALT)));
+		assert_eq!(lnk.name.as_deref(), Some("Hi"));
+		assert!(lnk.link_info.is_none() && lnk.arguments.is_none());
+
+		assert_eq!(lnk.serialize().unwrap(), FIXTURE);
+	}
+
+	#[test]
+	fn round_trip() {
+		let mut lnk = ShellLinkFile::new("C:\\Program Files\\App\\app.exe");
+		lnk.arguments = Some("--verbose \u{e9}".to_owned());
+		lnk.working_dir = Some("C:\\Program Files\\App".to_owned());
+		lnk.environment_target = Some("%ProgramFiles%\\App\\app.exe".to_owned());
+		lnk.known_folder = Some((co::KNOWNFOLDERID::ProgramFiles, 0x20));
+		let fmtid = GUID::from_str("9f4c2855-9f79-4b39-a8d0-e1d42de1d5f3");
+		lnk.properties.push(ShellLinkProperty::new_str(&fmtid, 5, "Company.App"));
+		lnk.extra_blocks.push((0xa000_0004, vec![1, 2, 3, 4]));
+
+		let blob = lnk.serialize().unwrap();
+		let parsed = ShellLinkFile::parse(&blob).unwrap();
+		assert!(parsed == lnk);
+		assert_eq!(parsed.target_path().as_deref(), Some("C:\\Program Files\\App\\app.exe"));
+		assert_eq!(parsed.serialize().unwrap(), blob);
+	}
+
+	#[test]
+	fn unc_target() {
+		let lnk = ShellLinkFile::new("\\\\server\\share\\dir\\file.txt");
+		let parsed = ShellLinkFile::parse(&lnk.serialize().unwrap()).unwrap();
+		let info = parsed.link_info.as_ref().unwrap();
+		assert_eq!(info.net_name.as_deref(), Some("\\\\server\\share"));
+		assert_eq!(info.common_path_suffix, "dir\\file.txt");
+		assert_eq!(parsed.target_path().as_deref(), Some("\\\\server\\share\\dir\\file.txt"));
+	}
+
+	#[test]
+	fn truncated() {
+		assert!(ShellLinkFile::parse(&FIXTURE[..FIXTURE.len() - 6]).is_err());
+		assert!(ShellLinkFile::parse(&FIXTURE[4..]).is_err());
+	}
+}
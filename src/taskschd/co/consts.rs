@@ -10,6 +10,19 @@ const_ordinary! { TASK_ACTION_TYPE: u32;
 	SHOW_MESSAGE 7
 }
 
+const_ordinary! { TASK_COMPATIBILITY: u32;
+	/// [`TASK_COMPATIBILITY`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/ne-taskschd-task_compatibility)
+	/// enumeration (`u32`).
+	=>
+	AT 0
+	V1 1
+	V2 2
+	V2_1 3
+	V2_2 4
+	V2_3 5
+	V2_4 6
+}
+
 const_bitflag! { TASK_CREATION: u32;
 	/// [`TASK_CREATION`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/ne-taskschd-task_creation)
 	/// enumeration (`u32`).
@@ -25,6 +38,16 @@ const_bitflag! { TASK_CREATION: u32;
 	IGNORE_REGISTRATION_TRIGGERS 0x20
 }
 
+const_ordinary! { TASK_INSTANCES_POLICY: u32;
+	/// [`TASK_INSTANCES_POLICY`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/ne-taskschd-task_instances_policy)
+	/// enumeration (`u32`).
+	=>
+	PARALLEL 0
+	QUEUE 1
+	IGNORE_NEW 2
+	STOP_EXISTING 3
+}
+
 const_bitflag! { TASK_LOGON: u32;
 	/// [`TASK_LOGON_TYPE`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/ne-taskschd-task_logon_type)
 	/// enumeration (`u32`).
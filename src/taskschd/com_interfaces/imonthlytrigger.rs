@@ -0,0 +1,107 @@
+#![allow(non_camel_case_types, non_snake_case)]
+
+use crate::decl::*;
+use crate::ole::privs::*;
+use crate::prelude::*;
+use crate::taskschd::vts::*;
+
+com_interface! { IMonthlyTrigger: "97c45ef1-6b02-4a1a-9c0e-1ebfba1500ac";
+	/// [`IMonthlyTrigger`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nn-taskschd-imonthlytrigger)
+	/// COM interface.
+	///
+	/// Automatically calls
+	/// [`Release`](https://learn.microsoft.com/en-us/windows/win32/api/unknwn/nf-unknwn-iunknown-release)
+	/// when the object goes out of scope.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let trigger: w::ITrigger; // initialized somewhere
+	/// # let trigger = unsafe { w::ITrigger::null() };
+	///
+	/// let monthly_trigger = trigger
+	///     .QueryInterface::<w::IMonthlyTrigger>()?;
+	/// # w::HrResult::Ok(())
+	/// ```
+}
+
+impl oleaut_IDispatch for IMonthlyTrigger {}
+impl taskschd_ITrigger for IMonthlyTrigger {}
+impl taskschd_IMonthlyTrigger for IMonthlyTrigger {}
+
+/// This trait is enabled with the `taskschd` feature, and provides methods for
+/// [`IMonthlyTrigger`](crate::IMonthlyTrigger).
+///
+/// Prefer importing this trait through the prelude:
+///
+/// ```no_run
+/// use winsafe::prelude::*;
+/// ```
+pub trait taskschd_IMonthlyTrigger: taskschd_ITrigger {
+	/// [`IMonthlyTrigger::get_DaysOfMonth`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-imonthlytrigger-get_daysofmonth)
+	/// method.
+	#[must_use]
+	fn get_DaysOfMonth(&self) -> HrResult<i32> {
+		let mut days = i32::default();
+		HrRet(unsafe { (vt::<IMonthlyTriggerVT>(self).get_DaysOfMonth)(self.ptr(), &mut days) })
+			.to_hrresult()
+			.map(|_| days)
+	}
+
+	/// [`IMonthlyTrigger::get_MonthsOfYear`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-imonthlytrigger-get_monthsofyear)
+	/// method.
+	#[must_use]
+	fn get_MonthsOfYear(&self) -> HrResult<i16> {
+		let mut months = i16::default();
+		HrRet(unsafe { (vt::<IMonthlyTriggerVT>(self).get_MonthsOfYear)(self.ptr(), &mut months) })
+			.to_hrresult()
+			.map(|_| months)
+	}
+
+	fn_com_bstr_get! { get_RandomDelay: IMonthlyTriggerVT;
+		/// [`IMonthlyTrigger::get_RandomDelay`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-imonthlytrigger-get_randomdelay)
+		/// method.
+	}
+
+	/// [`IMonthlyTrigger::get_RunOnLastDayOfMonth`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-imonthlytrigger-get_runonlastdayofmonth)
+	/// method.
+	#[must_use]
+	fn get_RunOnLastDayOfMonth(&self) -> HrResult<bool> {
+		let mut last_day = i16::default();
+		HrRet(unsafe {
+			(vt::<IMonthlyTriggerVT>(self).get_RunOnLastDayOfMonth)(self.ptr(), &mut last_day)
+		})
+		.to_hrresult()
+		.map(|_| last_day != 0)
+	}
+
+	/// [`IMonthlyTrigger::put_DaysOfMonth`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-imonthlytrigger-put_daysofmonth)
+	/// method.
+	fn put_DaysOfMonth(&self, days: i32) -> HrResult<()> {
+		HrRet(unsafe { (vt::<IMonthlyTriggerVT>(self).put_DaysOfMonth)(self.ptr(), days) })
+			.to_hrresult()
+	}
+
+	/// [`IMonthlyTrigger::put_MonthsOfYear`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-imonthlytrigger-put_monthsofyear)
+	/// method.
+	fn put_MonthsOfYear(&self, months: i16) -> HrResult<()> {
+		HrRet(unsafe { (vt::<IMonthlyTriggerVT>(self).put_MonthsOfYear)(self.ptr(), months) })
+			.to_hrresult()
+	}
+
+	fn_com_bstr_set! { put_RandomDelay: IMonthlyTriggerVT, random_delay;
+		/// [`IMonthlyTrigger::put_RandomDelay`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-imonthlytrigger-put_randomdelay)
+		/// method.
+	}
+
+	/// [`IMonthlyTrigger::put_RunOnLastDayOfMonth`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-imonthlytrigger-put_runonlastdayofmonth)
+	/// method.
+	fn put_RunOnLastDayOfMonth(&self, last_day: bool) -> HrResult<()> {
+		HrRet(unsafe {
+			(vt::<IMonthlyTriggerVT>(self).put_RunOnLastDayOfMonth)(self.ptr(), last_day as _)
+		})
+		.to_hrresult()
+	}
+}
@@ -29,8 +29,78 @@ pub trait taskschd_IRegistrationInfo: oleaut_IDispatch {
 		/// method.
 	}
 
+	fn_com_bstr_get! { get_Date: IRegistrationInfoVT;
+		/// [`IRegistrationInfo::get_Date`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-get_date)
+		/// method.
+	}
+
+	fn_com_bstr_get! { get_Description: IRegistrationInfoVT;
+		/// [`IRegistrationInfo::get_Description`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-get_description)
+		/// method.
+	}
+
+	fn_com_bstr_get! { get_Documentation: IRegistrationInfoVT;
+		/// [`IRegistrationInfo::get_Documentation`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-get_documentation)
+		/// method.
+	}
+
+	fn_com_bstr_get! { get_Source: IRegistrationInfoVT;
+		/// [`IRegistrationInfo::get_Source`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-get_source)
+		/// method.
+	}
+
+	fn_com_bstr_get! { get_URI: IRegistrationInfoVT;
+		/// [`IRegistrationInfo::get_URI`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-get_uri)
+		/// method.
+	}
+
+	fn_com_bstr_get! { get_Version: IRegistrationInfoVT;
+		/// [`IRegistrationInfo::get_Version`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-get_version)
+		/// method.
+	}
+
+	fn_com_bstr_get! { get_XmlText: IRegistrationInfoVT;
+		/// [`IRegistrationInfo::get_XmlText`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-get_xmltext)
+		/// method.
+	}
+
 	fn_com_bstr_set! { put_Author: IRegistrationInfoVT, author;
 		/// [`IRegistrationInfo::put_Author`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-put_author)
 		/// method.
 	}
+
+	fn_com_bstr_set! { put_Date: IRegistrationInfoVT, date;
+		/// [`IRegistrationInfo::put_Date`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-put_date)
+		/// method.
+	}
+
+	fn_com_bstr_set! { put_Description: IRegistrationInfoVT, description;
+		/// [`IRegistrationInfo::put_Description`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-put_description)
+		/// method.
+	}
+
+	fn_com_bstr_set! { put_Documentation: IRegistrationInfoVT, documentation;
+		/// [`IRegistrationInfo::put_Documentation`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-put_documentation)
+		/// method.
+	}
+
+	fn_com_bstr_set! { put_Source: IRegistrationInfoVT, source;
+		/// [`IRegistrationInfo::put_Source`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-put_source)
+		/// method.
+	}
+
+	fn_com_bstr_set! { put_URI: IRegistrationInfoVT, uri;
+		/// [`IRegistrationInfo::put_URI`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-put_uri)
+		/// method.
+	}
+
+	fn_com_bstr_set! { put_Version: IRegistrationInfoVT, version;
+		/// [`IRegistrationInfo::put_Version`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-put_version)
+		/// method.
+	}
+
+	fn_com_bstr_set! { put_XmlText: IRegistrationInfoVT, xml;
+		/// [`IRegistrationInfo::put_XmlText`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iregistrationinfo-put_xmltext)
+		/// method.
+	}
 }
@@ -0,0 +1,69 @@
+#![allow(non_camel_case_types, non_snake_case)]
+
+use crate::decl::*;
+use crate::ole::privs::*;
+use crate::prelude::*;
+use crate::taskschd::vts::*;
+
+com_interface! { IRepetitionPattern: "7fb9acf1-26be-400e-85b5-294b9c75dfd6";
+	/// [`IRepetitionPattern`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nn-taskschd-irepetitionpattern)
+	/// COM interface.
+	///
+	/// Automatically calls
+	/// [`Release`](https://learn.microsoft.com/en-us/windows/win32/api/unknwn/nf-unknwn-iunknown-release)
+	/// when the object goes out of scope.
+}
+
+impl oleaut_IDispatch for IRepetitionPattern {}
+impl taskschd_IRepetitionPattern for IRepetitionPattern {}
+
+/// This trait is enabled with the `taskschd` feature, and provides methods for
+/// [`IRepetitionPattern`](crate::IRepetitionPattern).
+///
+/// Prefer importing this trait through the prelude:
+///
+/// ```no_run
+/// use winsafe::prelude::*;
+/// ```
+pub trait taskschd_IRepetitionPattern: oleaut_IDispatch {
+	fn_com_bstr_get! { get_Duration: IRepetitionPatternVT;
+		/// [`IRepetitionPattern::get_Duration`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-irepetitionpattern-get_duration)
+		/// method.
+	}
+
+	fn_com_bstr_get! { get_Interval: IRepetitionPatternVT;
+		/// [`IRepetitionPattern::get_Interval`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-irepetitionpattern-get_interval)
+		/// method.
+	}
+
+	/// [`IRepetitionPattern::get_StopAtDurationEnd`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-irepetitionpattern-get_stopatdurationend)
+	/// method.
+	#[must_use]
+	fn get_StopAtDurationEnd(&self) -> HrResult<bool> {
+		let mut stop = i16::default();
+		HrRet(unsafe {
+			(vt::<IRepetitionPatternVT>(self).get_StopAtDurationEnd)(self.ptr(), &mut stop)
+		})
+		.to_hrresult()
+		.map(|_| stop != 0)
+	}
+
+	fn_com_bstr_set! { put_Duration: IRepetitionPatternVT, duration;
+		/// [`IRepetitionPattern::put_Duration`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-irepetitionpattern-put_duration)
+		/// method.
+	}
+
+	fn_com_bstr_set! { put_Interval: IRepetitionPatternVT, interval;
+		/// [`IRepetitionPattern::put_Interval`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-irepetitionpattern-put_interval)
+		/// method.
+	}
+
+	/// [`IRepetitionPattern::put_StopAtDurationEnd`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-irepetitionpattern-put_stopatdurationend)
+	/// method.
+	fn put_StopAtDurationEnd(&self, stop: bool) -> HrResult<()> {
+		HrRet(unsafe {
+			(vt::<IRepetitionPatternVT>(self).put_StopAtDurationEnd)(self.ptr(), stop as _)
+		})
+		.to_hrresult()
+	}
+}
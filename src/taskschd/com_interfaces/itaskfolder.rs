@@ -65,6 +65,39 @@ pub trait taskschd_ITaskFolder: oleaut_IDispatch {
 		.map(|_| queried)
 	}
 
+	/// [`ITaskFolder::RegisterTask`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-itaskfolder-registertask)
+	/// method.
+	///
+	/// The task definition is given as XML, which can be produced by
+	/// [`TaskXml::to_xml`](crate::TaskXml::to_xml).
+	fn RegisterTask(
+		&self,
+		path: Option<&str>,
+		xml: &str,
+		flags: co::TASK_CREATION,
+		user_id: Option<&str>,
+		password: Option<&str>,
+		logon_type: co::TASK_LOGON,
+		sddl: Option<VARIANT>,
+	) -> HrResult<IRegisteredTask> {
+		let mut queried = unsafe { IRegisteredTask::null() };
+		HrRet(unsafe {
+			(vt::<ITaskFolderVT>(self).RegisterTask)(
+				self.ptr(),
+				BSTR::SysAllocString(path.unwrap_or_default())?.as_ptr(),
+				BSTR::SysAllocString(xml)?.as_ptr(),
+				flags.raw() as _,
+				Variant::from_opt_str(user_id).to_raw()?,
+				Variant::from_opt_str(password).to_raw()?,
+				logon_type.raw(),
+				sddl.unwrap_or_default(),
+				queried.as_mut(),
+			)
+		})
+		.to_hrresult()
+		.map(|_| queried)
+	}
+
 	/// [`ITaskFolder::RegisterTaskDefinition`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-itaskfolder-registertaskdefinition)
 	/// method.
 	fn RegisterTaskDefinition(
@@ -0,0 +1,50 @@
+#![allow(non_camel_case_types, non_snake_case)]
+
+use crate::prelude::*;
+use crate::taskschd::vts::*;
+
+com_interface! { ITimeTrigger: "b45747e0-eba7-4276-9f29-85c5bb300006";
+	/// [`ITimeTrigger`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nn-taskschd-itimetrigger)
+	/// COM interface.
+	///
+	/// Automatically calls
+	/// [`Release`](https://learn.microsoft.com/en-us/windows/win32/api/unknwn/nf-unknwn-iunknown-release)
+	/// when the object goes out of scope.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let trigger: w::ITrigger; // initialized somewhere
+	/// # let trigger = unsafe { w::ITrigger::null() };
+	///
+	/// let time_trigger = trigger
+	///     .QueryInterface::<w::ITimeTrigger>()?;
+	/// # w::HrResult::Ok(())
+	/// ```
+}
+
+impl oleaut_IDispatch for ITimeTrigger {}
+impl taskschd_ITrigger for ITimeTrigger {}
+impl taskschd_ITimeTrigger for ITimeTrigger {}
+
+/// This trait is enabled with the `taskschd` feature, and provides methods for
+/// [`ITimeTrigger`](crate::ITimeTrigger).
+///
+/// Prefer importing this trait through the prelude:
+///
+/// ```no_run
+/// use winsafe::prelude::*;
+/// ```
+pub trait taskschd_ITimeTrigger: taskschd_ITrigger {
+	fn_com_bstr_get! { get_RandomDelay: ITimeTriggerVT;
+		/// [`ITimeTrigger::get_RandomDelay`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-itimetrigger-get_randomdelay)
+		/// method.
+	}
+
+	fn_com_bstr_set! { put_RandomDelay: ITimeTriggerVT, random_delay;
+		/// [`ITimeTrigger::put_RandomDelay`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-itimetrigger-put_randomdelay)
+		/// method.
+	}
+}
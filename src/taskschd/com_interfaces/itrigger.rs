@@ -52,6 +52,11 @@ pub trait taskschd_ITrigger: oleaut_IDispatch {
 		/// method.
 	}
 
+	fn_com_interface_get! { get_Repetition: ITriggerVT => IRepetitionPattern;
+		/// [`ITrigger::get_Repetition`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-itrigger-get_repetition)
+		/// method.
+	}
+
 	fn_com_bstr_get! { get_StartBoundary: ITriggerVT;
 		/// [`ITrigger::get_StartBoundary`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-itrigger-get_startboundary)
 		/// method.
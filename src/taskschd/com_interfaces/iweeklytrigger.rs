@@ -0,0 +1,86 @@
+#![allow(non_camel_case_types, non_snake_case)]
+
+use crate::decl::*;
+use crate::ole::privs::*;
+use crate::prelude::*;
+use crate::taskschd::vts::*;
+
+com_interface! { IWeeklyTrigger: "5038fc98-82ff-436d-8728-a512a57c9dc1";
+	/// [`IWeeklyTrigger`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nn-taskschd-iweeklytrigger)
+	/// COM interface.
+	///
+	/// Automatically calls
+	/// [`Release`](https://learn.microsoft.com/en-us/windows/win32/api/unknwn/nf-unknwn-iunknown-release)
+	/// when the object goes out of scope.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*};
+	///
+	/// let trigger: w::ITrigger; // initialized somewhere
+	/// # let trigger = unsafe { w::ITrigger::null() };
+	///
+	/// let weekly_trigger = trigger
+	///     .QueryInterface::<w::IWeeklyTrigger>()?;
+	/// # w::HrResult::Ok(())
+	/// ```
+}
+
+impl oleaut_IDispatch for IWeeklyTrigger {}
+impl taskschd_ITrigger for IWeeklyTrigger {}
+impl taskschd_IWeeklyTrigger for IWeeklyTrigger {}
+
+/// This trait is enabled with the `taskschd` feature, and provides methods for
+/// [`IWeeklyTrigger`](crate::IWeeklyTrigger).
+///
+/// Prefer importing this trait through the prelude:
+///
+/// ```no_run
+/// use winsafe::prelude::*;
+/// ```
+pub trait taskschd_IWeeklyTrigger: taskschd_ITrigger {
+	/// [`IWeeklyTrigger::get_DaysOfWeek`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iweeklytrigger-get_daysofweek)
+	/// method.
+	#[must_use]
+	fn get_DaysOfWeek(&self) -> HrResult<i16> {
+		let mut days = i16::default();
+		HrRet(unsafe { (vt::<IWeeklyTriggerVT>(self).get_DaysOfWeek)(self.ptr(), &mut days) })
+			.to_hrresult()
+			.map(|_| days)
+	}
+
+	fn_com_bstr_get! { get_RandomDelay: IWeeklyTriggerVT;
+		/// [`IWeeklyTrigger::get_RandomDelay`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iweeklytrigger-get_randomdelay)
+		/// method.
+	}
+
+	/// [`IWeeklyTrigger::get_WeeksInterval`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iweeklytrigger-get_weeksinterval)
+	/// method.
+	#[must_use]
+	fn get_WeeksInterval(&self) -> HrResult<i16> {
+		let mut weeks = i16::default();
+		HrRet(unsafe { (vt::<IWeeklyTriggerVT>(self).get_WeeksInterval)(self.ptr(), &mut weeks) })
+			.to_hrresult()
+			.map(|_| weeks)
+	}
+
+	/// [`IWeeklyTrigger::put_DaysOfWeek`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iweeklytrigger-put_daysofweek)
+	/// method.
+	fn put_DaysOfWeek(&self, days: i16) -> HrResult<()> {
+		HrRet(unsafe { (vt::<IWeeklyTriggerVT>(self).put_DaysOfWeek)(self.ptr(), days) })
+			.to_hrresult()
+	}
+
+	fn_com_bstr_set! { put_RandomDelay: IWeeklyTriggerVT, random_delay;
+		/// [`IWeeklyTrigger::put_RandomDelay`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iweeklytrigger-put_randomdelay)
+		/// method.
+	}
+
+	/// [`IWeeklyTrigger::put_WeeksInterval`](https://learn.microsoft.com/en-us/windows/win32/api/taskschd/nf-taskschd-iweeklytrigger-put_weeksinterval)
+	/// method.
+	fn put_WeeksInterval(&self, weeks: i16) -> HrResult<()> {
+		HrRet(unsafe { (vt::<IWeeklyTriggerVT>(self).put_WeeksInterval)(self.ptr(), weeks) })
+			.to_hrresult()
+	}
+}
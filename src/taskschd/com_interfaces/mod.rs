@@ -8,14 +8,19 @@ mod ieventtrigger;
 mod iexecaction;
 mod iidletrigger;
 mod ilogontrigger;
+mod imonthlytrigger;
 mod iprincipal;
 mod iregisteredtask;
 mod iregistrationinfo;
+mod irepetitionpattern;
 mod itaskdefinition;
 mod itaskfolder;
 mod itaskservice;
+mod itasksettings;
+mod itimetrigger;
 mod itrigger;
 mod itriggercollection;
+mod iweeklytrigger;
 
 pub mod decl {
 	pub use super::iaction::IAction;
@@ -28,14 +33,19 @@ pub mod decl {
 	pub use super::iexecaction::IExecAction;
 	pub use super::iidletrigger::IIdleTrigger;
 	pub use super::ilogontrigger::ILogonTrigger;
+	pub use super::imonthlytrigger::IMonthlyTrigger;
 	pub use super::iprincipal::IPrincipal;
 	pub use super::iregisteredtask::IRegisteredTask;
 	pub use super::iregistrationinfo::IRegistrationInfo;
+	pub use super::irepetitionpattern::IRepetitionPattern;
 	pub use super::itaskdefinition::ITaskDefinition;
 	pub use super::itaskfolder::ITaskFolder;
 	pub use super::itaskservice::ITaskService;
+	pub use super::itasksettings::ITaskSettings;
+	pub use super::itimetrigger::ITimeTrigger;
 	pub use super::itrigger::ITrigger;
 	pub use super::itriggercollection::ITriggerCollection;
+	pub use super::iweeklytrigger::IWeeklyTrigger;
 }
 
 pub mod traits {
@@ -49,12 +59,17 @@ pub mod traits {
 	pub use super::iexecaction::taskschd_IExecAction;
 	pub use super::iidletrigger::taskschd_IIdleTrigger;
 	pub use super::ilogontrigger::taskschd_ILogonTrigger;
+	pub use super::imonthlytrigger::taskschd_IMonthlyTrigger;
 	pub use super::iprincipal::taskschd_IPrincipal;
 	pub use super::iregisteredtask::taskschd_IRegisteredTask;
 	pub use super::iregistrationinfo::taskschd_IRegistrationInfo;
+	pub use super::irepetitionpattern::taskschd_IRepetitionPattern;
 	pub use super::itaskdefinition::taskschd_ITaskDefinition;
 	pub use super::itaskfolder::taskschd_ITaskFolder;
 	pub use super::itaskservice::taskschd_ITaskService;
+	pub use super::itasksettings::taskschd_ITaskSettings;
+	pub use super::itimetrigger::taskschd_ITimeTrigger;
 	pub use super::itrigger::taskschd_ITrigger;
 	pub use super::itriggercollection::taskschd_ITriggerCollection;
+	pub use super::iweeklytrigger::taskschd_IWeeklyTrigger;
 }
@@ -1,12 +1,14 @@
 #![cfg(feature = "taskschd")]
 
 mod com_interfaces;
+mod utilities;
 
 pub mod co;
 pub(in crate::taskschd) mod vts;
 
 pub mod decl {
 	pub use super::com_interfaces::decl::*;
+	pub use super::utilities::*;
 }
 
 pub mod traits {
@@ -0,0 +1,6 @@
+mod task_xml;
+
+pub use task_xml::{
+	TaskXml, TaskXmlAction, TaskXmlPrincipal, TaskXmlRegistration, TaskXmlRepetition,
+	TaskXmlSchedule, TaskXmlSettings, TaskXmlTrigger,
+};
@@ -0,0 +1,1302 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::xml::XmlElem;
+
+const NS_TASK: &str = "http://schemas.microsoft.com/windows/2004/02/mit/task";
+
+const DAYS: [&str; 7] =
+	["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+const MONTHS: [&str; 12] = [
+	"January",
+	"February",
+	"March",
+	"April",
+	"May",
+	"June",
+	"July",
+	"August",
+	"September",
+	"October",
+	"November",
+	"December",
+];
+
+/// Typed model of a
+/// [Task Scheduler XML](https://learn.microsoft.com/en-us/windows/win32/taskschd/task-scheduler-schema)
+/// task definition, schema version 1.2, which can be rendered to XML, parsed
+/// from existing definitions and checked offline.
+///
+/// The rendered XML can be registered with
+/// [`ITaskFolder::RegisterTask`](crate::prelude::taskschd_ITaskFolder::RegisterTask),
+/// loaded into an existing definition with
+/// [`ITaskDefinition::put_XmlText`](crate::prelude::taskschd_ITaskDefinition::put_XmlText),
+/// or saved to a file accepted by `schtasks /create /xml`.
+///
+/// Durations are ISO 8601 strings like `PT5M` or `P1D`, and boundaries are
+/// dates like `2024-01-31T08:00:00`, as in the XML itself.
+///
+/// # Examples
+///
+/// Registering a task which runs every day:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let task = w::TaskXml {
+///     registration: w::TaskXmlRegistration {
+///         description: Some("Cleans the temp folder.".to_owned()),
+///         ..Default::default()
+///     },
+///     triggers: vec![w::TaskXmlTrigger {
+///         start_boundary: Some("2024-01-01T03:00:00".to_owned()),
+///         ..w::TaskXmlTrigger::new(w::TaskXmlSchedule::Daily {
+///             days_interval: 1,
+///             random_delay: None,
+///         })
+///     }],
+///     actions: vec![w::TaskXmlAction::Exec {
+///         command: "C:\\Tools\\cleanup.exe".to_owned(),
+///         arguments: Some("--temp".to_owned()),
+///         working_directory: None,
+///     }],
+///     ..Default::default()
+/// };
+///
+/// let problems = task.validate();
+/// if !problems.is_empty() {
+///     panic!("Invalid task: {}", problems.join(" "));
+/// }
+///
+/// let _com_guard = w::CoInitializeEx(
+///     co::COINIT::APARTMENTTHREADED | co::COINIT::DISABLE_OLE1DDE)?;
+///
+/// let service = w::CoCreateInstance::<w::ITaskService>(
+///     &co::CLSID::TaskScheduler,
+///     None::<&w::IUnknown>,
+///     co::CLSCTX::INPROC_SERVER,
+/// )?;
+/// service.Connect(None, None, None, None)?;
+///
+/// let folder = service.GetFolder("\\")?;
+/// folder.RegisterTask(
+///     Some("Cleanup"),
+///     &task.to_xml(),
+///     co::TASK_CREATION::CREATE_OR_UPDATE,
+///     None,
+///     None,
+///     co::TASK_LOGON::INTERACTIVE_TOKEN,
+///     None,
+/// )?;
+/// # w::AnyResult::Ok(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct TaskXml {
+	/// Administrative information about the task.
+	pub registration: TaskXmlRegistration,
+	/// Triggers which start the task; up to 48.
+	pub triggers: Vec<TaskXmlTrigger>,
+	/// Security context the task runs under. If `None`, the task runs as the
+	/// user who registers it.
+	pub principal: Option<TaskXmlPrincipal>,
+	/// Settings which control how the task is run.
+	pub settings: TaskXmlSettings,
+	/// Actions performed by the task; from 1 to 32.
+	pub actions: Vec<TaskXmlAction>,
+	/// Arbitrary data, kept with the task and ignored by the Task Scheduler.
+	pub data: Option<String>,
+}
+
+/// The `RegistrationInfo` of a [`TaskXml`](crate::TaskXml).
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct TaskXmlRegistration {
+	/// Date the task was registered.
+	pub date: Option<String>,
+	/// Author of the task.
+	pub author: Option<String>,
+	/// Version of the task.
+	pub version: Option<String>,
+	/// Who provided the task, like a company or an application.
+	pub source: Option<String>,
+	/// Description of the task.
+	pub description: Option<String>,
+	/// Additional documentation of the task.
+	pub documentation: Option<String>,
+	/// Path of the task in the folder hierarchy, like `\Folder\Task`.
+	pub uri: Option<String>,
+}
+
+/// A trigger of a [`TaskXml`](crate::TaskXml).
+#[derive(Clone, PartialEq, Eq)]
+pub struct TaskXmlTrigger {
+	/// Identifier of the trigger.
+	pub id: Option<String>,
+	/// When the trigger fires.
+	pub schedule: TaskXmlSchedule,
+	/// Whether the trigger is enabled.
+	pub enabled: bool,
+	/// When the trigger is activated. Required by the time-based schedules.
+	pub start_boundary: Option<String>,
+	/// When the trigger is deactivated.
+	pub end_boundary: Option<String>,
+	/// Maximum run time of the task started by this trigger.
+	pub execution_time_limit: Option<String>,
+	/// How the task is repeated after the trigger fires.
+	pub repetition: Option<TaskXmlRepetition>,
+}
+
+/// The schedule of a [`TaskXmlTrigger`](crate::TaskXmlTrigger).
+///
+/// Days of the week are bitmasks with `0x01` for Sunday up to `0x40` for
+/// Saturday; months are bitmasks with `0x001` for January up to `0x800` for
+/// December, and days of the month are bitmasks with `0x1` for the first day.
+/// These are the same values used by
+/// [`IWeeklyTrigger`](crate::IWeeklyTrigger) and
+/// [`IMonthlyTrigger`](crate::IMonthlyTrigger).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskXmlSchedule {
+	/// Fires once, at the start boundary.
+	Time { random_delay: Option<String> },
+	/// Fires every given number of days.
+	Daily { days_interval: u16, random_delay: Option<String> },
+	/// Fires on the given days of the week, every given number of weeks.
+	Weekly { weeks_interval: u16, days_of_week: u8, random_delay: Option<String> },
+	/// Fires on the given days of the given months.
+	Monthly { days_of_month: u32, run_on_last_day: bool, months: u16, random_delay: Option<String> },
+	/// Fires on the given days of the week, on the given weeks of the given
+	/// months. Weeks are a bitmask with `0x01` for the first week up to `0x08`
+	/// for the fourth, and `0x10` for the last.
+	MonthlyDow { weeks: u8, days_of_week: u8, months: u16, random_delay: Option<String> },
+	/// Fires when the system starts.
+	Boot { delay: Option<String> },
+	/// Fires when the given user, or any user, logs on.
+	Logon { user_id: Option<String>, delay: Option<String> },
+	/// Fires when the system becomes idle.
+	Idle,
+	/// Fires when an event matching the XPath query is logged.
+	Event { subscription: String, delay: Option<String> },
+	/// Fires when the task is registered or updated.
+	Registration { delay: Option<String> },
+}
+
+/// The `Repetition` of a [`TaskXmlTrigger`](crate::TaskXmlTrigger).
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct TaskXmlRepetition {
+	/// Time between runs, from 1 minute to 31 days.
+	pub interval: String,
+	/// How long the repetition lasts; if `None`, indefinitely.
+	pub duration: Option<String>,
+	/// Whether running instances are stopped at the end of the duration.
+	pub stop_at_duration_end: bool,
+}
+
+/// The `Principal` of a [`TaskXml`](crate::TaskXml).
+#[derive(Clone, PartialEq, Eq)]
+pub struct TaskXmlPrincipal {
+	/// Identifier of the principal, referenced by the actions. Defaults to
+	/// `Author`.
+	pub id: String,
+	/// User account or SID, like `S-1-5-18` for the local system.
+	pub user_id: Option<String>,
+	/// Group account or SID; excludes `user_id`.
+	pub group_id: Option<String>,
+	/// How the user is logged on. Only
+	/// [`PASSWORD`](crate::co::TASK_LOGON::PASSWORD),
+	/// [`S4U`](crate::co::TASK_LOGON::S4U),
+	/// [`INTERACTIVE_TOKEN`](crate::co::TASK_LOGON::INTERACTIVE_TOKEN) and
+	/// [`INTERACTIVE_TOKEN_OR_PASSWORD`](crate::co::TASK_LOGON::INTERACTIVE_TOKEN_OR_PASSWORD)
+	/// can be written to the XML.
+	pub logon_type: Option<co::TASK_LOGON>,
+	/// Privilege level the task runs with.
+	pub run_level: Option<co::TASK_RUNLEVEL>,
+}
+
+/// The `Settings` of a [`TaskXml`](crate::TaskXml).
+///
+/// The default values are the same of the Task Scheduler.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TaskXmlSettings {
+	/// What happens when the task is started while already running.
+	pub multiple_instances: co::TASK_INSTANCES_POLICY,
+	/// Whether the task won't start on batteries.
+	pub disallow_start_if_on_batteries: bool,
+	/// Whether the task is stopped when switching to batteries.
+	pub stop_if_going_on_batteries: bool,
+	/// Whether the task can be terminated with `TerminateProcess`.
+	pub allow_hard_terminate: bool,
+	/// Whether a missed run is started as soon as possible.
+	pub start_when_available: bool,
+	/// Whether the task runs only when a network is available.
+	pub run_only_if_network_available: bool,
+	/// How long the computer must be idle before the task starts.
+	pub idle_duration: Option<String>,
+	/// How long to wait for the computer to become idle.
+	pub idle_wait_timeout: Option<String>,
+	/// Whether the task is stopped when the computer is no longer idle.
+	pub stop_on_idle_end: bool,
+	/// Whether the task is restarted when the computer becomes idle again.
+	pub restart_on_idle: bool,
+	/// Whether the task can be started manually.
+	pub allow_start_on_demand: bool,
+	/// Whether the task is enabled.
+	pub enabled: bool,
+	/// Whether the task is hidden from the user interface.
+	pub hidden: bool,
+	/// Whether the task runs only when the computer is idle.
+	pub run_only_if_idle: bool,
+	/// Whether the computer is woken to run the task.
+	pub wake_to_run: bool,
+	/// Maximum run time of the task; if `None`, unlimited.
+	pub execution_time_limit: Option<String>,
+	/// How long after its last trigger expires the task is deleted.
+	pub delete_expired_task_after: Option<String>,
+	/// Priority of the task, from 0 (highest) to 10 (lowest).
+	pub priority: u8,
+	/// Interval and count of restarts when the task fails.
+	pub restart_on_failure: Option<(String, u16)>,
+}
+
+impl Default for TaskXmlSettings {
+	fn default() -> Self {
+		Self {
+			multiple_instances: co::TASK_INSTANCES_POLICY::IGNORE_NEW,
+			disallow_start_if_on_batteries: true,
+			stop_if_going_on_batteries: true,
+			allow_hard_terminate: true,
+			start_when_available: false,
+			run_only_if_network_available: false,
+			idle_duration: None,
+			idle_wait_timeout: None,
+			stop_on_idle_end: true,
+			restart_on_idle: false,
+			allow_start_on_demand: true,
+			enabled: true,
+			hidden: false,
+			run_only_if_idle: false,
+			wake_to_run: false,
+			execution_time_limit: Some("PT72H".to_owned()),
+			delete_expired_task_after: None,
+			priority: 7,
+			restart_on_failure: None,
+		}
+	}
+}
+
+/// An action of a [`TaskXml`](crate::TaskXml).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskXmlAction {
+	/// Runs a program.
+	Exec { command: String, arguments: Option<String>, working_directory: Option<String> },
+	/// Runs a COM handler, given its CLSID like
+	/// `{00000000-0000-0000-0000-000000000000}`.
+	ComHandler { class_id: String, data: Option<String> },
+}
+
+impl TaskXml {
+	/// Parses a task definition, as returned by
+	/// [`ITaskDefinition::get_XmlText`](crate::prelude::taskschd_ITaskDefinition::get_XmlText).
+	///
+	/// Malformed XML, or a root element other than `Task`, yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA). Triggers
+	/// and actions not modeled by this type yield
+	/// [`co::ERROR::NOT_SUPPORTED`](crate::co::ERROR::NOT_SUPPORTED), so they
+	/// are never silently dropped.
+	#[must_use]
+	pub fn parse(xml: &str) -> SysResult<Self> {
+		Self::from_root(XmlElem::parse(xml)?)
+	}
+
+	/// Parses the raw contents of a task definition file, either UTF-8 or
+	/// UTF-16 with BOM, like those exported by `schtasks /query /xml`.
+	///
+	/// Errors are the same of [`TaskXml::parse`](crate::TaskXml::parse).
+	#[must_use]
+	pub fn parse_bytes(data: &[u8]) -> SysResult<Self> {
+		Self::from_root(XmlElem::parse_bytes(data)?)
+	}
+
+	fn from_root(root: XmlElem) -> SysResult<Self> {
+		if root.name != "Task" {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+
+		let reg = root.find("RegistrationInfo");
+		let reg_text = |name: &str| opt_text(reg.and_then(|r| r.child_text(name)));
+
+		Ok(Self {
+			registration: TaskXmlRegistration {
+				date: reg_text("Date"),
+				author: reg_text("Author"),
+				version: reg_text("Version"),
+				source: reg_text("Source"),
+				description: reg_text("Description"),
+				documentation: reg_text("Documentation"),
+				uri: reg_text("URI"),
+			},
+			triggers: match root.find("Triggers") {
+				Some(triggers) => triggers
+					.children
+					.iter()
+					.map(TaskXmlTrigger::from_xml)
+					.collect::<SysResult<Vec<_>>>()?,
+				None => Vec::new(),
+			},
+			principal: root
+				.find_path(&["Principals", "Principal"])
+				.map(TaskXmlPrincipal::from_xml),
+			settings: match root.find("Settings") {
+				Some(settings) => TaskXmlSettings::from_xml(settings)?,
+				None => TaskXmlSettings::default(),
+			},
+			actions: match root.find("Actions") {
+				Some(actions) => actions
+					.children
+					.iter()
+					.map(TaskXmlAction::from_xml)
+					.collect::<SysResult<Vec<_>>>()?,
+				None => Vec::new(),
+			},
+			data: opt_text(root.child_text("Data")),
+		})
+	}
+
+	/// Renders the task definition as XML, to be passed to
+	/// [`ITaskFolder::RegisterTask`](crate::prelude::taskschd_ITaskFolder::RegisterTask)
+	/// or
+	/// [`ITaskDefinition::put_XmlText`](crate::prelude::taskschd_ITaskDefinition::put_XmlText).
+	#[must_use]
+	pub fn to_xml(&self) -> String {
+		let mut root = XmlElem::new("Task")
+			.attr("version", "1.2")
+			.attr("xmlns", NS_TASK);
+
+		let r = &self.registration;
+		let mut reg = XmlElem::new("RegistrationInfo");
+		for (name, val) in [
+			("Date", &r.date),
+			("Author", &r.author),
+			("Version", &r.version),
+			("Source", &r.source),
+			("Description", &r.description),
+			("Documentation", &r.documentation),
+			("URI", &r.uri),
+		] {
+			if let Some(val) = val {
+				reg = reg.child(XmlElem::with_text(name, val));
+			}
+		}
+		root = root.child(reg);
+
+		let mut triggers = XmlElem::new("Triggers");
+		triggers.children = self.triggers.iter().map(|t| t.to_xml()).collect();
+		root = root.child(triggers);
+
+		if let Some(principal) = &self.principal {
+			root = root.child(XmlElem::new("Principals").child(principal.to_xml()));
+		}
+
+		root = root.child(self.settings.to_xml());
+
+		if let Some(data) = &self.data {
+			root = root.child(XmlElem::with_text("Data", data));
+		}
+
+		let mut actions = XmlElem::new("Actions");
+		if let Some(principal) = &self.principal {
+			actions = actions.attr("Context", &principal.id); // must reference a declared principal
+		}
+		actions.children = self.actions.iter().map(|a| a.to_xml()).collect();
+		root.child(actions).render("UTF-16", false)
+	}
+
+	/// Serializes the task definition as UTF-16 with BOM, the format of the
+	/// files accepted by `schtasks /create /xml`.
+	#[must_use]
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut buf = vec![0xff, 0xfe];
+		self.to_xml()
+			.encode_utf16()
+			.for_each(|ch| buf.extend_from_slice(&ch.to_le_bytes()));
+		buf
+	}
+
+	/// Checks the task definition against the rules of the Task Scheduler,
+	/// returning a description of each problem found. An empty `Vec` means
+	/// the definition is valid.
+	///
+	/// This allows definitions to be checked offline; the Task Scheduler
+	/// itself may still reject a definition, for example when an account
+	/// doesn't exist.
+	#[must_use]
+	pub fn validate(&self) -> Vec<String> {
+		let mut probs = Vec::<String>::new();
+
+		if let Some(date) = &self.registration.date {
+			if !is_date_time(date) {
+				probs.push(format!("Registration date \"{}\" is not a valid date.", date));
+			}
+		}
+
+		if self.triggers.len() > 48 {
+			probs.push(format!("{} triggers, the maximum is 48.", self.triggers.len()));
+		}
+		for (idx, trigger) in self.triggers.iter().enumerate() {
+			trigger.validate(idx, &mut probs);
+		}
+
+		if let Some(principal) = &self.principal {
+			if principal.user_id.is_some() && principal.group_id.is_some() {
+				probs.push("Principal has both a user and a group.".to_owned());
+			}
+			if let Some(logon_type) = principal.logon_type {
+				if logon_type_name(logon_type).is_none() {
+					probs.push(format!("Principal logon type {} cannot be written.", logon_type));
+				}
+			}
+		}
+
+		self.settings.validate(&mut probs);
+		if self.settings.delete_expired_task_after.is_some()
+			&& !self.triggers.iter().any(|t| t.end_boundary.is_some())
+		{
+			probs.push(
+				"Deleting an expired task requires a trigger with an end boundary.".to_owned(),
+			);
+		}
+
+		if self.actions.is_empty() {
+			probs.push("At least one action is required.".to_owned());
+		} else if self.actions.len() > 32 {
+			probs.push(format!("{} actions, the maximum is 32.", self.actions.len()));
+		}
+		for (idx, action) in self.actions.iter().enumerate() {
+			match action {
+				TaskXmlAction::Exec { command, .. } => {
+					if command.trim().is_empty() {
+						probs.push(format!("Action {}: empty command.", idx));
+					}
+				},
+				TaskXmlAction::ComHandler { class_id, .. } => {
+					if !is_clsid(class_id) {
+						probs.push(format!(
+							"Action {}: \"{}\" is not a valid CLSID.",
+							idx, class_id
+						));
+					}
+				},
+			}
+		}
+
+		probs
+	}
+}
+
+impl TaskXmlTrigger {
+	/// Returns a new enabled trigger with the given schedule, and no
+	/// boundaries, limit or repetition.
+	#[must_use]
+	pub const fn new(schedule: TaskXmlSchedule) -> Self {
+		Self {
+			id: None,
+			schedule,
+			enabled: true,
+			start_boundary: None,
+			end_boundary: None,
+			execution_time_limit: None,
+			repetition: None,
+		}
+	}
+
+	fn from_xml(elem: &XmlElem) -> SysResult<Self> {
+		let text = |name: &str| opt_text(elem.child_text(name));
+		let random_delay = text("RandomDelay");
+		let delay = text("Delay");
+
+		let schedule = match elem.name.as_str() {
+			"TimeTrigger" => TaskXmlSchedule::Time { random_delay },
+			"CalendarTrigger" => {
+				if let Some(by_day) = elem.find("ScheduleByDay") {
+					TaskXmlSchedule::Daily {
+						days_interval: parse_num(by_day.child_text("DaysInterval"), 1)?,
+						random_delay,
+					}
+				} else if let Some(by_week) = elem.find("ScheduleByWeek") {
+					TaskXmlSchedule::Weekly {
+						weeks_interval: parse_num(by_week.child_text("WeeksInterval"), 1)?,
+						days_of_week: parse_names(by_week.find("DaysOfWeek"), &DAYS)? as _,
+						random_delay,
+					}
+				} else if let Some(by_month) = elem.find("ScheduleByMonth") {
+					let mut days_of_month = 0u32;
+					let mut run_on_last_day = false;
+					for day in by_month
+						.find("DaysOfMonth")
+						.into_iter()
+						.flat_map(|d| d.find_all("Day"))
+					{
+						if day.text.eq_ignore_ascii_case("Last") {
+							run_on_last_day = true;
+						} else {
+							match day.text.parse::<u32>() {
+								Ok(n @ 1..=31) => days_of_month |= 1 << (n - 1),
+								_ => return Err(co::ERROR::INVALID_DATA),
+							}
+						}
+					}
+					TaskXmlSchedule::Monthly {
+						days_of_month,
+						run_on_last_day,
+						months: parse_names(by_month.find("Months"), &MONTHS)? as _,
+						random_delay,
+					}
+				} else if let Some(by_dow) = elem.find("ScheduleByMonthDayOfWeek") {
+					let mut weeks = 0u8;
+					for week in by_dow
+						.find("Weeks")
+						.into_iter()
+						.flat_map(|w| w.find_all("Week"))
+					{
+						weeks |= match week.text.as_str() {
+							"1" => 0x01,
+							"2" => 0x02,
+							"3" => 0x04,
+							"4" => 0x08,
+							w if w.eq_ignore_ascii_case("Last") => 0x10,
+							_ => return Err(co::ERROR::INVALID_DATA),
+						};
+					}
+					TaskXmlSchedule::MonthlyDow {
+						weeks,
+						days_of_week: parse_names(by_dow.find("DaysOfWeek"), &DAYS)? as _,
+						months: parse_names(by_dow.find("Months"), &MONTHS)? as _,
+						random_delay,
+					}
+				} else {
+					return Err(co::ERROR::INVALID_DATA); // calendar trigger without schedule
+				}
+			},
+			"BootTrigger" => TaskXmlSchedule::Boot { delay },
+			"LogonTrigger" => TaskXmlSchedule::Logon { user_id: text("UserId"), delay },
+			"IdleTrigger" => TaskXmlSchedule::Idle,
+			"EventTrigger" => TaskXmlSchedule::Event {
+				subscription: elem
+					.child_text("Subscription")
+					.unwrap_or_default()
+					.to_owned(),
+				delay,
+			},
+			"RegistrationTrigger" => TaskXmlSchedule::Registration { delay },
+			_ => return Err(co::ERROR::NOT_SUPPORTED),
+		};
+
+		Ok(Self {
+			id: elem.get_attr("id").map(|s| s.to_owned()),
+			schedule,
+			enabled: parse_bool(elem.child_text("Enabled"), true)?,
+			start_boundary: text("StartBoundary"),
+			end_boundary: text("EndBoundary"),
+			execution_time_limit: text("ExecutionTimeLimit"),
+			repetition: match elem.find("Repetition") {
+				Some(rep) => Some(TaskXmlRepetition {
+					interval: rep.child_text("Interval").unwrap_or_default().to_owned(),
+					duration: opt_text(rep.child_text("Duration")),
+					stop_at_duration_end: parse_bool(rep.child_text("StopAtDurationEnd"), false)?,
+				}),
+				None => None,
+			},
+		})
+	}
+
+	fn to_xml(&self) -> XmlElem {
+		let name = match &self.schedule {
+			TaskXmlSchedule::Time { .. } => "TimeTrigger",
+			TaskXmlSchedule::Daily { .. }
+			| TaskXmlSchedule::Weekly { .. }
+			| TaskXmlSchedule::Monthly { .. }
+			| TaskXmlSchedule::MonthlyDow { .. } => "CalendarTrigger",
+			TaskXmlSchedule::Boot { .. } => "BootTrigger",
+			TaskXmlSchedule::Logon { .. } => "LogonTrigger",
+			TaskXmlSchedule::Idle => "IdleTrigger",
+			TaskXmlSchedule::Event { .. } => "EventTrigger",
+			TaskXmlSchedule::Registration { .. } => "RegistrationTrigger",
+		};
+
+		let mut elem = XmlElem::new(name);
+		if let Some(id) = &self.id {
+			elem = elem.attr("id", id);
+		}
+		if let Some(rep) = &self.repetition {
+			let mut rep_elem =
+				XmlElem::new("Repetition").child(XmlElem::with_text("Interval", &rep.interval));
+			if let Some(duration) = &rep.duration {
+				rep_elem = rep_elem.child(XmlElem::with_text("Duration", duration));
+			}
+			elem = elem.child(rep_elem.child(XmlElem::with_text(
+				"StopAtDurationEnd",
+				bool_text(rep.stop_at_duration_end),
+			)));
+		}
+		for (name, val) in [
+			("StartBoundary", &self.start_boundary),
+			("EndBoundary", &self.end_boundary),
+			("ExecutionTimeLimit", &self.execution_time_limit),
+		] {
+			if let Some(val) = val {
+				elem = elem.child(XmlElem::with_text(name, val));
+			}
+		}
+		elem = elem.child(XmlElem::with_text("Enabled", bool_text(self.enabled)));
+
+		let opt_child = |elem: XmlElem, name: &str, val: &Option<String>| match val {
+			Some(val) => elem.child(XmlElem::with_text(name, val)),
+			None => elem,
+		};
+
+		match &self.schedule {
+			TaskXmlSchedule::Time { random_delay } => opt_child(elem, "RandomDelay", random_delay),
+			TaskXmlSchedule::Daily { days_interval, random_delay } => {
+				opt_child(elem, "RandomDelay", random_delay).child(
+					XmlElem::new("ScheduleByDay")
+						.child(XmlElem::with_text("DaysInterval", &days_interval.to_string())),
+				)
+			},
+			TaskXmlSchedule::Weekly {
+				weeks_interval,
+				days_of_week,
+				random_delay,
+			} => opt_child(elem, "RandomDelay", random_delay).child(
+				XmlElem::new("ScheduleByWeek")
+					.child(names_to_xml("DaysOfWeek", *days_of_week as _, &DAYS))
+					.child(XmlElem::with_text("WeeksInterval", &weeks_interval.to_string())),
+			),
+			TaskXmlSchedule::Monthly {
+				days_of_month,
+				run_on_last_day,
+				months,
+				random_delay,
+			} => {
+				let mut days = XmlElem::new("DaysOfMonth");
+				for n in (0..31).filter(|n| days_of_month & (1 << n) != 0) {
+					days = days.child(XmlElem::with_text("Day", &(n + 1).to_string()));
+				}
+				if *run_on_last_day {
+					days = days.child(XmlElem::with_text("Day", "Last"));
+				}
+				opt_child(elem, "RandomDelay", random_delay).child(
+					XmlElem::new("ScheduleByMonth")
+						.child(days)
+						.child(names_to_xml("Months", *months as _, &MONTHS)),
+				)
+			},
+			TaskXmlSchedule::MonthlyDow {
+				weeks,
+				days_of_week,
+				months,
+				random_delay,
+			} => {
+				let mut weeks_elem = XmlElem::new("Weeks");
+				for (bit, week) in ["1", "2", "3", "4", "Last"].iter().enumerate() {
+					if weeks & (1 << bit) != 0 {
+						weeks_elem = weeks_elem.child(XmlElem::with_text("Week", week));
+					}
+				}
+				opt_child(elem, "RandomDelay", random_delay).child(
+					XmlElem::new("ScheduleByMonthDayOfWeek")
+						.child(weeks_elem)
+						.child(names_to_xml("DaysOfWeek", *days_of_week as _, &DAYS))
+						.child(names_to_xml("Months", *months as _, &MONTHS)),
+				)
+			},
+			TaskXmlSchedule::Boot { delay } | TaskXmlSchedule::Registration { delay } => {
+				opt_child(elem, "Delay", delay)
+			},
+			TaskXmlSchedule::Logon { user_id, delay } => {
+				opt_child(opt_child(elem, "UserId", user_id), "Delay", delay)
+			},
+			TaskXmlSchedule::Idle => elem,
+			TaskXmlSchedule::Event { subscription, delay } => opt_child(
+				elem.child(XmlElem::with_text("Subscription", subscription)),
+				"Delay",
+				delay,
+			),
+		}
+	}
+
+	fn validate(&self, idx: usize, probs: &mut Vec<String>) {
+		let mut check_duration = |what: &str, val: &Option<String>| {
+			if let Some(val) = val {
+				if duration_secs(val).is_none() {
+					probs.push(format!(
+						"Trigger {}: {} \"{}\" is not a valid duration.",
+						idx, what, val
+					));
+				}
+			}
+		};
+		check_duration("execution time limit", &self.execution_time_limit);
+		match &self.schedule {
+			TaskXmlSchedule::Time { random_delay }
+			| TaskXmlSchedule::Daily { random_delay, .. }
+			| TaskXmlSchedule::Weekly { random_delay, .. }
+			| TaskXmlSchedule::Monthly { random_delay, .. }
+			| TaskXmlSchedule::MonthlyDow { random_delay, .. } => {
+				check_duration("random delay", random_delay);
+			},
+			TaskXmlSchedule::Boot { delay }
+			| TaskXmlSchedule::Logon { delay, .. }
+			| TaskXmlSchedule::Event { delay, .. }
+			| TaskXmlSchedule::Registration { delay } => check_duration("delay", delay),
+			TaskXmlSchedule::Idle => {},
+		}
+
+		for (what, val) in [("start", &self.start_boundary), ("end", &self.end_boundary)] {
+			if let Some(val) = val {
+				if !is_date_time(val) {
+					probs.push(format!(
+						"Trigger {}: {} boundary \"{}\" is not a valid date.",
+						idx, what, val
+					));
+				}
+			}
+		}
+
+		let is_time_based = matches!(
+			self.schedule,
+			TaskXmlSchedule::Time { .. }
+				| TaskXmlSchedule::Daily { .. }
+				| TaskXmlSchedule::Weekly { .. }
+				| TaskXmlSchedule::Monthly { .. }
+				| TaskXmlSchedule::MonthlyDow { .. }
+		);
+		if is_time_based && self.start_boundary.is_none() {
+			probs.push(format!("Trigger {}: a start boundary is required.", idx));
+		}
+
+		match &self.schedule {
+			TaskXmlSchedule::Daily { days_interval, .. } if !(1..=365).contains(days_interval) => {
+				probs.push(format!("Trigger {}: days interval must be from 1 to 365.", idx));
+			},
+			TaskXmlSchedule::Weekly { weeks_interval, days_of_week, .. } => {
+				if !(1..=52).contains(weeks_interval) {
+					probs.push(format!("Trigger {}: weeks interval must be from 1 to 52.", idx));
+				}
+				if *days_of_week == 0 || *days_of_week > 0x7f {
+					probs.push(format!("Trigger {}: invalid days of the week.", idx));
+				}
+			},
+			TaskXmlSchedule::Monthly {
+				days_of_month, run_on_last_day, months, ..
+			} => {
+				if (*days_of_month == 0 && !run_on_last_day) || *days_of_month > 0x7fff_ffff {
+					probs.push(format!("Trigger {}: invalid days of the month.", idx));
+				}
+				if *months == 0 || *months > 0xfff {
+					probs.push(format!("Trigger {}: invalid months.", idx));
+				}
+			},
+			TaskXmlSchedule::MonthlyDow { weeks, days_of_week, months, .. } => {
+				if *weeks == 0 || *weeks > 0x1f {
+					probs.push(format!("Trigger {}: invalid weeks of the month.", idx));
+				}
+				if *days_of_week == 0 || *days_of_week > 0x7f {
+					probs.push(format!("Trigger {}: invalid days of the week.", idx));
+				}
+				if *months == 0 || *months > 0xfff {
+					probs.push(format!("Trigger {}: invalid months.", idx));
+				}
+			},
+			TaskXmlSchedule::Event { subscription, .. } if subscription.trim().is_empty() => {
+				probs.push(format!("Trigger {}: empty event subscription.", idx));
+			},
+			_ => {},
+		}
+
+		if let Some(rep) = &self.repetition {
+			match duration_secs(&rep.interval) {
+				Some(secs) if (60..=31 * 86400).contains(&secs) => {
+					if let Some(duration) = &rep.duration {
+						match duration_secs(duration) {
+							Some(dur_secs) if dur_secs >= secs => {},
+							Some(_) => probs.push(format!(
+								"Trigger {}: repetition duration is shorter than its interval.",
+								idx,
+							)),
+							None => probs.push(format!(
+								"Trigger {}: repetition duration \"{}\" is not a valid duration.",
+								idx, duration,
+							)),
+						}
+					}
+				},
+				_ => probs.push(format!(
+					"Trigger {}: repetition interval \"{}\" must be from 1 minute to 31 days.",
+					idx, rep.interval,
+				)),
+			}
+		}
+	}
+}
+
+impl Default for TaskXmlPrincipal {
+	fn default() -> Self {
+		Self {
+			id: "Author".to_owned(),
+			user_id: None,
+			group_id: None,
+			logon_type: None,
+			run_level: None,
+		}
+	}
+}
+
+impl TaskXmlPrincipal {
+	fn from_xml(elem: &XmlElem) -> Self {
+		Self {
+			id: elem.get_attr("id").unwrap_or("Author").to_owned(),
+			user_id: opt_text(elem.child_text("UserId")),
+			group_id: opt_text(elem.child_text("GroupId")),
+			logon_type: elem.child_text("LogonType").and_then(|t| match t {
+				"S4U" => Some(co::TASK_LOGON::S4U),
+				"Password" => Some(co::TASK_LOGON::PASSWORD),
+				"InteractiveToken" => Some(co::TASK_LOGON::INTERACTIVE_TOKEN),
+				"InteractiveTokenOrPassword" => Some(co::TASK_LOGON::INTERACTIVE_TOKEN_OR_PASSWORD),
+				_ => None,
+			}),
+			run_level: elem.child_text("RunLevel").and_then(|t| match t {
+				"LeastPrivilege" => Some(co::TASK_RUNLEVEL::LUA),
+				"HighestAvailable" => Some(co::TASK_RUNLEVEL::HIGHEST),
+				_ => None,
+			}),
+		}
+	}
+
+	fn to_xml(&self) -> XmlElem {
+		let mut elem = XmlElem::new("Principal").attr("id", &self.id);
+		if let Some(user_id) = &self.user_id {
+			elem = elem.child(XmlElem::with_text("UserId", user_id));
+		}
+		if let Some(group_id) = &self.group_id {
+			elem = elem.child(XmlElem::with_text("GroupId", group_id));
+		}
+		if let Some(name) = self.logon_type.and_then(logon_type_name) {
+			elem = elem.child(XmlElem::with_text("LogonType", name));
+		}
+		match self.run_level {
+			Some(co::TASK_RUNLEVEL::LUA) => {
+				elem = elem.child(XmlElem::with_text("RunLevel", "LeastPrivilege"));
+			},
+			Some(co::TASK_RUNLEVEL::HIGHEST) => {
+				elem = elem.child(XmlElem::with_text("RunLevel", "HighestAvailable"));
+			},
+			_ => {},
+		}
+		elem
+	}
+}
+
+impl TaskXmlSettings {
+	fn from_xml(elem: &XmlElem) -> SysResult<Self> {
+		let def = Self::default();
+		let flag = |name: &str, def: bool| parse_bool(elem.child_text(name), def);
+		let idle = elem.find("IdleSettings");
+
+		Ok(Self {
+			multiple_instances: match elem.child_text("MultipleInstancesPolicy") {
+				None => def.multiple_instances,
+				Some("Parallel") => co::TASK_INSTANCES_POLICY::PARALLEL,
+				Some("Queue") => co::TASK_INSTANCES_POLICY::QUEUE,
+				Some("IgnoreNew") => co::TASK_INSTANCES_POLICY::IGNORE_NEW,
+				Some("StopExisting") => co::TASK_INSTANCES_POLICY::STOP_EXISTING,
+				Some(_) => return Err(co::ERROR::INVALID_DATA),
+			},
+			disallow_start_if_on_batteries: flag(
+				"DisallowStartIfOnBatteries",
+				def.disallow_start_if_on_batteries,
+			)?,
+			stop_if_going_on_batteries: flag(
+				"StopIfGoingOnBatteries",
+				def.stop_if_going_on_batteries,
+			)?,
+			allow_hard_terminate: flag("AllowHardTerminate", def.allow_hard_terminate)?,
+			start_when_available: flag("StartWhenAvailable", def.start_when_available)?,
+			run_only_if_network_available: flag(
+				"RunOnlyIfNetworkAvailable",
+				def.run_only_if_network_available,
+			)?,
+			idle_duration: opt_text(idle.and_then(|i| i.child_text("Duration"))),
+			idle_wait_timeout: opt_text(idle.and_then(|i| i.child_text("WaitTimeout"))),
+			stop_on_idle_end: parse_bool(
+				idle.and_then(|i| i.child_text("StopOnIdleEnd")),
+				def.stop_on_idle_end,
+			)?,
+			restart_on_idle: parse_bool(
+				idle.and_then(|i| i.child_text("RestartOnIdle")),
+				def.restart_on_idle,
+			)?,
+			allow_start_on_demand: flag("AllowStartOnDemand", def.allow_start_on_demand)?,
+			enabled: flag("Enabled", def.enabled)?,
+			hidden: flag("Hidden", def.hidden)?,
+			run_only_if_idle: flag("RunOnlyIfIdle", def.run_only_if_idle)?,
+			wake_to_run: flag("WakeToRun", def.wake_to_run)?,
+			execution_time_limit: match elem.child_text("ExecutionTimeLimit") {
+				Some("PT0S") => None, // zero means unlimited
+				Some(limit) => Some(limit.to_owned()),
+				None => def.execution_time_limit,
+			},
+			delete_expired_task_after: opt_text(elem.child_text("DeleteExpiredTaskAfter")),
+			priority: parse_num(elem.child_text("Priority"), def.priority)?,
+			restart_on_failure: match elem.find("RestartOnFailure") {
+				Some(restart) => Some((
+					restart
+						.child_text("Interval")
+						.unwrap_or_default()
+						.to_owned(),
+					parse_num(restart.child_text("Count"), 1)?,
+				)),
+				None => None,
+			},
+		})
+	}
+
+	fn to_xml(&self) -> XmlElem {
+		let policy = match self.multiple_instances {
+			co::TASK_INSTANCES_POLICY::PARALLEL => "Parallel",
+			co::TASK_INSTANCES_POLICY::QUEUE => "Queue",
+			co::TASK_INSTANCES_POLICY::STOP_EXISTING => "StopExisting",
+			_ => "IgnoreNew",
+		};
+		let mut elem =
+			XmlElem::new("Settings").child(XmlElem::with_text("MultipleInstancesPolicy", policy));
+
+		for (name, val) in [
+			("DisallowStartIfOnBatteries", self.disallow_start_if_on_batteries),
+			("StopIfGoingOnBatteries", self.stop_if_going_on_batteries),
+			("AllowHardTerminate", self.allow_hard_terminate),
+			("StartWhenAvailable", self.start_when_available),
+			("RunOnlyIfNetworkAvailable", self.run_only_if_network_available),
+		] {
+			elem = elem.child(XmlElem::with_text(name, bool_text(val)));
+		}
+
+		let mut idle = XmlElem::new("IdleSettings");
+		if let Some(duration) = &self.idle_duration {
+			idle = idle.child(XmlElem::with_text("Duration", duration));
+		}
+		if let Some(timeout) = &self.idle_wait_timeout {
+			idle = idle.child(XmlElem::with_text("WaitTimeout", timeout));
+		}
+		elem = elem.child(
+			idle.child(XmlElem::with_text("StopOnIdleEnd", bool_text(self.stop_on_idle_end)))
+				.child(XmlElem::with_text("RestartOnIdle", bool_text(self.restart_on_idle))),
+		);
+
+		for (name, val) in [
+			("AllowStartOnDemand", self.allow_start_on_demand),
+			("Enabled", self.enabled),
+			("Hidden", self.hidden),
+			("RunOnlyIfIdle", self.run_only_if_idle),
+			("WakeToRun", self.wake_to_run),
+		] {
+			elem = elem.child(XmlElem::with_text(name, bool_text(val)));
+		}
+
+		elem = elem.child(XmlElem::with_text(
+			"ExecutionTimeLimit",
+			self.execution_time_limit.as_deref().unwrap_or("PT0S"),
+		));
+		if let Some(delete) = &self.delete_expired_task_after {
+			elem = elem.child(XmlElem::with_text("DeleteExpiredTaskAfter", delete));
+		}
+		elem = elem.child(XmlElem::with_text("Priority", &self.priority.to_string()));
+		if let Some((interval, count)) = &self.restart_on_failure {
+			elem = elem.child(
+				XmlElem::new("RestartOnFailure")
+					.child(XmlElem::with_text("Interval", interval))
+					.child(XmlElem::with_text("Count", &count.to_string())),
+			);
+		}
+		elem
+	}
+
+	fn validate(&self, probs: &mut Vec<String>) {
+		for (what, val) in [
+			("Execution time limit", &self.execution_time_limit),
+			("Delete expired task delay", &self.delete_expired_task_after),
+			("Idle duration", &self.idle_duration),
+			("Idle wait timeout", &self.idle_wait_timeout),
+		] {
+			if let Some(val) = val {
+				if duration_secs(val).is_none() {
+					probs.push(format!("{} \"{}\" is not a valid duration.", what, val));
+				}
+			}
+		}
+		if self.priority > 10 {
+			probs.push(format!("Priority {} is out of range, the maximum is 10.", self.priority));
+		}
+		if let Some((interval, count)) = &self.restart_on_failure {
+			if !duration_secs(interval).is_some_and(|secs| (60..=31 * 86400).contains(&secs)) {
+				probs.push(format!(
+					"Restart interval \"{}\" must be from 1 minute to 31 days.",
+					interval
+				));
+			}
+			if !(1..=999).contains(count) {
+				probs.push(format!("Restart count {} must be from 1 to 999.", count));
+			}
+		}
+	}
+}
+
+impl TaskXmlAction {
+	fn from_xml(elem: &XmlElem) -> SysResult<Self> {
+		match elem.name.as_str() {
+			"Exec" => Ok(Self::Exec {
+				command: elem.child_text("Command").unwrap_or_default().to_owned(),
+				arguments: opt_text(elem.child_text("Arguments")),
+				working_directory: opt_text(elem.child_text("WorkingDirectory")),
+			}),
+			"ComHandler" => Ok(Self::ComHandler {
+				class_id: elem.child_text("ClassId").unwrap_or_default().to_owned(),
+				data: opt_text(elem.child_text("Data")),
+			}),
+			_ => Err(co::ERROR::NOT_SUPPORTED),
+		}
+	}
+
+	fn to_xml(&self) -> XmlElem {
+		match self {
+			Self::Exec { command, arguments, working_directory } => {
+				let mut elem = XmlElem::new("Exec").child(XmlElem::with_text("Command", command));
+				if let Some(arguments) = arguments {
+					elem = elem.child(XmlElem::with_text("Arguments", arguments));
+				}
+				if let Some(dir) = working_directory {
+					elem = elem.child(XmlElem::with_text("WorkingDirectory", dir));
+				}
+				elem
+			},
+			Self::ComHandler { class_id, data } => {
+				let mut elem =
+					XmlElem::new("ComHandler").child(XmlElem::with_text("ClassId", class_id));
+				if let Some(data) = data {
+					elem = elem.child(XmlElem::with_text("Data", data));
+				}
+				elem
+			},
+		}
+	}
+}
+
+/// Returns the XML name of the logon type, if it can be written.
+fn logon_type_name(logon_type: co::TASK_LOGON) -> Option<&'static str> {
+	match logon_type {
+		co::TASK_LOGON::S4U => Some("S4U"),
+		co::TASK_LOGON::PASSWORD => Some("Password"),
+		co::TASK_LOGON::INTERACTIVE_TOKEN => Some("InteractiveToken"),
+		co::TASK_LOGON::INTERACTIVE_TOKEN_OR_PASSWORD => Some("InteractiveTokenOrPassword"),
+		_ => None,
+	}
+}
+
+/// Returns the text as an owned string, if present and not empty.
+fn opt_text(text: Option<&str>) -> Option<String> {
+	text.filter(|t| !t.is_empty()).map(|t| t.to_owned())
+}
+
+fn bool_text(val: bool) -> &'static str {
+	if val { "true" } else { "false" }
+}
+
+/// Parses an `xs:boolean`, returning the default if absent.
+fn parse_bool(text: Option<&str>, def: bool) -> SysResult<bool> {
+	match text {
+		None => Ok(def),
+		Some("true" | "1") => Ok(true),
+		Some("false" | "0") => Ok(false),
+		Some(_) => Err(co::ERROR::INVALID_DATA),
+	}
+}
+
+/// Parses a number, returning the default if absent.
+fn parse_num<T: std::str::FromStr>(text: Option<&str>, def: T) -> SysResult<T> {
+	match text {
+		None => Ok(def),
+		Some(t) => t.parse().map_err(|_| co::ERROR::INVALID_DATA),
+	}
+}
+
+/// Parses a list of empty elements named after days or months into a
+/// bitmask.
+fn parse_names(elem: Option<&XmlElem>, names: &[&str]) -> SysResult<u16> {
+	let mut mask = 0u16;
+	for child in elem.iter().flat_map(|e| e.children.iter()) {
+		let idx = names
+			.iter()
+			.position(|n| *n == child.name)
+			.ok_or(co::ERROR::INVALID_DATA)?;
+		mask |= 1 << idx;
+	}
+	Ok(mask)
+}
+
+/// Renders a bitmask as a list of empty elements named after days or months.
+fn names_to_xml(name: &str, mask: u16, names: &[&str]) -> XmlElem {
+	let mut elem = XmlElem::new(name);
+	for (idx, child) in names.iter().enumerate() {
+		if mask & (1 << idx) != 0 {
+			elem = elem.child(XmlElem::new(child));
+		}
+	}
+	elem
+}
+
+/// Parses an ISO 8601 duration like `P1DT12H`, returning the approximate
+/// number of seconds, with months of 30 days and years of 365.
+fn duration_secs(text: &str) -> Option<u64> {
+	let mut rest = text.strip_prefix('P')?;
+	if rest.is_empty() {
+		return None;
+	}
+
+	let mut total = 0u64;
+	let mut in_time = false;
+	let mut units_seen = 0;
+	while !rest.is_empty() {
+		if let Some(time) = rest.strip_prefix('T') {
+			if in_time || time.is_empty() {
+				return None;
+			}
+			in_time = true;
+			rest = time;
+			continue;
+		}
+		let num_len = rest.find(|ch: char| !ch.is_ascii_digit() && ch != '.')?;
+		let num = &rest[..num_len];
+		let unit = rest[num_len..].chars().next()?;
+		rest = &rest[num_len + 1..];
+		if num.is_empty() {
+			return None;
+		}
+		let mult = match (in_time, unit) {
+			(false, 'Y') => 365 * 86400,
+			(false, 'M') => 30 * 86400,
+			(false, 'W') => 7 * 86400,
+			(false, 'D') => 86400,
+			(true, 'H') => 3600,
+			(true, 'M') => 60,
+			(true, 'S') => 1,
+			_ => return None,
+		};
+		let val =
+			if unit == 'S' { num.parse::<f64>().ok()? as u64 } else { num.parse::<u64>().ok()? };
+		total = total.checked_add(val.checked_mul(mult)?)?;
+		units_seen += 1;
+	}
+	if units_seen == 0 { None } else { Some(total) }
+}
+
+/// Tells whether the text is an `xs:dateTime`, like `2024-01-31T08:00:00`,
+/// optionally with fractional seconds and a time zone.
+fn is_date_time(text: &str) -> bool {
+	let b = text.as_bytes();
+	let digits = |range: std::ops::Range<usize>| {
+		b.get(range)
+			.is_some_and(|d| d.iter().all(u8::is_ascii_digit))
+	};
+	if b.len() < 19
+		|| !digits(0..4)
+		|| b[4] != b'-'
+		|| !digits(5..7)
+		|| b[7] != b'-'
+		|| !digits(8..10)
+		|| b[10] != b'T'
+		|| !digits(11..13)
+		|| b[13] != b':'
+		|| !digits(14..16)
+		|| b[16] != b':'
+		|| !digits(17..19)
+	{
+		return false;
+	}
+
+	let num = |range: std::ops::Range<usize>| text[range].parse::<u32>().unwrap_or(0);
+	if !(1..=12).contains(&num(5..7))
+		|| !(1..=31).contains(&num(8..10))
+		|| num(11..13) > 23
+		|| num(14..16) > 59
+		|| num(17..19) > 59
+	{
+		return false;
+	}
+
+	let mut rest = &text[19..];
+	if let Some(frac) = rest.strip_prefix('.') {
+		let len = frac
+			.find(|ch: char| !ch.is_ascii_digit())
+			.unwrap_or(frac.len());
+		if len == 0 {
+			return false;
+		}
+		rest = &frac[len..];
+	}
+	match rest.as_bytes() {
+		[] | [b'Z'] => true,
+		[b'+' | b'-', h1, h2, b':', m1, m2] => [h1, h2, m1, m2].iter().all(|d| d.is_ascii_digit()),
+		_ => false,
+	}
+}
+
+/// Tells whether the text is a CLSID in the registry format.
+fn is_clsid(text: &str) -> bool {
+	let Some(inner) = text.strip_prefix('{').and_then(|t| t.strip_suffix('}')) else {
+		return false;
+	};
+	let groups = inner.split('-').collect::<Vec<_>>();
+	groups.len() == 5
+		&& groups
+			.iter()
+			.zip([8, 4, 4, 4, 12])
+			.all(|(g, len)| g.len() == len && g.chars().all(|ch| ch.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn notepad_task() -> TaskXml {
+		TaskXml {
+			actions: vec![TaskXmlAction::Exec {
+				command: "notepad.exe".to_owned(),
+				arguments: None,
+				working_directory: None,
+			}],
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn actions_context() {
+		let mut task = notepad_task();
+		let xml = task.to_xml();
+		assert!(!xml.contains("<Principals"));
+		assert!(!xml.contains("Context="));
+		assert!(TaskXml::parse(&xml).unwrap() == task);
+
+		task.principal = Some(TaskXmlPrincipal {
+			user_id: Some("S-1-5-18".to_owned()),
+			..Default::default()
+		});
+		let xml = task.to_xml();
+		assert!(xml.contains("<Principal id=\"Author\">"));
+		assert!(xml.contains("<Actions Context=\"Author\">"));
+		assert!(TaskXml::parse(&xml).unwrap() == task);
+	}
+
+	#[test]
+	fn serialize_bom() {
+		let task = notepad_task();
+		let data = task.serialize();
+		assert_eq!(&data[..2], [0xff, 0xfe]);
+		assert!(TaskXml::parse_bytes(&data).unwrap() == task);
+	}
+}
@@ -108,6 +108,19 @@ pub struct ILogonTriggerVT {
 	pub put_UserId: fn(COMPTR, PCSTR) -> HRES,
 }
 
+#[repr(C)]
+pub struct IMonthlyTriggerVT {
+	pub ITriggerVT: ITriggerVT,
+	pub get_DaysOfMonth: fn(COMPTR, *mut i32) -> HRES,
+	pub put_DaysOfMonth: fn(COMPTR, i32) -> HRES,
+	pub get_MonthsOfYear: fn(COMPTR, *mut i16) -> HRES,
+	pub put_MonthsOfYear: fn(COMPTR, i16) -> HRES,
+	pub get_RunOnLastDayOfMonth: fn(COMPTR, *mut i16) -> HRES,
+	pub put_RunOnLastDayOfMonth: fn(COMPTR, i16) -> HRES,
+	pub get_RandomDelay: fn(COMPTR, *mut PSTR) -> HRES,
+	pub put_RandomDelay: fn(COMPTR, PCSTR) -> HRES,
+}
+
 #[repr(C)]
 pub struct IPrincipalVT {
 	pub IDispatchVT: IDispatchVT,
@@ -171,6 +184,17 @@ pub struct IRegistrationInfoVT {
 	pub put_Source: fn(COMPTR, PSTR) -> HRES,
 }
 
+#[repr(C)]
+pub struct IRepetitionPatternVT {
+	pub IDispatchVT: IDispatchVT,
+	pub get_Interval: fn(COMPTR, *mut PSTR) -> HRES,
+	pub put_Interval: fn(COMPTR, PCSTR) -> HRES,
+	pub get_Duration: fn(COMPTR, *mut PSTR) -> HRES,
+	pub put_Duration: fn(COMPTR, PCSTR) -> HRES,
+	pub get_StopAtDurationEnd: fn(COMPTR, *mut i16) -> HRES,
+	pub put_StopAtDurationEnd: fn(COMPTR, i16) -> HRES,
+}
+
 #[repr(C)]
 pub struct ITaskDefinitionVT {
 	pub IDispatchVT: IDispatchVT,
@@ -224,6 +248,58 @@ pub struct ITaskServiceVT {
 	pub get_HighestVersion: fn(COMPTR, *mut u32) -> HRES,
 }
 
+#[repr(C)]
+pub struct ITaskSettingsVT {
+	pub IDispatchVT: IDispatchVT,
+	pub get_AllowDemandStart: fn(COMPTR, *mut i16) -> HRES,
+	pub put_AllowDemandStart: fn(COMPTR, i16) -> HRES,
+	pub get_RestartInterval: fn(COMPTR, *mut PSTR) -> HRES,
+	pub put_RestartInterval: fn(COMPTR, PCSTR) -> HRES,
+	pub get_RestartCount: fn(COMPTR, *mut i32) -> HRES,
+	pub put_RestartCount: fn(COMPTR, i32) -> HRES,
+	pub get_MultipleInstances: fn(COMPTR, *mut u32) -> HRES,
+	pub put_MultipleInstances: fn(COMPTR, u32) -> HRES,
+	pub get_StopIfGoingOnBatteries: fn(COMPTR, *mut i16) -> HRES,
+	pub put_StopIfGoingOnBatteries: fn(COMPTR, i16) -> HRES,
+	pub get_DisallowStartIfOnBatteries: fn(COMPTR, *mut i16) -> HRES,
+	pub put_DisallowStartIfOnBatteries: fn(COMPTR, i16) -> HRES,
+	pub get_AllowHardTerminate: fn(COMPTR, *mut i16) -> HRES,
+	pub put_AllowHardTerminate: fn(COMPTR, i16) -> HRES,
+	pub get_StartWhenAvailable: fn(COMPTR, *mut i16) -> HRES,
+	pub put_StartWhenAvailable: fn(COMPTR, i16) -> HRES,
+	pub get_XmlText: fn(COMPTR, *mut PSTR) -> HRES,
+	pub put_XmlText: fn(COMPTR, PCSTR) -> HRES,
+	pub get_RunOnlyIfNetworkAvailable: fn(COMPTR, *mut i16) -> HRES,
+	pub put_RunOnlyIfNetworkAvailable: fn(COMPTR, i16) -> HRES,
+	pub get_ExecutionTimeLimit: fn(COMPTR, *mut PSTR) -> HRES,
+	pub put_ExecutionTimeLimit: fn(COMPTR, PCSTR) -> HRES,
+	pub get_Enabled: fn(COMPTR, *mut i16) -> HRES,
+	pub put_Enabled: fn(COMPTR, i16) -> HRES,
+	pub get_DeleteExpiredTaskAfter: fn(COMPTR, *mut PSTR) -> HRES,
+	pub put_DeleteExpiredTaskAfter: fn(COMPTR, PCSTR) -> HRES,
+	pub get_Priority: fn(COMPTR, *mut i32) -> HRES,
+	pub put_Priority: fn(COMPTR, i32) -> HRES,
+	pub get_Compatibility: fn(COMPTR, *mut u32) -> HRES,
+	pub put_Compatibility: fn(COMPTR, u32) -> HRES,
+	pub get_Hidden: fn(COMPTR, *mut i16) -> HRES,
+	pub put_Hidden: fn(COMPTR, i16) -> HRES,
+	pub get_IdleSettings: fn(COMPTR, *mut COMPTR) -> HRES,
+	pub put_IdleSettings: fn(COMPTR, COMPTR) -> HRES,
+	pub get_RunOnlyIfIdle: fn(COMPTR, *mut i16) -> HRES,
+	pub put_RunOnlyIfIdle: fn(COMPTR, i16) -> HRES,
+	pub get_WakeToRun: fn(COMPTR, *mut i16) -> HRES,
+	pub put_WakeToRun: fn(COMPTR, i16) -> HRES,
+	pub get_NetworkSettings: fn(COMPTR, *mut COMPTR) -> HRES,
+	pub put_NetworkSettings: fn(COMPTR, COMPTR) -> HRES,
+}
+
+#[repr(C)]
+pub struct ITimeTriggerVT {
+	pub ITriggerVT: ITriggerVT,
+	pub get_RandomDelay: fn(COMPTR, *mut PSTR) -> HRES,
+	pub put_RandomDelay: fn(COMPTR, PCSTR) -> HRES,
+}
+
 #[repr(C)]
 pub struct ITriggerVT {
 	pub IDispatchVT: IDispatchVT,
@@ -252,3 +328,14 @@ pub struct ITriggerCollectionVT {
 	pub Remove: fn(COMPTR, VARIANT) -> HRES,
 	pub Clear: fn(COMPTR) -> HRES,
 }
+
+#[repr(C)]
+pub struct IWeeklyTriggerVT {
+	pub ITriggerVT: ITriggerVT,
+	pub get_DaysOfWeek: fn(COMPTR, *mut i16) -> HRES,
+	pub put_DaysOfWeek: fn(COMPTR, i16) -> HRES,
+	pub get_WeeksInterval: fn(COMPTR, *mut i16) -> HRES,
+	pub put_WeeksInterval: fn(COMPTR, i16) -> HRES,
+	pub get_RandomDelay: fn(COMPTR, *mut PSTR) -> HRES,
+	pub put_RandomDelay: fn(COMPTR, PCSTR) -> HRES,
+}
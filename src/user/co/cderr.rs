@@ -22,19 +22,28 @@ impl std::error::Error for CDERR {
 
 impl std::fmt::Display for CDERR {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if let Some(name) = crate::kernel::privs::const_name(Self::ALL_NAMES, self.0 as _) {
+			write!(f, "CDERR::{} ", name)?;
+		}
 		write!(f, "[{}] Common dialog error.", self.0)
 	}
 }
 impl std::fmt::Debug for CDERR {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match crate::kernel::privs::const_name(Self::ALL_NAMES, self.0 as _) {
+			Some(name) => write!(f, "CDERR::{} ", name)?,
+			None => write!(f, "CDERR")?,
+		}
 		if self.0 > 0xffff {
-			write!(f, "CDERR({:#010x} {}) Common dialog error.", self.0, self.0)
+			write!(f, "({:#010x} {}) Common dialog error.", self.0, self.0)
 		} else {
-			write!(f, "CDERR({:#06x} {}) Common dialog error.", self.0, self.0)
+			write!(f, "({:#06x} {}) Common dialog error.", self.0, self.0)
 		}
 	}
 }
 
+const_impl_names!(CDERR ordinary);
+
 const_values_pub! { CDERR;
 	/// None of the actual values (zero).
 	NoValue 0
@@ -195,7 +195,7 @@ const_ordinary! { BST: u32;
 	FOCUS 0x0008
 }
 
-const_wm! { CB;
+const_wm! { CB [#[cfg(feature = "comctl")] NAMES_COMCTL];
 	/// Combo box control
 	/// [messages](https://learn.microsoft.com/en-us/windows/win32/controls/bumper-combobox-control-reference-messages)
 	/// (`u32`).
@@ -1089,6 +1089,32 @@ const_ordinary! { DPI_HOSTING_BEHAVIOR: u32;
 	MIXED 1
 }
 
+const_ws! { DS: u32;
+	/// Dialog box
+	/// [styles](https://learn.microsoft.com/en-us/windows/win32/dlgbox/dialog-box-styles)
+	/// (`u32`).
+	=>
+	/// None of the actual values (zero).
+	NoValue 0
+	ABSALIGN 0x0001
+	SYSMODAL 0x0002
+	/// Obsolete, the system automatically applies the three-dimensional look.
+	LOOK3D 0x0004
+	FIXEDSYS 0x0008
+	NOFAILCREATE 0x0010
+	LOCALEDIT 0x0020
+	SETFONT 0x0040
+	MODALFRAME 0x0080
+	NOIDLEMSG 0x0100
+	SETFOREGROUND 0x0200
+	CONTROL 0x0400
+	CENTER 0x0800
+	CENTERMOUSE 0x1000
+	CONTEXTHELP 0x2000
+	SHELLFONT Self::SETFONT.0 | Self::FIXEDSYS.0
+	USEPIXELS 0x8000
+}
+
 const_bitflag! { DT: u32;
 	/// [`HDC::DrawText`](crate::HDC::DrawText) `format` (`u32`).
 	=>
@@ -1148,7 +1174,7 @@ const_bitflag! { EIMES: u16;
 	COMPLETECOMPSTRKILLFOCUS 0x0004
 }
 
-const_wm! { EM;
+const_wm! { EM [#[cfg(feature = "comctl")] NAMES_COMCTL];
 	/// Edit control
 	/// [messages](https://learn.microsoft.com/en-us/windows/win32/controls/bumper-edit-control-reference-messages)
 	/// (`u32`).
@@ -6,6 +6,35 @@ use crate::kernel::privs::*;
 use crate::user::ffi;
 
 impl HINSTANCE {
+	/// [`CreateDialogIndirectParam`](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createdialogindirectparamw)
+	/// function.
+	///
+	/// The template is usually built with
+	/// [`DlgTemplateEx::serialize`](crate::DlgTemplateEx::serialize).
+	///
+	/// # Safety
+	///
+	/// To create a dialog, you must provide a dialog procedure. The template
+	/// must be followed by its variable-length data.
+	pub unsafe fn CreateDialogIndirectParam(
+		&self,
+		dialog_template: &DLGTEMPLATE,
+		hwnd_parent: Option<&HWND>,
+		dialog_proc: DLGPROC,
+		init_param: Option<isize>,
+	) -> SysResult<HWND> {
+		PtrRet(unsafe {
+			ffi::CreateDialogIndirectParamW(
+				self.ptr(),
+				pcvoid(dialog_template),
+				hwnd_parent.map_or(std::ptr::null_mut(), |h| h.ptr()),
+				dialog_proc as _,
+				init_param.unwrap_or_default(),
+			)
+		})
+		.to_sysresult_handle()
+	}
+
 	/// [`CreateDialogParam`](https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createdialogparamw)
 	/// function.
 	///
@@ -0,0 +1,496 @@
+use crate::co;
+use crate::decl::*;
+use crate::guard::*;
+use crate::user::utilities::res_io::*;
+
+/// `fFlags` bit of the last entry of an accelerator table resource.
+const RES_LAST: u16 = 0x80;
+
+/// Names of the virtual keys which are not letters, digits or function keys.
+/// The first name of each key is used when formatting; the others are aliases
+/// accepted when parsing.
+const KEY_NAMES: &[(co::VK, &str)] = &[
+	(co::VK::BACK, "Backspace"),
+	(co::VK::BACK, "Back"),
+	(co::VK::TAB, "Tab"),
+	(co::VK::CLEAR, "Clear"),
+	(co::VK::RETURN, "Enter"),
+	(co::VK::RETURN, "Return"),
+	(co::VK::PAUSE, "Pause"),
+	(co::VK::CAPITAL, "CapsLock"),
+	(co::VK::ESCAPE, "Esc"),
+	(co::VK::ESCAPE, "Escape"),
+	(co::VK::SPACE, "Space"),
+	(co::VK::PRIOR, "PgUp"),
+	(co::VK::PRIOR, "PageUp"),
+	(co::VK::NEXT, "PgDn"),
+	(co::VK::NEXT, "PageDown"),
+	(co::VK::END, "End"),
+	(co::VK::HOME, "Home"),
+	(co::VK::LEFT, "Left"),
+	(co::VK::UP, "Up"),
+	(co::VK::RIGHT, "Right"),
+	(co::VK::DOWN, "Down"),
+	(co::VK::SNAPSHOT, "PrintScreen"),
+	(co::VK::SNAPSHOT, "PrtSc"),
+	(co::VK::INSERT, "Ins"),
+	(co::VK::INSERT, "Insert"),
+	(co::VK::DELETE, "Del"),
+	(co::VK::DELETE, "Delete"),
+	(co::VK::HELP, "Help"),
+	(co::VK::APPS, "Apps"),
+	(co::VK::SLEEP, "Sleep"),
+	(co::VK::NUMPAD0, "Num0"),
+	(co::VK::NUMPAD1, "Num1"),
+	(co::VK::NUMPAD2, "Num2"),
+	(co::VK::NUMPAD3, "Num3"),
+	(co::VK::NUMPAD4, "Num4"),
+	(co::VK::NUMPAD5, "Num5"),
+	(co::VK::NUMPAD6, "Num6"),
+	(co::VK::NUMPAD7, "Num7"),
+	(co::VK::NUMPAD8, "Num8"),
+	(co::VK::NUMPAD9, "Num9"),
+	(co::VK::MULTIPLY, "NumMultiply"),
+	(co::VK::ADD, "NumAdd"),
+	(co::VK::SUBTRACT, "NumSubtract"),
+	(co::VK::DECIMAL, "NumDecimal"),
+	(co::VK::DIVIDE, "NumDivide"),
+	(co::VK::NUMLOCK, "NumLock"),
+	(co::VK::SCROLL, "ScrollLock"),
+	(co::VK::OEM_PLUS, "Plus"),
+	(co::VK::OEM_PLUS, "OemPlus"),
+	(co::VK::OEM_PLUS, "="),
+	(co::VK::OEM_PLUS, "+"),
+	(co::VK::OEM_MINUS, "Minus"),
+	(co::VK::OEM_MINUS, "OemMinus"),
+	(co::VK::OEM_MINUS, "-"),
+	(co::VK::OEM_COMMA, "Comma"),
+	(co::VK::OEM_COMMA, "OemComma"),
+	(co::VK::OEM_COMMA, ","),
+	(co::VK::OEM_PERIOD, "Period"),
+	(co::VK::OEM_PERIOD, "OemPeriod"),
+	(co::VK::OEM_PERIOD, "."),
+	(co::VK::OEM_1, "Oem1"),
+	(co::VK::OEM_1, ";"),
+	(co::VK::OEM_2, "Oem2"),
+	(co::VK::OEM_2, "/"),
+	(co::VK::OEM_3, "Oem3"),
+	(co::VK::OEM_3, "`"),
+	(co::VK::OEM_4, "Oem4"),
+	(co::VK::OEM_4, "["),
+	(co::VK::OEM_5, "Oem5"),
+	(co::VK::OEM_5, "\\"),
+	(co::VK::OEM_6, "Oem6"),
+	(co::VK::OEM_6, "]"),
+	(co::VK::OEM_7, "Oem7"),
+	(co::VK::OEM_7, "'"),
+	(co::VK::OEM_8, "Oem8"),
+	(co::VK::OEM_102, "Oem102"),
+	(co::VK::BROWSER_BACK, "BrowserBack"),
+	(co::VK::BROWSER_FORWARD, "BrowserForward"),
+	(co::VK::BROWSER_REFRESH, "BrowserRefresh"),
+	(co::VK::BROWSER_STOP, "BrowserStop"),
+	(co::VK::BROWSER_SEARCH, "BrowserSearch"),
+	(co::VK::BROWSER_FAVORITES, "BrowserFavorites"),
+	(co::VK::BROWSER_HOME, "BrowserHome"),
+	(co::VK::VOLUME_MUTE, "VolumeMute"),
+	(co::VK::VOLUME_DOWN, "VolumeDown"),
+	(co::VK::VOLUME_UP, "VolumeUp"),
+	(co::VK::MEDIA_NEXT_TRACK, "MediaNext"),
+	(co::VK::MEDIA_PREV_TRACK, "MediaPrev"),
+	(co::VK::MEDIA_STOP, "MediaStop"),
+	(co::VK::MEDIA_PLAY_PAUSE, "MediaPlayPause"),
+];
+
+impl ACCEL {
+	/// Parses a shortcut like `"Ctrl+Shift+S"`, `"Alt+F4"` or
+	/// `"Ctrl+OemPlus"` into a virtual-key `ACCEL`.
+	///
+	/// The modifiers are `Ctrl` (or `Control`), `Shift` and `Alt`, followed by
+	/// a single key, all separated by `+` and case-insensitive. The key can
+	/// be a letter, a digit, `F1` to `F24`, a key name like `Enter`, `PgUp`
+	/// or `Num5`, a US-layout punctuation character like `[`, or a raw
+	/// virtual-key code like `0xbb`.
+	///
+	/// An invalid shortcut yields
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER).
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let accel = w::ACCEL::from_shortcut("Ctrl+Shift+S", 2001)?;
+	/// assert_eq!(accel.key, co::VK::CHAR_S);
+	/// assert_eq!(accel.shortcut_text(), "Ctrl+Shift+S");
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn from_shortcut(shortcut: &str, cmd: u16) -> SysResult<ACCEL> {
+		let shortcut = shortcut.trim();
+		let (mods, key) = match shortcut.strip_suffix('+') {
+			Some(rest) if rest.is_empty() || rest.trim_end().ends_with('+') => {
+				(rest.trim_end().strip_suffix('+').unwrap_or(""), "+") // the key is "+" itself
+			},
+			Some(_) => return Err(co::ERROR::INVALID_PARAMETER), // dangling "+"
+			None => match shortcut.rsplit_once('+') {
+				Some((mods, key)) => (mods, key),
+				None => ("", shortcut),
+			},
+		};
+
+		let mut f_virt = co::ACCELF::VIRTKEY;
+		if !mods.trim().is_empty() {
+			for m in mods.split('+').map(|m| m.trim()) {
+				f_virt |= if m.eq_ignore_ascii_case("Ctrl") || m.eq_ignore_ascii_case("Control") {
+					co::ACCELF::CONTROL
+				} else if m.eq_ignore_ascii_case("Shift") {
+					co::ACCELF::SHIFT
+				} else if m.eq_ignore_ascii_case("Alt") {
+					co::ACCELF::ALT
+				} else {
+					return Err(co::ERROR::INVALID_PARAMETER);
+				};
+			}
+		}
+
+		Ok(ACCEL {
+			fVirt: f_virt,
+			key: key_from_name(key.trim()).ok_or(co::ERROR::INVALID_PARAMETER)?,
+			cmd,
+		})
+	}
+
+	/// Returns the display text of the shortcut, like `"Ctrl+Shift+S"`. For
+	/// virtual-key entries, the text can be parsed back with
+	/// [`ACCEL::from_shortcut`](crate::ACCEL::from_shortcut).
+	///
+	/// This is the text usually shown at the right of a menu entry, like in
+	/// [`MenuEntry::accel_text`](crate::MenuEntry::accel_text).
+	#[must_use]
+	pub fn shortcut_text(&self) -> String {
+		let mut text = String::new();
+		for (flag, name) in [
+			(co::ACCELF::CONTROL, "Ctrl+"),
+			(co::ACCELF::SHIFT, "Shift+"),
+			(co::ACCELF::ALT, "Alt+"),
+		] {
+			if self.fVirt.has(flag) {
+				text.push_str(name);
+			}
+		}
+
+		if self.fVirt.has(co::ACCELF::VIRTKEY) {
+			text.push_str(&key_name(self.key));
+		} else {
+			match char::from_u32(self.key.raw() as _) {
+				Some(ch) if !ch.is_control() && ch != '+' => text.push(ch), // character code
+				_ => text.push_str(&format!("{:#06x}", self.key.raw())),
+			}
+		}
+		text
+	}
+}
+
+/// Accelerator table, which can be serialized to and from the payload of an
+/// [`RT::ACCELERATOR`](crate::co::RT::ACCELERATOR) resource, and used to
+/// create an [`HACCEL`](crate::HACCEL).
+///
+/// [`parse`](crate::AccelTable::parse),
+/// [`serialize`](crate::AccelTable::serialize) and
+/// [`conflicts`](crate::AccelTable::conflicts) make no system calls.
+///
+/// # Examples
+///
+/// Loading user-configured keybindings:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let bindings = [("Ctrl+S", 2001), ("Ctrl+Shift+S", 2002), ("Alt+F4", 2003)];
+///
+/// let table = w::AccelTable::from_shortcuts(&bindings)?;
+/// if let Some((a, b)) = table.conflicts().first() {
+///     println!("Shortcuts {} and {} conflict", a, b);
+/// }
+///
+/// let haccel = table.create()?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct AccelTable {
+	/// The entries of the table.
+	pub entries: Vec<ACCEL>,
+}
+
+impl AccelTable {
+	/// Parses a list of shortcut strings along with their command IDs, with
+	/// [`ACCEL::from_shortcut`](crate::ACCEL::from_shortcut).
+	#[must_use]
+	pub fn from_shortcuts(bindings: &[(impl AsRef<str>, u16)]) -> SysResult<Self> {
+		Ok(Self {
+			entries: bindings
+				.iter()
+				.map(|(shortcut, cmd)| ACCEL::from_shortcut(shortcut.as_ref(), *cmd))
+				.collect::<SysResult<Vec<_>>>()?,
+		})
+	}
+
+	/// Parses the raw data of an accelerator table resource, as returned by
+	/// [`HINSTANCE::LockResource`](crate::HINSTANCE::LockResource).
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(blob: &[u8]) -> SysResult<Self> {
+		let mut rd = Reader { blob, off: 0 };
+		let mut entries = Vec::<ACCEL>::new();
+
+		while rd.off < blob.len() {
+			let flags = rd.word()?;
+			let key = rd.word()?;
+			let cmd = rd.word()?;
+			rd.word()?; // padding
+			entries.push(ACCEL {
+				fVirt: unsafe { co::ACCELF::from_raw((flags & !RES_LAST) as _) },
+				key: unsafe { co::VK::from_raw(key) },
+				cmd,
+			});
+			if flags & RES_LAST != 0 {
+				return Ok(Self { entries });
+			}
+		}
+
+		if entries.is_empty() {
+			Ok(Self { entries }) // no entries at all
+		} else {
+			Err(co::ERROR::INVALID_DATA) // last entry not marked
+		}
+	}
+
+	/// Serializes the table into the raw data of an accelerator table
+	/// resource, which can be written to a `.res` file.
+	#[must_use]
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut buf = Vec::<u8>::with_capacity(self.entries.len() * 8);
+		for (idx, accel) in self.entries.iter().enumerate() {
+			let last = if idx == self.entries.len() - 1 { RES_LAST } else { 0 };
+			put_word(&mut buf, accel.fVirt.raw() as u16 | last);
+			put_word(&mut buf, accel.key.raw());
+			put_word(&mut buf, accel.cmd);
+			put_word(&mut buf, 0); // padding
+		}
+		buf
+	}
+
+	/// Returns the index pairs of the entries which are triggered by the same
+	/// key combination, thus only the first one of each pair will ever be
+	/// used.
+	#[must_use]
+	pub fn conflicts(&self) -> Vec<(usize, usize)> {
+		let combo = |accel: &ACCEL| {
+			let flags = accel.fVirt
+				& (co::ACCELF::VIRTKEY | co::ACCELF::SHIFT | co::ACCELF::CONTROL | co::ACCELF::ALT);
+			(flags, accel.key)
+		};
+
+		let mut pairs = Vec::<(usize, usize)>::new();
+		for (a, accel_a) in self.entries.iter().enumerate() {
+			for (b, accel_b) in self.entries.iter().enumerate().skip(a + 1) {
+				if combo(accel_a) == combo(accel_b) {
+					pairs.push((a, b));
+				}
+			}
+		}
+		pairs
+	}
+
+	/// Creates the accelerator table with
+	/// [`HACCEL::CreateAcceleratorTable`](crate::HACCEL::CreateAcceleratorTable).
+	#[must_use]
+	pub fn create(&self) -> SysResult<DestroyAcceleratorTableGuard> {
+		HACCEL::CreateAcceleratorTable(&self.entries)
+	}
+}
+
+/// Returns the virtual key of the given name, case-insensitive.
+#[must_use]
+fn key_from_name(name: &str) -> Option<co::VK> {
+	if let Some(hex) = name.strip_prefix("0x").or_else(|| name.strip_prefix("0X")) {
+		return u16::from_str_radix(hex, 16)
+			.ok()
+			.filter(|vk| *vk != 0)
+			.map(|vk| unsafe { co::VK::from_raw(vk) });
+	}
+
+	if let [ch] = name.as_bytes() {
+		if ch.is_ascii_alphanumeric() {
+			return Some(unsafe { co::VK::from_raw(ch.to_ascii_uppercase() as _) }); // same as the VK codes
+		}
+	}
+
+	if let Some(num) = name
+		.strip_prefix('F')
+		.or_else(|| name.strip_prefix('f'))
+		.and_then(|num| num.parse::<u16>().ok())
+	{
+		if (1..=24).contains(&num) {
+			return Some(unsafe { co::VK::from_raw(co::VK::F1.raw() + num - 1) });
+		}
+	}
+
+	KEY_NAMES
+		.iter()
+		.find(|(_, key_name)| key_name.eq_ignore_ascii_case(name))
+		.map(|(vk, _)| *vk)
+}
+
+/// Returns the display name of the given virtual key.
+#[must_use]
+fn key_name(vk: co::VK) -> String {
+	let raw = vk.raw();
+	if (co::VK::CHAR_0.raw()..=co::VK::CHAR_9.raw()).contains(&raw)
+		|| (co::VK::CHAR_A.raw()..=co::VK::CHAR_Z.raw()).contains(&raw)
+	{
+		(raw as u8 as char).to_string()
+	} else if (co::VK::F1.raw()..=co::VK::F24.raw()).contains(&raw) {
+		format!("F{}", raw - co::VK::F1.raw() + 1)
+	} else if let Some((_, name)) = KEY_NAMES.iter().find(|(key, _)| *key == vk) {
+		(*name).to_owned()
+	} else {
+		format!("{:#04x}", raw)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn accel(f_virt: co::ACCELF, key: co::VK, cmd: u16) -> ACCEL {
+		ACCEL {
+			fVirt: co::ACCELF::VIRTKEY | f_virt,
+			key,
+			cmd,
+		}
+	}
+
+	#[test]
+	fn from_shortcut() {
+		let ctrl_shift = co::ACCELF::CONTROL | co::ACCELF::SHIFT;
+		for (text, expected) in [
+			("Ctrl+Shift+S", accel(ctrl_shift, co::VK::CHAR_S, 1)),
+			(" control + SHIFT + s ", accel(ctrl_shift, co::VK::CHAR_S, 1)),
+			("alt+f4", accel(co::ACCELF::ALT, co::VK::F4, 1)),
+			("F24", accel(co::ACCELF::VIRTKEY, co::VK::F24, 1)),
+			("Ctrl+9", accel(co::ACCELF::CONTROL, co::VK::CHAR_9, 1)),
+			("Shift+PgDn", accel(co::ACCELF::SHIFT, co::VK::NEXT, 1)),
+			("Ctrl+[", accel(co::ACCELF::CONTROL, co::VK::OEM_4, 1)),
+			("Ctrl++", accel(co::ACCELF::CONTROL, co::VK::OEM_PLUS, 1)),
+			("Ctrl+Alt + +", accel(co::ACCELF::CONTROL | co::ACCELF::ALT, co::VK::OEM_PLUS, 1)),
+			("+", accel(co::ACCELF::VIRTKEY, co::VK::OEM_PLUS, 1)),
+			("Ctrl+0xbb", accel(co::ACCELF::CONTROL, co::VK::OEM_PLUS, 1)),
+			("0XE8", accel(co::ACCELF::VIRTKEY, unsafe { co::VK::from_raw(0xe8) }, 1)),
+		] {
+			assert!(ACCEL::from_shortcut(text, 1) == Ok(expected), "{}", text);
+		}
+
+		for text in ["", "Ctrl+", "Ctrl+ ", "Shift++A", "Win+A", "Ctrl", "F25", "0x0", "0xzz", "AB"]
+		{
+			assert!(ACCEL::from_shortcut(text, 1) == Err(co::ERROR::INVALID_PARAMETER), "{}", text);
+		}
+	}
+
+	#[test]
+	fn shortcut_text() {
+		let ctrl_shift_alt = co::ACCELF::CONTROL | co::ACCELF::SHIFT | co::ACCELF::ALT;
+		for (accel, expected) in [
+			(accel(ctrl_shift_alt, co::VK::DELETE, 1), "Ctrl+Shift+Alt+Del"),
+			(accel(co::ACCELF::CONTROL, co::VK::OEM_PLUS, 1), "Ctrl+Plus"),
+			(accel(co::ACCELF::VIRTKEY, co::VK::F12, 1), "F12"),
+			(accel(co::ACCELF::VIRTKEY, unsafe { co::VK::from_raw(0xe8) }, 1), "0xe8"),
+			(
+				ACCEL {
+					fVirt: co::ACCELF::ALT,
+					key: unsafe { co::VK::from_raw(b'a' as _) },
+					cmd: 1,
+				},
+				"Alt+a",
+			),
+			(
+				ACCEL {
+					fVirt: unsafe { co::ACCELF::from_raw(0) },
+					key: unsafe { co::VK::from_raw(b'+' as _) },
+					cmd: 1,
+				},
+				"0x002b",
+			),
+		] {
+			assert_eq!(accel.shortcut_text(), expected);
+		}
+	}
+
+	#[test]
+	fn shortcut_round_trip() {
+		for raw in 1..=0xff {
+			let key = unsafe { co::VK::from_raw(raw) };
+			for mods in
+				[co::ACCELF::VIRTKEY, co::ACCELF::CONTROL, co::ACCELF::SHIFT | co::ACCELF::ALT]
+			{
+				let accel = accel(mods, key, 7);
+				let text = accel.shortcut_text();
+				assert!(ACCEL::from_shortcut(&text, 7) == Ok(accel), "{}", text);
+			}
+		}
+	}
+
+	#[test]
+	fn parse_serialize() {
+		#[rustfmt::skip]
+		let blob = [
+			0x0d, 0x00, b'S', 0x00, 0xd1, 0x07, 0x00, 0x00, // Ctrl+Shift+S, 2001
+			0x91, 0x00, 0x73, 0x00, 0xd3, 0x07, 0x00, 0x00, // Alt+F4, 2003, last
+		];
+		let table = AccelTable::parse(&blob).unwrap();
+		assert_eq!(table.entries.len(), 2);
+		assert_eq!(table.entries[0].shortcut_text(), "Ctrl+Shift+S");
+		assert_eq!(table.entries[1].shortcut_text(), "Alt+F4");
+		assert_eq!(table.entries[1].cmd, 2003);
+		assert_eq!(table.serialize(), blob);
+		assert!(
+			AccelTable::from_shortcuts(&[("Ctrl+Shift+S", 2001), ("Alt+F4", 2003)]).unwrap()
+				== table
+		);
+
+		assert!(AccelTable::parse(&blob[..8]) == Err(co::ERROR::INVALID_DATA)); // last entry not marked
+		assert!(AccelTable::parse(&blob[..12]) == Err(co::ERROR::INVALID_DATA));
+		assert!(AccelTable::parse(&[]).unwrap().entries.is_empty());
+		assert!(AccelTable::default().serialize().is_empty());
+
+		let mut longer = blob.to_vec();
+		longer.extend_from_slice(&[0; 8]);
+		assert!(AccelTable::parse(&longer).unwrap() == table); // data after last entry is ignored
+	}
+
+	#[test]
+	fn conflicts() {
+		let table = AccelTable::from_shortcuts(&[
+			("Ctrl+S", 1),
+			("Ctrl+Shift+S", 2),
+			("control+s", 3),
+			("Alt+F4", 4),
+		])
+		.unwrap();
+		assert_eq!(table.conflicts(), [(0, 2)]);
+
+		let mut table = table;
+		table.entries.push(ACCEL {
+			fVirt: table.entries[0].fVirt | unsafe { co::ACCELF::from_raw(0x02) }, // FNOINVERT
+			..table.entries[0]
+		});
+		table.entries.push(ACCEL {
+			fVirt: co::ACCELF::CONTROL, // character code, not a virtual key
+			..table.entries[0]
+		});
+		assert_eq!(table.conflicts(), [(0, 2), (0, 4), (2, 4)]);
+	}
+}
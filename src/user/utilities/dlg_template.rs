@@ -0,0 +1,540 @@
+use crate::co;
+use crate::decl::*;
+use crate::user::utilities::res_io::*;
+
+/// Parsed contents of a
+/// [`DLGTEMPLATEEX`](https://learn.microsoft.com/en-us/windows/win32/dlgbox/dlgtemplateex)
+/// block, which describes a dialog box and its controls. This is the payload of
+/// an [`RT::DIALOG`](crate::co::RT::DIALOG) resource.
+///
+/// This is a pure parser and writer: no system calls are made. The serialized
+/// block can be passed to
+/// [`HINSTANCE::CreateDialogIndirectParam`](crate::HINSTANCE::CreateDialogIndirectParam),
+/// or used to create a [`gui::WindowMain`](crate::gui::WindowMain) or a
+/// [`gui::WindowModal`](crate::gui::WindowModal) without any resource
+/// compiler.
+///
+/// Positions and sizes are in dialog units.
+///
+/// # Examples
+///
+/// A dialog with a label and an OK button:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let tmpl = w::DlgTemplateEx {
+///     title: "Hello".to_owned(),
+///     cx: 160,
+///     cy: 60,
+///     items: vec![
+///         w::DlgItemTemplateEx {
+///             class: w::DlgItemClass::Static,
+///             text: w::IdStr::from_str("Hello, world."),
+///             x: 10,
+///             y: 10,
+///             cx: 140,
+///             cy: 10,
+///             ..Default::default()
+///         },
+///         w::DlgItemTemplateEx {
+///             class: w::DlgItemClass::Button,
+///             text: w::IdStr::from_str("OK"),
+///             style: co::WS::CHILD | co::WS::VISIBLE | co::WS::TABSTOP
+///                 | co::BS::DEFPUSHBUTTON.into(),
+///             id: co::DLGID::OK.raw() as _,
+///             x: 100,
+///             y: 38,
+///             cx: 50,
+///             cy: 14,
+///             ..Default::default()
+///         },
+///     ],
+///     ..Default::default()
+/// };
+///
+/// let blob = tmpl.serialize()?;
+/// let tmpl2 = w::DlgTemplateEx::parse(&blob)?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone)]
+pub struct DlgTemplateEx {
+	/// Help context identifier.
+	///
+	/// Defaults to zero.
+	pub help_id: u32,
+	/// Extended window styles.
+	///
+	/// Defaults to `WS_EX::NoValue`.
+	pub ex_style: co::WS_EX,
+	/// Window styles, including the [`co::DS`](crate::co::DS) ones. The
+	/// `DS::SETFONT` flag is set or cleared according to the `font` field;
+	/// without a font, `DS::FIXEDSYS` is cleared too.
+	///
+	/// Defaults to `WS::POPUP | WS::CAPTION | WS::SYSMENU | DS::MODALFRAME |
+	/// DS::SHELLFONT`.
+	pub style: co::WS,
+	/// Horizontal position.
+	///
+	/// Defaults to zero.
+	pub x: i16,
+	/// Vertical position.
+	///
+	/// Defaults to zero.
+	pub y: i16,
+	/// Width.
+	///
+	/// Defaults to zero.
+	pub cx: i16,
+	/// Height.
+	///
+	/// Defaults to zero.
+	pub cy: i16,
+	/// Menu resource of the dialog, if any.
+	///
+	/// Defaults to `None`.
+	pub menu: Option<IdStr>,
+	/// Window class of the dialog; `None` means the predefined dialog class.
+	///
+	/// Defaults to `None`.
+	pub class: Option<IdStr>,
+	/// Title of the dialog.
+	///
+	/// Defaults to an empty string.
+	pub title: String,
+	/// Font of the dialog and its controls.
+	///
+	/// Defaults to 8-point "MS Shell Dlg".
+	pub font: Option<DlgTemplateFont>,
+	/// The controls, in tab order.
+	///
+	/// Defaults to none.
+	pub items: Vec<DlgItemTemplateEx>,
+}
+
+impl Default for DlgTemplateEx {
+	fn default() -> Self {
+		Self {
+			help_id: 0,
+			ex_style: co::WS_EX::NoValue,
+			style: co::WS::POPUP
+				| co::WS::CAPTION
+				| co::WS::SYSMENU
+				| co::DS::MODALFRAME.into()
+				| co::DS::SHELLFONT.into(),
+			x: 0,
+			y: 0,
+			cx: 0,
+			cy: 0,
+			menu: None,
+			class: None,
+			title: String::new(),
+			font: Some(DlgTemplateFont::default()),
+			items: Vec::new(),
+		}
+	}
+}
+
+/// Font of a [`DlgTemplateEx`](crate::DlgTemplateEx).
+#[derive(Clone)]
+pub struct DlgTemplateFont {
+	/// Size of the font, in points.
+	///
+	/// Defaults to 8.
+	pub point_size: u16,
+	/// Weight of the font, like `co::FW::NORMAL`.
+	///
+	/// Defaults to 400.
+	pub weight: u16,
+	/// Whether the font is italic.
+	///
+	/// Defaults to `false`.
+	pub italic: bool,
+	/// Character set of the font, like `co::CHARSET::DEFAULT`.
+	///
+	/// Defaults to 1.
+	pub charset: u8,
+	/// Name of the typeface.
+	///
+	/// Defaults to "MS Shell Dlg".
+	pub face_name: String,
+}
+
+impl Default for DlgTemplateFont {
+	fn default() -> Self {
+		Self {
+			point_size: 8,
+			weight: 400,
+			italic: false,
+			charset: 1,
+			face_name: "MS Shell Dlg".to_owned(),
+		}
+	}
+}
+
+/// Window class of a [`DlgItemTemplateEx`](crate::DlgItemTemplateEx).
+#[derive(Clone, PartialEq, Eq)]
+pub enum DlgItemClass {
+	/// Predefined `BUTTON` class.
+	Button,
+	/// Predefined `EDIT` class.
+	Edit,
+	/// Predefined `STATIC` class.
+	Static,
+	/// Predefined `LISTBOX` class.
+	ListBox,
+	/// Predefined `SCROLLBAR` class.
+	ScrollBar,
+	/// Predefined `COMBOBOX` class.
+	ComboBox,
+	/// Another class atom.
+	Atom(u16),
+	/// A class name, like `SysListView32`.
+	Name(String),
+}
+
+impl DlgItemClass {
+	#[must_use]
+	fn from_atom(atom: u16) -> Self {
+		match atom {
+			0x0080 => Self::Button,
+			0x0081 => Self::Edit,
+			0x0082 => Self::Static,
+			0x0083 => Self::ListBox,
+			0x0084 => Self::ScrollBar,
+			0x0085 => Self::ComboBox,
+			atom => Self::Atom(atom),
+		}
+	}
+
+	#[must_use]
+	fn atom(&self) -> Option<u16> {
+		match self {
+			Self::Button => Some(0x0080),
+			Self::Edit => Some(0x0081),
+			Self::Static => Some(0x0082),
+			Self::ListBox => Some(0x0083),
+			Self::ScrollBar => Some(0x0084),
+			Self::ComboBox => Some(0x0085),
+			Self::Atom(atom) => Some(*atom),
+			Self::Name(_) => None,
+		}
+	}
+}
+
+/// A control of a [`DlgTemplateEx`](crate::DlgTemplateEx), described by a
+/// [`DLGITEMTEMPLATEEX`](https://learn.microsoft.com/en-us/windows/win32/dlgbox/dlgitemtemplateex)
+/// block.
+#[derive(Clone)]
+pub struct DlgItemTemplateEx {
+	/// Help context identifier.
+	///
+	/// Defaults to zero.
+	pub help_id: u32,
+	/// Extended window styles.
+	///
+	/// Defaults to `WS_EX::NoValue`.
+	pub ex_style: co::WS_EX,
+	/// Window styles, including the control-specific ones, like
+	/// [`co::BS`](crate::co::BS).
+	///
+	/// Defaults to `WS::CHILD | WS::VISIBLE`.
+	pub style: co::WS,
+	/// Horizontal position.
+	///
+	/// Defaults to zero.
+	pub x: i16,
+	/// Vertical position.
+	///
+	/// Defaults to zero.
+	pub y: i16,
+	/// Width.
+	///
+	/// Defaults to zero.
+	pub cx: i16,
+	/// Height.
+	///
+	/// Defaults to zero.
+	pub cy: i16,
+	/// Control ID.
+	///
+	/// Defaults to zero.
+	pub id: u32,
+	/// Window class of the control.
+	///
+	/// Defaults to `DlgItemClass::Static`.
+	pub class: DlgItemClass,
+	/// Text of the control, or the ID of a resource, like an icon for a static
+	/// control.
+	///
+	/// Defaults to an empty string.
+	pub text: IdStr,
+	/// Data passed to the control in the `lParam` of
+	/// [`wm::Create`](crate::msg::wm::Create).
+	///
+	/// Defaults to none.
+	pub creation_data: Vec<u8>,
+}
+
+impl Default for DlgItemTemplateEx {
+	fn default() -> Self {
+		Self {
+			help_id: 0,
+			ex_style: co::WS_EX::NoValue,
+			style: co::WS::CHILD | co::WS::VISIBLE,
+			x: 0,
+			y: 0,
+			cx: 0,
+			cy: 0,
+			id: 0,
+			class: DlgItemClass::Static,
+			text: IdStr::Str(WString::new()),
+			creation_data: Vec::new(),
+		}
+	}
+}
+
+impl DlgTemplateEx {
+	/// Parses a raw dialog template block, as returned by
+	/// [`HINSTANCE::LockResource`](crate::HINSTANCE::LockResource). Both the
+	/// extended `DLGTEMPLATEEX` and the older
+	/// [`DLGTEMPLATE`](crate::DLGTEMPLATE) formats are accepted.
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(blob: &[u8]) -> SysResult<Self> {
+		let mut rd = Reader { blob, off: 0 };
+		let is_ex = blob.get(0..4) == Some(&[1, 0, 0xff, 0xff]);
+
+		let (help_id, ex_style, style, num_items) = if is_ex {
+			rd.off = 4;
+			let help_id = rd.dword()?;
+			let ex_style = rd.dword()?;
+			let style = rd.dword()?;
+			(help_id, ex_style, style, rd.word()?)
+		} else {
+			let style = rd.dword()?;
+			let ex_style = rd.dword()?;
+			(0, ex_style, style, rd.word()?)
+		};
+
+		let mut tmpl = Self {
+			help_id,
+			ex_style: unsafe { co::WS_EX::from_raw(ex_style) },
+			style: unsafe { co::WS::from_raw(style) },
+			x: rd.word()? as _,
+			y: rd.word()? as _,
+			cx: rd.word()? as _,
+			cy: rd.word()? as _,
+			menu: rd.sz_or_ord()?,
+			class: rd.sz_or_ord()?,
+			title: rd.sz()?,
+			font: None,
+			items: Vec::with_capacity(num_items as _),
+		};
+
+		if style & co::DS::SETFONT.raw() != 0 {
+			let point_size = rd.word()?;
+			let (weight, italic, charset) = if is_ex {
+				let weight = rd.word()?;
+				let italic = rd.byte()? != 0;
+				(weight, italic, rd.byte()?)
+			} else {
+				(400, false, 1)
+			};
+			tmpl.font = Some(DlgTemplateFont {
+				point_size,
+				weight,
+				italic,
+				charset,
+				face_name: rd.sz()?,
+			});
+		}
+
+		for _ in 0..num_items {
+			rd.align4();
+			tmpl.items.push(DlgItemTemplateEx::parse(&mut rd, is_ex)?);
+		}
+
+		Ok(tmpl)
+	}
+
+	/// Serializes the template into a raw `DLGTEMPLATEEX` block, followed by
+	/// its items.
+	///
+	/// More than 65535 items, or an item with more than 65535 bytes of
+	/// creation data, yield
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER).
+	#[must_use]
+	pub fn serialize(&self) -> SysResult<Vec<u8>> {
+		let style = if self.font.is_some() {
+			self.style.raw() | co::DS::SETFONT.raw()
+		} else {
+			self.style.raw() & !co::DS::SHELLFONT.raw() // FIXEDSYS requires SETFONT
+		};
+		let num_items =
+			u16::try_from(self.items.len()).map_err(|_| co::ERROR::INVALID_PARAMETER)?;
+
+		let mut buf = Vec::<u8>::new();
+		put_word(&mut buf, 1); // dlgVer
+		put_word(&mut buf, 0xffff); // signature
+		put_dword(&mut buf, self.help_id);
+		put_dword(&mut buf, self.ex_style.raw());
+		put_dword(&mut buf, style);
+		put_word(&mut buf, num_items);
+		[self.x, self.y, self.cx, self.cy]
+			.iter()
+			.for_each(|v| put_word(&mut buf, *v as _));
+		put_sz_or_ord(&mut buf, self.menu.as_ref());
+		put_sz_or_ord(&mut buf, self.class.as_ref());
+		put_sz(&mut buf, &self.title);
+
+		if let Some(font) = &self.font {
+			put_word(&mut buf, font.point_size);
+			put_word(&mut buf, font.weight);
+			buf.push(font.italic as _);
+			buf.push(font.charset);
+			put_sz(&mut buf, &font.face_name);
+		}
+
+		for item in self.items.iter() {
+			buf.resize(align4(buf.len()), 0);
+			item.serialize(&mut buf)?;
+		}
+		Ok(buf)
+	}
+}
+
+impl DlgItemTemplateEx {
+	fn parse(rd: &mut Reader, is_ex: bool) -> SysResult<Self> {
+		let (help_id, ex_style, style) = if is_ex {
+			let help_id = rd.dword()?;
+			let ex_style = rd.dword()?;
+			(help_id, ex_style, rd.dword()?)
+		} else {
+			let style = rd.dword()?;
+			(0, rd.dword()?, style)
+		};
+		let (x, y, cx, cy) = (rd.word()?, rd.word()?, rd.word()?, rd.word()?);
+		let id = if is_ex { rd.dword()? } else { rd.word()? as _ };
+
+		let class = match rd.sz_or_ord()? {
+			Some(IdStr::Id(atom)) => DlgItemClass::from_atom(atom),
+			Some(IdStr::Str(ws)) => DlgItemClass::Name(ws.to_string()),
+			None => return Err(co::ERROR::INVALID_DATA), // class is mandatory
+		};
+		let text = rd
+			.sz_or_ord()?
+			.unwrap_or_else(|| IdStr::Str(WString::new()));
+
+		let creation_data = match rd.word()? as usize {
+			0 => Vec::new(),
+			sz if is_ex => rd.bytes(sz)?.to_vec(),
+			sz => rd
+				.bytes(sz.checked_sub(2).ok_or(co::ERROR::INVALID_DATA)?)?
+				.to_vec(), // size includes itself
+		};
+
+		Ok(Self {
+			help_id,
+			ex_style: unsafe { co::WS_EX::from_raw(ex_style) },
+			style: unsafe { co::WS::from_raw(style) },
+			x: x as _,
+			y: y as _,
+			cx: cx as _,
+			cy: cy as _,
+			id,
+			class,
+			text,
+			creation_data,
+		})
+	}
+
+	fn serialize(&self, buf: &mut Vec<u8>) -> SysResult<()> {
+		let data_len =
+			u16::try_from(self.creation_data.len()).map_err(|_| co::ERROR::INVALID_PARAMETER)?;
+
+		put_dword(buf, self.help_id);
+		put_dword(buf, self.ex_style.raw());
+		put_dword(buf, self.style.raw());
+		[self.x, self.y, self.cx, self.cy]
+			.iter()
+			.for_each(|v| put_word(buf, *v as _));
+		put_dword(buf, self.id);
+		match self.class.atom() {
+			Some(atom) => put_sz_or_ord(buf, Some(&IdStr::Id(atom))),
+			None => {
+				if let DlgItemClass::Name(name) = &self.class {
+					put_sz(buf, name);
+				}
+			},
+		}
+		put_sz_or_ord(buf, Some(&self.text));
+		put_word(buf, data_len);
+		buf.extend_from_slice(&self.creation_data);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn font_flags() {
+		let mut tmpl = DlgTemplateEx::default();
+		let parsed = DlgTemplateEx::parse(&tmpl.serialize().unwrap()).unwrap();
+		assert_eq!(parsed.style.raw() & co::DS::SHELLFONT.raw(), co::DS::SHELLFONT.raw());
+		assert_eq!(parsed.font.unwrap().face_name, "MS Shell Dlg");
+
+		tmpl.font = None;
+		let parsed = DlgTemplateEx::parse(&tmpl.serialize().unwrap()).unwrap();
+		assert_eq!(parsed.style.raw() & co::DS::SHELLFONT.raw(), 0);
+		assert!(parsed.font.is_none());
+	}
+
+	#[test]
+	fn round_trip_items() {
+		let tmpl = DlgTemplateEx {
+			title: "Hello".to_owned(),
+			items: vec![
+				DlgItemTemplateEx {
+					class: DlgItemClass::Button,
+					text: IdStr::from_str("OK"),
+					id: 1,
+					creation_data: vec![1, 2, 3],
+					..Default::default()
+				},
+				DlgItemTemplateEx {
+					class: DlgItemClass::Name("SysListView32".to_owned()),
+					..Default::default()
+				},
+			],
+			..Default::default()
+		};
+		let parsed = DlgTemplateEx::parse(&tmpl.serialize().unwrap()).unwrap();
+		assert_eq!(parsed.title, "Hello");
+		assert_eq!(parsed.items.len(), 2);
+		assert!(parsed.items[0].class == DlgItemClass::Button);
+		assert!(matches!(&parsed.items[0].text, IdStr::Str(ws) if ws.to_string() == "OK"));
+		assert_eq!(parsed.items[0].id, 1);
+		assert_eq!(parsed.items[0].creation_data, [1, 2, 3]);
+		assert!(parsed.items[1].class == DlgItemClass::Name("SysListView32".to_owned()));
+	}
+
+	#[test]
+	fn too_large() {
+		let mut tmpl = DlgTemplateEx {
+			items: vec![DlgItemTemplateEx::default(); 0x1_0000],
+			..Default::default()
+		};
+		assert_eq!(tmpl.serialize().err(), Some(co::ERROR::INVALID_PARAMETER));
+
+		tmpl.items = vec![DlgItemTemplateEx {
+			creation_data: vec![0; 0x1_0000],
+			..Default::default()
+		}];
+		assert_eq!(tmpl.serialize().err(), Some(co::ERROR::INVALID_PARAMETER));
+	}
+}
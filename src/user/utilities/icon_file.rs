@@ -0,0 +1,809 @@
+use crate::co;
+use crate::decl::*;
+#[cfg(feature = "gdi")]
+use crate::guard::*;
+#[cfg(feature = "gdi")]
+use crate::prelude::*;
+use crate::user::utilities::res_io::*;
+
+/// Signature at the start of PNG-compressed images.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// Size of `ICONDIR`, the header of files and group resources.
+const DIR_HEADER_SZ: usize = 6;
+/// Size of `ICONDIRENTRY`, in files.
+const FILE_ENTRY_SZ: usize = 16;
+/// Size of `GRPICONDIRENTRY` and `GRPCURSORDIRENTRY`, in group resources.
+const GROUP_ENTRY_SZ: usize = 14;
+
+/// Contents of an `.ico` or `.cur` file, which is also the content of an
+/// [`RT::GROUP_ICON`](crate::co::RT::GROUP_ICON) or
+/// [`RT::GROUP_CURSOR`](crate::co::RT::GROUP_CURSOR) resource along with
+/// its [`RT::ICON`](crate::co::RT::ICON) or
+/// [`RT::CURSOR`](crate::co::RT::CURSOR) images.
+///
+/// This is a pure parser and writer: no system calls are made, except when
+/// loading from a module.
+///
+/// # Examples
+///
+/// Embedding the icon of an application into a `.res` file:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*};
+///
+/// let fin = w::FileMapped::open("C:\\Temp\\app.ico", w::FileAccess::ExistingReadOnly)?;
+/// let ico = w::IconFile::parse(fin.as_slice())?;
+///
+/// let res = w::ResFile {
+///     entries: ico.to_res_entries(w::IdStr::Id(1), 1, w::LANGID::USER_DEFAULT),
+/// };
+///
+/// let fout = w::File::open("C:\\Temp\\app.res", w::FileAccess::OpenOrCreateRW)?;
+/// fout.erase_and_write(&res.serialize())?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct IconFile {
+	/// Whether the file is a cursor, whose images have hotspots.
+	///
+	/// Defaults to `false`.
+	pub is_cursor: bool,
+	/// The images, usually with different sizes and color depths.
+	///
+	/// Defaults to none.
+	pub images: Vec<IconImage>,
+}
+
+/// A single image of an [`IconFile`](crate::IconFile).
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct IconImage {
+	/// Width of the image, in pixels.
+	pub width: u32,
+	/// Height of the image, in pixels.
+	pub height: u32,
+	/// Bits per pixel.
+	pub bit_count: u16,
+	/// Hotspot coordinates, used only by cursors.
+	pub hotspot: (u16, u16),
+	/// Raw image data: either a DIB, which is a `BITMAPINFOHEADER` followed by
+	/// the color table, the color bits and the mask bits, or a PNG file.
+	pub data: Vec<u8>,
+}
+
+impl IconFile {
+	/// Parses the raw contents of an `.ico` or `.cur` file.
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(blob: &[u8]) -> SysResult<Self> {
+		let (is_cursor, count) = read_dir_header(blob)?;
+		let mut images = Vec::<IconImage>::with_capacity(count);
+
+		for idx in 0..count {
+			let mut rd = Reader {
+				blob,
+				off: DIR_HEADER_SZ + idx * FILE_ENTRY_SZ,
+			};
+			let width = rd.byte()?;
+			let height = rd.byte()?;
+			rd.bytes(2)?; // color count and reserved
+			let planes_or_x = rd.word()?;
+			let bit_count_or_y = rd.word()?;
+			let data_sz = rd.dword()? as usize;
+			let data_off = rd.dword()? as usize;
+
+			let data = blob
+				.get(data_off..data_off.saturating_add(data_sz))
+				.ok_or(co::ERROR::INVALID_DATA)?;
+			images.push(IconImage::from_dir_entry(
+				width,
+				height,
+				if is_cursor { 0 } else { bit_count_or_y },
+				if is_cursor { (planes_or_x, bit_count_or_y) } else { (0, 0) },
+				data,
+			)?);
+		}
+		Ok(Self { is_cursor, images })
+	}
+
+	/// Serializes the images into the raw contents of an `.ico` or `.cur`
+	/// file.
+	#[must_use]
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut buf = Vec::<u8>::new();
+		self.write_dir_header(&mut buf);
+
+		let mut data_off = DIR_HEADER_SZ + self.images.len() * FILE_ENTRY_SZ;
+		for img in self.images.iter() {
+			img.write_dir_entry_head(&mut buf, self.is_cursor);
+			put_dword(&mut buf, img.data.len() as _);
+			put_dword(&mut buf, data_off as _);
+			data_off += img.data.len();
+		}
+		self.images
+			.iter()
+			.for_each(|img| buf.extend_from_slice(&img.data));
+		buf
+	}
+
+	/// Parses the raw data of an [`RT::GROUP_ICON`](crate::co::RT::GROUP_ICON)
+	/// or [`RT::GROUP_CURSOR`](crate::co::RT::GROUP_CURSOR) resource. The
+	/// data of each image is retrieved by calling `image_data` with its
+	/// [`RT::ICON`](crate::co::RT::ICON) or
+	/// [`RT::CURSOR`](crate::co::RT::CURSOR) resource ID.
+	///
+	/// Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse_group<F>(group_blob: &[u8], mut image_data: F) -> SysResult<Self>
+	where
+		F: FnMut(u16) -> SysResult<Vec<u8>>,
+	{
+		let (is_cursor, count) = read_dir_header(group_blob)?;
+		let mut images = Vec::<IconImage>::with_capacity(count);
+
+		for idx in 0..count {
+			let mut rd = Reader {
+				blob: group_blob,
+				off: DIR_HEADER_SZ + idx * GROUP_ENTRY_SZ,
+			};
+			let (width, height, bit_count) = if is_cursor {
+				let width = rd.word()?;
+				let height = rd.word()? / 2; // includes the mask
+				rd.word()?; // planes
+				(width.min(256) as u8, height.min(256) as u8, rd.word()?)
+			} else {
+				let width = rd.byte()?;
+				let height = rd.byte()?;
+				rd.bytes(4)?; // color count, reserved and planes
+				(width, height, rd.word()?)
+			};
+			rd.dword()?; // data size
+			let id = rd.word()?;
+
+			let data = image_data(id)?;
+			images.push(if is_cursor {
+				let mut rd = Reader { blob: &data, off: 0 };
+				let hotspot = (rd.word()?, rd.word()?);
+				IconImage::from_dir_entry(width, height, bit_count, hotspot, &data[4..])?
+			} else {
+				IconImage::from_dir_entry(width, height, bit_count, (0, 0), &data)?
+			});
+		}
+		Ok(Self { is_cursor, images })
+	}
+
+	/// Serializes the images into the raw data of an
+	/// [`RT::GROUP_ICON`](crate::co::RT::GROUP_ICON) or
+	/// [`RT::GROUP_CURSOR`](crate::co::RT::GROUP_CURSOR) resource, along with
+	/// the raw data of each [`RT::ICON`](crate::co::RT::ICON) or
+	/// [`RT::CURSOR`](crate::co::RT::CURSOR) resource, whose IDs are
+	/// sequential starting at `first_id`.
+	#[must_use]
+	pub fn serialize_group(&self, first_id: u16) -> (Vec<u8>, Vec<(u16, Vec<u8>)>) {
+		let mut group = Vec::<u8>::new();
+		self.write_dir_header(&mut group);
+		let mut images = Vec::<(u16, Vec<u8>)>::with_capacity(self.images.len());
+
+		for (idx, img) in self.images.iter().enumerate() {
+			let id = first_id.wrapping_add(idx as _);
+			let mut data = Vec::<u8>::with_capacity(img.data.len() + 4);
+			if self.is_cursor {
+				put_word(&mut group, img.width.min(256) as _);
+				put_word(&mut group, (img.height.min(256) * 2) as _); // includes the mask
+				put_word(&mut group, 1); // planes
+				put_word(&mut group, img.bit_count);
+				put_word(&mut data, img.hotspot.0);
+				put_word(&mut data, img.hotspot.1);
+			} else {
+				img.write_dir_entry_head(&mut group, false);
+			}
+			data.extend_from_slice(&img.data);
+			put_dword(&mut group, data.len() as _);
+			put_word(&mut group, id);
+			images.push((id, data));
+		}
+		(group, images)
+	}
+
+	/// Loads an [`RT::GROUP_ICON`](crate::co::RT::GROUP_ICON) resource – or an
+	/// [`RT::GROUP_CURSOR`](crate::co::RT::GROUP_CURSOR), if `is_cursor` –
+	/// along with all its images.
+	#[must_use]
+	pub fn load(hinst: &HINSTANCE, name: IdStr, is_cursor: bool) -> SysResult<Self> {
+		let (group_type, image_type) = if is_cursor {
+			(co::RT::GROUP_CURSOR, co::RT::CURSOR)
+		} else {
+			(co::RT::GROUP_ICON, co::RT::ICON)
+		};
+		let load_res = |name: IdStr, res_type: co::RT| -> SysResult<Vec<u8>> {
+			let hrsrc = hinst.FindResource(name, RtStr::Rt(res_type))?;
+			let hmem = hinst.LoadResource(&hrsrc)?;
+			hinst.LockResource(&hrsrc, &hmem).map(|blob| blob.to_vec())
+		};
+
+		let group_blob = load_res(name, group_type)?;
+		Self::parse_group(&group_blob, |id| load_res(IdStr::Id(id), image_type))
+	}
+
+	/// Returns the [`ResFile`](crate::ResFile) entries of the group resource,
+	/// with the given name, and of its images, whose IDs are sequential
+	/// starting at `first_image_id`.
+	#[must_use]
+	pub fn to_res_entries(&self, name: IdStr, first_image_id: u16, lang: LANGID) -> Vec<ResEntry> {
+		let (group_type, image_type) = if self.is_cursor {
+			(co::RT::GROUP_CURSOR, co::RT::CURSOR)
+		} else {
+			(co::RT::GROUP_ICON, co::RT::ICON)
+		};
+		let (group, images) = self.serialize_group(first_image_id);
+
+		let mut entries = images
+			.into_iter()
+			.map(|(id, data)| ResEntry::new(RtStr::Rt(image_type), IdStr::Id(id), lang, data))
+			.collect::<Vec<_>>();
+		entries.push(ResEntry::new(RtStr::Rt(group_type), name, lang, group));
+		entries
+	}
+
+	/// Returns the image which best fits the given logical size, in pixels,
+	/// scaled to the given DPI, where 96 is 100%.
+	///
+	/// The image with the exact size is preferred; otherwise the smallest one
+	/// which is larger, to be scaled down; otherwise the largest one. Among
+	/// images with the same size, the one with most colors is chosen.
+	#[must_use]
+	pub fn best_image(&self, size: u32, dpi: u32) -> Option<&IconImage> {
+		let target = (size * dpi + 48) / 96;
+		self.images
+			.iter()
+			.filter(|img| img.width == target)
+			.max_by_key(|img| img.bit_count)
+			.or_else(|| {
+				self.images
+					.iter()
+					.filter(|img| img.width > target)
+					.min_by_key(|img| (img.width, u16::MAX - img.bit_count))
+			})
+			.or_else(|| {
+				self.images
+					.iter()
+					.max_by_key(|img| (img.width, img.bit_count))
+			})
+	}
+
+	fn write_dir_header(&self, buf: &mut Vec<u8>) {
+		put_word(buf, 0); // reserved
+		put_word(buf, if self.is_cursor { 2 } else { 1 });
+		put_word(buf, self.images.len() as _);
+	}
+}
+
+impl IconImage {
+	/// Creates a 32-bit DIB image from top-down BGRA pixels, which is the
+	/// layout of [`RGBQUAD`](crate::RGBQUAD) arrays. The mask is built from
+	/// the alpha channel.
+	///
+	/// Width and height must be between 1 and 256, and `bgra` must have
+	/// exactly `width * height * 4` bytes, otherwise
+	/// [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER)
+	/// is returned.
+	#[must_use]
+	pub fn from_bgra(width: u32, height: u32, bgra: &[u8]) -> SysResult<Self> {
+		if !(1..=256).contains(&width)
+			|| !(1..=256).contains(&height)
+			|| bgra.len() != (width * height * 4) as usize
+		{
+			return Err(co::ERROR::INVALID_PARAMETER);
+		}
+
+		let mask_stride = mask_stride(width);
+		let mut data = Vec::<u8>::with_capacity(40 + bgra.len() + mask_stride * height as usize);
+		put_dword(&mut data, 40); // biSize
+		put_dword(&mut data, width);
+		put_dword(&mut data, height * 2); // includes the mask
+		put_word(&mut data, 1); // biPlanes
+		put_word(&mut data, 32); // biBitCount
+		put_dword(&mut data, 0); // biCompression
+		put_dword(&mut data, (bgra.len() + mask_stride * height as usize) as _);
+		data.extend_from_slice(&[0; 16]); // resolution and color counts
+
+		let rows = bgra.chunks_exact(width as usize * 4);
+		rows.clone()
+			.rev()
+			.for_each(|row| data.extend_from_slice(row)); // bottom-up
+		for row in rows.rev() {
+			let mut mask_row = vec![0u8; mask_stride];
+			for (x, px) in row.chunks_exact(4).enumerate() {
+				if px[3] == 0 {
+					mask_row[x / 8] |= 0x80 >> (x % 8); // transparent
+				}
+			}
+			data.extend_from_slice(&mask_row);
+		}
+
+		Ok(Self {
+			width,
+			height,
+			bit_count: 32,
+			hotspot: (0, 0),
+			data,
+		})
+	}
+
+	/// Tells whether the image data is PNG-compressed.
+	#[must_use]
+	pub fn is_png(&self) -> bool {
+		self.data.starts_with(PNG_SIGNATURE)
+	}
+
+	/// Decodes a DIB image into top-down BGRA pixels, which is the layout of
+	/// [`RGBQUAD`](crate::RGBQUAD) arrays. Images with 1, 4, 8, 24 and 32
+	/// bits per pixel are supported; transparency is taken from the alpha
+	/// channel of 32-bit images which have one, otherwise from the mask.
+	///
+	/// PNG-compressed images yield
+	/// [`co::ERROR::NOT_SUPPORTED`](crate::co::ERROR::NOT_SUPPORTED), and
+	/// malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn to_bgra(&self) -> SysResult<Vec<u8>> {
+		if self.is_png() {
+			return Err(co::ERROR::NOT_SUPPORTED);
+		}
+
+		let mut rd = Reader { blob: &self.data, off: 0 };
+		let header_sz = rd.dword()? as usize;
+		let width = rd.dword()? as i32;
+		let height = rd.dword()? as i32 / 2; // includes the mask
+		rd.word()?; // planes
+		let bit_count = rd.word()?;
+		let compression = rd.dword()?;
+		rd.bytes(12)?; // image size and resolution
+		let clr_used = rd.dword()? as usize;
+		if header_sz < 40
+			|| width <= 0
+			|| height <= 0
+			|| compression != 0
+			|| ![1, 4, 8, 24, 32].contains(&bit_count)
+		{
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let (width, height) = (width as usize, height as usize);
+
+		let palette_len = match bit_count {
+			1 | 4 | 8 if clr_used == 0 => 1 << bit_count,
+			1 | 4 | 8 => clr_used.min(1 << bit_count),
+			_ => 0,
+		};
+		rd.off = header_sz.min(self.data.len()); // larger offsets would overflow
+		let palette = rd.bytes(palette_len * 4)?;
+		let color_stride = (width * bit_count as usize).div_ceil(32) * 4;
+		let colors = rd.bytes(color_stride * height)?;
+		let mask = rd.bytes(mask_stride(width as _) * height).ok(); // some 32-bit images omit it
+
+		let mut bgra = vec![0u8; width * height * 4];
+		for y in 0..height {
+			let src_row = &colors[(height - 1 - y) * color_stride..][..color_stride]; // bottom-up
+			for x in 0..width {
+				let px = &mut bgra[(y * width + x) * 4..][..4];
+				match bit_count {
+					32 => px.copy_from_slice(&src_row[x * 4..x * 4 + 4]),
+					24 => {
+						px[..3].copy_from_slice(&src_row[x * 3..x * 3 + 3]);
+						px[3] = 0xff;
+					},
+					_ => {
+						let bits = bit_count as usize;
+						let idx = (src_row[x * bits / 8] >> (8 - bits - (x * bits % 8)))
+							& ((1 << bits) - 1) as u8;
+						let color = palette
+							.get(idx as usize * 4..idx as usize * 4 + 3)
+							.ok_or(co::ERROR::INVALID_DATA)?;
+						px[..3].copy_from_slice(color);
+						px[3] = 0xff;
+					},
+				}
+			}
+		}
+
+		let has_alpha = bit_count == 32 && bgra.chunks_exact(4).any(|px| px[3] != 0);
+		if !has_alpha {
+			if let Some(mask) = mask {
+				let stride = mask_stride(width as _);
+				for y in 0..height {
+					let mask_row = &mask[(height - 1 - y) * stride..][..stride];
+					for x in 0..width {
+						let transparent = mask_row[x / 8] & (0x80 >> (x % 8)) != 0;
+						bgra[(y * width + x) * 4 + 3] = if transparent { 0 } else { 0xff };
+					}
+				}
+			} else {
+				bgra.chunks_exact_mut(4).for_each(|px| px[3] = 0xff);
+			}
+		}
+		Ok(bgra)
+	}
+
+	/// Builds the image from the values of a directory entry, taking the
+	/// actual size and color depth from the data, if available.
+	fn from_dir_entry(
+		width: u8,
+		height: u8,
+		bit_count: u16,
+		hotspot: (u16, u16),
+		data: &[u8],
+	) -> SysResult<Self> {
+		let dir_size = |n: u8| if n == 0 { 256 } else { n as u32 };
+		let (width, height, bit_count) =
+			image_info(data).unwrap_or((dir_size(width), dir_size(height), bit_count));
+		if data.is_empty() {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		Ok(Self {
+			width,
+			height,
+			bit_count,
+			hotspot,
+			data: data.to_vec(),
+		})
+	}
+
+	/// Writes the first 12 bytes of a directory entry, which are common to
+	/// files and icon groups.
+	fn write_dir_entry_head(&self, buf: &mut Vec<u8>, is_cursor: bool) {
+		buf.push(if self.width >= 256 { 0 } else { self.width as _ });
+		buf.push(if self.height >= 256 { 0 } else { self.height as _ });
+		buf.push(if self.bit_count < 8 { 1 << self.bit_count } else { 0 }); // color count
+		buf.push(0); // reserved
+		if is_cursor {
+			put_word(buf, self.hotspot.0);
+			put_word(buf, self.hotspot.1);
+		} else {
+			put_word(buf, 1); // planes
+			put_word(buf, self.bit_count);
+		}
+	}
+}
+
+#[cfg(feature = "gdi")]
+impl IconImage {
+	/// Creates an icon from the image with
+	/// [`HICON::CreateIconFromResourceEx`](crate::HICON::CreateIconFromResourceEx),
+	/// in its actual size.
+	///
+	/// If `is_cursor` is `true`, a cursor is created at the image
+	/// [`hotspot`](crate::IconImage::hotspot), and the returned handle can be
+	/// used as an `HCURSOR`.
+	///
+	/// **Note:** To use this method, enable the `gdi`
+	/// [Cargo feature](https://doc.rust-lang.org/cargo/reference/features.html#the-features-section).
+	#[must_use]
+	pub fn to_hicon(&self, is_cursor: bool) -> SysResult<DestroyIconGuard> {
+		let sz = SIZE::with(self.width as _, self.height as _);
+		if is_cursor {
+			let mut bits = Vec::with_capacity(4 + self.data.len());
+			put_word(&mut bits, self.hotspot.0);
+			put_word(&mut bits, self.hotspot.1);
+			bits.extend_from_slice(&self.data);
+			HICON::CreateIconFromResourceEx(&bits, false, sz, co::LR::DEFAULTCOLOR)
+		} else {
+			HICON::CreateIconFromResourceEx(&self.data, true, sz, co::LR::DEFAULTCOLOR)
+		}
+	}
+
+	/// Creates a 32-bit image from an icon, retrieving its bitmaps with
+	/// [`HICON::GetIconInfo`](crate::HICON::GetIconInfo) and
+	/// [`HDC::GetDIBits`](crate::HDC::GetDIBits).
+	///
+	/// **Note:** To use this method, enable the `gdi`
+	/// [Cargo feature](https://doc.rust-lang.org/cargo/reference/features.html#the-features-section).
+	///
+	/// # Examples
+	///
+	/// Saving an icon to an `.ico` file:
+	///
+	/// ```no_run
+	/// use winsafe::{self as w, prelude::*, co};
+	///
+	/// let hicon: w::HICON; // initialized somewhere
+	/// # let hicon = w::HICON::NULL;
+	///
+	/// let ico = w::IconFile {
+	///     is_cursor: false,
+	///     images: vec![w::IconImage::from_hicon(&hicon)?],
+	/// };
+	///
+	/// let fout = w::File::open("C:\\Temp\\saved.ico", w::FileAccess::OpenOrCreateRW)?;
+	/// fout.erase_and_write(&ico.serialize())?;
+	/// # w::SysResult::Ok(())
+	/// ```
+	#[must_use]
+	pub fn from_hicon(hicon: &HICON) -> SysResult<Self> {
+		let ii = hicon.GetIconInfo()?;
+		let hbm_mask = unsafe { DeleteObjectGuard::new(ii.hbmMask.raw_copy()) };
+		let hbm_color = if ii.hbmColor == HBITMAP::NULL {
+			None // monochrome icon
+		} else {
+			Some(unsafe { DeleteObjectGuard::new(ii.hbmColor.raw_copy()) })
+		};
+		let hdc = HWND::DESKTOP.GetDC()?;
+
+		let get_bgra = |hbm: &HBITMAP| -> SysResult<(u32, u32, Vec<u8>)> {
+			let bmp = hbm.GetObject()?;
+			let mut bi = BITMAPINFO::default();
+			bi.bmiHeader.biWidth = bmp.bmWidth;
+			bi.bmiHeader.biHeight = -bmp.bmHeight; // top-down
+			bi.bmiHeader.biPlanes = 1;
+			bi.bmiHeader.biBitCount = 32;
+			bi.bmiHeader.biCompression = co::BI::RGB;
+			let mut buf = vec![0u8; bmp.bmWidth as usize * bmp.bmHeight as usize * 4];
+			unsafe {
+				hdc.GetDIBits(
+					hbm,
+					0,
+					bmp.bmHeight as _,
+					Some(&mut buf),
+					&mut bi,
+					co::DIB::RGB_COLORS,
+				)?;
+			}
+			Ok((bmp.bmWidth as _, bmp.bmHeight as _, buf))
+		};
+
+		let (width, mask_height, mask) = get_bgra(&hbm_mask)?;
+		let (height, mut bgra) = match &hbm_color {
+			Some(hbm_color) => {
+				let (_, height, bgra) = get_bgra(hbm_color)?;
+				(height, bgra)
+			},
+			None => {
+				let height = mask_height / 2; // the mask is on top of the colors
+				(height, mask[mask.len() / 2..].to_vec())
+			},
+		};
+
+		let has_alpha = hbm_color.is_some() && bgra.chunks_exact(4).any(|px| px[3] != 0);
+		if !has_alpha {
+			for (px, mask_px) in bgra.chunks_exact_mut(4).zip(mask.chunks_exact(4)) {
+				px[3] = if mask_px[0] != 0 { 0 } else { 0xff }; // white mask is transparent
+			}
+		}
+
+		let mut img = Self::from_bgra(width, height, &bgra).map_err(|_| co::ERROR::INVALID_DATA)?;
+		if !ii.fIcon() {
+			img.hotspot = (ii.xHotspot as _, ii.yHotspot as _);
+		}
+		Ok(img)
+	}
+}
+
+/// Reads the header of an `.ico` file or group resource, returning whether
+/// it's a cursor, and the number of images.
+fn read_dir_header(blob: &[u8]) -> SysResult<(bool, usize)> {
+	let mut rd = Reader { blob, off: 0 };
+	let reserved = rd.word()?;
+	let res_type = rd.word()?;
+	let count = rd.word()? as usize;
+	if reserved != 0 || !(1..=2).contains(&res_type) {
+		return Err(co::ERROR::INVALID_DATA);
+	}
+	Ok((res_type == 2, count))
+}
+
+/// Returns the width, height and bit count stored in the header of the image
+/// data, either DIB or PNG.
+#[must_use]
+fn image_info(data: &[u8]) -> Option<(u32, u32, u16)> {
+	let dword_be = |off: usize| {
+		data.get(off..off + 4)
+			.map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+	};
+
+	if data.starts_with(PNG_SIGNATURE) {
+		let channels = match data.get(25)? {
+			0 | 3 => 1, // grayscale or palette
+			2 => 3,     // RGB
+			4 => 2,     // grayscale with alpha
+			_ => 4,     // RGBA
+		};
+		Some((dword_be(16)?, dword_be(20)?, *data.get(24)? as u16 * channels))
+	} else {
+		let mut rd = Reader { blob: data, off: 4 };
+		let width = rd.dword().ok()? as i32;
+		let height = rd.dword().ok()? as i32 / 2; // includes the mask
+		rd.word().ok()?; // planes
+		let bit_count = rd.word().ok()?;
+		(width > 0 && height > 0).then_some((width as _, height as _, bit_count))
+	}
+}
+
+/// Returns the size of each row of the 1-bit mask, which is DWORD-aligned.
+#[must_use]
+const fn mask_stride(width: u32) -> usize {
+	width.div_ceil(32) as usize * 4
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Top-down 2x2 BGRA pixels, the last one transparent.
+	#[rustfmt::skip]
+	const BGRA: [u8; 16] = [
+		0x01, 0x02, 0x03, 0xff,  0x04, 0x05, 0x06, 0x80,
+		0x07, 0x08, 0x09, 0xff,  0x00, 0x00, 0x00, 0x00,
+	];
+
+	/// Start of a 32x16 RGBA PNG.
+	#[rustfmt::skip]
+	const PNG: &[u8] = &[
+		0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n',
+		0x00, 0x00, 0x00, 0x0d, b'I', b'H', b'D', b'R',
+		0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x10,
+		0x08, 0x06, 0x00, 0x00, 0x00,
+	];
+
+	fn png_image() -> IconImage {
+		IconImage::from_dir_entry(0, 0, 0, (0, 0), PNG).unwrap()
+	}
+
+	#[test]
+	fn from_to_bgra() {
+		let img = IconImage::from_bgra(2, 2, &BGRA).unwrap();
+		assert_eq!((img.width, img.height, img.bit_count), (2, 2, 32));
+		assert_eq!(&img.data[..12], [40, 0, 0, 0, 2, 0, 0, 0, 4, 0, 0, 0]); // height includes the mask
+		assert_eq!(img.data.len(), 40 + 16 + 2 * 4);
+		assert_eq!(&img.data[40..48], &BGRA[8..]); // bottom-up
+		assert_eq!(&img.data[56..], [0x40, 0, 0, 0, 0x00, 0, 0, 0]);
+		assert!(!img.is_png());
+		assert_eq!(img.to_bgra().unwrap(), BGRA);
+
+		let opaque = BGRA.map(|_| 0xff);
+		let img = IconImage::from_bgra(2, 2, &opaque).unwrap();
+		assert_eq!(img.to_bgra().unwrap(), opaque);
+
+		for (w, h, len) in [(0, 1, 0), (257, 1, 257 * 4), (2, 2, 15)] {
+			assert!(IconImage::from_bgra(w, h, &vec![0; len]) == Err(co::ERROR::INVALID_PARAMETER));
+		}
+	}
+
+	#[test]
+	fn to_bgra_mask() {
+		#[rustfmt::skip]
+		let data = [
+			40, 0, 0, 0,  2, 0, 0, 0,  4, 0, 0, 0,  1, 0,  1, 0, // 1-bit
+			0, 0, 0, 0,  0, 0, 0, 0,  0, 0, 0, 0,  0, 0, 0, 0,
+			0, 0, 0, 0,  0, 0, 0, 0,
+			0x10, 0x20, 0x30, 0x00,  0xa0, 0xb0, 0xc0, 0x00, // palette
+			0b0100_0000, 0, 0, 0,  0b1000_0000, 0, 0, 0, // colors, bottom-up
+			0b0000_0000, 0, 0, 0,  0b0100_0000, 0, 0, 0, // mask, bottom-up
+		];
+		let img = IconImage::from_dir_entry(2, 2, 1, (0, 0), &data).unwrap();
+		assert_eq!((img.width, img.height, img.bit_count), (2, 2, 1));
+		#[rustfmt::skip]
+		assert_eq!(img.to_bgra().unwrap(), [
+			0xa0, 0xb0, 0xc0, 0xff,  0x10, 0x20, 0x30, 0x00,
+			0x10, 0x20, 0x30, 0xff,  0xa0, 0xb0, 0xc0, 0xff,
+		]);
+
+		let mut bad = img.clone();
+		bad.data[0..4].copy_from_slice(&u32::MAX.to_le_bytes()); // header size
+		assert!(bad.to_bgra() == Err(co::ERROR::INVALID_DATA));
+		bad.data.truncate(60);
+		bad.data[0] = 40;
+		assert!(bad.to_bgra() == Err(co::ERROR::INVALID_DATA));
+		assert!(png_image().to_bgra() == Err(co::ERROR::NOT_SUPPORTED));
+	}
+
+	#[test]
+	fn file_round_trip() {
+		let ico = IconFile {
+			is_cursor: false,
+			images: vec![IconImage::from_bgra(2, 2, &BGRA).unwrap(), png_image()],
+		};
+		assert!(ico.images[1].is_png());
+		assert_eq!(
+			(ico.images[1].width, ico.images[1].height, ico.images[1].bit_count),
+			(32, 16, 32)
+		);
+
+		let blob = ico.serialize();
+		let data_sz = ico.images[0].data.len() as u8;
+		#[rustfmt::skip]
+		assert_eq!(&blob[..38], [
+			0, 0, 1, 0, 2, 0,
+			2, 2, 0, 0, 1, 0, 32, 0, data_sz, 0, 0, 0, 38, 0, 0, 0,
+			32, 16, 0, 0, 1, 0, 32, 0, PNG.len() as u8, 0, 0, 0, 38 + data_sz, 0, 0, 0,
+		]);
+		assert!(IconFile::parse(&blob).unwrap() == ico);
+
+		let mut cur = ico.clone();
+		cur.is_cursor = true;
+		cur.images[0].hotspot = (1, 0);
+		cur.images[1].hotspot = (16, 8);
+		let blob = cur.serialize();
+		assert_eq!(&blob[..6], [0, 0, 2, 0, 2, 0]);
+		assert_eq!(&blob[10..14], [1, 0, 0, 0]); // hotspot
+		assert!(IconFile::parse(&blob).unwrap() == cur);
+
+		assert!(IconFile::parse(&blob[..30]).is_err());
+		assert!(IconFile::parse(&[0, 0, 3, 0, 0, 0]).is_err());
+	}
+
+	#[test]
+	fn group_round_trip() {
+		let mut cur = IconFile {
+			is_cursor: true,
+			images: vec![IconImage::from_bgra(2, 2, &BGRA).unwrap(), png_image()],
+		};
+		cur.images[0].hotspot = (1, 1);
+		let (group, images) = cur.serialize_group(10);
+		let data_sz = cur.images[0].data.len() as u8 + 4;
+		#[rustfmt::skip]
+		assert_eq!(group, [
+			0, 0, 2, 0, 2, 0,
+			2, 0, 4, 0, 1, 0, 32, 0, data_sz, 0, 0, 0, 10, 0, // height includes the mask
+			32, 0, 32, 0, 1, 0, 32, 0, PNG.len() as u8 + 4, 0, 0, 0, 11, 0,
+		]);
+		assert_eq!(images.len(), 2);
+		assert_eq!(&images[0].1[..4], [1, 0, 1, 0]); // hotspot prefix
+		assert_eq!(&images[1].1[4..], PNG);
+
+		let image_data = |id: u16| {
+			images
+				.iter()
+				.find(|(img_id, _)| *img_id == id)
+				.map(|(_, data)| data.clone())
+				.ok_or(co::ERROR::RESOURCE_NAME_NOT_FOUND)
+		};
+		assert!(IconFile::parse_group(&group, image_data).unwrap() == cur);
+
+		let ico = IconFile { is_cursor: false, ..cur.clone() };
+		let (group, images) = ico.serialize_group(1);
+		assert_eq!(&images[1].1, PNG); // no prefix
+		let parsed =
+			IconFile::parse_group(&group, |id| Ok(images[id as usize - 1].1.clone())).unwrap();
+		assert!(parsed.images.iter().all(|img| img.hotspot == (0, 0)));
+		assert!(
+			parsed
+				.images
+				.iter()
+				.map(|img| &img.data)
+				.eq(ico.images.iter().map(|img| &img.data))
+		);
+
+		assert!(
+			IconFile::parse_group(&group, |_| Err(co::ERROR::RESOURCE_NAME_NOT_FOUND)).is_err()
+		);
+		assert!(
+			IconFile::parse_group(&group[..20], |id| Ok(images[id as usize - 1].1.clone()))
+				.is_err()
+		);
+	}
+
+	#[test]
+	fn best_image() {
+		let img = |width: u32, bit_count: u16| IconImage {
+			width,
+			height: width,
+			bit_count,
+			..Default::default()
+		};
+		let ico = IconFile {
+			is_cursor: false,
+			images: vec![img(16, 8), img(16, 32), img(32, 32), img(48, 4), img(48, 32)],
+		};
+		let best = |size, dpi| {
+			ico.best_image(size, dpi)
+				.map(|img| (img.width, img.bit_count))
+		};
+		assert_eq!(best(16, 96), Some((16, 32))); // exact, most colors
+		assert_eq!(best(24, 96), Some((32, 32))); // next larger
+		assert_eq!(best(16, 144), Some((32, 32))); // 24 at 150%
+		assert_eq!(best(32, 144), Some((48, 32))); // exact at 150%
+		assert_eq!(best(64, 96), Some((48, 32))); // largest
+		assert!(IconFile::default().best_image(16, 96).is_none());
+	}
+}
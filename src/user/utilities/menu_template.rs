@@ -0,0 +1,555 @@
+use crate::co;
+use crate::decl::*;
+use crate::guard::*;
+use crate::user::utilities::res_io::*;
+
+/// `bResInfo` flag of the last item of a menu.
+const RES_LAST: u16 = 0x80;
+/// `bResInfo` flag of an item which opens a submenu.
+const RES_POPUP: u16 = 0x01;
+/// ID of the separator written as the only item of an empty submenu, which is
+/// skipped by the parser.
+const EMPTY_POPUP_ID: u32 = u32::MAX;
+/// Limit of nested submenus accepted by the parser.
+const MAX_DEPTH: usize = 32;
+
+/// Declarative description of a menu, which can be serialized to and from a
+/// [`MENUEX_TEMPLATE`](https://learn.microsoft.com/en-us/windows/win32/menurc/menuex-template-header)
+/// block – the payload of an [`RT::MENU`](crate::co::RT::MENU) resource –
+/// and used to build or update an [`HMENU`](crate::HMENU).
+///
+/// [`parse`](crate::MenuTemplate::parse) and
+/// [`serialize`](crate::MenuTemplate::serialize) make no system calls.
+///
+/// # Examples
+///
+/// Building a menu bar:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, seq_ids};
+///
+/// seq_ids! {
+///     ID_FILE_OPEN = 2001;
+///     ID_FILE_EXIT
+///     ID_VIEW_LIST
+///     ID_VIEW_DETAILS
+/// }
+///
+/// let menu = w::MenuTemplate {
+///     items: vec![
+///         w::MenuNode::Popup(w::MenuPopup {
+///             text: "&File".to_owned(),
+///             items: vec![
+///                 w::MenuNode::Entry(w::MenuEntry {
+///                     cmd_id: ID_FILE_OPEN,
+///                     text: "&Open...".to_owned(),
+///                     accel_text: "Ctrl+O".to_owned(),
+///                     default: true,
+///                     ..Default::default()
+///                 }),
+///                 w::MenuNode::Separator,
+///                 w::MenuNode::Entry(w::MenuEntry {
+///                     cmd_id: ID_FILE_EXIT,
+///                     text: "E&xit".to_owned(),
+///                     ..Default::default()
+///                 }),
+///             ],
+///             ..Default::default()
+///         }),
+///         w::MenuNode::Popup(w::MenuPopup {
+///             text: "&View".to_owned(),
+///             items: vec![
+///                 w::MenuNode::Entry(w::MenuEntry {
+///                     cmd_id: ID_VIEW_LIST,
+///                     text: "&List".to_owned(),
+///                     radio: true,
+///                     checked: true,
+///                     ..Default::default()
+///                 }),
+///                 w::MenuNode::Entry(w::MenuEntry {
+///                     cmd_id: ID_VIEW_DETAILS,
+///                     text: "&Details".to_owned(),
+///                     radio: true,
+///                     ..Default::default()
+///                 }),
+///             ],
+///             ..Default::default()
+///         }),
+///     ],
+///     ..Default::default()
+/// };
+///
+/// let hmenu = menu.build()?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct MenuTemplate {
+	/// Help context identifier of the menu.
+	pub help_id: u32,
+	/// The items of the menu.
+	pub items: Vec<MenuNode>,
+}
+
+/// An item of a [`MenuTemplate`](crate::MenuTemplate).
+#[derive(Clone, PartialEq, Eq)]
+pub enum MenuNode {
+	/// A selectable entry.
+	Entry(MenuEntry),
+	/// A separator.
+	Separator,
+	/// An entry which opens a submenu.
+	Popup(MenuPopup),
+}
+
+/// A selectable entry of a [`MenuTemplate`](crate::MenuTemplate).
+#[derive(Clone, PartialEq, Eq)]
+pub struct MenuEntry {
+	/// Command ID sent in [`wm::Command`](crate::msg::wm::Command).
+	///
+	/// Defaults to zero.
+	pub cmd_id: u16,
+	/// Text of the entry.
+	///
+	/// Defaults to an empty string.
+	pub text: String,
+	/// Text of the keyboard shortcut, shown aligned at the right, like
+	/// `Ctrl+O`. It's only a label: the accelerator itself must be loaded
+	/// separately.
+	///
+	/// Defaults to an empty string.
+	pub accel_text: String,
+	/// Whether the check mark is a radio bullet. Consecutive radio entries
+	/// form a group, usually handled with
+	/// [`HMENU::CheckMenuRadioItem`](crate::HMENU::CheckMenuRadioItem).
+	///
+	/// Defaults to `false`.
+	pub radio: bool,
+	/// Whether the entry is checked.
+	///
+	/// Defaults to `false`.
+	pub checked: bool,
+	/// Whether the entry is enabled.
+	///
+	/// Defaults to `true`.
+	pub enabled: bool,
+	/// Whether the entry is the default one, shown in bold.
+	///
+	/// Defaults to `false`.
+	pub default: bool,
+}
+
+impl Default for MenuEntry {
+	fn default() -> Self {
+		Self {
+			cmd_id: 0,
+			text: String::new(),
+			accel_text: String::new(),
+			radio: false,
+			checked: false,
+			enabled: true,
+			default: false,
+		}
+	}
+}
+
+/// An entry of a [`MenuTemplate`](crate::MenuTemplate) which opens a submenu.
+#[derive(Clone, PartialEq, Eq)]
+pub struct MenuPopup {
+	/// Identifier of the entry, usually zero.
+	///
+	/// Defaults to zero.
+	pub id: u32,
+	/// Text of the entry.
+	///
+	/// Defaults to an empty string.
+	pub text: String,
+	/// Help context identifier of the submenu.
+	///
+	/// Defaults to zero.
+	pub help_id: u32,
+	/// Whether the entry is enabled.
+	///
+	/// Defaults to `true`.
+	pub enabled: bool,
+	/// The items of the submenu. Since the template format can't represent an
+	/// empty submenu, it's serialized with a placeholder separator, which is
+	/// skipped when parsing.
+	///
+	/// Defaults to none.
+	pub items: Vec<MenuNode>,
+}
+
+impl Default for MenuPopup {
+	fn default() -> Self {
+		Self {
+			id: 0,
+			text: String::new(),
+			help_id: 0,
+			enabled: true,
+			items: Vec::new(),
+		}
+	}
+}
+
+impl MenuTemplate {
+	/// Parses a raw `MENUEX_TEMPLATE` block, as returned by
+	/// [`HINSTANCE::LockResource`](crate::HINSTANCE::LockResource).
+	///
+	/// Type and state flags which have no field in
+	/// [`MenuEntry`](crate::MenuEntry) are ignored. Malformed data, including
+	/// the older `MENU` format and entry IDs which don't fit in a `u16`, yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(blob: &[u8]) -> SysResult<Self> {
+		let mut rd = Reader { blob, off: 0 };
+		let version = rd.word()?;
+		let offset = rd.word()?;
+		if version != 1 || offset < 4 {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let help_id = rd.dword()?;
+		rd.off = 4 + offset as usize;
+
+		let items = if rd.off < blob.len() {
+			read_items(&mut rd, 0)?
+		} else {
+			Vec::new() // no items at all
+		};
+		Ok(Self { help_id, items })
+	}
+
+	/// Serializes the menu into a raw `MENUEX_TEMPLATE` block, which can be
+	/// loaded with `LoadMenuIndirect` or written to a `.res` file.
+	#[must_use]
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut buf = Vec::<u8>::new();
+		put_word(&mut buf, 1); // wVersion
+		put_word(&mut buf, 4); // wOffset
+		put_dword(&mut buf, self.help_id);
+		write_items(&mut buf, &self.items);
+		buf
+	}
+
+	/// Creates a menu bar with
+	/// [`HMENU::CreateMenu`](crate::HMENU::CreateMenu), filled with the
+	/// items.
+	#[must_use]
+	pub fn build(&self) -> SysResult<HMENU> {
+		let hmenu = HMENU::CreateMenu()?;
+		fill_menu(&hmenu, &self.items)?;
+		Ok(hmenu)
+	}
+
+	/// Creates a popup menu with
+	/// [`HMENU::CreatePopupMenu`](crate::HMENU::CreatePopupMenu), filled with
+	/// the items. Useful for context menus.
+	#[must_use]
+	pub fn build_popup(&self) -> SysResult<HMENU> {
+		let hmenu = HMENU::CreatePopupMenu()?;
+		fill_menu(&hmenu, &self.items)?;
+		Ok(hmenu)
+	}
+
+	/// Updates a live menu, previously built from `prev`, so it matches this
+	/// template. Items are compared by position: only changed items are
+	/// updated, and items of a different kind are replaced.
+	///
+	/// If the menu is a window menu bar, call
+	/// [`HWND::DrawMenuBar`](crate::HWND::DrawMenuBar) afterwards.
+	pub fn update_menu(&self, hmenu: &HMENU, prev: &MenuTemplate) -> SysResult<()> {
+		update_items(hmenu, &self.items, &prev.items)
+	}
+}
+
+fn read_items(rd: &mut Reader, depth: usize) -> SysResult<Vec<MenuNode>> {
+	if depth > MAX_DEPTH {
+		return Err(co::ERROR::INVALID_DATA);
+	}
+
+	let mut items = Vec::<MenuNode>::new();
+	loop {
+		rd.align4();
+		let ty = unsafe { co::MFT::from_raw(rd.dword()?) };
+		let state = unsafe { co::MFS::from_raw(rd.dword()?) };
+		let id = rd.dword()?;
+		let res_info = rd.word()?;
+		let text = rd.sz()?;
+
+		if res_info & RES_POPUP != 0 {
+			rd.align4();
+			let help_id = rd.dword()?;
+			items.push(MenuNode::Popup(MenuPopup {
+				id,
+				text,
+				help_id,
+				enabled: !state.has(co::MFS::GRAYED),
+				items: read_items(rd, depth + 1)?,
+			}));
+		} else if ty.has(co::MFT::SEPARATOR) {
+			let placeholder =
+				depth > 0 && items.is_empty() && id == EMPTY_POPUP_ID && res_info & RES_LAST != 0;
+			if !placeholder {
+				items.push(MenuNode::Separator);
+			}
+		} else {
+			let (text, accel_text) = match text.split_once('\t') {
+				Some((text, accel)) => (text.to_owned(), accel.to_owned()),
+				None => (text, String::new()),
+			};
+			items.push(MenuNode::Entry(MenuEntry {
+				cmd_id: u16::try_from(id).map_err(|_| co::ERROR::INVALID_DATA)?,
+				text,
+				accel_text,
+				radio: ty.has(co::MFT::RADIOCHECK),
+				checked: state.has(co::MFS::CHECKED),
+				enabled: !state.has(co::MFS::GRAYED),
+				default: state.has(co::MFS::DEFAULT),
+			}));
+		}
+
+		if res_info & RES_LAST != 0 {
+			break;
+		}
+	}
+	Ok(items)
+}
+
+fn write_items(buf: &mut Vec<u8>, items: &[MenuNode]) {
+	for (idx, item) in items.iter().enumerate() {
+		let last = if idx == items.len() - 1 { RES_LAST } else { 0 };
+		buf.resize(align4(buf.len()), 0);
+
+		let (ty, state, id) = item_flags(item);
+		put_dword(buf, ty.raw());
+		put_dword(buf, state.raw());
+		put_dword(buf, id);
+
+		match item {
+			MenuNode::Popup(popup) => {
+				put_word(buf, RES_POPUP | last);
+				put_sz(buf, &popup.text);
+				buf.resize(align4(buf.len()), 0);
+				put_dword(buf, popup.help_id);
+				if popup.items.is_empty() {
+					buf.resize(align4(buf.len()), 0); // placeholder, can't be empty
+					put_dword(buf, co::MFT::SEPARATOR.raw());
+					put_dword(buf, co::MFS::ENABLED.raw());
+					put_dword(buf, EMPTY_POPUP_ID);
+					put_word(buf, RES_LAST);
+					put_sz(buf, "");
+				} else {
+					write_items(buf, &popup.items);
+				}
+			},
+			_ => {
+				put_word(buf, last);
+				put_sz(buf, &item_text(item));
+			},
+		}
+	}
+}
+
+/// Returns the type, state and ID of the item, as stored in templates and
+/// `MENUITEMINFO`.
+#[must_use]
+fn item_flags(item: &MenuNode) -> (co::MFT, co::MFS, u32) {
+	let enabled_state = |enabled: bool| {
+		if enabled { co::MFS::ENABLED } else { co::MFS::GRAYED }
+	};
+
+	match item {
+		MenuNode::Entry(entry) => {
+			let mut state = enabled_state(entry.enabled);
+			if entry.checked {
+				state |= co::MFS::CHECKED;
+			}
+			if entry.default {
+				state |= co::MFS::DEFAULT;
+			}
+			let ty = if entry.radio { co::MFT::RADIOCHECK } else { co::MFT::STRING };
+			(ty, state, entry.cmd_id as _)
+		},
+		MenuNode::Separator => (co::MFT::SEPARATOR, co::MFS::ENABLED, 0),
+		MenuNode::Popup(popup) => (co::MFT::STRING, enabled_state(popup.enabled), popup.id),
+	}
+}
+
+/// Returns the displayed text of the item, with the accelerator text after a
+/// tab.
+#[must_use]
+fn item_text(item: &MenuNode) -> String {
+	match item {
+		MenuNode::Entry(entry) if entry.accel_text.is_empty() => entry.text.clone(),
+		MenuNode::Entry(entry) => format!("{}\t{}", entry.text, entry.accel_text),
+		MenuNode::Separator => String::new(),
+		MenuNode::Popup(popup) => popup.text.clone(),
+	}
+}
+
+/// Inserts or updates the menu item at the given position.
+fn put_menu_item(hmenu: &HMENU, pos: u32, item: &MenuNode, insert: bool) -> SysResult<()> {
+	let (ty, state, id) = item_flags(item);
+	let mut wtext = WString::from_str(item_text(item));
+
+	let mut mii = MENUITEMINFO::default();
+	mii.fMask = co::MIIM::FTYPE | co::MIIM::STATE | co::MIIM::ID;
+	mii.fType = ty;
+	mii.fState = state;
+	mii.wID = id;
+	if !matches!(item, MenuNode::Separator) {
+		mii.fMask |= co::MIIM::STRING;
+		mii.dwTypeData = unsafe { wtext.as_mut_ptr() };
+	}
+
+	if insert {
+		let mut hsub = match item {
+			MenuNode::Popup(popup) => {
+				let hsub = unsafe { DestroyMenuGuard::new(HMENU::CreatePopupMenu()?) };
+				fill_menu(&hsub, &popup.items)?; // guard destroys it on failure
+				mii.fMask |= co::MIIM::SUBMENU;
+				mii.hSubMenu = unsafe { hsub.raw_copy() };
+				Some(hsub)
+			},
+			_ => None,
+		};
+		hmenu.InsertMenuItem(IdPos::Pos(pos), &mii)?;
+		if let Some(hsub) = hsub.as_mut() {
+			let _ = hsub.leak(); // now owned by the parent menu
+		}
+		Ok(())
+	} else {
+		hmenu.SetMenuItemInfo(IdPos::Pos(pos), &mii)
+	}
+}
+
+fn fill_menu(hmenu: &HMENU, items: &[MenuNode]) -> SysResult<()> {
+	items
+		.iter()
+		.enumerate()
+		.try_for_each(|(pos, item)| put_menu_item(hmenu, pos as _, item, true))
+}
+
+fn update_items(hmenu: &HMENU, items: &[MenuNode], prev: &[MenuNode]) -> SysResult<()> {
+	for (pos, item) in items.iter().enumerate() {
+		let pos = pos as u32;
+		match (item, prev.get(pos as usize)) {
+			(_, Some(prev_item)) if prev_item == item => {}, // unchanged
+			(MenuNode::Entry(_), Some(MenuNode::Entry(_)))
+			| (MenuNode::Separator, Some(MenuNode::Separator)) => {
+				put_menu_item(hmenu, pos, item, false)?;
+			},
+			(MenuNode::Popup(popup), Some(MenuNode::Popup(prev_popup))) => {
+				put_menu_item(hmenu, pos, item, false)?;
+				let hsub = hmenu
+					.GetSubMenu(pos)
+					.ok_or(co::ERROR::INVALID_MENU_HANDLE)?;
+				update_items(&hsub, &popup.items, &prev_popup.items)?;
+			},
+			(_, Some(_)) => {
+				hmenu.DeleteMenu(IdPos::Pos(pos))?; // different kind, replace it
+				put_menu_item(hmenu, pos, item, true)?;
+			},
+			(_, None) => put_menu_item(hmenu, pos, item, true)?,
+		}
+	}
+
+	for pos in (items.len()..prev.len()).rev() {
+		hmenu.DeleteMenu(IdPos::Pos(pos as _))?; // items which no longer exist
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sample() -> MenuTemplate {
+		MenuTemplate {
+			help_id: 7,
+			items: vec![
+				MenuNode::Popup(MenuPopup {
+					text: "&File".to_owned(),
+					items: vec![
+						MenuNode::Entry(MenuEntry {
+							cmd_id: 2001,
+							text: "&Open...".to_owned(),
+							accel_text: "Ctrl+O".to_owned(),
+							default: true,
+							..Default::default()
+						}),
+						MenuNode::Separator,
+						MenuNode::Entry(MenuEntry {
+							cmd_id: 2002,
+							text: "E&xit".to_owned(),
+							enabled: false,
+							..Default::default()
+						}),
+					],
+					..Default::default()
+				}),
+				MenuNode::Entry(MenuEntry {
+					cmd_id: 3001,
+					text: "&List".to_owned(),
+					radio: true,
+					checked: true,
+					..Default::default()
+				}),
+			],
+		}
+	}
+
+	#[test]
+	fn round_trip() {
+		let menu = sample();
+		let blob = menu.serialize();
+		assert_eq!(&blob[..8], &[1, 0, 4, 0, 7, 0, 0, 0]); // header
+		assert_eq!(blob.len() % 2, 0);
+
+		let parsed = MenuTemplate::parse(&blob).unwrap();
+		assert!(parsed == menu);
+		assert_eq!(parsed.serialize(), blob);
+	}
+
+	#[test]
+	fn empty_popup_round_trip() {
+		let menu = MenuTemplate {
+			help_id: 0,
+			items: vec![
+				MenuNode::Popup(MenuPopup {
+					text: "&Empty".to_owned(),
+					..Default::default()
+				}),
+				MenuNode::Popup(MenuPopup {
+					text: "&Sep".to_owned(),
+					items: vec![MenuNode::Separator],
+					..Default::default()
+				}),
+			],
+		};
+		let blob = menu.serialize();
+		let parsed = MenuTemplate::parse(&blob).unwrap();
+		assert!(parsed == menu);
+		assert_eq!(parsed.serialize(), blob);
+	}
+
+	#[test]
+	fn rejects_32bit_cmd_id() {
+		let mut blob = Vec::<u8>::new();
+		put_word(&mut blob, 1); // wVersion
+		put_word(&mut blob, 4); // wOffset
+		put_dword(&mut blob, 0); // dwHelpId
+		put_dword(&mut blob, co::MFT::STRING.raw());
+		put_dword(&mut blob, co::MFS::ENABLED.raw());
+		put_dword(&mut blob, 0x1_0001); // id
+		put_word(&mut blob, RES_LAST);
+		put_sz(&mut blob, "Big");
+		assert_eq!(MenuTemplate::parse(&blob).err(), Some(co::ERROR::INVALID_DATA));
+
+		blob[16..20].copy_from_slice(&0xffffu32.to_le_bytes());
+		let parsed = MenuTemplate::parse(&blob).unwrap();
+		match &parsed.items[..] {
+			[MenuNode::Entry(entry)] => assert_eq!(entry.cmd_id, 0xffff),
+			_ => panic!("unexpected items"),
+		}
+	}
+}
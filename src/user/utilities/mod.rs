@@ -0,0 +1,10 @@
+mod accel_table;
+mod dlg_template;
+mod icon_file;
+mod menu_template;
+mod res_io;
+
+pub use accel_table::AccelTable;
+pub use dlg_template::{DlgItemClass, DlgItemTemplateEx, DlgTemplateEx, DlgTemplateFont};
+pub use icon_file::{IconFile, IconImage};
+pub use menu_template::{MenuEntry, MenuNode, MenuPopup, MenuTemplate};
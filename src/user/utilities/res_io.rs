@@ -0,0 +1,87 @@
+use crate::co;
+use crate::decl::*;
+
+pub(in crate::user) use crate::kernel::bytes::align4;
+
+/// Sequential little-endian reader over a resource block.
+pub(in crate::user) struct Reader<'a> {
+	pub(in crate::user) blob: &'a [u8],
+	pub(in crate::user) off: usize,
+}
+
+impl<'a> Reader<'a> {
+	pub(in crate::user) fn bytes(&mut self, sz: usize) -> SysResult<&'a [u8]> {
+		let chunk = self
+			.blob
+			.get(self.off..self.off + sz)
+			.ok_or(co::ERROR::INVALID_DATA)?;
+		self.off += sz;
+		Ok(chunk)
+	}
+
+	pub(in crate::user) fn byte(&mut self) -> SysResult<u8> {
+		self.bytes(1).map(|b| b[0])
+	}
+
+	pub(in crate::user) fn word(&mut self) -> SysResult<u16> {
+		self.bytes(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+	}
+
+	pub(in crate::user) fn dword(&mut self) -> SysResult<u32> {
+		self.bytes(4)
+			.map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+	}
+
+	pub(in crate::user) fn align4(&mut self) {
+		self.off = align4(self.off);
+	}
+
+	/// Reads a null-terminated string.
+	pub(in crate::user) fn sz(&mut self) -> SysResult<String> {
+		let mut wchars = Vec::<u16>::new();
+		loop {
+			match self.word()? {
+				0 => break,
+				ch => wchars.push(ch),
+			}
+		}
+		Ok(String::from_utf16_lossy(&wchars))
+	}
+
+	/// Reads an empty value, an ordinal or a null-terminated string.
+	pub(in crate::user) fn sz_or_ord(&mut self) -> SysResult<Option<IdStr>> {
+		match self.word()? {
+			0x0000 => Ok(None),
+			0xffff => Ok(Some(IdStr::Id(self.word()?))),
+			_ => {
+				self.off -= 2; // the word was the first char
+				Ok(Some(IdStr::from_str(&self.sz()?)))
+			},
+		}
+	}
+}
+
+pub(in crate::user) fn put_word(buf: &mut Vec<u8>, val: u16) {
+	buf.extend_from_slice(&val.to_le_bytes());
+}
+
+pub(in crate::user) fn put_dword(buf: &mut Vec<u8>, val: u32) {
+	buf.extend_from_slice(&val.to_le_bytes());
+}
+
+pub(in crate::user) fn put_sz(buf: &mut Vec<u8>, s: &str) {
+	s.encode_utf16()
+		.chain(std::iter::once(0))
+		.for_each(|ch| put_word(buf, ch));
+}
+
+pub(in crate::user) fn put_sz_or_ord(buf: &mut Vec<u8>, val: Option<&IdStr>) {
+	match val {
+		None => put_word(buf, 0),
+		Some(IdStr::Id(id)) => {
+			put_word(buf, 0xffff);
+			put_word(buf, *id);
+		},
+		Some(IdStr::Str(ws)) => put_sz(buf, &ws.to_string()),
+	}
+}
@@ -2,6 +2,7 @@
 
 mod handles;
 mod structs;
+mod utilities;
 
 pub mod co;
 pub(in crate::version) mod ffi;
@@ -10,4 +11,5 @@ pub mod guards;
 pub mod decl {
 	pub use super::handles::decl::*;
 	pub use super::structs::*;
+	pub use super::utilities::*;
 }
@@ -0,0 +1,404 @@
+use crate::co;
+use crate::decl::*;
+use crate::kernel::bytes::*;
+
+const KEY_ROOT: &str = "VS_VERSION_INFO";
+const KEY_STRING_FILE_INFO: &str = "StringFileInfo";
+const KEY_VAR_FILE_INFO: &str = "VarFileInfo";
+const KEY_TRANSLATION: &str = "Translation";
+
+const TYPE_BINARY: u16 = 0;
+const TYPE_TEXT: u16 = 1;
+
+/// Parsed contents of a
+/// [`VS_VERSIONINFO`](https://learn.microsoft.com/en-us/windows/win32/menurc/vs-versioninfo)
+/// block, which is the payload of an
+/// [`RT::VERSION`](crate::co::RT::VERSION) resource.
+///
+/// This is a pure parser and writer: no system calls are made, so a block can
+/// be validated on any platform. The serialized block can be written to an
+/// executable with
+/// [`HUPDATERSRC::UpdateResource`](crate::HUPDATERSRC::UpdateResource).
+///
+/// # Examples
+///
+/// Reading the version block of the current executable:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let hinst = w::HINSTANCE::GetModuleHandle(None)?;
+/// let hres = hinst.FindResource(w::IdStr::Id(1), w::RtStr::Rt(co::RT::VERSION))?;
+/// let hres_load = hinst.LoadResource(&hres)?;
+/// let blob = hinst.LockResource(&hres, &hres_load)?;
+///
+/// let vi = w::VersionInfo::parse(blob)?;
+/// if let Some(fixed) = vi.fixed.as_ref() {
+///     println!("{:?}", fixed.dwFileVersion());
+/// }
+/// for table in vi.string_tables.iter() {
+///     for (name, val) in table.strings.iter() {
+///         println!("{}: {}", name, val);
+///     }
+/// }
+/// # w::SysResult::Ok(())
+/// ```
+///
+/// Stamping a new version into an executable:
+///
+/// ```no_run
+/// use winsafe::{self as w, prelude::*, co};
+///
+/// let mut fixed = w::VS_FIXEDFILEINFO::default();
+/// fixed.set_dwFileVersion([1, 2, 3, 4]);
+/// fixed.set_dwProductVersion([1, 2, 3, 4]);
+/// fixed.dwFileOS = co::VOS::NT_WINDOWS32;
+/// fixed.dwFileType = co::VFT::APP;
+///
+/// let lang_cp = (w::LANGID::new(co::LANG::ENGLISH, co::SUBLANG::ENGLISH_US), co::CP::UTF16);
+///
+/// let vi = w::VersionInfo {
+///     fixed: Some(fixed),
+///     string_tables: vec![w::VersionStringTable {
+///         lang_and_cp: lang_cp,
+///         strings: vec![
+///             ("FileVersion".to_owned(), "1.2.3.4".to_owned()),
+///             ("ProductName".to_owned(), "My App".to_owned()),
+///         ],
+///     }],
+///     translations: vec![lang_cp],
+/// };
+///
+/// let hupd = w::HUPDATERSRC::BeginUpdateResource("C:\\Temp\\app.exe", false)?;
+/// hupd.UpdateResource(
+///     w::RtStr::Rt(co::RT::VERSION),
+///     w::IdStr::Id(1),
+///     lang_cp.0,
+///     &vi.serialize()?,
+/// )?;
+/// # w::SysResult::Ok(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct VersionInfo {
+	/// The fixed version information, if present.
+	pub fixed: Option<VS_FIXEDFILEINFO>,
+	/// The `StringFileInfo` tables, one for each language and code page.
+	pub string_tables: Vec<VersionStringTable>,
+	/// The `VarFileInfo\Translation` pairs of language and code page.
+	pub translations: Vec<(LANGID, co::CP)>,
+}
+
+/// A `StringTable` of a [`VersionInfo`](crate::VersionInfo).
+#[derive(Clone, Debug, Default)]
+pub struct VersionStringTable {
+	/// The language and code page of the table, written as the 8-digit
+	/// hexadecimal key of the table, like `040904b0`.
+	pub lang_and_cp: (LANGID, co::CP),
+	/// The name/value pairs, in block order.
+	pub strings: Vec<(String, String)>,
+}
+
+impl VersionInfo {
+	/// Parses a raw `VS_VERSIONINFO` block, as returned by
+	/// [`HINSTANCE::LockResource`](crate::HINSTANCE::LockResource) or
+	/// [`HVERSIONINFO::GetFileVersionInfo`](crate::HVERSIONINFO::GetFileVersionInfo).
+	/// Any bytes past the length declared in the block are ignored.
+	///
+	/// Unknown child blocks are skipped. Malformed data yields
+	/// [`co::ERROR::INVALID_DATA`](crate::co::ERROR::INVALID_DATA).
+	#[must_use]
+	pub fn parse(blob: &[u8]) -> SysResult<Self> {
+		let root = Node::read(blob, 0)?;
+		if root.key != KEY_ROOT {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+
+		let mut vi = Self::default();
+		if !root.value.is_empty() {
+			vi.fixed = Some(read_fixed(root.value)?);
+		}
+
+		for child in root.children(blob) {
+			let child = child?;
+			match child.key.as_str() {
+				KEY_STRING_FILE_INFO => {
+					for table in child.children(blob) {
+						vi.string_tables
+							.push(VersionStringTable::parse(blob, &table?)?);
+					}
+				},
+				KEY_VAR_FILE_INFO => {
+					for var in child.children(blob) {
+						let var = var?;
+						if var.key == KEY_TRANSLATION {
+							vi.translations
+								.extend(var.value.chunks_exact(4).map(|pair| unsafe {
+									(
+										LANGID::from_raw(u16::from_le_bytes([pair[0], pair[1]])),
+										co::CP::from_raw(u16::from_le_bytes([pair[2], pair[3]])),
+									)
+								}));
+						}
+					}
+				},
+				_ => {}, // unknown block
+			}
+		}
+		Ok(vi)
+	}
+
+	/// Serializes the contents into a `VS_VERSIONINFO` block, which can be
+	/// written with
+	/// [`HUPDATERSRC::UpdateResource`](crate::HUPDATERSRC::UpdateResource).
+	///
+	/// `StringFileInfo` and `VarFileInfo` are written only if not empty.
+	///
+	/// Returns [`co::ERROR::INVALID_PARAMETER`](crate::co::ERROR::INVALID_PARAMETER)
+	/// if a block, or a value, exceeds the 64 KB limit of the format.
+	#[must_use]
+	pub fn serialize(&self) -> SysResult<Vec<u8>> {
+		let fixed_bytes = self.fixed.as_ref().map_or(&[][..], |fixed| unsafe {
+			std::slice::from_raw_parts(
+				fixed as *const _ as *const u8,
+				std::mem::size_of::<VS_FIXEDFILEINFO>(),
+			)
+		});
+
+		let mut buf = Vec::<u8>::new();
+		write_node(&mut buf, KEY_ROOT, TYPE_BINARY, fixed_bytes, fixed_bytes.len(), |buf| {
+			if !self.string_tables.is_empty() {
+				write_node(buf, KEY_STRING_FILE_INFO, TYPE_TEXT, &[], 0, |buf| {
+					self.string_tables
+						.iter()
+						.try_for_each(|table| table.serialize(buf))
+				})?;
+			}
+			if !self.translations.is_empty() {
+				write_node(buf, KEY_VAR_FILE_INFO, TYPE_TEXT, &[], 0, |buf| {
+					let pairs = self
+						.translations
+						.iter()
+						.flat_map(|(lang, cp)| {
+							let (lang, cp): (u16, u16) = ((*lang).into(), (*cp).into());
+							lang.to_le_bytes().into_iter().chain(cp.to_le_bytes())
+						})
+						.collect::<Vec<_>>();
+					write_node(buf, KEY_TRANSLATION, TYPE_BINARY, &pairs, pairs.len(), |_| Ok(()))
+				})?;
+			}
+			Ok(())
+		})?;
+		Ok(buf)
+	}
+
+	/// Returns the string value with the given name, in the table of the given
+	/// language and code page, if any.
+	///
+	/// Names are compared case-insensitively, like
+	/// [`HVERSIONINFO::str_val`](crate::HVERSIONINFO::str_val) does.
+	#[must_use]
+	pub fn str_val(&self, lang_and_cp: (LANGID, co::CP), name: &str) -> Option<&str> {
+		self.string_tables
+			.iter()
+			.find(|table| table.lang_and_cp == lang_and_cp)
+			.and_then(|table| table.str_val(name))
+	}
+}
+
+impl VersionStringTable {
+	/// Returns the string value with the given name, if any. Names are
+	/// compared case-insensitively.
+	#[must_use]
+	pub fn str_val(&self, name: &str) -> Option<&str> {
+		self.strings
+			.iter()
+			.find(|(key, _)| key.eq_ignore_ascii_case(name))
+			.map(|(_, val)| val.as_str())
+	}
+
+	fn parse(blob: &[u8], node: &Node) -> SysResult<Self> {
+		if node.key.len() != 8 || !node.key.is_ascii() {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+		let lang = u16::from_str_radix(&node.key[..4], 16).map_err(|_| co::ERROR::INVALID_DATA)?;
+		let cp = u16::from_str_radix(&node.key[4..], 16).map_err(|_| co::ERROR::INVALID_DATA)?;
+
+		let mut table = Self {
+			lang_and_cp: unsafe { (LANGID::from_raw(lang), co::CP::from_raw(cp)) },
+			strings: Vec::new(),
+		};
+		for entry in node.children(blob) {
+			let entry = entry?;
+			let val = if entry.text.is_empty() {
+				String::new() // value omitted altogether
+			} else {
+				read_utf16z(entry.text, 0)?
+			};
+			table.strings.push((entry.key, val));
+		}
+		Ok(table)
+	}
+
+	fn serialize(&self, buf: &mut Vec<u8>) -> SysResult<()> {
+		let (lang, cp): (u16, u16) = (self.lang_and_cp.0.into(), self.lang_and_cp.1.into());
+		let key = format!("{:04x}{:04x}", lang, cp);
+		write_node(buf, &key, TYPE_TEXT, &[], 0, |buf| {
+			self.strings.iter().try_for_each(|(name, val)| {
+				let wval = val
+					.encode_utf16()
+					.chain(std::iter::once(0))
+					.flat_map(u16::to_le_bytes)
+					.collect::<Vec<_>>();
+				write_node(buf, name, TYPE_TEXT, &wval, wval.len() / 2, |_| Ok(()))
+			})
+		})
+	}
+}
+
+/// A single block header with its key and value, pointing into the blob.
+struct Node<'a> {
+	key: String,
+	value: &'a [u8],
+	text: &'a [u8],
+	children_off: usize,
+	end: usize,
+}
+
+impl<'a> Node<'a> {
+	fn read(blob: &'a [u8], off: usize) -> SysResult<Self> {
+		let len = read_word(blob, off)? as usize;
+		let value_len = read_word(blob, off + 2)? as usize;
+		let ty = read_word(blob, off + 4)?;
+		let end = off + len;
+		if len < 6 || end > blob.len() {
+			return Err(co::ERROR::INVALID_DATA);
+		}
+
+		let mut key_end = off + 6;
+		let mut key = Vec::<u16>::new();
+		loop {
+			if key_end + 2 > end {
+				return Err(co::ERROR::INVALID_DATA); // key not null-terminated
+			}
+			let ch = read_word(blob, key_end)?;
+			key_end += 2;
+			if ch == 0 {
+				break;
+			}
+			key.push(ch);
+		}
+
+		let value_off = align4(key_end).min(end);
+		let value_sz = if ty == TYPE_TEXT { value_len * 2 } else { value_len };
+		let value_end = (value_off + value_sz).min(end);
+
+		Ok(Self {
+			key: String::from_utf16_lossy(&key),
+			value: &blob[value_off..value_end],
+			text: &blob[value_off..end], // some writers get the text length wrong
+			children_off: align4(value_end).min(end),
+			end,
+		})
+	}
+
+	fn children(&self, blob: &'a [u8]) -> impl Iterator<Item = SysResult<Node<'a>>> {
+		let end = self.end;
+		let mut off = self.children_off;
+		std::iter::from_fn(move || {
+			if off + 6 > end {
+				return None;
+			}
+			match Node::read(&blob[..end], off) {
+				Ok(node) => {
+					off = align4(node.end);
+					Some(Ok(node))
+				},
+				Err(e) => {
+					off = end; // stop at the first error
+					Some(Err(e))
+				},
+			}
+		})
+	}
+}
+
+fn read_fixed(bytes: &[u8]) -> SysResult<VS_FIXEDFILEINFO> {
+	if bytes.len() < std::mem::size_of::<VS_FIXEDFILEINFO>()
+		|| bytes[..4] != 0xfeef_04bd_u32.to_le_bytes()
+	{
+		return Err(co::ERROR::INVALID_DATA);
+	}
+	Ok(unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const VS_FIXEDFILEINFO) })
+}
+
+/// Appends a block, aligned to 4 bytes, whose children are written by the
+/// closure; then fixes the block length.
+fn write_node(
+	buf: &mut Vec<u8>,
+	key: &str,
+	ty: u16,
+	value: &[u8],
+	value_len: usize,
+	children: impl FnOnce(&mut Vec<u8>) -> SysResult<()>,
+) -> SysResult<()> {
+	let value_len = u16::try_from(value_len).map_err(|_| co::ERROR::INVALID_PARAMETER)?;
+	buf.resize(align4(buf.len()), 0);
+	let start = buf.len();
+
+	buf.extend_from_slice(&[0, 0]); // wLength, fixed below
+	buf.extend_from_slice(&value_len.to_le_bytes());
+	buf.extend_from_slice(&ty.to_le_bytes());
+	buf.extend(
+		key.encode_utf16()
+			.chain(std::iter::once(0))
+			.flat_map(u16::to_le_bytes),
+	);
+	buf.resize(align4(buf.len()), 0);
+	buf.extend_from_slice(value);
+	children(buf)?;
+
+	let len = u16::try_from(buf.len() - start).map_err(|_| co::ERROR::INVALID_PARAMETER)?;
+	buf[start..start + 2].copy_from_slice(&len.to_le_bytes());
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sample(product: &str) -> VersionInfo {
+		let mut fixed = VS_FIXEDFILEINFO::default();
+		fixed.set_dwFileVersion([1, 2, 3, 4]);
+		let lang_cp = unsafe { (LANGID::from_raw(0x0409), co::CP::from_raw(1200)) };
+		VersionInfo {
+			fixed: Some(fixed),
+			string_tables: vec![VersionStringTable {
+				lang_and_cp: lang_cp,
+				strings: vec![
+					("FileVersion".to_owned(), "1.2.3.4".to_owned()),
+					("ProductName".to_owned(), product.to_owned()),
+				],
+			}],
+			translations: vec![lang_cp],
+		}
+	}
+
+	#[test]
+	fn round_trip() {
+		let blob = sample("My App").serialize().unwrap();
+		assert_eq!(read_word(&blob, 0).unwrap() as usize, blob.len());
+		assert_eq!(read_word(&blob, 2).unwrap() as usize, std::mem::size_of::<VS_FIXEDFILEINFO>());
+
+		let vi = VersionInfo::parse(&blob).unwrap();
+		assert_eq!(vi.fixed.as_ref().unwrap().dwFileVersion(), [1, 2, 3, 4]);
+		assert_eq!(vi.fixed.as_ref().unwrap().dwStrucVersion, 0x0001_0000);
+		assert_eq!(vi.str_val(vi.translations[0], "productname"), Some("My App"));
+		assert_eq!(vi.serialize().unwrap(), blob);
+	}
+
+	#[test]
+	fn too_long() {
+		let long = "x".repeat(40_000); // fits the value length, not the block
+		assert_eq!(sample(&long).serialize().err(), Some(co::ERROR::INVALID_PARAMETER));
+	}
+}
@@ -1,7 +1,7 @@
 use crate::co::*;
 use crate::wininet::privs::*;
 
-const_values_pub! { ERROR; // this error range is hardcoded in SystemError trait, keep them in sync
+const_values_pub! { ERROR NAMES_WININET; // this error range is hardcoded in SystemError trait, keep them in sync
 	INTERNET_OUT_OF_HANDLES INTERNET_ERROR_BASE + 1
 	INTERNET_TIMEOUT INTERNET_ERROR_BASE + 2
 	INTERNET_EXTENDED_ERROR INTERNET_ERROR_BASE + 3